@@ -0,0 +1,210 @@
+//! Benchmarks for the logging path: derive-generated serialization, batch
+//! accumulation and the write-out to a mock sink, at realistic frame mixes.
+//!
+//! Throughput is reported in lines per second (criterion elements are lines);
+//! run with `cargo bench -p influx`. Before the timed runs each workload
+//! executes once under a counting allocator and prints its allocations per
+//! iteration, so an accidental per-line allocation in the derive or the
+//! writer shows up even when it is too cheap to move the timings.
+
+use criterion::{criterion_group, BatchSize, BenchmarkId, Criterion, Throughput};
+use influx::writer::BatchWriter;
+use influx::{LineProtocol, ToLineProtocol};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// [`System`] with a counter, for the one-shot allocation report.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator;
+
+/// Allocations (and reallocations) made while running `f` once.
+fn allocations(f: impl FnOnce()) -> u64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// A full-rate telemetry frame of the shape the pipeline logs: one tag,
+/// always-present channels and sparse optionals exercising the derive's
+/// presence-dependent separator path.
+#[derive(ToLineProtocol)]
+#[influx(measurement = "engine")]
+struct Frame {
+    #[influx(tag)]
+    stand: i64,
+    chamber_pressure: f64,
+    feed_pressure: Option<f64>,
+    temperature: Option<f64>,
+    igniter_current: f64,
+}
+
+/// Ambient conditions, logged at a tenth of the frame rate.
+#[derive(ToLineProtocol)]
+#[influx(measurement = "ambient")]
+struct Ambient {
+    #[influx(tag)]
+    stand: i64,
+    outside_temp: f64,
+    wind_speed: f64,
+}
+
+/// The frame the pipeline would build at tick `n` of a simulated second.
+fn frame(n: usize) -> Frame {
+    Frame {
+        stand: 1,
+        chamber_pressure: 12.5 + (n % 7) as f64 * 0.125,
+        feed_pressure: Some(18.0),
+        // Sparse: the slow channel only carries a sample every tenth frame.
+        temperature: n.is_multiple_of(10).then_some(21.5),
+        igniter_current: 0.0,
+    }
+}
+
+/// Write endpoint stand-in: consumes batches the way the HTTP client would
+/// assemble a request body, without the network.
+#[derive(Default)]
+struct MockSink {
+    bytes: usize,
+    writes: usize,
+}
+
+impl MockSink {
+    fn write(&mut self, batch: &[LineProtocol]) {
+        for line in batch {
+            // Body size is line plus the joining newline.
+            self.bytes += line.0.len() + 1;
+        }
+        self.writes += 1;
+    }
+}
+
+/// Lines one simulated second produces at `rate` frames per second: a frame
+/// per tick plus ambient conditions every tenth tick.
+fn lines_per_second(rate: usize) -> usize {
+    rate + rate / 10
+}
+
+/// One simulated second of the full path at `rate` frames per second:
+/// serialize, batch under the pipeline's flush policy, write due batches to
+/// the mock sink and recycle them. Returns the bytes the sink consumed so
+/// the work cannot be optimized away.
+fn one_second(rate: usize) -> usize {
+    let mut writer = BatchWriter::new(500, Duration::from_millis(100), 10_000).with_monotonic(true);
+    let mut sink = MockSink::default();
+    for n in 0..rate {
+        let timestamp = (n as u128) * 1_000_000_000 / rate as u128;
+        writer.push(frame(n).to_line_protocol_at(timestamp));
+        if n.is_multiple_of(10) {
+            writer.push(
+                Ambient {
+                    stand: 1,
+                    outside_temp: 21.0,
+                    wind_speed: 3.5,
+                }
+                .to_line_protocol_at(timestamp),
+            );
+        }
+        if writer.due() {
+            let batch = writer.take();
+            sink.write(&batch);
+            writer.recycle(batch);
+        }
+    }
+    let batch = writer.take();
+    sink.write(&batch);
+    sink.bytes
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("derive");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("serialize_frame", |b| {
+        b.iter(|| frame(black_box(7)).to_line_protocol_at(black_box(1_000)))
+    });
+    group.finish();
+}
+
+fn bench_batching(c: &mut Criterion) {
+    let lines: Vec<LineProtocol> = (0..1_000)
+        .map(|n| frame(n).to_line_protocol_at(n as u128))
+        .collect();
+    let mut group = c.benchmark_group("writer");
+    group.throughput(Throughput::Elements(lines.len() as u64));
+    group.bench_function("batch_1000_lines", |b| {
+        b.iter_batched(
+            || lines.clone(),
+            |lines| {
+                let mut writer =
+                    BatchWriter::new(500, Duration::from_millis(100), 10_000).with_monotonic(true);
+                let mut sink = MockSink::default();
+                writer.extend(lines);
+                while !writer.is_empty() {
+                    let batch = writer.take();
+                    sink.write(&batch);
+                    writer.recycle(batch);
+                }
+                sink.bytes
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    for rate in [100_usize, 1_000] {
+        group.throughput(Throughput::Elements(lines_per_second(rate) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("one_second", format!("{rate}hz")),
+            &rate,
+            |b, &rate| b.iter(|| one_second(rate)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_serialize, bench_batching, bench_pipeline);
+
+fn main() {
+    eprintln!("allocations per iteration:");
+    eprintln!(
+        "  derive/serialize_frame: {}",
+        allocations(|| {
+            black_box(frame(7).to_line_protocol_at(1_000));
+        })
+    );
+    for rate in [100_usize, 1_000] {
+        eprintln!(
+            "  pipeline/one_second/{rate}hz: {} ({} lines)",
+            allocations(|| {
+                black_box(one_second(rate));
+            }),
+            lines_per_second(rate)
+        );
+    }
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
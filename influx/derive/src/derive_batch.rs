@@ -0,0 +1,131 @@
+//! Code generation for `ToLineProtocolBatch`: container structs whose
+//! members render themselves.
+//!
+//! A top-level frame aggregates many sensor structs; rendering each with
+//! `to_line_protocol` and concatenating by hand is the same boilerplate at
+//! every call site, and hand-written concatenation is where timestamps
+//! drift apart. The derive walks the members, renders every one at the
+//! single shared timestamp, and appends the lines — newline terminated —
+//! to a caller provided buffer.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned as _;
+use syn::{DataStruct, DeriveInput, Fields};
+
+/// How one member contributes to the batch.
+enum Role {
+    /// Renders one line through `ToLineProtocol` (the default).
+    Single,
+    /// Renders zero or more lines through `ToLineProtocolEntries`, marked
+    /// `#[influx(entries)]`.
+    Entries,
+    /// Left out of the batch, marked `#[influx(skip)]`.
+    Skip,
+}
+
+pub(crate) fn derive_batch(input: &DeriveInput, data: &DataStruct) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    // The members own their measurements, tags and precision; there is
+    // nothing for the container to declare.
+    for attr in &input.attrs {
+        if attr.path().is_ident("influx") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "ToLineProtocolBatch takes no container attributes",
+            ));
+        }
+    }
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ToLineProtocolBatch requires a struct with named fields",
+        ));
+    };
+
+    // Member types are checked where they are declared, as for the line
+    // derive, so a member missing its trait errors on the member.
+    let mut assertions = Vec::new();
+    let mut stmts = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        match member_role(field)? {
+            Role::Skip => {}
+            Role::Single => {
+                assertions.push(quote_spanned! {ty.span()=>
+                    assert_impl_to_line_protocol::<#ty>();
+                });
+                stmts.push(quote! {
+                    buf.push_str(
+                        &::influx::ToLineProtocol::to_line_protocol_at(
+                            &self.#ident,
+                            timestamp_ns,
+                        )
+                        .0,
+                    );
+                    buf.push('\n');
+                });
+            }
+            Role::Entries => {
+                assertions.push(quote_spanned! {ty.span()=>
+                    assert_impl_to_line_protocol_entries::<#ty>();
+                });
+                stmts.push(quote! {
+                    for line in ::influx::ToLineProtocolEntries::to_line_protocol_entries_at(
+                        &self.#ident,
+                        timestamp_ns,
+                    ) {
+                        buf.push_str(&line.0);
+                        buf.push('\n');
+                    }
+                });
+            }
+        }
+    }
+    if stmts.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ToLineProtocolBatch requires at least one member that is not skipped",
+        ));
+    }
+
+    Ok(quote! {
+        const _: () = {
+            fn assert_impl_to_line_protocol<T: ::influx::ToLineProtocol>() {}
+            fn assert_impl_to_line_protocol_entries<T: ::influx::ToLineProtocolEntries>() {}
+            #[allow(dead_code)]
+            fn assert_members() {
+                #(#assertions)*
+            }
+        };
+        impl ::influx::ToLineProtocolBatch for #name {
+            fn write_batch_at(&self, buf: &mut ::std::string::String, timestamp_ns: u128) {
+                #(#stmts)*
+            }
+        }
+    })
+}
+
+/// The member's role from its `#[influx(...)]` attribute, defaulting to a
+/// single line through `ToLineProtocol`.
+fn member_role(field: &syn::Field) -> syn::Result<Role> {
+    let mut role = Role::Single;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("influx") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("entries") {
+                role = Role::Entries;
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                role = Role::Skip;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported influx batch member attribute"))
+            }
+        })?;
+    }
+    Ok(role)
+}
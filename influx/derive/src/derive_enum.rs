@@ -0,0 +1,270 @@
+//! Code generation for enums: heterogeneous events logged to one measurement.
+//!
+//! Every line carries a `variant` tag naming the active variant. Struct-like
+//! variants render their own members with the same `#[influx(...)]` member
+//! attributes structs use; unit variants have no members of their own, so
+//! they render the marker field `occurred=true` to satisfy the line protocol
+//! requirement of at least one field.
+
+use crate::derive_struct::{
+    check_key_collisions, parse_container_attrs, parse_members, Member, MemberKind,
+};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned as _;
+use syn::{DataEnum, DeriveInput, Fields, LitStr};
+
+/// One parsed variant and the members it carries.
+struct Variant {
+    ident: syn::Ident,
+    /// Value of the `variant` tag, from `#[influx(rename = "...")]` on the
+    /// variant or the snake_cased variant name.
+    tag_value: String,
+    members: Vec<Member>,
+}
+
+pub(crate) fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = parse_container_attrs(input)?;
+    let measurement = container
+        .measurement
+        .clone()
+        .unwrap_or_else(|| crate::snake_case(&name.to_string()));
+    crate::check_identifier(name, &measurement)?;
+    if container.with.is_some() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "custom codecs (#[influx(with = \"...\")]) are only supported on structs",
+        ));
+    }
+
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ToLineProtocol cannot be derived for empty enums",
+        ));
+    }
+
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        let members = match &variant.fields {
+            Fields::Unit => Vec::new(),
+            Fields::Named(fields) => parse_members(fields, container.rename_all)?,
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    &variant.ident,
+                    "ToLineProtocol enum variants must be unit or struct-like",
+                ));
+            }
+        };
+        if members.iter().any(|m| matches!(m.kind, MemberKind::Timestamp)) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "timestamp members are not supported in enum variants",
+            ));
+        }
+        if !members.is_empty() && !members.iter().any(|m| matches!(m.kind, MemberKind::Field)) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "line protocol requires at least one field member per variant",
+            ));
+        }
+        if members.iter().any(|m| m.measurement.is_some()) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "enum variant members cannot be routed to another measurement",
+            ));
+        }
+        // The `variant` tag the derive emits counts as a tag of every line.
+        let tags: Vec<&Member> = members
+            .iter()
+            .filter(|m| matches!(m.kind, MemberKind::Tag))
+            .collect();
+        let fields: Vec<&Member> = members
+            .iter()
+            .filter(|m| matches!(m.kind, MemberKind::Field))
+            .collect();
+        check_key_collisions(&tags, &fields, Some("variant"))?;
+        let tag_value = variant_tag_value(variant)?;
+        crate::check_identifier(&variant.ident, &tag_value)?;
+        variants.push(Variant {
+            ident: variant.ident.clone(),
+            tag_value,
+            members,
+        });
+    }
+
+    // Same member-spanned ToFieldValue and Display assertions the struct
+    // derive emits.
+    let assertions = variants
+        .iter()
+        .flat_map(|v| &v.members)
+        .filter(|m| matches!(m.kind, MemberKind::Field))
+        .map(|m| {
+            let ty = &m.ty;
+            quote_spanned! {ty.span()=>
+                assert_impl_to_field_value::<#ty>();
+            }
+        });
+    let tag_assertions = variants
+        .iter()
+        .flat_map(|v| &v.members)
+        .filter(|m| matches!(m.kind, MemberKind::Tag))
+        .map(|m| {
+            let ty = &m.ty;
+            quote_spanned! {ty.span()=>
+                assert_impl_display::<#ty>();
+            }
+        });
+    let assertions = quote! {
+        const _: () = {
+            fn assert_impl_to_field_value<T: ::influx::ToFieldValue>() {}
+            fn assert_impl_display<T: ::std::fmt::Display>() {}
+            #[allow(dead_code)]
+            fn assert_members() {
+                #(#assertions)*
+                #(#tag_assertions)*
+            }
+        };
+    };
+
+    // Field metadata covers every variant's field members; the marker field
+    // of unit variants carries no unit or description worth describing.
+    let metas = variants.iter().flat_map(|v| {
+        v.members
+            .iter()
+            .filter(|m| matches!(m.kind, MemberKind::Field))
+            .map(|member| {
+                let key = &member.key;
+                let unit = member.unit.as_deref().unwrap_or_default();
+                let description = member.description.as_deref().unwrap_or_default();
+                quote! {
+                    ::influx::FieldMeta {
+                        measurement: #measurement,
+                        key: #key,
+                        unit: #unit,
+                        description: #description,
+                    }
+                }
+            })
+    });
+    let schema = quote! {
+        impl ::influx::FieldSchema for #name {
+            const FIELDS: &'static [::influx::FieldMeta] = &[#(#metas),*];
+        }
+    };
+
+    let precision = container.timestamp_precision.variant();
+    let arms = variants.iter().map(|v| arm(&measurement, v));
+
+    Ok(quote! {
+        #assertions
+        #schema
+        impl ::influx::ToLineProtocol for #name {
+            const PRECISION: ::influx::Precision = #precision;
+
+            fn to_line_protocol_at(&self, timestamp_ns: u128) -> ::influx::LineProtocol {
+                let mut line = ::std::string::String::with_capacity(64);
+                match self {
+                    #(#arms)*
+                }
+                let _ = ::std::fmt::Write::write_fmt(
+                    &mut line,
+                    ::std::format_args!(" {}", Self::PRECISION.truncate(timestamp_ns)),
+                );
+                ::influx::LineProtocol(line)
+            }
+        }
+    })
+}
+
+/// The match arm rendering one variant: the `variant` tag, then the
+/// variant's own tags, then its fields — or the marker field for unit
+/// variants. Static fragments are coalesced as in the struct derive.
+fn arm(measurement: &str, variant: &Variant) -> TokenStream {
+    let ident = &variant.ident;
+
+    // Static fragments are escaped at expansion time; tag values share the
+    // key escaping rules.
+    let measurement = crate::escape_measurement(measurement);
+    let tag_value = crate::escape_key(&variant.tag_value);
+
+    if variant.members.is_empty() {
+        let fragment = format!("{},variant={} occurred=true", measurement, tag_value);
+        return quote! {
+            Self::#ident => {
+                line.push_str(#fragment);
+            }
+        };
+    }
+
+    let bindings = variant.members.iter().map(|m| &m.ident);
+    let mut stmts = Vec::new();
+    let mut fragment = format!("{},variant={}", measurement, tag_value);
+    let mut first_field = true;
+    let tags = variant
+        .members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Tag));
+    let fields = variant
+        .members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Field));
+    // Tags precede fields regardless of declaration order, as for structs.
+    for member in tags.chain(fields) {
+        let binding = &member.ident;
+        match member.kind {
+            MemberKind::Tag => {
+                fragment.push(',');
+                fragment.push_str(&crate::escape_key(&member.key));
+                fragment.push('=');
+                stmts.push(quote! {
+                    line.push_str(#fragment);
+                    ::influx::escape::write_tag_value(
+                        &mut line,
+                        &::std::string::ToString::to_string(#binding),
+                    );
+                });
+            }
+            MemberKind::Field => {
+                fragment.push(if first_field { ' ' } else { ',' });
+                first_field = false;
+                fragment.push_str(&crate::escape_key(&member.key));
+                fragment.push('=');
+                stmts.push(quote! {
+                    line.push_str(#fragment);
+                    ::influx::ToFieldValue::write_field_value(#binding, &mut line);
+                });
+            }
+            MemberKind::Timestamp => unreachable!("rejected when the variant was parsed"),
+        }
+        fragment.clear();
+    }
+
+    quote! {
+        Self::#ident { #(#bindings),* } => {
+            #(#stmts)*
+        }
+    }
+}
+
+/// The `variant` tag value: `#[influx(rename = "...")]` on the variant or
+/// the snake_cased variant name.
+fn variant_tag_value(variant: &syn::Variant) -> syn::Result<String> {
+    let mut rename = None;
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("influx") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                rename = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported influx variant attribute"))
+            }
+        })?;
+    }
+    Ok(rename.unwrap_or_else(|| crate::snake_case(&variant.ident.to_string())))
+}
@@ -0,0 +1,783 @@
+//! Code generation for structs with named members.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned as _;
+use syn::{DataStruct, DeriveInput, Fields, Ident, LitStr, Type};
+
+/// How a struct member is rendered into line protocol.
+pub(crate) enum MemberKind {
+    Tag,
+    Field,
+    /// Supplies the line protocol timestamp instead of rendering into the
+    /// line; at most one per struct.
+    Timestamp,
+}
+
+pub(crate) struct Member {
+    pub(crate) ident: Ident,
+    pub(crate) ty: Type,
+    pub(crate) kind: MemberKind,
+    pub(crate) key: String,
+    /// Field-level measurement override; members without one go to the
+    /// container's measurement.
+    pub(crate) measurement: Option<String>,
+    /// Physical unit, from `#[influx(unit = "...")]`.
+    pub(crate) unit: Option<String>,
+    /// Human readable description, from `#[influx(description = "...")]`.
+    pub(crate) description: Option<String>,
+    /// For a field member of type `Option<T>`, the inner `T`; the field is
+    /// skipped when the value is `None`.
+    pub(crate) optional: Option<Type>,
+}
+
+/// Casing convention applied to keys derived from member names, set with the
+/// container attribute `#[influx(rename_all = "...")]`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum RenameAll {
+    /// `snake_case`, the default.
+    #[default]
+    Snake,
+    /// `kebab-case`, matching our dash separated influx field names.
+    Kebab,
+}
+
+impl RenameAll {
+    pub(crate) fn parse(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "snake_case" => Ok(Self::Snake),
+            "kebab-case" => Ok(Self::Kebab),
+            other => Err(syn::Error::new_spanned(
+                lit,
+                format!(r#"unknown rename_all value "{other}", expected "snake_case" or "kebab-case""#),
+            )),
+        }
+    }
+
+    /// Derive the line protocol key for a member name.
+    pub(crate) fn apply(self, ident: &str) -> String {
+        let snake = crate::snake_case(ident);
+        match self {
+            Self::Snake => snake,
+            Self::Kebab => snake.replace('_', "-"),
+        }
+    }
+}
+
+/// Timestamp precision selected with the container attribute
+/// `#[influx(timestamp_precision = "...")]`; mirrors `influx::Precision`.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum Precision {
+    #[default]
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+}
+
+impl Precision {
+    pub(crate) fn parse(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "nanoseconds" => Ok(Self::Nanoseconds),
+            "microseconds" => Ok(Self::Microseconds),
+            "milliseconds" => Ok(Self::Milliseconds),
+            "seconds" => Ok(Self::Seconds),
+            other => Err(syn::Error::new_spanned(
+                lit,
+                format!(
+                    r#"unknown timestamp_precision value "{other}", expected "nanoseconds", "microseconds", "milliseconds" or "seconds""#
+                ),
+            )),
+        }
+    }
+
+    /// The `influx::Precision` variant this maps to.
+    pub(crate) fn variant(self) -> TokenStream {
+        match self {
+            Self::Nanoseconds => quote! { ::influx::Precision::Nanoseconds },
+            Self::Microseconds => quote! { ::influx::Precision::Microseconds },
+            Self::Milliseconds => quote! { ::influx::Precision::Milliseconds },
+            Self::Seconds => quote! { ::influx::Precision::Seconds },
+        }
+    }
+}
+
+/// Container level `#[influx(...)]` attributes.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) measurement: Option<String>,
+    pub(crate) rename_all: RenameAll,
+    pub(crate) timestamp_precision: Precision,
+    /// Custom codec module from `#[influx(with = "...")]`; when set, field
+    /// rendering is delegated to the module instead of generated.
+    pub(crate) with: Option<syn::Path>,
+}
+
+pub(crate) fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = parse_container_attrs(input)?;
+    let measurement = container
+        .measurement
+        .clone()
+        .unwrap_or_else(|| crate::snake_case(&name.to_string()));
+    crate::check_identifier(name, &measurement)?;
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "ToLineProtocol can only be derived for structs with named members",
+        ));
+    };
+
+    let members = parse_members(fields, container.rename_all)?;
+
+    let mut timestamps = members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Timestamp));
+    let timestamp = timestamps.next();
+    if let Some(extra) = timestamps.next() {
+        return Err(syn::Error::new_spanned(
+            &extra.ident,
+            "at most one member may be the timestamp",
+        ));
+    }
+
+    let tags: Vec<&Member> = members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Tag))
+        .collect();
+
+    if let Some(module) = &container.with {
+        return derive_with(name, &measurement, module, &container, &members, &tags, timestamp);
+    }
+
+    if !members.iter().any(|m| matches!(m.kind, MemberKind::Field)) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "line protocol requires at least one field member",
+        ));
+    }
+
+    // Fields grouped by target measurement, in first-appearance order;
+    // members without a field-level override go to the container measurement.
+    let mut groups: Vec<(String, Vec<&Member>)> = Vec::new();
+    for member in members.iter().filter(|m| matches!(m.kind, MemberKind::Field)) {
+        let target = member.measurement.clone().unwrap_or_else(|| measurement.clone());
+        match groups.iter_mut().find(|(m, _)| *m == target) {
+            Some((_, group)) => group.push(member),
+            None => groups.push((target, vec![member])),
+        }
+    }
+
+    for (_, group) in &groups {
+        check_key_collisions(&tags, group, None)?;
+    }
+
+    // One assertion per field member, spanned to its type, so a member whose
+    // type lacks `ToFieldValue` produces a diagnostic on the member itself
+    // instead of deep inside the generated impl. Optional members assert
+    // their inner type; the `Option` wrapper is handled by the derive.
+    let assertions = members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Field))
+        .map(|m| {
+            let ty = m.optional.as_ref().unwrap_or(&m.ty);
+            quote_spanned! {ty.span()=>
+                assert_impl_to_field_value::<#ty>();
+            }
+        });
+    // Tags render through `Display`; the same member-spanned treatment keeps
+    // that requirement from surfacing as an error inside the generated impl.
+    let tag_assertions = tags.iter().map(|m| {
+        let ty = &m.ty;
+        quote_spanned! {ty.span()=>
+            assert_impl_display::<#ty>();
+        }
+    });
+    let timestamp_assertion = timestamp
+        .map(|m| {
+            let ty = &m.ty;
+            quote_spanned! {ty.span()=>
+                {
+                    fn assert_impl_to_timestamp<T: ::influx::ToTimestamp>() {}
+                    assert_impl_to_timestamp::<#ty>();
+                }
+            }
+        })
+        .unwrap_or_default();
+    let assertions = quote! {
+        const _: () = {
+            fn assert_impl_to_field_value<T: ::influx::ToFieldValue>() {}
+            fn assert_impl_display<T: ::std::fmt::Display>() {}
+            #[allow(dead_code)]
+            fn assert_members() {
+                #(#assertions)*
+                #(#tag_assertions)*
+                #timestamp_assertion
+            }
+        };
+    };
+
+    // Const field metadata for the companion `schema_fields` measurement,
+    // one entry per field member in group order.
+    let metas = groups.iter().flat_map(|(target, group)| {
+        group.iter().map(move |member| {
+            let key = &member.key;
+            let unit = member.unit.as_deref().unwrap_or_default();
+            let description = member.description.as_deref().unwrap_or_default();
+            quote! {
+                ::influx::FieldMeta {
+                    measurement: #target,
+                    key: #key,
+                    unit: #unit,
+                    description: #description,
+                }
+            }
+        })
+    });
+    let schema = quote! {
+        impl ::influx::FieldSchema for #name {
+            const FIELDS: &'static [::influx::FieldMeta] = &[#(#metas),*];
+        }
+    };
+
+    // The timestamp is truncated through the impl's own PRECISION const, so
+    // the rendered lines and the precision the type advertises to the write
+    // client are a single declaration.
+    let precision = container.timestamp_precision.variant();
+
+    // A `#[influx(timestamp)]` member overrides the serialization-time
+    // default: batched data carries its acquisition time. The explicit
+    // `*_at` primitive still honors the caller's timestamp.
+    let member_timestamp = timestamp.map(|m| {
+        let ident = &m.ident;
+        quote! {
+            ::influx::ToTimestamp::timestamp_ns(&self.#ident)
+                .unwrap_or_else(::influx::timestamp_now)
+        }
+    });
+    let now_override = member_timestamp
+        .as_ref()
+        .map(|ts| {
+            quote! {
+                fn to_line_protocol(&self) -> ::influx::LineProtocol {
+                    self.to_line_protocol_at(#ts)
+                }
+            }
+        })
+        .unwrap_or_default();
+    let now_entries_override = member_timestamp
+        .map(|ts| {
+            quote! {
+                fn to_line_protocol_entries(&self) -> ::std::vec::Vec<::influx::LineProtocol> {
+                    self.to_line_protocol_entries_at(#ts)
+                }
+            }
+        })
+        .unwrap_or_default();
+
+    if groups.len() == 1 {
+        // Single measurement: the classic one-line impl.
+        let (stmts, capacity) = line_stmts(&groups[0].0, &tags, &groups[0].1);
+        Ok(quote! {
+            #assertions
+            #schema
+            impl ::influx::ToLineProtocol for #name {
+                const PRECISION: ::influx::Precision = #precision;
+
+                fn to_line_protocol_at(&self, timestamp_ns: u128) -> ::influx::LineProtocol {
+                    let mut line = ::std::string::String::with_capacity(#capacity);
+                    #(#stmts)*
+                    let _ = ::std::fmt::Write::write_fmt(
+                        &mut line,
+                        ::std::format_args!(" {}", Self::PRECISION.truncate(timestamp_ns)),
+                    );
+                    ::influx::LineProtocol(line)
+                }
+
+                #now_override
+            }
+        })
+    } else {
+        // Multiple measurements: one line per group, all sharing the tags
+        // and the timestamp, rendered as entries.
+        let group_count = groups.len();
+        let lines = groups.iter().map(|(target, group)| {
+            let (stmts, capacity) = line_stmts(target, &tags, group);
+            quote! {
+                {
+                    let mut line = ::std::string::String::with_capacity(#capacity);
+                    #(#stmts)*
+                    let _ = ::std::fmt::Write::write_fmt(
+                        &mut line,
+                        ::std::format_args!(" {}", Self::PRECISION.truncate(timestamp_ns)),
+                    );
+                    entries.push(::influx::LineProtocol(line));
+                }
+            }
+        });
+        Ok(quote! {
+            #assertions
+            #schema
+            impl ::influx::ToLineProtocolEntries for #name {
+                const PRECISION: ::influx::Precision = #precision;
+
+                fn to_line_protocol_entries_at(
+                    &self,
+                    timestamp_ns: u128,
+                ) -> ::std::vec::Vec<::influx::LineProtocol> {
+                    let mut entries = ::std::vec::Vec::with_capacity(#group_count);
+                    #(#lines)*
+                    entries
+                }
+
+                #now_entries_override
+            }
+        })
+    }
+}
+
+/// Expansion for `#[influx(with = "module")]`: the derive renders the
+/// measurement-and-tags prefix and truncates the timestamp, then delegates
+/// the field set to `module::to_line_protocol(&self, &WithContext)` — for
+/// types whose field mapping is not a plain member-per-field. Tags, the
+/// timestamp member and annotated schema metadata keep working; field types
+/// are the module's business, so the `ToFieldValue` assertions are skipped.
+fn derive_with(
+    name: &Ident,
+    measurement: &str,
+    module: &syn::Path,
+    container: &ContainerAttrs,
+    members: &[Member],
+    tags: &[&Member],
+    timestamp: Option<&Member>,
+) -> syn::Result<TokenStream> {
+    if let Some(routed) = members.iter().find(|m| m.measurement.is_some()) {
+        return Err(syn::Error::new_spanned(
+            &routed.ident,
+            "a custom codec renders one line; members cannot be routed to another measurement",
+        ));
+    }
+    check_key_collisions(tags, &[], None)?;
+
+    let tag_assertions = tags.iter().map(|m| {
+        let ty = &m.ty;
+        quote_spanned! {ty.span()=>
+            assert_impl_display::<#ty>();
+        }
+    });
+    let timestamp_assertion = timestamp
+        .map(|m| {
+            let ty = &m.ty;
+            quote_spanned! {ty.span()=>
+                {
+                    fn assert_impl_to_timestamp<T: ::influx::ToTimestamp>() {}
+                    assert_impl_to_timestamp::<#ty>();
+                }
+            }
+        })
+        .unwrap_or_default();
+    let assertions = quote! {
+        const _: () = {
+            fn assert_impl_display<T: ::std::fmt::Display>() {}
+            #[allow(dead_code)]
+            fn assert_members() {
+                #(#tag_assertions)*
+                #timestamp_assertion
+            }
+        };
+    };
+
+    // Annotated members still describe the schema, under the container
+    // measurement; the module is trusted to keep the derived keys.
+    let metas = members
+        .iter()
+        .filter(|m| matches!(m.kind, MemberKind::Field))
+        .map(|member| {
+            let key = &member.key;
+            let unit = member.unit.as_deref().unwrap_or_default();
+            let description = member.description.as_deref().unwrap_or_default();
+            quote! {
+                ::influx::FieldMeta {
+                    measurement: #measurement,
+                    key: #key,
+                    unit: #unit,
+                    description: #description,
+                }
+            }
+        });
+    let schema = quote! {
+        impl ::influx::FieldSchema for #name {
+            const FIELDS: &'static [::influx::FieldMeta] = &[#(#metas),*];
+        }
+    };
+
+    // The prefix renders exactly as the head of a generated line: static
+    // fragments coalesced, runtime tag values escaped as they are written.
+    let mut stmts = Vec::new();
+    let mut fragment = crate::escape_measurement(measurement);
+    for member in tags {
+        let ident = &member.ident;
+        fragment.push(',');
+        fragment.push_str(&crate::escape_key(&member.key));
+        fragment.push('=');
+        stmts.push(quote! {
+            prefix.push_str(#fragment);
+            ::influx::escape::write_tag_value(
+                &mut prefix,
+                &::std::string::ToString::to_string(&self.#ident),
+            );
+        });
+        fragment.clear();
+    }
+    if tags.is_empty() {
+        stmts.push(quote! {
+            prefix.push_str(#fragment);
+        });
+    }
+    let capacity = measurement.len()
+        + tags.iter().map(|m| m.key.len() + 2).sum::<usize>()
+        + 16 * (tags.len() + 1);
+
+    let precision = container.timestamp_precision.variant();
+    let now_override = timestamp
+        .map(|m| {
+            let ident = &m.ident;
+            quote! {
+                fn to_line_protocol(&self) -> ::influx::LineProtocol {
+                    self.to_line_protocol_at(
+                        ::influx::ToTimestamp::timestamp_ns(&self.#ident)
+                            .unwrap_or_else(::influx::timestamp_now),
+                    )
+                }
+            }
+        })
+        .unwrap_or_default();
+
+    Ok(quote! {
+        #assertions
+        #schema
+        impl ::influx::ToLineProtocol for #name {
+            const PRECISION: ::influx::Precision = #precision;
+
+            fn to_line_protocol_at(&self, timestamp_ns: u128) -> ::influx::LineProtocol {
+                let mut prefix = ::std::string::String::with_capacity(#capacity);
+                #(#stmts)*
+                #module::to_line_protocol(
+                    self,
+                    &::influx::WithContext {
+                        prefix: &prefix,
+                        timestamp: Self::PRECISION.truncate(timestamp_ns),
+                    },
+                )
+            }
+
+            #now_override
+        }
+    })
+}
+
+/// Reject rendered keys that collide on one line: InfluxDB would silently
+/// keep one of the two values. Tags appear on every line, so a tag key
+/// collides with any field key; field keys only collide within their own
+/// measurement group. `reserved` names a key the derive itself emits (the
+/// enum `variant` tag), which no member may reuse.
+pub(crate) fn check_key_collisions(
+    tags: &[&Member],
+    fields: &[&Member],
+    reserved: Option<&str>,
+) -> syn::Result<()> {
+    let mut tag_keys = std::collections::HashSet::new();
+    if let Some(reserved) = reserved {
+        tag_keys.insert(reserved);
+    }
+    for tag in tags {
+        if Some(tag.key.as_str()) == reserved {
+            return Err(syn::Error::new_spanned(
+                &tag.ident,
+                format!("the key {:?} is reserved for the variant tag", tag.key),
+            ));
+        }
+        if !tag_keys.insert(tag.key.as_str()) {
+            return Err(syn::Error::new_spanned(
+                &tag.ident,
+                format!(
+                    "duplicate tag key {:?}; rename one member with #[influx(rename = \"...\")]",
+                    tag.key
+                ),
+            ));
+        }
+    }
+    let mut field_keys = std::collections::HashSet::new();
+    for field in fields {
+        if tag_keys.contains(field.key.as_str()) {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                format!(
+                    "key {:?} is used by both a tag and a field; rename one member with #[influx(rename = \"...\")]",
+                    field.key
+                ),
+            ));
+        }
+        if !field_keys.insert(field.key.as_str()) {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                format!(
+                    "duplicate field key {:?}; rename one member with #[influx(rename = \"...\")]",
+                    field.key
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Statements rendering one line — tags then fields — plus its capacity
+/// estimate. Everything known at compile time (the measurement, the keys,
+/// the separators) is coalesced into static string fragments, so at runtime
+/// only the dynamic values are formatted. With `Option` members in the group
+/// the field separator depends on which members are present, so field
+/// position is tracked in a runtime flag instead.
+fn line_stmts(measurement: &str, tags: &[&Member], fields: &[&Member]) -> (Vec<TokenStream>, usize) {
+    let any_optional = fields.iter().any(|m| m.optional.is_some());
+    let mut stmts = Vec::new();
+    // Static fragments are escaped at expansion time; runtime tag values go
+    // through `influx::escape` when the line is rendered.
+    let mut fragment = crate::escape_measurement(measurement);
+    let mut first_field = true;
+    // Tags precede fields in line protocol regardless of declaration order.
+    for member in tags.iter().chain(fields) {
+        let ident = &member.ident;
+        match member.kind {
+            MemberKind::Tag => {
+                fragment.push(',');
+                fragment.push_str(&crate::escape_key(&member.key));
+                fragment.push('=');
+                stmts.push(quote! {
+                    line.push_str(#fragment);
+                    ::influx::escape::write_tag_value(
+                        &mut line,
+                        &::std::string::ToString::to_string(&self.#ident),
+                    );
+                });
+            }
+            MemberKind::Field if !any_optional => {
+                fragment.push(if first_field { ' ' } else { ',' });
+                first_field = false;
+                fragment.push_str(&crate::escape_key(&member.key));
+                fragment.push('=');
+                stmts.push(quote! {
+                    line.push_str(#fragment);
+                    ::influx::ToFieldValue::write_field_value(&self.#ident, &mut line);
+                });
+            }
+            MemberKind::Field => {
+                if first_field {
+                    first_field = false;
+                    stmts.push(quote! {
+                        let mut first_field = true;
+                    });
+                }
+                let key = crate::escape_key(&member.key);
+                let leading = format!(" {key}=");
+                let following = format!(",{key}=");
+                let write = quote! {
+                    line.push_str(if first_field { #leading } else { #following });
+                    first_field = false;
+                };
+                stmts.push(match &member.optional {
+                    Some(_) => quote! {
+                        if let ::std::option::Option::Some(value) = &self.#ident {
+                            #write
+                            ::influx::ToFieldValue::write_field_value(value, &mut line);
+                        }
+                    },
+                    None => quote! {
+                        #write
+                        ::influx::ToFieldValue::write_field_value(&self.#ident, &mut line);
+                    },
+                });
+            }
+            MemberKind::Timestamp => unreachable!("timestamp members are not rendered"),
+        }
+        fragment.clear();
+    }
+    if any_optional {
+        // Line protocol requires at least one field; all the group's fields
+        // being optional and None has no valid rendering.
+        stmts.push(quote! {
+            debug_assert!(
+                !first_field,
+                "line protocol requires at least one field, but every optional member was None"
+            );
+        });
+    }
+
+    // Static fragments plus a formatting allowance per dynamic value.
+    let member_count = tags.len() + fields.len();
+    let static_len: usize = measurement.len()
+        + tags
+            .iter()
+            .chain(fields)
+            .map(|m| m.key.len() + 2)
+            .sum::<usize>();
+    let capacity = static_len + 16 * (member_count + 1);
+    (stmts, capacity)
+}
+
+/// Parse the container level `#[influx(...)]` attributes.
+pub(crate) fn parse_container_attrs(input: &DeriveInput) -> syn::Result<ContainerAttrs> {
+    let mut attrs = ContainerAttrs::default();
+    for attr in &input.attrs {
+        if !attr.path().is_ident("influx") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("measurement") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.measurement = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("rename_all") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.rename_all = RenameAll::parse(&lit)?;
+                Ok(())
+            } else if meta.path.is_ident("timestamp_precision") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.timestamp_precision = Precision::parse(&lit)?;
+                Ok(())
+            } else if meta.path.is_ident("with") {
+                let lit: LitStr = meta.value()?.parse()?;
+                attrs.with = Some(lit.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported influx container attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+pub(crate) fn parse_members(
+    fields: &syn::FieldsNamed,
+    rename_all: RenameAll,
+) -> syn::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named member");
+        let mut kind = MemberKind::Field;
+        let mut rename = None;
+        let mut measurement = None;
+        let mut unit = None;
+        let mut description = None;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("influx") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag") {
+                    kind = MemberKind::Tag;
+                    Ok(())
+                } else if meta.path.is_ident("field") {
+                    kind = MemberKind::Field;
+                    Ok(())
+                } else if meta.path.is_ident("timestamp") {
+                    kind = MemberKind::Timestamp;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    rename = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("measurement") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    measurement = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("unit") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    unit = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("description") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    description = Some(lit.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported influx member attribute"))
+                }
+            })?;
+        }
+
+        if measurement.is_some() && matches!(kind, MemberKind::Tag) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "tags apply to every measurement group and cannot be routed",
+            ));
+        }
+        if (unit.is_some() || description.is_some()) && matches!(kind, MemberKind::Tag) {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "unit and description describe field values, not tags",
+            ));
+        }
+        if matches!(kind, MemberKind::Timestamp)
+            && (rename.is_some() || measurement.is_some() || unit.is_some() || description.is_some())
+        {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                "a timestamp member is not rendered and takes no other attributes",
+            ));
+        }
+
+        let key = rename.unwrap_or_else(|| rename_all.apply(&ident.to_string()));
+        if !matches!(kind, MemberKind::Timestamp) {
+            crate::check_identifier(&ident, &key)?;
+        }
+        if let Some(measurement) = &measurement {
+            crate::check_identifier(&ident, measurement)?;
+        }
+        // `Option` timestamps are handled by `ToTimestamp`; only field
+        // members get the skip-when-`None` treatment.
+        let optional = match kind {
+            MemberKind::Field => option_inner(&field.ty).cloned(),
+            _ => None,
+        };
+        members.push(Member {
+            ident,
+            ty: field.ty.clone(),
+            kind,
+            key,
+            measurement,
+            unit,
+            description,
+            optional,
+        });
+    }
+    Ok(members)
+}
+
+/// The `T` of an `Option<T>` member type, recognizing the `Option`,
+/// `std::option::Option` and `core::option::Option` spellings.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segments: Vec<String> = path.path.segments.iter().map(|s| s.ident.to_string()).collect();
+    let path_is_option = matches!(
+        segments
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        ["Option"] | ["std" | "core", "option", "Option"]
+    );
+    if !path_is_option {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,171 @@
+//! Derive macro for `influx::ToLineProtocol`.
+//!
+//! ```ignore
+//! #[derive(ToLineProtocol)]
+//! #[influx(measurement = "pressure")]
+//! struct Pressure {
+//!     #[influx(tag)]
+//!     sensor: String,
+//!     value: f64,
+//! }
+//! ```
+//!
+//! Members default to fields; `#[influx(tag)]` marks a member as a tag and
+//! `#[influx(rename = "...")]` overrides the key derived from the member name.
+//! A field-level `#[influx(measurement = "...")]` routes the member to a
+//! different measurement; when members span several measurements the derive
+//! generates `ToLineProtocolEntries` — one line per measurement group, tags
+//! repeated on each — instead of `ToLineProtocol`.
+//!
+//! A field member of type `Option<T>` is rendered when `Some` and skipped
+//! when `None`, for readings that are not present on every line. Line
+//! protocol requires at least one field, so a line whose optional members
+//! are all `None` is a `debug_assert` failure.
+//!
+//! `#[influx(timestamp)]` marks one member (a `u64`/`u128` of epoch
+//! nanoseconds, a `Duration` since the epoch, a `SystemTime`, or an `Option`
+//! of those) as the line protocol timestamp: `to_line_protocol()` uses its
+//! value instead of the serialization time, falling back to now() when the
+//! member cannot supply one. The member is not rendered into the line.
+//!
+//! Enums derive too, for logging heterogeneous events as a single type:
+//! every line carries a `variant` tag naming the active variant, struct-like
+//! variants render their members as for structs, and unit variants render
+//! the marker field `occurred=true`. `#[influx(rename = "...")]` on a
+//! variant overrides the tag value.
+//!
+//! Measurements, keys and tag values are escaped per the line protocol
+//! grammar: static parts at expansion time, runtime tag values through
+//! `influx::escape` when the line is rendered. Names that escaping cannot
+//! fix — empty, reserved (leading `_`) or containing a newline — are
+//! compile errors, as are keys that would collide on one line: two members
+//! rendering to the same key (a tag and a field included, and `variant` in
+//! enums) would make InfluxDB silently keep one of the values. Member types
+//! are checked where they are declared — fields must implement
+//! `ToFieldValue` and tags `Display` — so a wrong type errors on the member
+//! instead of deep inside the generated impl.
+//!
+//! For types whose field mapping is not a plain member-per-field (a
+//! quaternion attitude, a GPS fix), the container attribute
+//! `#[influx(with = "module")]` delegates field rendering to
+//! `module::to_line_protocol(&self, &influx::WithContext)`. The derive still
+//! renders the measurement-and-tags prefix and truncates the timestamp —
+//! both handed over in the context — and annotated schema metadata still
+//! applies; field types are the module's business.
+//!
+//! A second derive, `ToLineProtocolBatch`, is for container structs whose
+//! members already implement `ToLineProtocol` (or `ToLineProtocolEntries`,
+//! marked `#[influx(entries)]`; members left out of the batch carry
+//! `#[influx(skip)]`). It appends every member's lines — newline
+//! terminated, all rendered at one shared timestamp — to a caller provided
+//! buffer, replacing the render-and-concatenate boilerplate of aggregate
+//! frame types.
+//!
+//! The container attribute `#[influx(timestamp_precision = "seconds")]`
+//! (`"nanoseconds"`, `"microseconds"`, `"milliseconds"` or `"seconds"`)
+//! truncates rendered timestamps and sets the impl's `PRECISION` const, which
+//! the write client turns into the matching `precision` query parameter.
+
+mod derive_batch;
+mod derive_enum;
+mod derive_struct;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+#[proc_macro_derive(ToLineProtocol, attributes(influx))]
+pub fn derive_to_line_protocol(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expanded = match &input.data {
+        syn::Data::Struct(data) => derive_struct::derive_struct(&input, data),
+        syn::Data::Enum(data) => derive_enum::derive_enum(&input, data),
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "ToLineProtocol cannot be derived for unions",
+        )),
+    };
+
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToLineProtocolBatch, attributes(influx))]
+pub fn derive_to_line_protocol_batch(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expanded = match &input.data {
+        syn::Data::Struct(data) => derive_batch::derive_batch(&input, data),
+        syn::Data::Enum(_) | syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "ToLineProtocolBatch can only be derived for structs",
+        )),
+    };
+
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Escape a measurement name for a static line fragment: `,` and space.
+///
+/// This deliberately duplicates `influx::escape`: the proc-macro crate
+/// cannot depend on `influx` (which depends on it), and this copy runs at
+/// expansion time on attribute literals while `influx::escape` runs at
+/// serialization time on runtime tag values.
+pub(crate) fn escape_measurement(name: &str) -> String {
+    escape(name, &[',', ' '])
+}
+
+/// Escape a key or a static tag value for a line fragment: `,`, `=` and
+/// space.
+pub(crate) fn escape_key(name: &str) -> String {
+    escape(name, &[',', '=', ' '])
+}
+
+fn escape(name: &str, special: &[char]) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Reject names that escaping cannot fix, mirroring
+/// `influx::escape::check_identifier` — but at expansion time, so a reserved
+/// measurement is a compile error instead of a silently dropped write.
+pub(crate) fn check_identifier<T: quote::ToTokens>(tokens: T, name: &str) -> syn::Result<()> {
+    let reason = if name.is_empty() {
+        "must not be empty"
+    } else if name.starts_with('_') {
+        "names starting with '_' are reserved by InfluxDB"
+    } else if name.contains('\n') {
+        "must not contain a newline"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(
+        tokens,
+        format!("invalid line protocol identifier {name:?}: {reason}"),
+    ))
+}
+
+/// Convert a Rust identifier to the snake_case key used in line protocol.
+pub(crate) fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len());
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
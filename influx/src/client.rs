@@ -0,0 +1,162 @@
+//! Minimal InfluxDB v2 HTTP write client.
+
+use crate::{LineProtocol, Precision, ToLineProtocol};
+
+/// Errors returned by [`Client`] operations.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("influx rejected write: {status}: {body}")]
+    Rejected { status: u16, body: String },
+    /// The request exceeded the client's configured timeout. Distinct from
+    /// [`Http`](Self::Http) so callers can treat a hung endpoint as
+    /// retryable instead of permanent.
+    #[error("request timed out after {after:?}")]
+    Timeout { after: std::time::Duration },
+}
+
+/// An InfluxDB v2 client bound to one organisation and bucket.
+#[derive(Clone, Debug)]
+pub struct Client {
+    url: String,
+    org: String,
+    bucket: String,
+    token: String,
+    http: reqwest::Client,
+    /// Per-request deadline; `None` waits for the server indefinitely.
+    timeout: Option<std::time::Duration>,
+}
+
+impl Client {
+    pub fn new(url: &str, org: &str, bucket: &str, token: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            org: org.to_string(),
+            bucket: bucket.to_string(),
+            token: token.to_string(),
+            http: reqwest::Client::new(),
+            timeout: None,
+        }
+    }
+
+    /// Bound every request by `timeout`, converting a hung endpoint into
+    /// [`ClientError::Timeout`] instead of an indefinite await.
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Map a reqwest error, attributing timeouts to the configured deadline.
+    fn map_error(&self, e: reqwest::Error) -> ClientError {
+        match (e.is_timeout(), self.timeout) {
+            (true, Some(after)) => ClientError::Timeout { after },
+            _ => ClientError::Http(e),
+        }
+    }
+
+    /// Write a batch of line protocol entries in a single request to the
+    /// client's default bucket, with nanosecond timestamps.
+    pub async fn write_batch(&self, entries: &[LineProtocol]) -> Result<(), ClientError> {
+        self.write_batch_to(&self.bucket, entries).await
+    }
+
+    /// Write a batch of line protocol entries in a single request to a
+    /// specific bucket, with nanosecond timestamps.
+    pub async fn write_batch_to(
+        &self,
+        bucket: &str,
+        entries: &[LineProtocol],
+    ) -> Result<(), ClientError> {
+        self.write_batch_with(bucket, Precision::Nanoseconds, entries)
+            .await
+    }
+
+    /// Write entries rendered by `T`, taking the `precision` query parameter
+    /// from [`T::PRECISION`](ToLineProtocol::PRECISION).
+    ///
+    /// A type deriving `#[influx(timestamp_precision = "seconds")]` truncates
+    /// its rendered timestamps through the same const, so the write request
+    /// always declares the precision the lines were rendered at.
+    pub async fn write_batch_as<T: ToLineProtocol>(
+        &self,
+        entries: &[LineProtocol],
+    ) -> Result<(), ClientError> {
+        self.write_batch_with(&self.bucket, T::PRECISION, entries)
+            .await
+    }
+
+    /// Write a batch under an explicit timestamp precision.
+    ///
+    /// Prefer [`write_batch_as`](Self::write_batch_as) when the entries come
+    /// from one derived type; this is the escape hatch for heterogeneous
+    /// batches whose producers all agree on a precision.
+    pub async fn write_batch_with(
+        &self,
+        bucket: &str,
+        precision: Precision,
+        entries: &[LineProtocol],
+    ) -> Result<(), ClientError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let body = entries
+            .iter()
+            .map(|entry| entry.0.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut request = self
+            .http
+            .post(format!("{}/api/v2/write", self.url))
+            .query(&[
+                ("org", self.org.as_str()),
+                ("bucket", bucket),
+                ("precision", precision.query_param()),
+            ])
+            .header("Authorization", format!("Token {}", self.token))
+            .body(body);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await.map_err(|e| self.map_error(e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ClientError::Rejected {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Run a Flux query and return its result as one flat table.
+    pub async fn query(&self, flux: &str) -> Result<crate::query::QueryResult, ClientError> {
+        let mut request = self
+            .http
+            .post(format!("{}/api/v2/query", self.url))
+            .query(&[("org", self.org.as_str())])
+            .header("Authorization", format!("Token {}", self.token))
+            .header("Content-Type", "application/vnd.flux")
+            .header("Accept", "application/csv")
+            .body(flux.to_string());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await.map_err(|e| self.map_error(e))?;
+
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(ClientError::Rejected {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        Ok(crate::query::parse_annotated_csv(&body))
+    }
+}
@@ -0,0 +1,133 @@
+//! Escaping and validation of line protocol identifiers.
+//!
+//! Line protocol delimits its elements with commas, spaces and equals signs,
+//! so those characters must be backslash-escaped when they appear inside a
+//! measurement name, a tag key or value, or a field key. A tag value like
+//! `engine bay` written unescaped does not fail — it silently splits the
+//! line and stores garbage under a truncated name, which is worse.
+//!
+//! Measurements escape `,` and space; keys and tag values additionally
+//! escape `=`. Field *values* are not handled here: strings are quoted by
+//! their [`ToFieldValue`](crate::ToFieldValue) impl and numbers need no
+//! escaping.
+//!
+//! Escaping cannot rescue every name: InfluxDB reserves identifiers starting
+//! with `_` for its own system measurements and silently drops writes to
+//! them, and a newline ends the line no matter what precedes it. Those are
+//! rejected as [`LineProtocolError::InvalidIdentifier`] instead.
+
+use std::borrow::Cow;
+
+/// A name that cannot be rendered into line protocol at all.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LineProtocolError {
+    #[error("invalid line protocol identifier {name:?}: {reason}")]
+    InvalidIdentifier {
+        name: String,
+        reason: &'static str,
+    },
+}
+
+/// Escape a measurement name: `,` and space.
+pub fn measurement(name: &str) -> Cow<'_, str> {
+    escape(name, &[',', ' '])
+}
+
+/// Escape a tag key or field key: `,`, `=` and space.
+pub fn key(name: &str) -> Cow<'_, str> {
+    escape(name, &[',', '=', ' '])
+}
+
+/// Escape a tag value: `,`, `=` and space.
+pub fn tag_value(value: &str) -> Cow<'_, str> {
+    escape(value, &[',', '=', ' '])
+}
+
+/// Append a tag value to a line under construction, escaped.
+///
+/// Generated `ToLineProtocol` impls render runtime tag values through this,
+/// so a tag member whose `Display` output contains a delimiter cannot
+/// corrupt the line.
+pub fn write_tag_value(out: &mut String, value: &str) {
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Check that a name is usable as a line protocol identifier at all.
+///
+/// Rejects what escaping cannot fix; callers escape the name afterwards with
+/// [`measurement`] or [`key`] as appropriate.
+pub fn check_identifier(name: &str) -> Result<(), LineProtocolError> {
+    let reason = if name.is_empty() {
+        "must not be empty"
+    } else if name.starts_with('_') {
+        "names starting with '_' are reserved by InfluxDB"
+    } else if name.contains('\n') {
+        "must not contain a newline"
+    } else {
+        return Ok(());
+    };
+    Err(LineProtocolError::InvalidIdentifier {
+        name: name.to_string(),
+        reason,
+    })
+}
+
+fn escape<'a>(value: &'a str, special: &[char]) -> Cow<'a, str> {
+    if !value.contains(special) {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        if special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimiters_are_backslash_escaped() {
+        assert_eq!(measurement("engine bay"), "engine\\ bay");
+        assert_eq!(measurement("a,b"), "a\\,b");
+        // `=` delimits nothing inside a measurement name.
+        assert_eq!(measurement("a=b"), "a=b");
+        assert_eq!(key("flow rate=x"), "flow\\ rate\\=x");
+        assert_eq!(tag_value("engine bay"), "engine\\ bay");
+    }
+
+    #[test]
+    fn clean_names_borrow() {
+        assert!(matches!(measurement("pressure"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn write_tag_value_matches_tag_value() {
+        let mut out = String::from("bay=");
+        write_tag_value(&mut out, "engine bay");
+        assert_eq!(out, "bay=engine\\ bay");
+    }
+
+    #[test]
+    fn reserved_and_unescapable_names_are_rejected() {
+        assert!(check_identifier("pressure").is_ok());
+        assert_eq!(
+            check_identifier("_internal"),
+            Err(LineProtocolError::InvalidIdentifier {
+                name: "_internal".to_string(),
+                reason: "names starting with '_' are reserved by InfluxDB",
+            })
+        );
+        assert!(check_identifier("").is_err());
+        assert!(check_identifier("a\nb").is_err());
+    }
+}
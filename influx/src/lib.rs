@@ -0,0 +1,611 @@
+//! Construction of InfluxDB line protocol and a minimal InfluxDB v2 write client.
+//!
+//! The traits in this crate are the interface between telemetry types and the
+//! logging pipeline: a type that implements [`ToLineProtocol`] (usually via the
+//! derive macro re-exported from `influx_derive`) renders itself into a single
+//! [`LineProtocol`] entry, frames that carry several optional channels
+//! implement [`ToLineProtocolEntries`] instead.
+//!
+//! The derive re-export and the HTTP client sit behind the default `derive`
+//! and `client` features; consumers that only render line protocol can turn
+//! both off.
+
+#[cfg(feature = "client")]
+pub mod client;
+pub mod escape;
+pub mod query;
+pub mod ser;
+pub mod writer;
+
+#[cfg(feature = "derive")]
+pub use influx_derive::{ToLineProtocol, ToLineProtocolBatch};
+
+use std::fmt;
+use std::fmt::Write;
+
+/// A single entry of InfluxDB line protocol.
+///
+/// The wrapped string is a complete line (measurement, tag set, field set and
+/// timestamp) without a trailing newline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineProtocol(pub String);
+
+impl fmt::Display for LineProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Conversion of a value into an InfluxDB field value literal.
+///
+/// The rendered string carries the type suffix expected by InfluxDB
+/// (`i` for signed integers, `u` for unsigned integers). Every numeric
+/// width is covered — narrower integers widen losslessly into influx's
+/// 64-bit value space — so hardware code logs register-sized values
+/// without casting.
+///
+/// There is no blanket impl for newtypes (it would collide with the
+/// string impls under coherence); a newtype implements the trait by
+/// delegating both methods to its inner value.
+pub trait ToFieldValue {
+    fn to_field_value(&self) -> String;
+
+    /// Append the rendered value to `out` without an intermediate allocation.
+    ///
+    /// Derived `ToLineProtocol` impls build their line through this method;
+    /// the default goes through [`to_field_value`](Self::to_field_value) for
+    /// impls that have no cheaper path.
+    fn write_field_value(&self, out: &mut String) {
+        out.push_str(&self.to_field_value());
+    }
+}
+
+impl ToFieldValue for f64 {
+    fn to_field_value(&self) -> String {
+        format!("{}", self)
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        let _ = write!(out, "{}", self);
+    }
+}
+
+impl ToFieldValue for i64 {
+    fn to_field_value(&self) -> String {
+        format!("{}i", self)
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        let _ = write!(out, "{}i", self);
+    }
+}
+
+impl ToFieldValue for u64 {
+    fn to_field_value(&self) -> String {
+        format!("{}u", self)
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        let _ = write!(out, "{}u", self);
+    }
+}
+
+impl ToFieldValue for f32 {
+    fn to_field_value(&self) -> String {
+        format!("{}", self)
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        let _ = write!(out, "{}", self);
+    }
+}
+
+// The narrower integer widths render exactly as their 64-bit form: influx
+// stores every integer field as 64-bit, and Display is identical after the
+// lossless widening.
+macro_rules! int_field_value {
+    ($suffix:literal => $($ty:ty),+) => {
+        $(impl ToFieldValue for $ty {
+            fn to_field_value(&self) -> String {
+                format!(concat!("{}", $suffix), self)
+            }
+
+            fn write_field_value(&self, out: &mut String) {
+                let _ = write!(out, concat!("{}", $suffix), self);
+            }
+        })+
+    };
+}
+
+int_field_value!("i" => i8, i16, i32, isize);
+int_field_value!("u" => u8, u16, u32, usize);
+
+impl ToFieldValue for bool {
+    fn to_field_value(&self) -> String {
+        format!("{}", self)
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        let _ = write!(out, "{}", self);
+    }
+}
+
+// String field values are wrapped in double quotes with embedded quotes and
+// backslashes escaped, per the line protocol grammar.
+impl ToFieldValue for str {
+    fn to_field_value(&self) -> String {
+        let mut out = String::with_capacity(self.len() + 2);
+        self.write_field_value(&mut out);
+        out
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        out.push('"');
+        for c in self.chars() {
+            if c == '"' || c == '\\' {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('"');
+    }
+}
+
+impl ToFieldValue for &str {
+    fn to_field_value(&self) -> String {
+        (**self).to_field_value()
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        (**self).write_field_value(out);
+    }
+}
+
+impl ToFieldValue for String {
+    fn to_field_value(&self) -> String {
+        self.as_str().to_field_value()
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        self.as_str().write_field_value(out);
+    }
+}
+
+impl ToFieldValue for std::borrow::Cow<'_, str> {
+    fn to_field_value(&self) -> String {
+        self.as_ref().to_field_value()
+    }
+
+    fn write_field_value(&self, out: &mut String) {
+        self.as_ref().write_field_value(out);
+    }
+}
+
+/// Conversion of a struct member into the line protocol timestamp.
+///
+/// Implemented for the types a `#[influx(timestamp)]` member may have. The
+/// value is nanoseconds since the unix epoch; `None` means the member cannot
+/// supply a timestamp right now (an unset `Option`, a pre-epoch
+/// `SystemTime`) and the serialization time is used instead.
+pub trait ToTimestamp {
+    fn timestamp_ns(&self) -> Option<u128>;
+}
+
+impl ToTimestamp for u64 {
+    fn timestamp_ns(&self) -> Option<u128> {
+        Some(*self as u128)
+    }
+}
+
+impl ToTimestamp for u128 {
+    fn timestamp_ns(&self) -> Option<u128> {
+        Some(*self)
+    }
+}
+
+impl ToTimestamp for std::time::Duration {
+    fn timestamp_ns(&self) -> Option<u128> {
+        Some(self.as_nanos())
+    }
+}
+
+impl ToTimestamp for std::time::SystemTime {
+    fn timestamp_ns(&self) -> Option<u128> {
+        self.duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_nanos())
+    }
+}
+
+impl<T: ToTimestamp> ToTimestamp for Option<T> {
+    fn timestamp_ns(&self) -> Option<u128> {
+        self.as_ref().and_then(ToTimestamp::timestamp_ns)
+    }
+}
+
+/// Compile-time description of one line protocol field, captured by the
+/// derive from `#[influx(unit = "...", description = "...")]` member
+/// attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldMeta {
+    /// Measurement the field is written to.
+    pub measurement: &'static str,
+    /// Field key inside the measurement.
+    pub key: &'static str,
+    /// Physical unit; empty when not annotated.
+    pub unit: &'static str,
+    /// Human readable description; empty when not annotated.
+    pub description: &'static str,
+}
+
+/// Const field metadata of a type that renders line protocol.
+///
+/// Implemented by the derive alongside [`ToLineProtocol`]; writers feed the
+/// entries through [`schema_lines_at`] into a companion `schema_fields`
+/// measurement, so dashboard builders can look up what a field means without
+/// asking whoever wrote the producer.
+pub trait FieldSchema {
+    const FIELDS: &'static [FieldMeta];
+}
+
+/// Render field metadata as `schema_fields` lines, one per field, tagged by
+/// measurement and key. Emitted on every startup; `last()` queries see the
+/// current metadata.
+pub fn schema_lines_at(fields: &[FieldMeta], timestamp_ns: u128) -> Vec<LineProtocol> {
+    let quote = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+    fields
+        .iter()
+        .map(|meta| {
+            // Measurement and key ride as tag values of the schema line and
+            // need tag escaping there even though the derive validated them.
+            LineProtocol(format!(
+                "schema_fields,measurement={},field={} unit=\"{}\",description=\"{}\" {}",
+                escape::tag_value(meta.measurement),
+                escape::tag_value(meta.key),
+                quote(meta.unit),
+                quote(meta.description),
+                timestamp_ns
+            ))
+        })
+        .collect()
+}
+
+/// Precision of the integer timestamps carried by rendered lines.
+///
+/// The timestamp trailing a line is a bare integer; InfluxDB interprets it
+/// according to the `precision` query parameter of the write request. A line
+/// rendered at one precision and written under another lands shifted by up
+/// to 10^9 — so the precision a type renders at is declared once, as
+/// [`ToLineProtocol::PRECISION`], and [`client::Client::write_batch_as`]
+/// derives the query parameter from the same value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// Full nanoseconds, the default.
+    #[default]
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+}
+
+impl Precision {
+    /// The value of the write endpoint's `precision` query parameter.
+    pub fn query_param(self) -> &'static str {
+        match self {
+            Self::Nanoseconds => "ns",
+            Self::Microseconds => "us",
+            Self::Milliseconds => "ms",
+            Self::Seconds => "s",
+        }
+    }
+
+    /// Truncate a nanosecond timestamp to this precision.
+    pub fn truncate(self, timestamp_ns: u128) -> u128 {
+        match self {
+            Self::Nanoseconds => timestamp_ns,
+            Self::Microseconds => timestamp_ns / 1_000,
+            Self::Milliseconds => timestamp_ns / 1_000_000,
+            Self::Seconds => timestamp_ns / 1_000_000_000,
+        }
+    }
+}
+
+/// Nanoseconds since the unix epoch, as used for line protocol timestamps.
+pub fn timestamp_now() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system time before unix epoch")
+        .as_nanos()
+}
+
+/// Where timestamps for written lines come from.
+///
+/// The default system clock is wrong in exactly one situation that matters
+/// here: an NTP step mid-test makes the stored timestamps non-monotonic.
+/// [`MonotonicAnchored`](Self::MonotonicAnchored) trades absolute accuracy
+/// for monotonicity by reading the system clock once at construction and
+/// advancing it with the monotonic clock from there; [`External`](Self::External)
+/// defers to per-sample timestamps supplied by the caller (an acquisition
+/// clock) and only falls back to the system clock for samples without one.
+#[derive(Clone, Copy, Debug)]
+pub enum TimestampSource {
+    /// Raw system time at serialization, via [`timestamp_now`].
+    System,
+    /// System time captured once, advanced by the monotonic clock; immune to
+    /// clock steps after construction.
+    MonotonicAnchored {
+        anchor_ns: u128,
+        anchor: std::time::Instant,
+    },
+    /// Per-sample timestamps supplied by the caller.
+    External,
+}
+
+impl TimestampSource {
+    /// A monotonic source anchored to the system clock now.
+    pub fn monotonic_anchored() -> Self {
+        Self::MonotonicAnchored {
+            anchor_ns: timestamp_now(),
+            anchor: std::time::Instant::now(),
+        }
+    }
+
+    /// The timestamp for one line. `external_ns` is the caller's per-sample
+    /// timestamp, used only by [`External`](Self::External); lines without
+    /// one (side-channel events) fall back to the system clock.
+    pub fn timestamp(&self, external_ns: Option<u128>) -> u128 {
+        match self {
+            Self::System => timestamp_now(),
+            Self::MonotonicAnchored { anchor_ns, anchor } => {
+                anchor_ns + anchor.elapsed().as_nanos()
+            }
+            Self::External => external_ns.unwrap_or_else(timestamp_now),
+        }
+    }
+}
+
+/// Context handed to a custom codec module named by the derive's container
+/// attribute `#[influx(with = "module")]`.
+///
+/// The derive keeps the declarative half — the measurement name, the escaped
+/// tag set and the precision-truncated timestamp — and the module renders
+/// only the field set, for types whose field mapping is not a plain
+/// member-per-field (a quaternion attitude, a GPS fix).
+/// [`line`](Self::line) assembles the parts in grammar order.
+pub struct WithContext<'a> {
+    /// Escaped `measurement[,tag=value]...` line prefix.
+    pub prefix: &'a str,
+    /// Timestamp already truncated to the type's [`Precision`].
+    pub timestamp: u128,
+}
+
+impl WithContext<'_> {
+    /// Assemble the line around a rendered `key=value[,key=value]...` field
+    /// set.
+    pub fn line(&self, fields: &str) -> LineProtocol {
+        LineProtocol(format!("{} {} {}", self.prefix, fields, self.timestamp))
+    }
+}
+
+/// Conversion of a value into a single line protocol entry.
+pub trait ToLineProtocol {
+    /// The precision the rendered timestamp is truncated to.
+    ///
+    /// Overridden by the derive's `#[influx(timestamp_precision = "...")]`
+    /// container attribute; the generated code truncates through this same
+    /// const, so the rendered lines and the write request's `precision`
+    /// parameter (see [`client::Client::write_batch_as`]) cannot disagree.
+    const PRECISION: Precision = Precision::Nanoseconds;
+
+    /// Render with an explicit timestamp in nanoseconds since the epoch.
+    ///
+    /// This is the primitive: batched data should carry its acquisition
+    /// timestamp instead of the serialization time, and tests need
+    /// deterministic output.
+    fn to_line_protocol_at(&self, timestamp_ns: u128) -> LineProtocol;
+
+    /// Render with the current system time as the timestamp.
+    fn to_line_protocol(&self) -> LineProtocol {
+        self.to_line_protocol_at(timestamp_now())
+    }
+}
+
+/// How a frame that renders to no entries is reported.
+///
+/// A frame with every optional member `None` produces an empty Vec, and a
+/// dead data path then writes nothing — silently. Callers that need to tell
+/// "nothing to log" apart from "nothing arriving" pick a policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullPolicy {
+    /// Return the empty Vec unchanged.
+    #[default]
+    Skip,
+    /// Emit a single `heartbeat no_data=true` line instead, so the write
+    /// path stays visibly alive in the stored data.
+    Heartbeat,
+    /// Return [`NoEntries`].
+    Error,
+}
+
+/// A value rendered to no line protocol entries under [`NullPolicy::Error`].
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("value produced no line protocol entries")]
+pub struct NoEntries;
+
+/// Conversion of a value into zero or more line protocol entries.
+///
+/// Implemented by frame types whose members map to separate measurements,
+/// where any subset of members may be populated.
+pub trait ToLineProtocolEntries {
+    /// The precision rendered timestamps are truncated to; see
+    /// [`ToLineProtocol::PRECISION`].
+    const PRECISION: Precision = Precision::Nanoseconds;
+
+    /// Render with an explicit timestamp in nanoseconds since the epoch.
+    fn to_line_protocol_entries_at(&self, timestamp_ns: u128) -> Vec<LineProtocol>;
+
+    /// Render with the current system time as the timestamp.
+    fn to_line_protocol_entries(&self) -> Vec<LineProtocol> {
+        self.to_line_protocol_entries_at(timestamp_now())
+    }
+
+    /// Render with an explicit timestamp, applying `policy` when the value
+    /// produces no entries.
+    fn to_line_protocol_entries_at_with(
+        &self,
+        timestamp_ns: u128,
+        policy: NullPolicy,
+    ) -> Result<Vec<LineProtocol>, NoEntries> {
+        let entries = self.to_line_protocol_entries_at(timestamp_ns);
+        if !entries.is_empty() {
+            return Ok(entries);
+        }
+        match policy {
+            NullPolicy::Skip => Ok(entries),
+            NullPolicy::Heartbeat => Ok(vec![LineProtocol(format!(
+                "heartbeat no_data=true {timestamp_ns}"
+            ))]),
+            NullPolicy::Error => Err(NoEntries),
+        }
+    }
+}
+
+/// Conversion of a container of renderable members into one newline
+/// terminated batch.
+///
+/// Implemented (usually via the `ToLineProtocolBatch` derive) by top-level
+/// frames that aggregate several sensor structs, where rendering each member
+/// and concatenating by hand repeats at every call site. Every member is
+/// rendered at the same timestamp — each truncating to its own
+/// [`PRECISION`] — and the lines are appended to the caller's buffer, one
+/// per entry with a trailing newline, ready for the write endpoint's
+/// newline separated body.
+///
+/// [`PRECISION`]: ToLineProtocol::PRECISION
+pub trait ToLineProtocolBatch {
+    /// Append every member's lines to `buf` with an explicit shared
+    /// timestamp in nanoseconds since the epoch.
+    fn write_batch_at(&self, buf: &mut String, timestamp_ns: u128);
+
+    /// Append with the current system time as the shared timestamp.
+    fn write_batch(&self, buf: &mut String) {
+        self.write_batch_at(buf, timestamp_now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_value_suffixes() {
+        assert_eq!(1.5_f64.to_field_value(), "1.5");
+        assert_eq!(3_i64.to_field_value(), "3i");
+        assert_eq!(3_u64.to_field_value(), "3u");
+        assert_eq!(true.to_field_value(), "true");
+    }
+
+    #[test]
+    fn narrow_numeric_widths_render_as_their_wide_form() {
+        assert_eq!(1.5_f32.to_field_value(), "1.5");
+        assert_eq!((-128_i8).to_field_value(), "-128i");
+        assert_eq!(i16::MIN.to_field_value(), "-32768i");
+        assert_eq!(7_i32.to_field_value(), "7i");
+        assert_eq!((-2_isize).to_field_value(), "-2i");
+        assert_eq!(u8::MAX.to_field_value(), "255u");
+        assert_eq!(9_u16.to_field_value(), "9u");
+        assert_eq!(u32::MAX.to_field_value(), "4294967295u");
+        assert_eq!(11_usize.to_field_value(), "11u");
+    }
+
+    #[test]
+    fn string_field_values_are_quoted_and_escaped() {
+        assert_eq!("plain".to_field_value(), "\"plain\"");
+        assert_eq!(
+            r#"say "hi" \ bye"#.to_field_value(),
+            r#""say \"hi\" \\ bye""#
+        );
+        assert_eq!(String::from("owned").to_field_value(), "\"owned\"");
+        assert_eq!(
+            std::borrow::Cow::Borrowed("cow").to_field_value(),
+            "\"cow\""
+        );
+    }
+
+    #[test]
+    fn write_field_value_matches_to_field_value() {
+        let mut out = String::from("x=");
+        3_i64.write_field_value(&mut out);
+        assert_eq!(out, "x=3i");
+    }
+
+    #[test]
+    fn precision_truncation_matches_its_query_param() {
+        assert_eq!(Precision::Nanoseconds.truncate(1_500_000_000), 1_500_000_000);
+        assert_eq!(Precision::Microseconds.truncate(1_500_000_000), 1_500_000);
+        assert_eq!(Precision::Milliseconds.truncate(1_500_000_000), 1_500);
+        assert_eq!(Precision::Seconds.truncate(1_500_000_000), 1);
+        assert_eq!(Precision::Seconds.query_param(), "s");
+        assert_eq!(Precision::default().query_param(), "ns");
+    }
+
+    #[test]
+    fn timestamp_sources() {
+        // Monotonic stamps never go backwards and stay anchored near the
+        // system clock (no step can happen inside a test run).
+        let monotonic = TimestampSource::monotonic_anchored();
+        let a = monotonic.timestamp(None);
+        let b = monotonic.timestamp(None);
+        assert!(b >= a);
+        assert!(timestamp_now().abs_diff(b) < 1_000_000_000);
+
+        // External passes the caller's timestamp through, falling back to
+        // the system clock when a line carries none.
+        let external = TimestampSource::External;
+        assert_eq!(external.timestamp(Some(42)), 42);
+        assert!(external.timestamp(None) > 0);
+
+        // System ignores per-sample timestamps entirely.
+        assert_ne!(TimestampSource::System.timestamp(Some(42)), 42);
+    }
+
+    /// Renders one entry per wrapped value.
+    struct Sparse(Vec<f64>);
+
+    impl ToLineProtocolEntries for Sparse {
+        fn to_line_protocol_entries_at(&self, timestamp_ns: u128) -> Vec<LineProtocol> {
+            self.0
+                .iter()
+                .map(|v| LineProtocol(format!("m value={v} {timestamp_ns}")))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn null_policy_applies_only_to_empty_renders() {
+        let populated = Sparse(vec![1.0]);
+        let empty = Sparse(vec![]);
+
+        assert_eq!(
+            populated
+                .to_line_protocol_entries_at_with(1, NullPolicy::Error)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(empty
+            .to_line_protocol_entries_at_with(1, NullPolicy::Skip)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            empty
+                .to_line_protocol_entries_at_with(1, NullPolicy::Heartbeat)
+                .unwrap(),
+            vec![LineProtocol("heartbeat no_data=true 1".into())]
+        );
+        assert_eq!(
+            empty.to_line_protocol_entries_at_with(1, NullPolicy::Error),
+            Err(NoEntries)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Parsing of Flux query results.
+//!
+//! `/api/v2/query` answers in annotated CSV; this module reduces that to a
+//! plain table of strings, which is all the ad-hoc query UI needs. Typed
+//! decoding of result columns is left to callers that know their query.
+
+/// A Flux query result as one flat table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Parse the annotated CSV body of a query response.
+///
+/// Annotation lines (`#datatype`, `#group`, `#default`) are dropped, the
+/// first remaining line becomes the header and later headers (one per result
+/// table) are skipped, flattening multi-table results. The leading unnamed
+/// annotation column is stripped.
+pub fn parse_annotated_csv(body: &str) -> QueryResult {
+    let mut result = QueryResult::default();
+    for line in body.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut cells = split_csv_line(line);
+        if cells.first().is_some_and(|c| c.is_empty()) {
+            cells.remove(0);
+        }
+        if result.columns.is_empty() {
+            result.columns = cells;
+        } else if cells != result.columns {
+            result.rows.push(cells);
+        }
+    }
+    result
+}
+
+/// Split one CSV line, honouring double-quoted cells with `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_annotated_csv_into_one_table() {
+        let body = "\
+#datatype,string,long,double\n\
+#group,false,false,false\n\
+#default,_result,,\n\
+,result,table,_value\n\
+,,0,1.5\n\
+,,0,\"a,\"\"b\"\"\"\n\
+\n\
+,result,table,_value\n\
+,,1,2.5\n";
+        let result = parse_annotated_csv(body);
+        assert_eq!(result.columns, ["result", "table", "_value"]);
+        assert_eq!(result.rows.len(), 3);
+        assert_eq!(result.rows[1], ["", "0", "a,\"b\""]);
+        assert_eq!(result.rows[2], ["", "1", "2.5"]);
+    }
+}
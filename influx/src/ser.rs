@@ -0,0 +1,433 @@
+//! A `serde` serializer that emits line protocol.
+//!
+//! For types we own, the [`ToLineProtocol`](crate::ToLineProtocol) derive is
+//! the right tool; this module covers the rest — third-party structs or
+//! one-off types where adding the derive and its attributes is impractical.
+//! Struct members become fields, the members named in [`Options::tags`]
+//! become tags, and nested structs flatten into dotted field names.
+//!
+//! ```
+//! use influx::ser::{to_line_protocol, Options};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Sample {
+//!     bank: u8,
+//!     pressure: f64,
+//! }
+//!
+//! let line = to_line_protocol(
+//!     &Sample { bank: 2, pressure: 12.5 },
+//!     &Options { measurement: "engine", tags: &["bank"], timestamp: Some(1) },
+//! )
+//! .unwrap();
+//! assert_eq!(line.0, "engine,bank=2 pressure=12.5 1");
+//! ```
+
+use crate::{LineProtocol, ToFieldValue};
+use serde::ser::{self, Impossible, Serialize};
+use std::fmt::Write;
+
+/// Errors produced while serializing a value into line protocol.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The value contains something line protocol cannot carry (sequences,
+    /// enum variants with data, byte strings).
+    #[error("{0} cannot be represented in line protocol")]
+    Unsupported(&'static str),
+    /// The value serialized to no field members; a line without fields is
+    /// invalid.
+    #[error("value produced no fields")]
+    NoFields,
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+/// How a value is mapped onto a line.
+pub struct Options<'a> {
+    /// Measurement name of the emitted line.
+    pub measurement: &'a str,
+    /// Member names serialized as tags instead of fields; nested members are
+    /// named by their dotted path.
+    pub tags: &'a [&'a str],
+    /// Timestamp in nanoseconds; `None` stamps the line with now.
+    pub timestamp: Option<u128>,
+}
+
+/// Serialize `value` into a single line protocol entry.
+pub fn to_line_protocol<T: Serialize>(
+    value: &T,
+    options: &Options<'_>,
+) -> Result<LineProtocol, Error> {
+    let mut collected = Collected::default();
+    value.serialize(ValueSerializer {
+        options,
+        path: String::new(),
+        out: &mut collected,
+    })?;
+    if collected.fields.is_empty() {
+        return Err(Error::NoFields);
+    }
+
+    let mut line = String::from(options.measurement);
+    for (key, value) in &collected.tags {
+        let _ = write!(line, ",{key}={value}");
+    }
+    line.push(' ');
+    for (i, (key, value)) in collected.fields.iter().enumerate() {
+        if i > 0 {
+            line.push(',');
+        }
+        let _ = write!(line, "{key}={value}");
+    }
+    let _ = write!(
+        line,
+        " {}",
+        options.timestamp.unwrap_or_else(crate::timestamp_now)
+    );
+    Ok(LineProtocol(line))
+}
+
+/// Tag and field members gathered during serialization, in declaration order.
+#[derive(Default)]
+struct Collected {
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, String)>,
+}
+
+impl Collected {
+    fn record(&mut self, options: &Options<'_>, path: &str, rendered: String, is_string: bool) -> Result<(), Error> {
+        if options.tags.contains(&path) {
+            self.tags.push((path.to_string(), rendered));
+        } else if is_string {
+            // Strings become quoted field values with the same escaping as
+            // the `ToFieldValue` impls for `str` and friends.
+            self.fields.push((path.to_string(), rendered.as_str().to_field_value()));
+        } else {
+            self.fields.push((path.to_string(), rendered));
+        }
+        Ok(())
+    }
+}
+
+/// Serializes one member (or, with an empty path, the top-level value).
+struct ValueSerializer<'a, 'o> {
+    options: &'o Options<'o>,
+    /// Dotted path of the member being serialized; empty at top level.
+    path: String,
+    out: &'a mut Collected,
+}
+
+impl<'a, 'o> ValueSerializer<'a, 'o> {
+    fn record(self, rendered: String, is_string: bool) -> Result<(), Error> {
+        if self.path.is_empty() {
+            return Err(Error::Unsupported("a non-struct top-level value"));
+        }
+        self.out.record(self.options, &self.path, rendered, is_string)
+    }
+}
+
+macro_rules! serialize_number {
+    ($method:ident, $ty:ty, $suffix:literal) => {
+        fn $method(self, v: $ty) -> Result<(), Error> {
+            // Tags carry the bare value; the type suffix is a field notion.
+            let rendered = if self.options.tags.contains(&self.path.as_str()) {
+                v.to_string()
+            } else {
+                format!(concat!("{}", $suffix), v)
+            };
+            self.record(rendered, false)
+        }
+    };
+}
+
+impl<'a, 'o> ser::Serializer for ValueSerializer<'a, 'o> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = StructSerializer<'a, 'o>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_number!(serialize_i8, i8, "i");
+    serialize_number!(serialize_i16, i16, "i");
+    serialize_number!(serialize_i32, i32, "i");
+    serialize_number!(serialize_i64, i64, "i");
+    serialize_number!(serialize_u8, u8, "u");
+    serialize_number!(serialize_u16, u16, "u");
+    serialize_number!(serialize_u32, u32, "u");
+    serialize_number!(serialize_u64, u64, "u");
+    serialize_number!(serialize_f32, f32, "");
+    serialize_number!(serialize_f64, f64, "");
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.record(v.to_string(), false)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.record(v.to_string(), true)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.record(v.to_string(), true)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+        Err(Error::Unsupported("byte strings"))
+    }
+
+    /// A `None` member is simply absent from the line, like an unpopulated
+    /// channel in a frame.
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Err(Error::Unsupported("unit values"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Err(Error::Unsupported("unit structs"))
+    }
+
+    /// Unit enum variants render as their name, usable as tags.
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.record(variant.to_string(), true)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(Error::Unsupported("sequences"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(Error::Unsupported("tuples"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::Unsupported("tuple structs"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::Unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::Unsupported("maps"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        let prefix = if self.path.is_empty() {
+            String::new()
+        } else {
+            format!("{}.", self.path)
+        };
+        Ok(StructSerializer {
+            options: self.options,
+            prefix,
+            out: self.out,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::Unsupported("enum variants with data"))
+    }
+}
+
+/// Walks a struct's members, nesting into dotted paths.
+struct StructSerializer<'a, 'o> {
+    options: &'o Options<'o>,
+    prefix: String,
+    out: &'a mut Collected,
+}
+
+impl ser::SerializeStruct for StructSerializer<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(ValueSerializer {
+            options: self.options,
+            path: format!("{}{}", self.prefix, key),
+            out: self.out,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Gps {
+        fix: bool,
+        satellites: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Frame {
+        bank: u8,
+        pressure: f64,
+        current: Option<f64>,
+        gps: Gps,
+    }
+
+    fn frame() -> Frame {
+        Frame {
+            bank: 2,
+            pressure: 12.5,
+            current: None,
+            gps: Gps {
+                fix: true,
+                satellites: 7,
+            },
+        }
+    }
+
+    #[test]
+    fn structs_serialize_with_tags_and_dotted_nesting() {
+        let line = to_line_protocol(
+            &frame(),
+            &Options {
+                measurement: "engine",
+                tags: &["bank"],
+                timestamp: Some(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            line.0,
+            "engine,bank=2 pressure=12.5,gps.fix=true,gps.satellites=7u 1"
+        );
+    }
+
+    #[test]
+    fn string_members_are_bare_tags_or_quoted_fields() {
+        #[derive(Serialize)]
+        struct Named {
+            name: &'static str,
+            value: f64,
+        }
+        let named = Named {
+            name: "ox",
+            value: 1.0,
+        };
+        let line = to_line_protocol(
+            &named,
+            &Options {
+                measurement: "m",
+                tags: &["name"],
+                timestamp: Some(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(line.0, "m,name=ox value=1 1");
+
+        let line = to_line_protocol(
+            &Named {
+                name: r#"ox "A""#,
+                value: 1.0,
+            },
+            &Options {
+                measurement: "m",
+                tags: &[],
+                timestamp: Some(1),
+            },
+        )
+        .unwrap();
+        assert_eq!(line.0, r#"m name="ox \"A\"",value=1 1"#);
+    }
+
+    #[test]
+    fn fieldless_values_are_rejected() {
+        #[derive(Serialize)]
+        struct OnlyTags {
+            bank: u8,
+        }
+        let err = to_line_protocol(
+            &OnlyTags { bank: 1 },
+            &Options {
+                measurement: "m",
+                tags: &["bank"],
+                timestamp: Some(1),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::NoFields));
+
+        let err = to_line_protocol(
+            &1.0_f64,
+            &Options {
+                measurement: "m",
+                tags: &[],
+                timestamp: Some(1),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+}
@@ -0,0 +1,260 @@
+//! Accumulation of line protocol entries under a flush policy.
+//!
+//! Writing every line in its own request would swamp the endpoint; holding
+//! lines until a batch fills means a quiet producer's entries sit unwritten
+//! indefinitely. [`BatchWriter`] buffers entries and reports a flush as due
+//! when either the batch size is reached or the flush interval has elapsed
+//! with lines waiting, whichever comes first.
+//!
+//! The writer does not perform the write itself: callers run their own
+//! filtering and routing between [`take`](BatchWriter::take) and the client,
+//! and async callers can drive the interval policy from
+//! [`until_due`](BatchWriter::until_due) without the writer owning a timer.
+//!
+//! The writer can also enforce per-series timestamp monotonicity
+//! ([`with_monotonic`](BatchWriter::with_monotonic)): an NTP step or
+//! scheduler hiccup occasionally stamps a line at or before its
+//! predecessor, which InfluxDB accepts — equal timestamps silently
+//! overwrite — but Flux windowing downstream mishandles. Offending
+//! timestamps are bumped one unit past the series' last seen value and the
+//! corrections counted.
+
+use crate::LineProtocol;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Buffers line protocol entries and decides when they should be written.
+pub struct BatchWriter {
+    buffer: Vec<LineProtocol>,
+    batch_size: usize,
+    flush_interval: Duration,
+    max_buffered: usize,
+    /// When the buffer last became empty; anchors the interval policy.
+    last_flush: Instant,
+    /// A spent batch handed back by [`recycle`](Self::recycle), reused as
+    /// the next buffer so steady-state batching does not allocate.
+    spare: Vec<LineProtocol>,
+    dropped: u64,
+    /// Last timestamp per series key (measurement plus tag set), kept across
+    /// batches; `None` disables enforcement.
+    monotonic: Option<HashMap<String, u128>>,
+    corrected: u64,
+}
+
+impl BatchWriter {
+    /// A writer flushing after `batch_size` lines or `flush_interval`,
+    /// whichever comes first, and holding at most `max_buffered` lines.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is zero or exceeds `max_buffered`.
+    pub fn new(batch_size: usize, flush_interval: Duration, max_buffered: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be non-zero");
+        assert!(
+            batch_size <= max_buffered,
+            "batch size must not exceed the buffer capacity"
+        );
+        Self {
+            buffer: Vec::with_capacity(batch_size),
+            batch_size,
+            flush_interval,
+            max_buffered,
+            last_flush: Instant::now(),
+            spare: Vec::new(),
+            dropped: 0,
+            monotonic: None,
+            corrected: 0,
+        }
+    }
+
+    /// Enable or disable per-series timestamp monotonicity enforcement.
+    ///
+    /// A line stamped at or before its series' previous timestamp is bumped
+    /// one unit past it — one nanosecond at nanosecond precision — keeping
+    /// the series strictly increasing while preserving arrival order.
+    pub fn with_monotonic(mut self, enabled: bool) -> Self {
+        self.monotonic = enabled.then(HashMap::new);
+        self
+    }
+
+    /// Buffer one entry. At capacity — the endpoint has been unreachable for
+    /// longer than the buffer covers — the entry is dropped and counted
+    /// rather than growing without bound.
+    pub fn push(&mut self, mut line: LineProtocol) {
+        if self.buffer.len() >= self.max_buffered {
+            self.dropped += 1;
+            return;
+        }
+        if let Some(last_by_series) = &mut self.monotonic {
+            self.corrected += enforce_monotonic(&mut line, last_by_series) as u64;
+        }
+        self.buffer.push(line);
+    }
+
+    /// Buffer several entries; see [`push`](Self::push).
+    pub fn extend(&mut self, lines: impl IntoIterator<Item = LineProtocol>) {
+        for line in lines {
+            self.push(line);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Entries dropped at capacity since construction.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Timestamps bumped by monotonicity enforcement since construction.
+    pub fn corrected(&self) -> u64 {
+        self.corrected
+    }
+
+    /// Whether a flush is due under either policy.
+    pub fn due(&self) -> bool {
+        self.buffer.len() >= self.batch_size
+            || (!self.buffer.is_empty() && self.last_flush.elapsed() >= self.flush_interval)
+    }
+
+    /// Time until the interval policy makes the buffer due; the full
+    /// interval while the buffer is empty, zero when already due.
+    pub fn until_due(&self) -> Duration {
+        if self.buffer.is_empty() {
+            return self.flush_interval;
+        }
+        self.flush_interval.saturating_sub(self.last_flush.elapsed())
+    }
+
+    /// Take every buffered entry for writing and restart the interval. The
+    /// replacement buffer is the last recycled batch where one exists.
+    pub fn take(&mut self) -> Vec<LineProtocol> {
+        self.last_flush = Instant::now();
+        std::mem::replace(&mut self.buffer, std::mem::take(&mut self.spare))
+    }
+
+    /// Hand a written-out batch back so its allocation backs the next one.
+    pub fn recycle(&mut self, mut batch: Vec<LineProtocol>) {
+        batch.clear();
+        self.spare = batch;
+    }
+}
+
+/// Bump the line's timestamp one unit past its series' last seen value when
+/// it is equal or backwards; returns whether a correction was made. Lines
+/// without a parseable trailing timestamp pass through untouched.
+fn enforce_monotonic(line: &mut LineProtocol, last_by_series: &mut HashMap<String, u128>) -> bool {
+    let Some((body, stamp)) = line.0.rsplit_once(' ') else {
+        return false;
+    };
+    let Ok(timestamp) = stamp.parse::<u128>() else {
+        return false;
+    };
+    // The series key is the measurement and tag set: everything before the
+    // first unescaped space.
+    let series = body.split(' ').next().unwrap_or(body);
+    match last_by_series.get_mut(series) {
+        Some(last) if timestamp <= *last => {
+            *last += 1;
+            line.0 = format!("{} {}", body, *last);
+            true
+        }
+        Some(last) => {
+            *last = timestamp;
+            false
+        }
+        None => {
+            last_by_series.insert(series.to_string(), timestamp);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(n: usize) -> LineProtocol {
+        LineProtocol(format!("m value={n} {n}"))
+    }
+
+    #[test]
+    fn size_policy_makes_a_full_batch_due() {
+        let mut writer = BatchWriter::new(2, Duration::from_secs(60), 10);
+        assert!(!writer.due());
+        writer.push(line(1));
+        assert!(!writer.due());
+        writer.push(line(2));
+        assert!(writer.due());
+        assert_eq!(writer.take(), vec![line(1), line(2)]);
+        assert!(!writer.due());
+    }
+
+    #[test]
+    fn interval_policy_makes_a_partial_batch_due() {
+        let mut writer = BatchWriter::new(100, Duration::ZERO, 1000);
+        // An empty buffer is never due, however long it sits.
+        assert!(!writer.due());
+        assert_eq!(writer.until_due(), Duration::ZERO);
+        writer.push(line(1));
+        assert!(writer.due());
+        assert_eq!(writer.take().len(), 1);
+    }
+
+    #[test]
+    fn capacity_drops_and_counts_overflow() {
+        let mut writer = BatchWriter::new(1, Duration::from_secs(60), 2);
+        writer.extend([line(1), line(2), line(3)]);
+        assert_eq!(writer.len(), 2);
+        assert_eq!(writer.dropped(), 1);
+        assert_eq!(writer.take(), vec![line(1), line(2)]);
+    }
+
+    #[test]
+    fn recycled_batches_back_the_next_buffer() {
+        let mut writer = BatchWriter::new(2, Duration::from_secs(60), 10);
+        writer.extend([line(1), line(2)]);
+        let batch = writer.take();
+        let capacity = batch.capacity();
+        writer.recycle(batch);
+        writer.push(line(3));
+        assert_eq!(writer.take(), vec![line(3)]);
+        assert!(capacity >= 2);
+    }
+
+    #[test]
+    fn monotonicity_bumps_equal_and_backwards_timestamps_per_series() {
+        let mut writer =
+            BatchWriter::new(100, Duration::from_secs(60), 1000).with_monotonic(true);
+        writer.push(LineProtocol("pressure value=1 100".to_string()));
+        // Equal and backwards stamps step one past the series' last value.
+        writer.push(LineProtocol("pressure value=2 100".to_string()));
+        writer.push(LineProtocol("pressure value=3 99".to_string()));
+        // Another series is tracked independently.
+        writer.push(LineProtocol("valve,actuator=main state=true 50".to_string()));
+        assert_eq!(writer.corrected(), 2);
+        assert_eq!(
+            writer.take(),
+            vec![
+                LineProtocol("pressure value=1 100".to_string()),
+                LineProtocol("pressure value=2 101".to_string()),
+                LineProtocol("pressure value=3 102".to_string()),
+                LineProtocol("valve,actuator=main state=true 50".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn monotonicity_is_off_by_default() {
+        let mut writer = BatchWriter::new(100, Duration::from_secs(60), 1000);
+        writer.push(LineProtocol("pressure value=1 100".to_string()));
+        writer.push(LineProtocol("pressure value=2 50".to_string()));
+        assert_eq!(writer.corrected(), 0);
+        assert_eq!(writer.take()[1], LineProtocol("pressure value=2 50".to_string()));
+    }
+}
@@ -0,0 +1,356 @@
+//! Behavioral tests for the `ToLineProtocol` derive macro.
+
+#![cfg(feature = "derive")]
+
+use influx::{FieldSchema, ToLineProtocol, ToLineProtocolBatch, ToLineProtocolEntries};
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "engine")]
+struct Basic {
+    #[influx(tag)]
+    bank: i64,
+    chamber_pressure: f64,
+    #[influx(rename = "igniter-current")]
+    igniter_current: f64,
+}
+
+#[test]
+fn tags_fields_and_renames() {
+    let line = Basic {
+        bank: 2,
+        chamber_pressure: 12.5,
+        igniter_current: 1.25,
+    }
+    .to_line_protocol_at(1);
+    assert_eq!(
+        line.0,
+        "engine,bank=2 chamber_pressure=12.5,igniter-current=1.25 1"
+    );
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "ambient", rename_all = "kebab-case")]
+#[allow(non_snake_case)]
+struct KebabCased {
+    outsideTemp: f64,
+    wind_speed: f64,
+    #[influx(rename = "rh")]
+    relative_humidity: f64,
+}
+
+#[test]
+fn rename_all_kebab_case() {
+    let line = KebabCased {
+        outsideTemp: 21.0,
+        wind_speed: 3.5,
+        relative_humidity: 40.0,
+    }
+    .to_line_protocol_at(1);
+    // Explicit renames win over the container convention.
+    assert_eq!(line.0, "ambient outside-temp=21,wind-speed=3.5,rh=40 1");
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "engine")]
+struct Annotated {
+    #[influx(tag)]
+    bank: i64,
+    #[influx(unit = "bar", description = "Chamber pressure at the injector")]
+    chamber_pressure: f64,
+    flow: f64,
+}
+
+#[test]
+fn field_metadata_is_captured_as_const_schema() {
+    assert_eq!(Annotated::FIELDS.len(), 2);
+    let meta = &Annotated::FIELDS[0];
+    assert_eq!(meta.measurement, "engine");
+    assert_eq!(meta.key, "chamber_pressure");
+    assert_eq!(meta.unit, "bar");
+    assert_eq!(meta.description, "Chamber pressure at the injector");
+    // Unannotated fields still appear, with empty metadata.
+    assert_eq!(Annotated::FIELDS[1].key, "flow");
+    assert_eq!(Annotated::FIELDS[1].unit, "");
+
+    let lines = influx::schema_lines_at(Annotated::FIELDS, 1);
+    assert_eq!(
+        lines[0].0,
+        "schema_fields,measurement=engine,field=chamber_pressure \
+         unit=\"bar\",description=\"Chamber pressure at the injector\" 1"
+    );
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "feed_system")]
+struct Grouped {
+    #[influx(tag)]
+    stand: i64,
+    pressure: f64,
+    #[influx(measurement = "ambient")]
+    outside_temp: f64,
+    #[influx(measurement = "ambient")]
+    wind_speed: f64,
+    flow: f64,
+}
+
+#[test]
+fn field_level_measurements_produce_one_line_per_group() {
+    let entries = Grouped {
+        stand: 1,
+        pressure: 12.5,
+        outside_temp: 21.0,
+        wind_speed: 3.5,
+        flow: 0.8,
+    }
+    .to_line_protocol_entries_at(1);
+    // Groups appear in first-appearance order, tags repeated on each line.
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].0, "feed_system,stand=1 pressure=12.5,flow=0.8 1");
+    assert_eq!(entries[1].0, "ambient,stand=1 outside_temp=21,wind_speed=3.5 1");
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "daily_totals", timestamp_precision = "seconds")]
+struct Coarse {
+    total: f64,
+}
+
+#[test]
+fn timestamp_precision_truncates_and_sets_the_const() {
+    // The rendered timestamp and the advertised precision come from the same
+    // const, so the write client's `precision` parameter cannot drift.
+    assert_eq!(Coarse::PRECISION, influx::Precision::Seconds);
+    let line = Coarse { total: 3.0 }.to_line_protocol_at(1_500_000_000);
+    assert_eq!(line.0, "daily_totals total=3 1");
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "events")]
+enum Event {
+    IgniterArmed,
+    #[influx(rename = "abort")]
+    AbortCommanded,
+    ValveMoved {
+        #[influx(tag)]
+        valve: String,
+        open: bool,
+        #[influx(unit = "ms")]
+        travel_ms: f64,
+    },
+}
+
+#[test]
+fn enum_variants_share_one_measurement_under_a_variant_tag() {
+    // Unit variants carry only the marker field.
+    assert_eq!(
+        Event::IgniterArmed.to_line_protocol_at(1).0,
+        "events,variant=igniter_armed occurred=true 1"
+    );
+    // A variant-level rename overrides the tag value.
+    assert_eq!(
+        Event::AbortCommanded.to_line_protocol_at(2).0,
+        "events,variant=abort occurred=true 2"
+    );
+    // Struct-like variants render their own tags and fields.
+    assert_eq!(
+        Event::ValveMoved {
+            valve: "main".to_string(),
+            open: true,
+            travel_ms: 85.0,
+        }
+        .to_line_protocol_at(3)
+        .0,
+        "events,variant=valve_moved,valve=main open=true,travel_ms=85 3"
+    );
+    // Schema metadata covers every variant's field members.
+    assert_eq!(Event::FIELDS.len(), 2);
+    assert_eq!(Event::FIELDS[1].unit, "ms");
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "burst_sample")]
+struct Stamped {
+    value: f64,
+    #[influx(timestamp)]
+    acquired_ns: u64,
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "burst_sample")]
+struct MaybeStamped {
+    value: f64,
+    #[influx(timestamp)]
+    acquired: Option<std::time::SystemTime>,
+}
+
+#[test]
+fn timestamp_members_replace_serialization_time() {
+    // The member's value is the timestamp; it is not rendered into the line.
+    let line = Stamped {
+        value: 1.5,
+        acquired_ns: 42,
+    }
+    .to_line_protocol();
+    assert_eq!(line.0, "burst_sample value=1.5 42");
+
+    // The explicit primitive still honors the caller's timestamp.
+    let line = Stamped {
+        value: 1.5,
+        acquired_ns: 42,
+    }
+    .to_line_protocol_at(7);
+    assert_eq!(line.0, "burst_sample value=1.5 7");
+
+    // A member that cannot supply a timestamp falls back to now().
+    let line = MaybeStamped {
+        value: 1.5,
+        acquired: None,
+    }
+    .to_line_protocol();
+    let stamp: u128 = line.0.rsplit(' ').next().unwrap().parse().unwrap();
+    assert!(stamp > 1_000_000_000);
+}
+
+#[test]
+fn delimiters_in_names_and_tag_values_are_escaped() {
+    #[derive(ToLineProtocol)]
+    #[influx(measurement = "engine bay")]
+    struct Located {
+        #[influx(tag)]
+        sensor: String,
+        #[influx(rename = "flow rate")]
+        value: f64,
+    }
+
+    let line = Located {
+        sensor: "upper deck".to_string(),
+        value: 1.5,
+    }
+    .to_line_protocol_at(7);
+    assert_eq!(
+        line.0,
+        "engine\\ bay,sensor=upper\\ deck flow\\ rate=1.5 7"
+    );
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "attitude", with = "attitude_codec")]
+struct Attitude {
+    #[influx(tag)]
+    imu: i64,
+    #[influx(unit = "quaternion")]
+    w: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+/// Custom codec: renders the quaternion components plus a derived field the
+/// member-per-field mapping could not express.
+mod attitude_codec {
+    pub fn to_line_protocol(
+        attitude: &super::Attitude,
+        ctx: &influx::WithContext,
+    ) -> influx::LineProtocol {
+        let norm = (attitude.w.powi(2)
+            + attitude.x.powi(2)
+            + attitude.y.powi(2)
+            + attitude.z.powi(2))
+        .sqrt();
+        ctx.line(&format!(
+            "w={},x={},y={},z={},norm={norm}",
+            attitude.w, attitude.x, attitude.y, attitude.z
+        ))
+    }
+}
+
+#[test]
+fn with_module_renders_fields_inside_the_derived_prefix() {
+    let line = Attitude {
+        imu: 1,
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    }
+    .to_line_protocol_at(7);
+    // The derive owns the measurement, the tag set and the timestamp; the
+    // module owns only the field set.
+    assert_eq!(line.0, "attitude,imu=1 w=1,x=0,y=0,z=0,norm=1 7");
+    // Annotated schema metadata survives the escape hatch.
+    assert_eq!(Attitude::FIELDS[0].unit, "quaternion");
+}
+
+#[derive(ToLineProtocolBatch)]
+struct Frame {
+    engine: Basic,
+    #[influx(entries)]
+    feed: Grouped,
+    // Skipped members need no trait impl and never reach the buffer.
+    #[influx(skip)]
+    #[allow(dead_code)]
+    seq: u64,
+}
+
+#[test]
+fn batch_derive_appends_every_member_at_one_timestamp() {
+    let frame = Frame {
+        engine: Basic {
+            bank: 2,
+            chamber_pressure: 12.5,
+            igniter_current: 1.25,
+        },
+        feed: Grouped {
+            stand: 1,
+            pressure: 12.5,
+            outside_temp: 21.0,
+            wind_speed: 3.5,
+            flow: 0.8,
+        },
+        seq: 42,
+    };
+    let mut buf = String::new();
+    frame.write_batch_at(&mut buf, 1);
+    assert_eq!(
+        buf,
+        "engine,bank=2 chamber_pressure=12.5,igniter-current=1.25 1\n\
+         feed_system,stand=1 pressure=12.5,flow=0.8 1\n\
+         ambient,stand=1 outside_temp=21,wind_speed=3.5 1\n"
+    );
+    // Repeated calls compose into one write body.
+    frame.write_batch_at(&mut buf, 2);
+    assert_eq!(buf.lines().count(), 6);
+}
+
+#[derive(ToLineProtocol)]
+#[influx(measurement = "weather")]
+struct Sparse {
+    #[influx(tag)]
+    station: i64,
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+    samples: i64,
+}
+
+#[test]
+fn optional_members_are_skipped_when_none() {
+    let line = Sparse {
+        station: 3,
+        temperature: Some(21.5),
+        humidity: None,
+        samples: 60,
+    }
+    .to_line_protocol_at(1);
+    assert_eq!(line.0, "weather,station=3 temperature=21.5,samples=60i 1");
+
+    // The separator follows presence: with the leading optionals gone the
+    // first rendered field takes the space.
+    let line = Sparse {
+        station: 3,
+        temperature: None,
+        humidity: None,
+        samples: 60,
+    }
+    .to_line_protocol_at(1);
+    assert_eq!(line.0, "weather,station=3 samples=60i 1");
+}
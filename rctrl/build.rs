@@ -0,0 +1,32 @@
+//! Captures build provenance (git hash, profile, rustc version) into
+//! environment variables read by `provenance.rs`, so every dataset can be
+//! traced back to the exact build that produced it.
+
+use std::process::Command;
+
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RCTRL_GIT_HASH={git_hash}");
+
+    let profile = std::env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=RCTRL_PROFILE={profile}");
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("-V")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RCTRL_RUSTC={rustc_version}");
+
+    // Rebuild when the checked-out commit changes, not on every build.
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+}
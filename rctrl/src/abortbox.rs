@@ -0,0 +1,221 @@
+//! Independent UDP status link to the abort box.
+//!
+//! The flight termination box must know the stand's safety state even when
+//! everything else is failing — influx down, clients gone, disks full. It
+//! therefore gets its own path: tiny fixed-format UDP packets at a fixed
+//! rate, from a task with its own socket and its own clock, fed the latest
+//! state through a watch channel. Nothing on this path waits on the
+//! pipeline; with the pipeline wedged the task keeps sending the last known
+//! state, flagged stale so the box can act on the silence.
+//!
+//! The packet is 28 bytes, little-endian, CRC-16/CCITT protected:
+//!
+//! | offset | size | content                                    |
+//! |--------|------|--------------------------------------------|
+//! | 0      | 2    | magic `RA`                                 |
+//! | 2      | 1    | format version, currently 1                |
+//! | 3      | 1    | flags, see below                           |
+//! | 4      | 4    | packet sequence number                     |
+//! | 8      | 4    | mission time `f32` seconds                 |
+//! | 12     | 4    | feed pressure `f32` bar, NaN when unknown  |
+//! | 16     | 4    | chamber pressure `f32` bar, NaN when unknown |
+//! | 20     | 4    | igniter current `f32` A, NaN when unknown  |
+//! | 24     | 2    | age of the state in ms, saturating         |
+//! | 26     | 2    | CRC-16/CCITT-FALSE over bytes 0..26        |
+//!
+//! Flags: bit 0 valve command known, bit 1 valve commanded open, bit 2
+//! valve feedback known, bit 3 valve confirmed open, bit 4 a safety rule
+//! has fired (latched for the session), bit 5 state is stale.
+
+use crate::config::AbortBoxConfig;
+use crate::metrics::METRICS;
+use rctrl_api::prelude::*;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Fixed packet size; the box rejects any datagram of another length.
+pub const PACKET_LEN: usize = 28;
+const MAGIC: [u8; 2] = *b"RA";
+const VERSION: u8 = 1;
+
+/// State older than this is flagged stale: at 100 Hz the sync loop missing
+/// half a second of frames means the data path is in real trouble.
+const STALE_AFTER: Duration = Duration::from_millis(500);
+
+/// The slice of stand state the abort box cares about, updated by the
+/// pipeline from every frame.
+#[derive(Clone, Debug, Default)]
+pub struct Status {
+    pub mission_time_s: f64,
+    pub valve: Option<bool>,
+    pub valve_feedback: Option<bool>,
+    pub pressure: Option<f64>,
+    pub fc_pressure: Option<f64>,
+    pub igniter_current: Option<f64>,
+    /// Latched once any safety rule fires; an abort condition does not
+    /// un-happen.
+    pub rule_fired: bool,
+    /// When the state was last updated, for the staleness flag.
+    pub at: Option<Instant>,
+}
+
+impl Status {
+    /// Fold a frame in. Readings absent from this frame keep their last
+    /// known value; the age field tells the box how old the state is.
+    pub fn update(&mut self, data: &Data) {
+        self.mission_time_s = data.time.as_secs_f64();
+        self.valve = data.valve.or(self.valve);
+        self.valve_feedback = data.valve_feedback.or(self.valve_feedback);
+        self.pressure = data.pressure.or(self.pressure);
+        self.fc_pressure = data.fc_pressure.or(self.fc_pressure);
+        self.igniter_current = data.igniter_current.or(self.igniter_current);
+        self.rule_fired |= !data.rules_fired.is_empty();
+        self.at = Some(Instant::now());
+    }
+}
+
+/// Send status packets at the configured rate until the process exits.
+pub async fn task(config: AbortBoxConfig, status_rx: watch::Receiver<Status>) {
+    let socket = match tokio::net::UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(e) => {
+            tracing::error!(target: "alarm", "abort box socket unavailable: {e}");
+            return;
+        }
+    };
+    if let Err(e) = socket.connect(&config.target).await {
+        tracing::error!(
+            target: "alarm",
+            "abort box target '{}' unusable: {e}",
+            config.target
+        );
+        return;
+    }
+    tracing::info!(
+        "abort box link to {} at {} Hz",
+        config.target,
+        config.rate_hz
+    );
+    let mut interval = tokio::time::interval(Duration::from_millis(1_000 / config.rate_hz));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut seq: u32 = 0;
+    let mut failed = false;
+    loop {
+        interval.tick().await;
+        let packet = encode(&status_rx.borrow().clone(), seq);
+        seq = seq.wrapping_add(1);
+        match socket.send(&packet).await {
+            Ok(_) => {
+                METRICS.incr("abortbox_packets", 1);
+                failed = false;
+            }
+            Err(e) => {
+                METRICS.incr("abortbox_send_failures", 1);
+                // Once per outage, not once per packet.
+                if !failed {
+                    tracing::warn!("abort box send failed: {e}");
+                    failed = true;
+                }
+            }
+        }
+    }
+}
+
+/// Render one status packet.
+fn encode(status: &Status, seq: u32) -> [u8; PACKET_LEN] {
+    let mut packet = [0u8; PACKET_LEN];
+    packet[0..2].copy_from_slice(&MAGIC);
+    packet[2] = VERSION;
+    let age = status.at.map(|at| at.elapsed());
+    let mut flags = 0u8;
+    if let Some(open) = status.valve {
+        flags |= 0x01 | if open { 0x02 } else { 0 };
+    }
+    if let Some(open) = status.valve_feedback {
+        flags |= 0x04 | if open { 0x08 } else { 0 };
+    }
+    if status.rule_fired {
+        flags |= 0x10;
+    }
+    if age.is_none_or(|age| age > STALE_AFTER) {
+        flags |= 0x20;
+    }
+    packet[3] = flags;
+    packet[4..8].copy_from_slice(&seq.to_le_bytes());
+    packet[8..12].copy_from_slice(&(status.mission_time_s as f32).to_le_bytes());
+    packet[12..16].copy_from_slice(&reading(status.pressure).to_le_bytes());
+    packet[16..20].copy_from_slice(&reading(status.fc_pressure).to_le_bytes());
+    packet[20..24].copy_from_slice(&reading(status.igniter_current).to_le_bytes());
+    let age_ms = age
+        .map(|age| age.as_millis().min(u16::MAX as u128) as u16)
+        .unwrap_or(u16::MAX);
+    packet[24..26].copy_from_slice(&age_ms.to_le_bytes());
+    let crc = crc16(&packet[..26]);
+    packet[26..28].copy_from_slice(&crc.to_le_bytes());
+    packet
+}
+
+fn reading(value: Option<f64>) -> f32 {
+    value.map(|v| v as f32).unwrap_or(f32::NAN)
+}
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial 0xFFFF, no reflection.
+/// Small enough to reimplement on whatever the abort box runs.
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc16_matches_the_ccitt_false_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn packets_carry_state_flags_and_a_valid_crc() {
+        let status = Status {
+            mission_time_s: 12.5,
+            valve: Some(true),
+            valve_feedback: Some(false),
+            pressure: Some(30.0),
+            fc_pressure: None,
+            igniter_current: None,
+            rule_fired: true,
+            at: Some(Instant::now()),
+        };
+        let packet = encode(&status, 7);
+        assert_eq!(&packet[0..2], b"RA");
+        assert_eq!(packet[2], VERSION);
+        // Valve commanded open, feedback known closed, rule latched, fresh.
+        assert_eq!(packet[3], 0x01 | 0x02 | 0x04 | 0x10);
+        assert_eq!(u32::from_le_bytes(packet[4..8].try_into().unwrap()), 7);
+        assert_eq!(
+            f32::from_le_bytes(packet[8..12].try_into().unwrap()),
+            12.5
+        );
+        assert!(f32::from_le_bytes(packet[16..20].try_into().unwrap()).is_nan());
+        let crc = u16::from_le_bytes(packet[26..28].try_into().unwrap());
+        assert_eq!(crc, crc16(&packet[..26]));
+    }
+
+    #[test]
+    fn a_never_updated_status_is_stale() {
+        let packet = encode(&Status::default(), 0);
+        assert_eq!(packet[3] & 0x20, 0x20);
+        assert_eq!(u16::from_le_bytes(packet[24..26].try_into().unwrap()), u16::MAX);
+    }
+}
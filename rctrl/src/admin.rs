@@ -0,0 +1,143 @@
+//! Staged config deployment over the WebSocket.
+//!
+//! Updating `rctrl.toml` over SSH on the stand computer mid-campaign is
+//! error-prone: a typo is only discovered at the next restart, with the
+//! crew already out at the stand. Instead an authenticated operator uploads
+//! the new file over the command channel; it is validated with exactly the
+//! checks [`Config::load`] applies at startup, written next to the active
+//! file as `<path>.staged`, and applied on the next restart — never
+//! mid-session. The hashes of the active and staged files ride in the
+//! snapshot and on `/status`, so "which config is this stand actually
+//! running" always has an answer.
+//!
+//! The admin token lives in the [`TOKEN_ENV`] environment variable, never
+//! in the config file — the file an upload would replace cannot be what
+//! authorizes replacing it.
+
+use crate::config::Config;
+use std::path::PathBuf;
+
+/// Environment variable holding the admin token; uploads are disabled when
+/// it is unset or empty.
+pub const TOKEN_ENV: &str = "RCTRL_ADMIN_TOKEN";
+
+/// Where an upload for `config_path` is staged, next to the active file.
+fn staged_path(config_path: &str) -> PathBuf {
+    PathBuf::from(format!("{config_path}.staged"))
+}
+
+/// Compare the offered token against the configured one without an early
+/// exit, so response timing does not reveal how much of a guess matched.
+pub fn token_matches(expected: &str, offered: &str) -> bool {
+    expected.len() == offered.len()
+        && expected
+            .bytes()
+            .zip(offered.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Validate and stage an uploaded config, returning the staged file's hash.
+///
+/// The error side carries the reason — parse or validation text, or the
+/// write failure — verbatim for the rejecting client.
+pub fn stage(config_path: &str, contents: &str) -> Result<String, String> {
+    Config::parse(contents).map_err(|e| e.to_string())?;
+    let path = staged_path(config_path);
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(crate::provenance::hash_bytes(contents.as_bytes()))
+}
+
+/// Hash of the currently staged file, `None` when nothing is staged.
+pub fn staged_hash(config_path: &str) -> Option<String> {
+    let hash = crate::provenance::config_hash(staged_path(config_path).to_str()?);
+    (hash != "absent").then_some(hash)
+}
+
+/// Apply a staged config, called at startup before the active file is
+/// loaded. A staged file that still validates replaces the active one (the
+/// replaced file is kept as `<path>.previous`); one that no longer does —
+/// staged against an older binary whose schema has moved on — is set aside
+/// as `<path>.rejected` so it is not retried on every boot.
+pub fn promote_staged(config_path: &str) {
+    let staged = staged_path(config_path);
+    let contents = match std::fs::read_to_string(&staged) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            tracing::error!("failed to read staged config {}: {e}", staged.display());
+            return;
+        }
+    };
+    if let Err(e) = Config::parse(&contents) {
+        let rejected = format!("{config_path}.rejected");
+        let _ = std::fs::rename(&staged, &rejected);
+        tracing::error!("staged config no longer validates, set aside as {rejected}: {e}");
+        return;
+    }
+    let previous = format!("{config_path}.previous");
+    // The active file may not exist (defaults in use); that is fine.
+    let _ = std::fs::rename(config_path, &previous);
+    match std::fs::rename(&staged, config_path) {
+        Ok(()) => tracing::info!(
+            "staged config applied ({}); replaced file kept as {previous}",
+            crate::provenance::config_hash(config_path)
+        ),
+        Err(e) => tracing::error!("failed to apply staged config: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_validates_before_writing() {
+        let dir = std::env::temp_dir();
+        let config = dir.join("admin_stage.toml");
+        let config = config.to_str().unwrap();
+        // Leftovers from an earlier run would make staged_hash non-empty.
+        let _ = std::fs::remove_file(format!("{config}.staged"));
+
+        // Unparseable and invalid uploads leave nothing staged.
+        assert!(stage(config, "not = toml = at all").is_err());
+        assert!(stage(config, "[log_rate]\nmin_rate = 0").is_err());
+        assert_eq!(staged_hash(config), None);
+
+        let hash = stage(config, "[network]\n").unwrap();
+        assert_eq!(staged_hash(config), Some(hash));
+    }
+
+    #[test]
+    fn promote_applies_valid_and_sets_aside_invalid() {
+        let dir = std::env::temp_dir();
+        let config = dir.join("admin_promote.toml");
+        let config = config.to_str().unwrap();
+        let _ = std::fs::remove_file(format!("{config}.staged"));
+        let _ = std::fs::remove_file(format!("{config}.rejected"));
+        std::fs::write(config, "# active\n").unwrap();
+
+        stage(config, "# staged\n[network]\n").unwrap();
+        promote_staged(config);
+        assert_eq!(staged_hash(config), None);
+        assert!(std::fs::read_to_string(config).unwrap().contains("staged"));
+        assert!(std::fs::read_to_string(format!("{config}.previous"))
+            .unwrap()
+            .contains("active"));
+
+        // A staged file corrupted after staging must not become active.
+        std::fs::write(format!("{config}.staged"), "???").unwrap();
+        promote_staged(config);
+        assert!(std::fs::read_to_string(config).unwrap().contains("staged"));
+        assert!(std::path::Path::new(&format!("{config}.rejected")).exists());
+    }
+
+    #[test]
+    fn token_comparison_requires_exact_match() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secret", "secres"));
+        assert!(!token_matches("secret", "secret "));
+        assert!(!token_matches("secret", ""));
+    }
+}
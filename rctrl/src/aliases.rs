@@ -0,0 +1,95 @@
+//! Measurement renames for schema evolution.
+//!
+//! Renaming a measurement breaks every dashboard still querying the old
+//! name. The alias layer lets code keep producing the deprecated name while
+//! the writer maps it to the new one — optionally double-writing both during
+//! a transition window — and warns (once per name) that the producer should
+//! be updated.
+
+use crate::config::AliasConfig;
+use crate::metrics::METRICS;
+use influx::LineProtocol;
+use std::collections::{HashMap, HashSet};
+
+/// Applies configured measurement renames to outgoing lines.
+pub struct AliasMap {
+    aliases: HashMap<String, AliasConfig>,
+    /// Deprecated names already warned about this run.
+    warned: HashSet<String>,
+}
+
+impl AliasMap {
+    pub fn new(aliases: HashMap<String, AliasConfig>) -> Self {
+        Self {
+            aliases,
+            warned: HashSet::new(),
+        }
+    }
+
+    /// Rewrite a line's measurement if it is deprecated. Returns the line(s)
+    /// to write: the renamed line, preceded by the original when the alias
+    /// double-writes.
+    pub fn apply(&mut self, line: LineProtocol) -> Vec<LineProtocol> {
+        let measurement_len = line
+            .0
+            .find([',', ' '])
+            .unwrap_or(line.0.len());
+        let Some(alias) = self.aliases.get(&line.0[..measurement_len]) else {
+            return vec![line];
+        };
+
+        if self.warned.insert(line.0[..measurement_len].to_string()) {
+            tracing::warn!(
+                "deprecated measurement '{}' still produced, writing as '{}'",
+                &line.0[..measurement_len],
+                alias.new
+            );
+        }
+        METRICS.incr("deprecated_lines", 1);
+
+        let renamed = LineProtocol(format!("{}{}", alias.new, &line.0[measurement_len..]));
+        if alias.double_write {
+            vec![line, renamed]
+        } else {
+            vec![renamed]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(double_write: bool) -> AliasMap {
+        AliasMap::new(HashMap::from([(
+            "pressure".to_string(),
+            AliasConfig {
+                new: "feed_pressure".to_string(),
+                double_write,
+            },
+        )]))
+    }
+
+    #[test]
+    fn deprecated_measurements_are_renamed() {
+        let mut aliases = map(false);
+        let out = aliases.apply(LineProtocol("pressure value=1 0".to_string()));
+        assert_eq!(out, [LineProtocol("feed_pressure value=1 0".to_string())]);
+        // Only full measurement names match, not prefixes.
+        let out = aliases.apply(LineProtocol("pressure_rate value=1 0".to_string()));
+        assert_eq!(out, [LineProtocol("pressure_rate value=1 0".to_string())]);
+    }
+
+    #[test]
+    fn double_write_emits_old_and_new() {
+        let mut aliases = map(true);
+        let out = aliases.apply(LineProtocol("pressure,gap=true value=1 0".to_string()));
+        assert_eq!(
+            out,
+            [
+                LineProtocol("pressure,gap=true value=1 0".to_string()),
+                LineProtocol("feed_pressure,gap=true value=1 0".to_string()),
+            ]
+        );
+    }
+}
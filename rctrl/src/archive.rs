@@ -0,0 +1,282 @@
+//! Archival export of a test session into Parquet.
+//!
+//! The stand's influx instance is working storage, not the archive; after a
+//! test the session is exported with `rctrl archive <start> <stop> [dir]`
+//! into one Parquet file per measurement, ready for upload to object
+//! storage. Each file embeds the session window as metadata, and the written
+//! row count is verified against an influx `count()` before the export is
+//! considered good.
+
+use influx::client::{Client, ClientError};
+use parquet::basic::{Compression, ConvertedType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::KeyValue;
+use parquet::schema::types::Type;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Errors produced by an archival export.
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("influx query failed: {0}")]
+    Influx(#[from] ClientError),
+    #[error("failed to write archive: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("invalid time bound {0:?} (expected RFC 3339 or a flux duration like -1h)")]
+    InvalidTime(String),
+    #[error(
+        "row count mismatch for '{measurement}': wrote {written} rows, influx counts {counted}"
+    )]
+    CountMismatch {
+        measurement: String,
+        written: u64,
+        counted: u64,
+    },
+}
+
+/// One exported measurement.
+pub struct MeasurementArchive {
+    pub measurement: String,
+    pub rows: u64,
+    pub path: PathBuf,
+}
+
+/// Export every measurement with points in `[start, stop)` to `out_dir`.
+pub async fn run(
+    client: &Client,
+    bucket: &str,
+    start: &str,
+    stop: &str,
+    out_dir: &Path,
+) -> Result<Vec<MeasurementArchive>, ArchiveError> {
+    validate_time(start)?;
+    validate_time(stop)?;
+
+    let measurements = client
+        .query(&format!(
+            "import \"influxdata/influxdb/schema\"\nschema.measurements(bucket: \"{bucket}\")"
+        ))
+        .await?;
+    let names = column_values(&measurements, "_value");
+
+    std::fs::create_dir_all(out_dir)?;
+    let mut archives = Vec::new();
+    for measurement in names {
+        if measurement.contains(['"', '\\']) {
+            tracing::warn!("skipping unexportable measurement name {measurement:?}");
+            continue;
+        }
+        let data = client
+            .query(&format!(
+                "from(bucket: \"{bucket}\") \
+                 |> range(start: {start}, stop: {stop}) \
+                 |> filter(fn: (r) => r._measurement == \"{measurement}\")"
+            ))
+            .await?;
+        if data.rows.is_empty() {
+            continue;
+        }
+
+        let path = out_dir.join(format!("{measurement}.parquet"));
+        let metadata = vec![
+            ("measurement", measurement.as_str()),
+            ("session_start", start),
+            ("session_stop", stop),
+        ];
+        let written = write_parquet(&path, &data.columns, &data.rows, &metadata)?;
+
+        // The archive only counts if it holds exactly what influx holds.
+        let counted = count_points(client, bucket, start, stop, &measurement).await?;
+        if counted != written {
+            return Err(ArchiveError::CountMismatch {
+                measurement,
+                written,
+                counted,
+            });
+        }
+        archives.push(MeasurementArchive {
+            measurement,
+            rows: written,
+            path,
+        });
+    }
+    Ok(archives)
+}
+
+/// Sum of per-series point counts for one measurement in the window.
+async fn count_points(
+    client: &Client,
+    bucket: &str,
+    start: &str,
+    stop: &str,
+    measurement: &str,
+) -> Result<u64, ArchiveError> {
+    let counts = client
+        .query(&format!(
+            "from(bucket: \"{bucket}\") \
+             |> range(start: {start}, stop: {stop}) \
+             |> filter(fn: (r) => r._measurement == \"{measurement}\") \
+             |> count()"
+        ))
+        .await?;
+    Ok(column_values(&counts, "_value")
+        .iter()
+        .filter_map(|v| v.parse::<u64>().ok())
+        .sum())
+}
+
+/// Every value of one named column of a query result.
+fn column_values(result: &influx::query::QueryResult, column: &str) -> Vec<String> {
+    let Some(index) = result.columns.iter().position(|c| c == column) else {
+        return Vec::new();
+    };
+    result
+        .rows
+        .iter()
+        .filter_map(|row| row.get(index).cloned())
+        .collect()
+}
+
+/// Write one measurement's rows as a single row group Parquet file. Columns
+/// whose every value parses as a number are written as doubles, everything
+/// else as UTF-8 strings; returns the number of rows written.
+fn write_parquet(
+    path: &Path,
+    columns: &[String],
+    rows: &[Vec<String>],
+    metadata: &[(&str, &str)],
+) -> Result<u64, ArchiveError> {
+    // Column-major view, with the numeric/string decision per column.
+    let numeric: Vec<bool> = (0..columns.len())
+        .map(|i| {
+            rows.iter()
+                .all(|row| row.get(i).is_some_and(|v| v.parse::<f64>().is_ok()))
+        })
+        .collect();
+
+    let fields = columns
+        .iter()
+        .zip(&numeric)
+        .map(|(name, &numeric)| {
+            let builder = if numeric {
+                Type::primitive_type_builder(name, PhysicalType::DOUBLE)
+            } else {
+                Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+                    .with_converted_type(ConvertedType::UTF8)
+            };
+            Ok(Arc::new(builder.with_repetition(Repetition::REQUIRED).build()?))
+        })
+        .collect::<Result<Vec<_>, parquet::errors::ParquetError>>()?;
+    let schema = Arc::new(
+        Type::group_type_builder("session")
+            .with_fields(fields)
+            .build()?,
+    );
+
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::UNCOMPRESSED)
+            .set_key_value_metadata(Some(
+                metadata
+                    .iter()
+                    .map(|(key, value)| KeyValue {
+                        key: key.to_string(),
+                        value: Some(value.to_string()),
+                    })
+                    .collect(),
+            ))
+            .build(),
+    );
+
+    let file = std::fs::File::create(path)?;
+    let mut writer = SerializedFileWriter::new(file, schema, props)?;
+    let mut row_group = writer.next_row_group()?;
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        if numeric[index] {
+            let values: Vec<f64> = rows
+                .iter()
+                .map(|row| row[index].parse().expect("checked numeric above"))
+                .collect();
+            column
+                .typed::<DoubleType>()
+                .write_batch(&values, None, None)?;
+        } else {
+            let values: Vec<ByteArray> = rows
+                .iter()
+                .map(|row| row[index].as_str().into())
+                .collect();
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(&values, None, None)?;
+        }
+        column.close()?;
+        index += 1;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(rows.len() as u64)
+}
+
+/// Accept only RFC 3339 timestamps or flux durations as time bounds, so they
+/// can be spliced into flux source verbatim.
+fn validate_time(bound: &str) -> Result<(), ArchiveError> {
+    let valid = !bound.is_empty()
+        && bound
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '+' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ArchiveError::InvalidTime(bound.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::FileReader;
+
+    #[test]
+    fn parquet_roundtrip_preserves_rows_and_metadata() {
+        let dir = std::env::temp_dir().join("rctrl_archive_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pressure.parquet");
+
+        let columns = vec!["_time".to_string(), "_value".to_string()];
+        let rows = vec![
+            vec!["2026-08-27T12:00:00Z".to_string(), "12.5".to_string()],
+            vec!["2026-08-27T12:00:01Z".to_string(), "12.6".to_string()],
+        ];
+        let written =
+            write_parquet(&path, &columns, &rows, &[("measurement", "pressure")]).unwrap();
+        assert_eq!(written, 2);
+
+        let reader =
+            parquet::file::reader::SerializedFileReader::new(std::fs::File::open(&path).unwrap())
+                .unwrap();
+        let file_metadata = reader.metadata().file_metadata();
+        assert_eq!(file_metadata.num_rows(), 2);
+        // _time stays a string, _value becomes a double.
+        let schema = file_metadata.schema();
+        assert_eq!(schema.get_fields()[0].get_physical_type(), PhysicalType::BYTE_ARRAY);
+        assert_eq!(schema.get_fields()[1].get_physical_type(), PhysicalType::DOUBLE);
+        let kv = file_metadata.key_value_metadata().unwrap();
+        assert!(kv
+            .iter()
+            .any(|kv| kv.key == "measurement" && kv.value.as_deref() == Some("pressure")));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn time_bounds_are_validated() {
+        assert!(validate_time("2026-08-27T12:00:00Z").is_ok());
+        assert!(validate_time("-1h").is_ok());
+        assert!(validate_time("\") |> drop()").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! Audit trail of operator actions and command routing decisions.
+//!
+//! Every accepted or rejected command is logged through here and written to
+//! the `audit` measurement, so post test review can reconstruct who asked for
+//! what and what rctrl did about it.
+
+use influx::LineProtocol;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// The outcome of a routed command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Accepted,
+    Rejected,
+    /// Held by the two-person gate, awaiting a second operator.
+    Proposed,
+    /// Confirmed by a second operator and executed.
+    Confirmed,
+}
+
+impl Outcome {
+    fn tag(self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+            Self::Proposed => "proposed",
+            Self::Confirmed => "confirmed",
+        }
+    }
+}
+
+/// Handle used by connection tasks to record audit events.
+#[derive(Clone)]
+pub struct AuditLog {
+    line_tx: mpsc::Sender<LineProtocol>,
+}
+
+impl AuditLog {
+    pub fn new(line_tx: mpsc::Sender<LineProtocol>) -> Self {
+        Self { line_tx }
+    }
+
+    /// Record a command routing decision.
+    pub fn record(&self, peer: &str, action: &str, outcome: Outcome) {
+        let accepted = matches!(outcome, Outcome::Accepted | Outcome::Confirmed);
+        tracing::info!(target: "audit", peer, action, outcome = outcome.tag());
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_nanos();
+        // Peer and action land in tags, which need no field value support.
+        let line = LineProtocol(format!(
+            "audit,peer={},action={},outcome={} accepted={} {}",
+            peer, action, outcome.tag(), accepted, timestamp
+        ));
+        if self.line_tx.try_send(line).is_err() {
+            tracing::warn!("audit line channel full, dropping audit entry");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Measurement → bucket routing for the influx writer.
+
+use crate::config::BucketsConfig;
+use influx::LineProtocol;
+
+/// Routes each line to a bucket by its measurement name.
+pub struct BucketRouter {
+    default: String,
+    /// `(pattern, bucket)` pairs; a pattern is an exact measurement name or
+    /// a prefix ending in `*`.
+    routes: Vec<(String, String)>,
+}
+
+impl BucketRouter {
+    pub fn new(config: BucketsConfig) -> Self {
+        let mut routes: Vec<(String, String)> = config.routes.into_iter().collect();
+        // Longest pattern first, so `fc_pressure` beats `fc_*`.
+        routes.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+        Self {
+            default: config.default,
+            routes,
+        }
+    }
+
+    /// The measurement name of a line: everything before the first `,` or
+    /// space.
+    fn measurement(line: &LineProtocol) -> &str {
+        line.0
+            .split([',', ' '])
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// The bucket this line belongs to.
+    pub fn bucket_for(&self, line: &LineProtocol) -> &str {
+        let measurement = Self::measurement(line);
+        for (pattern, bucket) in &self.routes {
+            let matches = match pattern.strip_suffix('*') {
+                Some(prefix) => measurement.starts_with(prefix),
+                None => measurement == pattern,
+            };
+            if matches {
+                return bucket;
+            }
+        }
+        &self.default
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn router() -> BucketRouter {
+        BucketRouter::new(BucketsConfig {
+            default: "rctrl".into(),
+            routes: HashMap::from([
+                ("audit".into(), "rctrl_audit".into()),
+                ("fc_*".into(), "flight".into()),
+                ("fc_pressure".into(), "chamber".into()),
+            ]),
+        })
+    }
+
+    #[test]
+    fn routes_by_measurement_with_prefix_patterns() {
+        let router = router();
+        let line = |s: &str| LineProtocol(s.to_string());
+        assert_eq!(router.bucket_for(&line("pressure value=1 0")), "rctrl");
+        assert_eq!(
+            router.bucket_for(&line("audit,peer=x action=y accepted=true 0")),
+            "rctrl_audit"
+        );
+        assert_eq!(router.bucket_for(&line("fc_altitude value=1 0")), "flight");
+        // Exact routes beat prefix routes.
+        assert_eq!(router.bucket_for(&line("fc_pressure value=1 0")), "chamber");
+    }
+}
@@ -0,0 +1,96 @@
+//! Triggered burst capture.
+//!
+//! Normally raw frames are averaged down before they reach influx. When a
+//! trigger fires (operator command, later redline proximity or ignition) we
+//! want full rate data around the event: a ring buffer holds the most recent
+//! raw frames, and on trigger those pre-trigger frames plus a configurable
+//! number of post-trigger frames are logged raw, after which the pipeline
+//! returns to the aggregated rate.
+
+use rctrl_api::prelude::*;
+use std::collections::VecDeque;
+
+/// Pre/post trigger burst capture stage.
+pub struct BurstCapture {
+    pre_frames: usize,
+    post_frames: usize,
+    ring: VecDeque<Data>,
+    remaining_post: usize,
+}
+
+impl BurstCapture {
+    pub fn new(pre_frames: usize, post_frames: usize) -> Self {
+        Self {
+            pre_frames,
+            post_frames,
+            ring: VecDeque::with_capacity(pre_frames),
+            remaining_post: 0,
+        }
+    }
+
+    /// Whether a burst window is currently open.
+    pub fn active(&self) -> bool {
+        self.remaining_post > 0
+    }
+
+    /// Fire the trigger: drains and returns the buffered pre-trigger frames
+    /// and opens the post-trigger window.
+    pub fn trigger(&mut self, reason: &str) -> Vec<Data> {
+        tracing::info!("burst capture triggered: {reason}");
+        self.remaining_post = self.post_frames;
+        self.ring.drain(..).collect()
+    }
+
+    /// Feed one raw frame. Returns the frame back when it falls inside an
+    /// open burst window and must be logged at full rate.
+    pub fn push(&mut self, data: &Data) -> Option<Data> {
+        if self.remaining_post > 0 {
+            self.remaining_post -= 1;
+            return Some(data.clone());
+        }
+
+        if self.ring.len() == self.pre_frames {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(data.clone());
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(t: u64) -> Data {
+        Data {
+            time: Duration::from_millis(t),
+            pressure: Some(t as f64),
+            ..Data::default()
+        }
+    }
+
+    #[test]
+    fn ring_keeps_most_recent_pre_trigger_frames() {
+        let mut burst = BurstCapture::new(2, 1);
+        for t in 0..4 {
+            assert!(burst.push(&frame(t)).is_none());
+        }
+
+        let pre = burst.trigger("test");
+        assert_eq!(pre.len(), 2);
+        assert_eq!(pre[0].time, Duration::from_millis(2));
+        assert_eq!(pre[1].time, Duration::from_millis(3));
+    }
+
+    #[test]
+    fn post_window_passes_frames_then_closes() {
+        let mut burst = BurstCapture::new(4, 2);
+        burst.trigger("test");
+        assert!(burst.active());
+        assert!(burst.push(&frame(0)).is_some());
+        assert!(burst.push(&frame(1)).is_some());
+        assert!(!burst.active());
+        assert!(burst.push(&frame(2)).is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! On-stand camera snapshot triggering.
+//!
+//! Correlating stand camera footage with data has been manual: someone scrubs
+//! the recording to the wall-clock time of an event. The snapshotter calls
+//! the camera recorder's snapshot endpoint when selected events occur —
+//! ignition, an abort rule firing, an alarm — and records the image
+//! reference the endpoint returns in an `events` measurement, so the frame
+//! belonging to a data feature can be found by query. The endpoint answers
+//! with the reference (a filename or URL) as plain text on its first line;
+//! IP cameras and an ffmpeg trigger script both fit that contract. Clients
+//! are told about each stored snapshot so the log view can link to it.
+
+use crate::config::CameraConfig;
+use crate::metrics::METRICS;
+use influx::{LineProtocol, ToFieldValue};
+use rctrl_api::prelude::*;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+
+/// Events that may trigger a snapshot; `[camera] on` entries name these.
+pub const EVENT_KINDS: &[&str] = &["ignition", "abort", "alarm"];
+
+/// Triggers snapshots and records their references.
+pub struct Snapshotter {
+    url: String,
+    on: Vec<String>,
+    min_interval: Duration,
+    http: reqwest::Client,
+    line_tx: mpsc::Sender<LineProtocol>,
+    msg_tx: broadcast::Sender<WsMessage>,
+    last: Option<Instant>,
+}
+
+impl Snapshotter {
+    pub fn new(
+        config: CameraConfig,
+        line_tx: mpsc::Sender<LineProtocol>,
+        msg_tx: broadcast::Sender<WsMessage>,
+    ) -> Self {
+        // Bounded per-request so a wedged recorder cannot pile up tasks; a
+        // snapshot that takes longer than this is too late to be useful.
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build http client");
+        Self {
+            url: config.snapshot_url,
+            on: config.on,
+            min_interval: Duration::from_secs(config.min_interval_s),
+            http,
+            line_tx,
+            msg_tx,
+            last: None,
+        }
+    }
+
+    /// Request a snapshot for an event, if its kind is configured and the
+    /// minimum interval has passed — an alarm storm must not become an HTTP
+    /// storm. The request runs in its own task; the pipeline never waits on
+    /// the camera.
+    pub fn trigger(&mut self, kind: &'static str, reason: &str, stamp: u128) {
+        if !self.on.iter().any(|k| k == kind) {
+            return;
+        }
+        if let Some(last) = self.last {
+            if last.elapsed() < self.min_interval {
+                return;
+            }
+        }
+        self.last = Some(Instant::now());
+        METRICS.incr("camera_snapshots", 1);
+        let http = self.http.clone();
+        let url = self.url.clone();
+        let line_tx = self.line_tx.clone();
+        let msg_tx = self.msg_tx.clone();
+        let reason = reason.to_string();
+        tokio::spawn(async move {
+            match snapshot(&http, &url).await {
+                Ok(reference) => {
+                    let _ = line_tx.send(event_line(kind, &reason, &reference, stamp)).await;
+                    let _ = msg_tx.send(WsMessage::CameraEvent {
+                        kind: kind.to_string(),
+                        reference,
+                        reason,
+                    });
+                }
+                Err(e) => {
+                    METRICS.incr("camera_snapshot_failures", 1);
+                    tracing::warn!("camera snapshot for {kind} failed: {e}");
+                }
+            }
+        });
+    }
+}
+
+/// Request one snapshot; the first non-empty line of the answer is the
+/// stored image's reference.
+async fn snapshot(http: &reqwest::Client, url: &str) -> Result<String, String> {
+    let response = http.get(url).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("endpoint answered {status}"));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    match body.lines().map(str::trim).find(|line| !line.is_empty()) {
+        Some(reference) => Ok(reference.to_string()),
+        None => Err("endpoint answered without an image reference".to_string()),
+    }
+}
+
+/// The `events` line recording a stored snapshot.
+fn event_line(kind: &str, reason: &str, reference: &str, stamp: u128) -> LineProtocol {
+    LineProtocol(format!(
+        "events,kind={} snapshot={},reason={} {}",
+        influx::escape::tag_value(kind),
+        reference.to_field_value(),
+        reason.to_field_value(),
+        stamp
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_lines_quote_the_reference_and_reason() {
+        let line = event_line("ignition", "igniter pulse 240 ms", "/rec/img_0042.jpg", 7);
+        assert_eq!(
+            line.0,
+            r#"events,kind=ignition snapshot="/rec/img_0042.jpg",reason="igniter pulse 240 ms" 7"#
+        );
+    }
+}
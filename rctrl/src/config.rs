@@ -0,0 +1,1509 @@
+//! Runtime configuration loaded from a TOML file.
+
+use rctrl_api::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Errors produced while loading configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+    /// Schema violations, all of them at once so one `check-config` run
+    /// surfaces every problem instead of the first.
+    #[error("invalid config:\n  {}", .0.join("\n  "))]
+    Invalid(Vec<String>),
+}
+
+/// Top level configuration for the rctrl daemon.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub permissions: PermissionMatrix,
+    /// Two-person confirmation for hazardous commands.
+    pub confirmation: ConfirmationConfig,
+    /// How the sync loop and the network stack are coupled.
+    pub process: ProcessConfig,
+    /// Listen addresses of the WebSocket and status servers.
+    pub network: NetworkConfig,
+    /// Connection-level protections on the WebSocket listener.
+    pub protection: ProtectionConfig,
+    /// The InfluxDB endpoint writes and queries go to.
+    pub influx: InfluxConfig,
+    /// Flight computer serial telemetry input; absent when no flight
+    /// computer is connected.
+    pub serial: Option<SerialConfig>,
+    /// Instrumentation power supply; absent when sensors are battery or
+    /// bench powered.
+    pub psu: Option<PsuConfig>,
+    /// Igniter firing pulse detection; absent on stands without a current
+    /// sense channel.
+    pub igniter: Option<IgniterConfig>,
+    /// Local weather station poller; absent when no ambient source exists.
+    pub weather: Option<WeatherConfig>,
+    /// Camera snapshot trigger; absent when no camera recorder exists.
+    pub camera: Option<CameraConfig>,
+    /// Independent UDP status link to the abort box; absent on stands
+    /// without one.
+    pub abort_box: Option<AbortBoxConfig>,
+    /// Black-box CSV flight recorder; absent when no local record is wanted.
+    pub recorder: Option<RecorderConfig>,
+    /// Measurement hardware on the stand.
+    #[serde(rename = "device")]
+    pub devices: Vec<DeviceConfig>,
+    /// Logical channels read from devices.
+    #[serde(rename = "channel")]
+    pub channels: Vec<ChannelConfig>,
+    /// Per-channel expectations for the pre-test data quality check, keyed by
+    /// telemetry channel name.
+    pub quality: HashMap<String, QualityExpectation>,
+    /// Dual-redundant channel pairs folded into one logical channel.
+    #[serde(rename = "redundant")]
+    pub redundant: Vec<RedundantConfig>,
+    /// Actuator ordering constraints, enforced before open commands execute.
+    #[serde(rename = "interlock")]
+    pub interlocks: Vec<InterlockConfig>,
+    /// Automated response rules, evaluated by the sync loop every cycle.
+    #[serde(rename = "rule")]
+    pub rules: Vec<RuleConfig>,
+    /// Safety-state-aware measurement routing in the pipeline.
+    #[serde(rename = "routing")]
+    pub routing: Vec<RoutingConfig>,
+    /// Influx bucket routing.
+    pub buckets: BucketsConfig,
+    /// Change-detected measurements, written only on change plus keepalive.
+    pub sparse: SparseConfig,
+    /// Writer-side measurement allowlist/blocklist.
+    pub filter: FilterConfig,
+    /// Bounds and lifetime of runtime per-channel log rate overrides.
+    pub log_rate: LogRateConfig,
+    /// Deadlines on external I/O awaits in the async side.
+    pub io: IoConfig,
+    /// Batching policy of the influx write path.
+    pub writer: WriterConfig,
+    /// Where influx timestamps for telemetry come from.
+    pub timestamp: TimestampConfig,
+    /// Simulated telemetry source settings for runs without stand hardware.
+    pub sim: SimConfig,
+    /// Valve actuator drive output; the valve stays model-only without it.
+    pub valve: ValveConfig,
+    /// On-disk rolling frame cache served by [`CmdEnum::QueryHistory`].
+    pub history: HistoryConfig,
+    /// Measurement renames applied by the influx writer, keyed by the
+    /// deprecated name.
+    #[serde(rename = "alias")]
+    pub aliases: HashMap<String, AliasConfig>,
+}
+
+/// Which influx bucket each measurement is written to.
+///
+/// Routes map a measurement name — or a prefix ending in `*` — to a bucket;
+/// everything else goes to `default`. Separate buckets let sensor data, audit
+/// events and software metrics carry different retention policies.
+///
+/// ```toml
+/// [buckets]
+/// default = "rctrl"
+///
+/// [buckets.routes]
+/// audit = "rctrl_audit"
+/// "fc_*" = "flight"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BucketsConfig {
+    pub default: String,
+    pub routes: HashMap<String, String>,
+}
+
+impl Default for BucketsConfig {
+    fn default() -> Self {
+        Self {
+            default: "rctrl".to_string(),
+            routes: HashMap::new(),
+        }
+    }
+}
+
+/// Measurements written only when their value changes.
+///
+/// Discrete channels (valve states, switch inputs) would otherwise produce
+/// one identical point per aggregation window. A keepalive line is still
+/// written every `keepalive_s` so query windows are never empty.
+///
+/// ```toml
+/// [sparse]
+/// measurements = ["valve"]
+/// keepalive_s = 10
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SparseConfig {
+    pub measurements: Vec<String>,
+    pub keepalive_s: u64,
+}
+
+impl Default for SparseConfig {
+    fn default() -> Self {
+        Self {
+            measurements: Vec::new(),
+            keepalive_s: 10,
+        }
+    }
+}
+
+/// Two-person confirmation for hazardous commands.
+///
+/// When enabled, a hazardous command (main valve open, power supply output
+/// on) does not execute on arrival: it becomes a proposal that a second,
+/// different client must repeat within the timeout. The exchange is audited
+/// and the gate state is broadcast to every client.
+///
+/// ```toml
+/// [confirmation]
+/// require_second_operator = true
+/// timeout_s = 10
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmationConfig {
+    pub require_second_operator: bool,
+    /// Seconds a proposal stays open for confirmation.
+    pub timeout_s: u64,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            require_second_operator: false,
+            timeout_s: 10,
+        }
+    }
+}
+
+/// How the sync loop and the network stack are coupled.
+///
+/// `in-process` (the default) runs both halves in one process sharing
+/// channels. `socket` expects the sync loop to run as its own process
+/// (`rctrl sync-loop`) and bridges the same channels over a Unix domain
+/// socket, so a network-side crash can never stall the control loop.
+///
+/// ```toml
+/// [process]
+/// mode = "socket"
+/// socket = "/run/rctrl.sock"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessConfig {
+    pub mode: ProcessMode,
+    /// Path of the Unix domain socket in `socket` mode.
+    pub socket: String,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            mode: ProcessMode::InProcess,
+            socket: "/run/rctrl.sock".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProcessMode {
+    #[default]
+    InProcess,
+    Socket,
+}
+
+/// Writer-side measurement filter.
+///
+/// A non-empty allowlist admits only matching measurements; the blocklist
+/// drops matches from whatever the allowlist passed. Patterns are exact
+/// measurement names or prefixes ending in `*`, like bucket routes. The
+/// lists can be swapped at runtime through the status server.
+///
+/// ```toml
+/// [filter]
+/// block = ["fc_*"]
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    pub allow: Vec<String>,
+    pub block: Vec<String>,
+}
+
+/// Listen addresses of the two servers rctrl runs.
+///
+/// The WebSocket server carries telemetry and commands to GUI clients; the
+/// status server answers plain-HTTP operational queries. Both default to
+/// loopback — exposing them beyond the stand network is a deliberate act.
+///
+/// ```toml
+/// [network]
+/// listen = "127.0.0.1:9090"
+/// status = "127.0.0.1:9091"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// WebSocket listen address for GUI clients.
+    pub listen: String,
+    /// Status server listen address.
+    pub status: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:9090".to_string(),
+            status: "127.0.0.1:9091".to_string(),
+        }
+    }
+}
+
+/// Connection-level protections on the WebSocket listener; see
+/// [`crate::guard`]. The stand network is shared with other teams, so the
+/// listener defends itself: connections beyond the per-IP cap are refused,
+/// handshakes that stall are cut off, oversized messages are rejected
+/// before deserialization, and a session that floods commands is dropped.
+///
+/// ```toml
+/// [protection]
+/// max_conns_per_ip = 4
+/// handshake_timeout_s = 5
+/// max_message_kb = 64
+/// cmd_rate = 10
+/// cmd_burst = 20
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtectionConfig {
+    /// Concurrent connections allowed per peer IP.
+    pub max_conns_per_ip: usize,
+    /// Seconds the WebSocket handshake and role declaration may take.
+    pub handshake_timeout_s: u64,
+    /// Largest incoming message accepted, in kilobytes; larger ones are
+    /// refused by the transport before they reach the decoder.
+    pub max_message_kb: usize,
+    /// Commands (and flux queries) a session may issue per second,
+    /// sustained.
+    pub cmd_rate: u32,
+    /// Commands a session may issue at once before the sustained rate
+    /// applies.
+    pub cmd_burst: u32,
+}
+
+impl Default for ProtectionConfig {
+    fn default() -> Self {
+        Self {
+            max_conns_per_ip: 4,
+            handshake_timeout_s: 5,
+            max_message_kb: 64,
+            cmd_rate: 10,
+            cmd_burst: 20,
+        }
+    }
+}
+
+/// The InfluxDB endpoint.
+///
+/// The token deliberately stays out of the config file — it comes from the
+/// `INFLUX_TOKEN` environment variable, so configs can be committed and
+/// copied between stands without carrying a credential.
+///
+/// ```toml
+/// [influx]
+/// url = "http://127.0.0.1:8086"
+/// org = "rctrl"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InfluxConfig {
+    pub url: String,
+    pub org: String,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8086".to_string(),
+            org: "rctrl".to_string(),
+        }
+    }
+}
+
+/// Deadlines on external I/O in the async side.
+///
+/// Every await against something outside the process — influx writes and
+/// queries, WebSocket sends to clients, serial reads from the flight
+/// computer — is bounded, so one unresponsive endpoint stalls a request, not
+/// a task.
+///
+/// ```toml
+/// [io]
+/// influx_timeout_s = 10
+/// ws_send_timeout_s = 5
+/// serial_read_timeout_s = 30
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IoConfig {
+    /// Deadline on each influx write or query request.
+    pub influx_timeout_s: u64,
+    /// Deadline on each WebSocket send; a client that cannot drain within it
+    /// is disconnected.
+    pub ws_send_timeout_s: u64,
+    /// Longest silence tolerated on the serial port before it is reopened.
+    pub serial_read_timeout_s: u64,
+}
+
+impl Default for IoConfig {
+    fn default() -> Self {
+        Self {
+            influx_timeout_s: 10,
+            ws_send_timeout_s: 5,
+            serial_read_timeout_s: 30,
+        }
+    }
+}
+
+/// Batching policy of the influx write path.
+///
+/// Buffered lines flush when `batch_lines` have accumulated or after
+/// `flush_interval_ms`, whichever comes first — so a quiet pipeline still
+/// writes its stragglers promptly. `max_buffered_lines` bounds memory while
+/// influx is unreachable; lines beyond it are dropped and counted.
+///
+/// `monotonic` keeps each series' timestamps strictly increasing: a line
+/// stamped at or before its series' previous timestamp — an NTP step, a
+/// scheduler hiccup — is bumped one nanosecond past it, since Flux
+/// windowing downstream mishandles out-of-order points.
+///
+/// ```toml
+/// [writer]
+/// batch_lines = 10
+/// flush_interval_ms = 1000
+/// max_buffered_lines = 100000
+/// monotonic = true
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WriterConfig {
+    /// Lines that trigger a flush on their own.
+    pub batch_lines: usize,
+    /// Longest a buffered line waits before a flush is forced.
+    pub flush_interval_ms: u64,
+    /// Hard cap on buffered lines while the endpoint is unreachable.
+    pub max_buffered_lines: usize,
+    /// Bump equal/backwards timestamps to keep each series monotonic.
+    pub monotonic: bool,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        Self {
+            batch_lines: 10,
+            flush_interval_ms: 1000,
+            max_buffered_lines: 100_000,
+            monotonic: true,
+        }
+    }
+}
+
+/// Bounds and lifetime of runtime per-channel log rate overrides
+/// ([`CmdEnum::SetChannelLogRate`]).
+///
+/// `min_rate` is the lowest raw-frames-per-point a client may request — 1
+/// admits raw full-rate logging, a higher floor caps the write load a
+/// troubleshooting session can add. Overrides revert on their own after
+/// `timeout_s`, so a forgotten override cannot degrade a later test.
+///
+/// ```toml
+/// [log_rate]
+/// min_rate = 1
+/// timeout_s = 300
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogRateConfig {
+    /// Lowest raw-frames-per-point clients may request.
+    pub min_rate: u32,
+    /// Seconds until an override reverts on its own.
+    pub timeout_s: u64,
+}
+
+impl Default for LogRateConfig {
+    fn default() -> Self {
+        Self {
+            min_rate: 1,
+            timeout_s: 300,
+        }
+    }
+}
+
+/// On-disk rolling frame cache, one segment file per wall-clock hour.
+///
+/// The cache answers [`CmdEnum::QueryHistory`] so clients can scroll back
+/// through a test without an influx backend; segments older than the
+/// retention window are pruned as new ones open.
+///
+/// ```toml
+/// [history]
+/// dir = "history"
+/// retention_h = 4
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Directory the segment files live in.
+    pub dir: String,
+    /// Hours of frames kept on disk.
+    pub retention_h: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            dir: "history".to_string(),
+            retention_h: 4,
+        }
+    }
+}
+
+/// Black-box CSV flight recorder, independent of the influx pipeline.
+///
+/// Every frame is appended to rotating CSV files on local disk, so a test
+/// always leaves a record even when network logging fails mid-burn; see
+/// [`crate::recorder`].
+///
+/// ```toml
+/// [recorder]
+/// dir = "blackbox"
+/// max_file_mb = 64
+/// rotate_s = 3600
+/// fsync_s = 1
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecorderConfig {
+    /// Directory the CSV files are written into.
+    pub dir: String,
+    /// Size at which the open file rotates, in megabytes.
+    pub max_file_mb: u64,
+    /// Age at which the open file rotates, in seconds.
+    pub rotate_s: u64,
+    /// Seconds between fsyncs of the open file, bounding what a power cut
+    /// can lose; `0` syncs on every append.
+    pub fsync_s: u64,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            dir: "blackbox".to_string(),
+            max_file_mb: 64,
+            rotate_s: 3_600,
+            fsync_s: 1,
+        }
+    }
+}
+
+/// Where influx timestamps for telemetry frames come from.
+///
+/// `system` is correct while the clock is stable; `monotonic` anchors the
+/// system clock at startup and advances it monotonically, so an NTP step
+/// mid-test cannot make the stored data non-monotonic; `external` trusts the
+/// acquisition timestamps the frames themselves carry.
+///
+/// ```toml
+/// [timestamp]
+/// source = "monotonic"
+/// ```
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimestampConfig {
+    pub source: TimestampSourceConfig,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimestampSourceConfig {
+    #[default]
+    System,
+    Monotonic,
+    External,
+}
+
+/// Simulated telemetry source settings.
+///
+/// With `profile` set the simulated pressure replays a recorded trace — CSV
+/// `time_s,value` rows from a previous test, resampled onto the loop rate —
+/// so rehearsals see real transients and noise instead of the tank model.
+///
+/// ```toml
+/// [sim]
+/// profile = "profiles/coldflow.csv"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimConfig {
+    pub profile: Option<String>,
+}
+
+/// The GPIO line that drives the main valve.
+///
+/// Without `gpio_line` the valve remains model-only: commands update the
+/// modelled state but touch no hardware, which is the right behaviour on
+/// development machines. `active_high = false` suits driver boards that
+/// energise the solenoid on a low-side pull.
+///
+/// ```toml
+/// [valve]
+/// gpio_chip = "/dev/gpiochip0"
+/// gpio_line = 17
+/// active_high = true
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValveConfig {
+    /// GPIO character device the line lives on.
+    pub gpio_chip: String,
+    /// Line offset on the chip; `None` leaves the valve model-only.
+    pub gpio_line: Option<u32>,
+    /// Whether a high level means open.
+    pub active_high: bool,
+}
+
+impl Default for ValveConfig {
+    fn default() -> Self {
+        Self {
+            gpio_chip: "/dev/gpiochip0".to_string(),
+            gpio_line: None,
+            active_high: true,
+        }
+    }
+}
+
+/// One measurement rename, applied while producers still emit the old name.
+///
+/// ```toml
+/// [alias.pressure]
+/// new = "feed_pressure"
+/// double_write = true
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AliasConfig {
+    /// The measurement name to write instead.
+    pub new: String,
+    /// Also keep writing the deprecated name during a transition window.
+    #[serde(default)]
+    pub double_write: bool,
+}
+
+/// Framing of the flight computer serial stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SerialFormat {
+    /// COBS framed postcard structs, zero byte delimited.
+    CobsPostcard,
+    /// Newline delimited `seq,pressure,altitude` lines.
+    Csv,
+}
+
+/// Serial telemetry input configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SerialConfig {
+    pub port: String,
+    #[serde(default = "default_baud")]
+    pub baud: u32,
+    pub format: SerialFormat,
+}
+
+fn default_baud() -> u32 {
+    115_200
+}
+
+/// SCPI bench power supply powering the stand instrumentation.
+///
+/// ```toml
+/// [psu]
+/// addr = "10.0.0.20:5025"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PsuConfig {
+    /// Address of the supply's SCPI socket.
+    pub addr: String,
+    /// Seconds between output telemetry polls.
+    #[serde(default = "default_psu_poll")]
+    pub poll_s: u64,
+}
+
+fn default_psu_poll() -> u64 {
+    1
+}
+
+/// Local weather station supplying ambient conditions.
+///
+/// The station is expected to answer an HTTP GET with a JSON object carrying
+/// `pressure_mbar` and/or `temperature_c`; readings enter the pipeline as the
+/// `ambient_pressure`/`ambient_temperature` channels, so they are logged with
+/// the same session stamps as the stand data they contextualize.
+///
+/// ```toml
+/// [weather]
+/// url = "http://10.0.0.30/conditions.json"
+/// poll_s = 60
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeatherConfig {
+    /// URL of the station's current-conditions endpoint.
+    pub url: String,
+    /// Seconds between polls.
+    #[serde(default = "default_weather_poll")]
+    pub poll_s: u64,
+}
+
+fn default_weather_poll() -> u64 {
+    60
+}
+
+/// Camera snapshot trigger.
+///
+/// On the configured events the daemon GETs `snapshot_url` — an IP camera's
+/// snapshot endpoint or an ffmpeg trigger script behind one — and records
+/// the image reference the endpoint answers with in an `events` measurement,
+/// so footage can be found from the data it belongs to.
+///
+/// ```toml
+/// [camera]
+/// snapshot_url = "http://10.0.0.31/snapshot"
+/// on = ["ignition", "abort", "alarm"]
+/// min_interval_s = 5
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraConfig {
+    /// URL of the recorder's snapshot endpoint.
+    pub snapshot_url: String,
+    /// Event kinds that trigger a snapshot; see
+    /// [`camera::EVENT_KINDS`](crate::camera::EVENT_KINDS).
+    #[serde(default = "default_camera_on")]
+    pub on: Vec<String>,
+    /// Shortest spacing between snapshots, so an alarm storm does not become
+    /// an HTTP storm.
+    #[serde(default = "default_camera_min_interval")]
+    pub min_interval_s: u64,
+}
+
+fn default_camera_on() -> Vec<String> {
+    crate::camera::EVENT_KINDS
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect()
+}
+
+fn default_camera_min_interval() -> u64 {
+    5
+}
+
+/// Independent UDP status link to the abort box.
+///
+/// Fixed-rate, CRC-protected status packets on a path with no dependency on
+/// the main pipeline; see [`crate::abortbox`] for the packet format.
+///
+/// ```toml
+/// [abort_box]
+/// target = "10.0.0.40:4750"
+/// rate_hz = 10
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbortBoxConfig {
+    /// Destination `host:port` the packets are sent to.
+    pub target: String,
+    /// Packets per second.
+    #[serde(default = "default_abortbox_rate")]
+    pub rate_hz: u64,
+}
+
+fn default_abortbox_rate() -> u64 {
+    10
+}
+
+/// Igniter firing pulse detection on the current sense channel.
+///
+/// ```toml
+/// [igniter]
+/// threshold_a = 2.0
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IgniterConfig {
+    /// Current above which the igniter counts as firing, in amperes.
+    pub threshold_a: f64,
+    /// Shortest sustained pulse reported as a firing, in milliseconds.
+    #[serde(default = "default_min_pulse")]
+    pub min_pulse_ms: u64,
+    /// Igniter supply voltage, for the pulse energy estimate.
+    #[serde(default = "default_igniter_supply")]
+    pub supply_v: f64,
+}
+
+fn default_min_pulse() -> u64 {
+    50
+}
+
+fn default_igniter_supply() -> f64 {
+    12.0
+}
+
+/// One actuator ordering constraint.
+///
+/// The named actuator may only open once the feedback channel has read
+/// closed, continuously, for the settle time. Both the command router and
+/// the sync loop refuse an open that violates a rule.
+///
+/// ```toml
+/// [[interlock]]
+/// actuator = "valve"
+/// requires_closed = "purge_feedback"
+/// settle_ms = 500
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterlockConfig {
+    /// The actuator the rule guards.
+    pub actuator: String,
+    /// Feedback channel that must read closed before the actuator may open.
+    pub requires_closed: String,
+    /// How long the channel must have read closed, in milliseconds.
+    #[serde(default = "default_settle")]
+    pub settle_ms: u64,
+}
+
+fn default_settle() -> u64 {
+    500
+}
+
+/// One automated response rule: a threshold condition on a channel mapped to
+/// an action.
+///
+/// The sync loop evaluates every rule each cycle. The condition is a value
+/// excursion past `above` or `below` (exactly one must be set) sustained for
+/// `for_ms`; a rule fires once per excursion and re-arms when the condition
+/// clears. Rules can be armed and disarmed at runtime with
+/// [`CmdEnum::SetRuleEnabled`]; every firing is audited.
+///
+/// ```toml
+/// [[rule]]
+/// name = "overpressure"
+/// channel = "pressure"
+/// above = 30.0
+/// for_ms = 100
+/// action = "close-valve"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// Name the rule is audited and toggled under.
+    pub name: String,
+    /// Telemetry channel the condition reads.
+    pub channel: String,
+    /// Fire when the value exceeds this threshold.
+    #[serde(default)]
+    pub above: Option<f64>,
+    /// Fire when the value falls below this threshold.
+    #[serde(default)]
+    pub below: Option<f64>,
+    /// How long the condition must hold before the rule fires, in
+    /// milliseconds.
+    #[serde(default)]
+    pub for_ms: u64,
+    pub action: RuleAction,
+    /// Initial armed state; adjustable at runtime.
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+/// What a fired rule does. Every firing raises an alarm and is audited;
+/// `close-valve` and `trigger-burst` act on top of that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleAction {
+    /// Close the main valve, in the sync loop itself.
+    CloseValve,
+    /// Raise the alarm only.
+    Alarm,
+    /// Open a burst capture window in the pipeline.
+    TriggerBurst,
+}
+
+/// One state-aware routing rule: channels duplicated into a dedicated
+/// measurement while the stand is in a safety state.
+///
+/// When the pipeline enters `state`, every listed channel is additionally
+/// written raw at full rate under `measurement` for `hold_s` seconds, on top
+/// of its normal aggregated series — so anomaly data sits under its own
+/// name afterwards instead of needing timestamp archaeology.
+///
+/// ```toml
+/// [[routing]]
+/// state = "abort"
+/// channels = ["pressure", "chamber_pressure"]
+/// measurement = "abort_event"
+/// hold_s = 30
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingConfig {
+    /// Safety state that activates the route.
+    pub state: crate::pipeline::SafetyState,
+    /// Channels duplicated while the route is active.
+    pub channels: Vec<String>,
+    /// Measurement the duplicated lines are written under.
+    pub measurement: String,
+    /// Seconds the route stays active after the state is entered.
+    #[serde(default = "default_routing_hold")]
+    pub hold_s: u64,
+}
+
+fn default_routing_hold() -> u64 {
+    30
+}
+
+/// One measurement device on the stand.
+///
+/// ```toml
+/// [[device]]
+/// id = "adc0"
+/// address = 0x48
+/// mux_channel = 3
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceConfig {
+    pub id: String,
+    /// I2C address.
+    pub address: u8,
+    /// TCA9548A segment the device sits behind, if any.
+    #[serde(default)]
+    pub mux_channel: Option<u8>,
+    /// Enable PGA gain auto-ranging for channels read from this device; the
+    /// active range is recorded on each sample and held while the valve is
+    /// commanded open.
+    #[serde(default)]
+    pub auto_range: bool,
+}
+
+/// One logical channel, read from an input of a device.
+///
+/// ```toml
+/// [[channel]]
+/// id = "tank_pressure"
+/// device = "adc0"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelConfig {
+    pub id: String,
+    /// The `id` of the [`DeviceConfig`] this channel reads from.
+    pub device: String,
+}
+
+/// Two physical channels voted into one logical channel.
+///
+/// While the readings agree within `max_disagreement` the logical value is
+/// their average; on disagreement the healthier reading is selected and the
+/// pair is flagged as degraded.
+///
+/// ```toml
+/// [[redundant]]
+/// id = "chamber_pressure"
+/// primary = "pressure"
+/// secondary = "fc_pressure"
+/// max_disagreement = 0.5
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedundantConfig {
+    /// Name of the logical channel.
+    pub id: String,
+    /// Telemetry channel names of the two transducers.
+    pub primary: String,
+    pub secondary: String,
+    /// Largest absolute difference still counted as agreement.
+    pub max_disagreement: f64,
+}
+
+/// What a healthy, unpressurized channel is expected to look like.
+///
+/// ```toml
+/// [quality.pressure]
+/// ambient = 1.0
+/// tolerance = 0.5
+/// max_noise = 0.3
+/// max_dropout = 0.05
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QualityExpectation {
+    /// Expected reading with the stand at rest.
+    pub ambient: f64,
+    /// Allowed absolute offset from `ambient` before the check fails.
+    pub tolerance: f64,
+    /// Noise floor (standard deviation) above which the check warns.
+    pub max_noise: f64,
+    /// Fraction of frames allowed to carry no sample before the check fails.
+    #[serde(default = "default_max_dropout")]
+    pub max_dropout: f64,
+}
+
+fn default_max_dropout() -> f64 {
+    0.05
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate config text without touching the filesystem. The
+    /// staged-upload path puts uploads through exactly the checks [`load`]
+    /// applies at startup, so a staged file cannot fail differently later.
+    ///
+    /// [`load`]: Self::load
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(text)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Cross-field schema checks serde cannot express. Unknown keys and type
+    /// mismatches are already rejected (with line context) during parsing;
+    /// this pass collects every remaining violation into one error.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = Vec::new();
+
+        let mut device_ids = HashSet::new();
+        for device in &self.devices {
+            if !device_ids.insert(device.id.as_str()) {
+                errors.push(format!("duplicate device id '{}'", device.id));
+            }
+            if !(0x08..=0x77).contains(&device.address) {
+                errors.push(format!(
+                    "device '{}': address {:#04x} outside the 7-bit I2C range 0x08..=0x77",
+                    device.id, device.address
+                ));
+            }
+            if let Some(channel) = device.mux_channel {
+                if channel > 7 {
+                    errors.push(format!(
+                        "device '{}': mux_channel {channel} out of range 0..=7",
+                        device.id
+                    ));
+                }
+            }
+        }
+
+        let mut channel_ids = HashSet::new();
+        for channel in &self.channels {
+            if !channel_ids.insert(channel.id.as_str()) {
+                errors.push(format!("duplicate channel id '{}'", channel.id));
+            }
+            if !device_ids.contains(channel.device.as_str()) {
+                errors.push(format!(
+                    "channel '{}' references unknown device '{}'",
+                    channel.id, channel.device
+                ));
+            }
+        }
+
+        for (old, alias) in &self.aliases {
+            if *old == alias.new {
+                errors.push(format!("alias '{old}' maps to itself"));
+            }
+            if self.aliases.contains_key(&alias.new) {
+                errors.push(format!(
+                    "alias '{old}' maps to '{}', which is itself deprecated",
+                    alias.new
+                ));
+            }
+        }
+
+        if let Some(psu) = &self.psu {
+            if psu.poll_s == 0 {
+                errors.push("psu: poll_s must be positive".to_string());
+            }
+        }
+
+        if let Some(igniter) = &self.igniter {
+            if !(igniter.threshold_a.is_finite() && igniter.threshold_a > 0.0) {
+                errors.push("igniter: threshold_a must be finite and positive".to_string());
+            }
+            if !(igniter.supply_v.is_finite() && igniter.supply_v > 0.0) {
+                errors.push("igniter: supply_v must be finite and positive".to_string());
+            }
+            if igniter.min_pulse_ms == 0 {
+                errors.push("igniter: min_pulse_ms must be positive".to_string());
+            }
+        }
+
+        if let Some(weather) = &self.weather {
+            if weather.url.is_empty() {
+                errors.push("weather: url must be set".to_string());
+            }
+            if weather.poll_s == 0 {
+                errors.push("weather: poll_s must be positive".to_string());
+            }
+        }
+
+        for interlock in &self.interlocks {
+            if interlock.actuator.is_empty() || interlock.requires_closed.is_empty() {
+                errors.push("interlock: actuator and requires_closed must be set".to_string());
+            }
+            if interlock.actuator == interlock.requires_closed {
+                errors.push(format!(
+                    "interlock: '{}' cannot gate on its own channel",
+                    interlock.actuator
+                ));
+            }
+        }
+
+        let mut rule_names = HashSet::new();
+        for rule in &self.rules {
+            if rule.name.is_empty() || rule.channel.is_empty() {
+                errors.push("rule: name and channel must be set".to_string());
+            }
+            if !rule_names.insert(rule.name.as_str()) {
+                errors.push(format!("duplicate rule name '{}'", rule.name));
+            }
+            match (rule.above, rule.below) {
+                (Some(t), None) | (None, Some(t)) if t.is_finite() => {}
+                (Some(_), None) | (None, Some(_)) => errors.push(format!(
+                    "rule '{}': threshold must be finite",
+                    rule.name
+                )),
+                _ => errors.push(format!(
+                    "rule '{}': exactly one of above and below must be set",
+                    rule.name
+                )),
+            }
+        }
+
+        for route in &self.routing {
+            if route.measurement.is_empty() || route.channels.is_empty() {
+                errors.push("routing: measurement and channels must be set".to_string());
+            }
+            if route.hold_s == 0 {
+                errors.push(format!(
+                    "routing: hold_s must be positive for '{}'",
+                    route.measurement
+                ));
+            }
+        }
+
+        if self.confirmation.require_second_operator && self.confirmation.timeout_s == 0 {
+            errors.push("confirmation: timeout_s must be positive".to_string());
+        }
+
+        if self.process.mode == ProcessMode::Socket && self.process.socket.is_empty() {
+            errors.push("process: socket path must be set in socket mode".to_string());
+        }
+        #[cfg(not(unix))]
+        if self.process.mode == ProcessMode::Socket {
+            errors.push("process: socket mode requires a Unix platform".to_string());
+        }
+
+        if self.network.listen.parse::<std::net::SocketAddr>().is_err() {
+            errors.push(format!(
+                "network: listen address '{}' is not a socket address",
+                self.network.listen
+            ));
+        }
+        if self.network.status.parse::<std::net::SocketAddr>().is_err() {
+            errors.push(format!(
+                "network: status address '{}' is not a socket address",
+                self.network.status
+            ));
+        }
+        if self.influx.url.is_empty() || self.influx.org.is_empty() {
+            errors.push("influx: url and org must be set".to_string());
+        }
+
+        if self.valve.gpio_line.is_some() && self.valve.gpio_chip.is_empty() {
+            errors.push("valve: gpio_chip must be set when gpio_line is".to_string());
+        }
+
+        if let Some(camera) = &self.camera {
+            if camera.snapshot_url.is_empty() {
+                errors.push("camera: snapshot_url must be set".to_string());
+            }
+            for kind in &camera.on {
+                if !crate::camera::EVENT_KINDS.contains(&kind.as_str()) {
+                    errors.push(format!(
+                        "camera: unknown event kind '{kind}' (expected one of {})",
+                        crate::camera::EVENT_KINDS.join(", ")
+                    ));
+                }
+            }
+        }
+
+        if let Some(abort_box) = &self.abort_box {
+            if abort_box.target.is_empty() {
+                errors.push("abort_box: target must be set".to_string());
+            }
+            if !(1..=100).contains(&abort_box.rate_hz) {
+                errors.push("abort_box: rate_hz must be between 1 and 100".to_string());
+            }
+        }
+
+        if let Some(recorder) = &self.recorder {
+            if recorder.dir.is_empty() {
+                errors.push("recorder: dir must be set".to_string());
+            }
+            if recorder.max_file_mb == 0 {
+                errors.push("recorder: max_file_mb must be positive".to_string());
+            }
+            if recorder.rotate_s == 0 {
+                errors.push("recorder: rotate_s must be positive".to_string());
+            }
+        }
+
+        if self.protection.max_conns_per_ip == 0 {
+            errors.push("protection: max_conns_per_ip must be positive".to_string());
+        }
+        if self.protection.handshake_timeout_s == 0 {
+            errors.push("protection: handshake_timeout_s must be positive".to_string());
+        }
+        if self.protection.max_message_kb == 0 {
+            errors.push("protection: max_message_kb must be positive".to_string());
+        }
+        if self.protection.cmd_rate == 0 || self.protection.cmd_burst == 0 {
+            errors.push("protection: cmd_rate and cmd_burst must be positive".to_string());
+        }
+
+        if self.io.influx_timeout_s == 0 {
+            errors.push("io: influx_timeout_s must be positive".to_string());
+        }
+        if self.io.ws_send_timeout_s == 0 {
+            errors.push("io: ws_send_timeout_s must be positive".to_string());
+        }
+        if self.io.serial_read_timeout_s == 0 {
+            errors.push("io: serial_read_timeout_s must be positive".to_string());
+        }
+
+        if self.writer.batch_lines == 0 {
+            errors.push("writer: batch_lines must be positive".to_string());
+        }
+        if self.writer.flush_interval_ms == 0 {
+            errors.push("writer: flush_interval_ms must be positive".to_string());
+        }
+        if self.writer.batch_lines > self.writer.max_buffered_lines {
+            errors.push(
+                "writer: batch_lines must not exceed max_buffered_lines".to_string(),
+            );
+        }
+
+        if self.log_rate.min_rate == 0 {
+            errors.push("log_rate: min_rate must be positive".to_string());
+        }
+        if self.log_rate.timeout_s == 0 {
+            errors.push("log_rate: timeout_s must be positive".to_string());
+        }
+
+        if self.history.retention_h == 0 {
+            errors.push("history: retention_h must be positive".to_string());
+        }
+
+        if !self.sparse.measurements.is_empty() && self.sparse.keepalive_s == 0 {
+            errors.push("sparse: keepalive_s must be positive".to_string());
+        }
+
+        let mut redundant_ids = HashSet::new();
+        for pair in &self.redundant {
+            if !redundant_ids.insert(pair.id.as_str()) {
+                errors.push(format!("duplicate redundant id '{}'", pair.id));
+            }
+            if pair.primary == pair.secondary {
+                errors.push(format!(
+                    "redundant '{}' pairs channel '{}' with itself",
+                    pair.id, pair.primary
+                ));
+            }
+            if !(pair.max_disagreement.is_finite() && pair.max_disagreement > 0.0) {
+                errors.push(format!(
+                    "redundant '{}': max_disagreement must be finite and positive",
+                    pair.id
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+}
+
+/// Which command categories each role is allowed to issue.
+///
+/// ```toml
+/// [permissions]
+/// operator = ["valves", "capture", "tare"]
+/// observer = []
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(transparent)]
+pub struct PermissionMatrix(HashMap<Role, HashSet<CmdCategory>>);
+
+impl Default for PermissionMatrix {
+    /// Operators may issue everything, observers nothing.
+    fn default() -> Self {
+        let all = [
+            CmdCategory::Valves,
+            CmdCategory::Power,
+            CmdCategory::Sequencer,
+            CmdCategory::Tare,
+            CmdCategory::Annotations,
+            CmdCategory::ConfigReload,
+            CmdCategory::Capture,
+            CmdCategory::Admin,
+        ]
+        .into_iter()
+        .collect();
+        Self(HashMap::from([
+            (Role::Operator, all),
+            (Role::Observer, HashSet::new()),
+        ]))
+    }
+}
+
+impl PermissionMatrix {
+    /// Whether `role` is allowed to issue commands in `category`.
+    pub fn allows(&self, role: Role, category: CmdCategory) -> bool {
+        self.0.get(&role).is_some_and(|set| set.contains(&category))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matrix_restricts_observers() {
+        let matrix = PermissionMatrix::default();
+        assert!(matrix.allows(Role::Operator, CmdCategory::Valves));
+        assert!(!matrix.allows(Role::Observer, CmdCategory::Valves));
+    }
+
+    #[test]
+    fn matrix_parses_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [permissions]
+            operator = ["valves", "config-reload"]
+            observer = ["annotations"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.permissions.allows(Role::Operator, CmdCategory::Valves));
+        assert!(!config.permissions.allows(Role::Operator, CmdCategory::Tare));
+        assert!(config
+            .permissions
+            .allows(Role::Observer, CmdCategory::Annotations));
+    }
+
+    #[test]
+    fn validation_reports_every_error_at_once() {
+        let config: Config = toml::from_str(
+            r#"
+            [[device]]
+            id = "adc0"
+            address = 0x48
+            mux_channel = 9
+
+            [[device]]
+            id = "adc0"
+            address = 0x49
+
+            [[channel]]
+            id = "tank_pressure"
+            device = "adc1"
+            "#,
+        )
+        .unwrap();
+        let Err(ConfigError::Invalid(errors)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().any(|e| e.contains("duplicate device id")));
+        assert!(errors.iter().any(|e| e.contains("out of range")));
+        assert!(errors.iter().any(|e| e.contains("unknown device 'adc1'")));
+    }
+
+    #[test]
+    fn devices_and_channels_parse_and_validate() {
+        let config: Config = toml::from_str(
+            r#"
+            [[device]]
+            id = "adc0"
+            address = 0x48
+            mux_channel = 3
+
+            [[channel]]
+            id = "tank_pressure"
+            device = "adc0"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.devices[0].mux_channel, Some(3));
+        assert_eq!(config.channels[0].device, "adc0");
+    }
+
+    #[test]
+    fn redundant_pairs_parse_and_validate() {
+        let config: Config = toml::from_str(
+            r#"
+            [[redundant]]
+            id = "chamber_pressure"
+            primary = "pressure"
+            secondary = "fc_pressure"
+            max_disagreement = 0.5
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.redundant[0].secondary, "fc_pressure");
+
+        let config: Config = toml::from_str(
+            r#"
+            [[redundant]]
+            id = "chamber_pressure"
+            primary = "pressure"
+            secondary = "pressure"
+            max_disagreement = 0.0
+            "#,
+        )
+        .unwrap();
+        let Err(ConfigError::Invalid(errors)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn rules_parse_and_validate() {
+        let config: Config = toml::from_str(
+            r#"
+            [[rule]]
+            name = "overpressure"
+            channel = "pressure"
+            above = 30.0
+            for_ms = 100
+            action = "close-valve"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.rules[0].action, RuleAction::CloseValve);
+        assert!(config.rules[0].enabled);
+
+        let config: Config = toml::from_str(
+            r#"
+            [[rule]]
+            name = "confused"
+            channel = "pressure"
+            above = 30.0
+            below = 1.0
+            action = "alarm"
+            "#,
+        )
+        .unwrap();
+        let Err(ConfigError::Invalid(errors)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        assert!(errors[0].contains("exactly one of above and below"));
+    }
+
+    #[test]
+    fn routing_parses_and_validates() {
+        let config: Config = toml::from_str(
+            r#"
+            [[routing]]
+            state = "abort"
+            channels = ["pressure", "temperature"]
+            measurement = "abort_event"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(
+            config.routing[0].state,
+            crate::pipeline::SafetyState::Abort
+        );
+        assert_eq!(config.routing[0].hold_s, 30);
+
+        let config: Config = toml::from_str(
+            r#"
+            [[routing]]
+            state = "abort"
+            channels = []
+            measurement = ""
+            "#,
+        )
+        .unwrap();
+        let Err(ConfigError::Invalid(errors)) = config.validate() else {
+            panic!("expected validation failure");
+        };
+        assert!(errors[0].contains("measurement and channels"));
+    }
+
+    #[test]
+    fn timestamp_section_parses() {
+        let config: Config = toml::from_str(
+            r#"
+            [timestamp]
+            source = "monotonic"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.timestamp.source, TimestampSourceConfig::Monotonic);
+        assert_eq!(
+            Config::default().timestamp.source,
+            TimestampSourceConfig::System
+        );
+    }
+
+    #[test]
+    fn serial_section_parses() {
+        let config: Config = toml::from_str(
+            r#"
+            [serial]
+            port = "/dev/ttyACM0"
+            format = "cobs-postcard"
+            "#,
+        )
+        .unwrap();
+        let serial = config.serial.unwrap();
+        assert_eq!(serial.port, "/dev/ttyACM0");
+        assert_eq!(serial.baud, 115_200);
+        assert_eq!(serial.format, SerialFormat::CobsPostcard);
+    }
+}
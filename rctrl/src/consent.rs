@@ -0,0 +1,152 @@
+//! Two-person confirmation for hazardous commands.
+//!
+//! With `[confirmation]` enabled, a hazardous command is not executed when
+//! it arrives: the first client's command becomes a proposal, and the same
+//! command must be sent by a second, different client before the timeout for
+//! it to execute. The router audits every step and broadcasts the gate state
+//! to all clients, so every console shows what is awaiting consent.
+
+use rctrl_api::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Whether a command is hazardous enough to require a second operator.
+///
+/// Opening the main valve and energizing the instrumentation supply are the
+/// commands that put the stand into a dangerous state; everything else is
+/// observable or reversible.
+pub fn hazardous(cmd: &CmdEnum) -> bool {
+    match cmd {
+        CmdEnum::ValveOpen | CmdEnum::PsuOutput { enable: true } => true,
+        // Scheduling a hazardous command is as hazardous as issuing it; the
+        // gate runs when the schedule is created, not when the clock fires.
+        CmdEnum::At { inner, .. } => hazardous(inner),
+        _ => false,
+    }
+}
+
+/// What the gate decided about an offered hazardous command.
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    /// A second operator confirmed the pending proposal: execute it.
+    Execute,
+    /// The command became the pending proposal, awaiting confirmation.
+    Proposed { expires_in_s: f64 },
+    /// The command was refused; the reason goes back to the sender.
+    Refused(String),
+}
+
+struct Pending {
+    action: String,
+    proposer: String,
+    deadline: Instant,
+}
+
+/// Serializes hazardous commands through the propose/confirm exchange.
+pub struct ConsentGate {
+    timeout: Duration,
+    pending: Option<Pending>,
+}
+
+impl ConsentGate {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: None,
+        }
+    }
+
+    /// Offer a hazardous command from `peer`. An expired proposal is dropped
+    /// before the decision, so a stale consent can never fire.
+    pub fn offer(&mut self, action: &str, peer: &str) -> Decision {
+        if let Some(pending) = &self.pending {
+            if pending.deadline <= Instant::now() {
+                tracing::warn!(
+                    "confirmation of {} expired unanswered",
+                    pending.action
+                );
+                self.pending = None;
+            }
+        }
+        match &self.pending {
+            None => {
+                self.pending = Some(Pending {
+                    action: action.to_string(),
+                    proposer: peer.to_string(),
+                    deadline: Instant::now() + self.timeout,
+                });
+                Decision::Proposed {
+                    expires_in_s: self.timeout.as_secs_f64(),
+                }
+            }
+            Some(pending) if pending.action != action => Decision::Refused(format!(
+                "{} is awaiting confirmation; resolve it first",
+                pending.action
+            )),
+            Some(pending) if pending.proposer == peer => Decision::Refused(
+                "confirmation must come from a second operator".to_string(),
+            ),
+            Some(_) => {
+                self.pending = None;
+                Decision::Execute
+            }
+        }
+    }
+
+    /// Current gate state, as broadcast to clients.
+    pub fn state(&self) -> ConfirmationState {
+        match &self.pending {
+            Some(pending) => ConfirmationState {
+                pending: Some(pending.action.clone()),
+                expires_in_s: pending
+                    .deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f64(),
+            },
+            None => ConfirmationState {
+                pending: None,
+                expires_in_s: 0.0,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_second_client_confirms_and_the_proposer_cannot() {
+        let mut gate = ConsentGate::new(Duration::from_secs(10));
+        assert!(matches!(
+            gate.offer("ValveOpen", "console-a"),
+            Decision::Proposed { .. }
+        ));
+        // The proposer repeating the command is not consent.
+        assert!(matches!(
+            gate.offer("ValveOpen", "console-a"),
+            Decision::Refused(_)
+        ));
+        // A different hazardous command cannot jump the queue.
+        assert!(matches!(
+            gate.offer("PsuOutput { enable: true }", "console-b"),
+            Decision::Refused(_)
+        ));
+        assert_eq!(gate.offer("ValveOpen", "console-b"), Decision::Execute);
+        assert_eq!(gate.state().pending, None);
+    }
+
+    #[test]
+    fn an_expired_proposal_becomes_a_fresh_one() {
+        let mut gate = ConsentGate::new(Duration::ZERO);
+        assert!(matches!(
+            gate.offer("ValveOpen", "console-a"),
+            Decision::Proposed { .. }
+        ));
+        // The proposal expired immediately; the "confirmation" starts over
+        // instead of executing.
+        assert!(matches!(
+            gate.offer("ValveOpen", "console-b"),
+            Decision::Proposed { .. }
+        ));
+    }
+}
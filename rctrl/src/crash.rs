@@ -0,0 +1,100 @@
+//! Panic capture for spawned runtime tasks.
+//!
+//! A panic inside a `tokio::spawn`ed task unwinds that task and nothing else;
+//! without supervision the payload vanishes and the operator only notices the
+//! missing behavior. Tasks are therefore spawned through [`Supervisor::spawn`],
+//! which inspects the [`JoinHandle`] outcome, raises an alarm log line, counts
+//! the panic in metrics and records a crash event to influx. A process-wide
+//! panic hook captures the backtrace at the point of panic, which is no longer
+//! available once the join error surfaces.
+//!
+//! [`JoinHandle`]: tokio::task::JoinHandle
+
+use crate::metrics::METRICS;
+use crate::shutdown::{Shutdown, ShutdownReason};
+use influx::LineProtocol;
+use std::future::Future;
+use tokio::sync::mpsc;
+
+/// Environment variable naming a directory for crash report files. When
+/// unset, no files are written.
+const CRASH_DIR_ENV: &str = "RCTRL_CRASH_DIR";
+
+/// Install a process-wide panic hook that logs the payload and backtrace
+/// before the default hook runs. Called once from `main`.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        tracing::error!(target: "alarm", "panic: {info}\n{backtrace}");
+        default_hook(info);
+    }));
+}
+
+/// Spawns tasks and reports their panics instead of swallowing them.
+#[derive(Clone)]
+pub struct Supervisor {
+    line_tx: mpsc::Sender<LineProtocol>,
+    shutdown: Shutdown,
+}
+
+impl Supervisor {
+    pub fn new(line_tx: mpsc::Sender<LineProtocol>, shutdown: Shutdown) -> Self {
+        Self { line_tx, shutdown }
+    }
+
+    /// Spawn `task` and watch its join handle; a panic is reported under
+    /// `name` rather than dropped on the floor, and takes the process down
+    /// through the software watchdog — a core task that silently stays dead
+    /// is worse than a restart.
+    pub fn spawn<F>(&self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let line_tx = self.line_tx.clone();
+        let shutdown = self.shutdown.clone();
+        let handle = tokio::spawn(task);
+        tokio::spawn(async move {
+            if let Err(e) = handle.await {
+                if e.is_panic() {
+                    report(name, payload_message(e.into_panic()), &line_tx);
+                    shutdown.request(ShutdownReason::Watchdog);
+                }
+            }
+        });
+    }
+}
+
+/// Render a panic payload into something loggable.
+fn payload_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Raise the alarm, count the panic and record a crash event.
+fn report(task: &str, payload: String, line_tx: &mpsc::Sender<LineProtocol>) {
+    tracing::error!(target: "alarm", "task '{task}' panicked: {payload}");
+    METRICS.incr("task_panics", 1);
+
+    // Hand-escaped string field until line protocol strings are supported
+    // properly.
+    let escaped = payload.replace('\\', r"\\").replace('"', r#"\""#);
+    let line = LineProtocol(format!(
+        "crash,task={task} payload=\"{escaped}\" {}",
+        influx::timestamp_now()
+    ));
+    let _ = line_tx.try_send(line);
+
+    if let Ok(dir) = std::env::var(CRASH_DIR_ENV) {
+        let path = std::path::Path::new(&dir).join(format!("crash-{}.txt", influx::timestamp_now()));
+        let body = format!("task: {task}\npayload: {payload}\n");
+        if let Err(e) = std::fs::write(&path, body) {
+            tracing::warn!("failed to write crash report {}: {e}", path.display());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Dead-letter buffer for influx write rejections.
+//!
+//! When the server rejects a batch (bad field type, malformed line), dropping
+//! it with a log line loses test data. Rejected batches are kept here with
+//! the server's error message instead; the status server exposes them on
+//! `/deadletter` for inspection and `/deadletter/retry` re-injects their
+//! lines into the write path after the underlying problem is fixed. With
+//! `RCTRL_DEADLETTER_DIR` set, each rejected batch is also dumped to a file
+//! for offline fix-up and re-ingestion.
+
+use crate::metrics::METRICS;
+use influx::LineProtocol;
+use std::collections::VecDeque;
+
+/// Environment variable naming a directory for dumped batches. When unset,
+/// no files are written.
+const DUMP_DIR_ENV: &str = "RCTRL_DEADLETTER_DIR";
+
+/// Rejected batches retained for inspection.
+pub const CAPACITY: usize = 32;
+
+/// One batch the server refused, with its reason.
+pub struct RejectedBatch {
+    /// Nanoseconds since the epoch at rejection time.
+    pub at: u128,
+    /// Server error message.
+    pub error: String,
+    pub lines: Vec<LineProtocol>,
+}
+
+/// Ring of the most recent rejected batches.
+#[derive(Default)]
+pub struct DeadLetter {
+    batches: VecDeque<RejectedBatch>,
+}
+
+impl DeadLetter {
+    /// Record a rejected batch, evicting the oldest past [`CAPACITY`].
+    pub fn record(&mut self, lines: Vec<LineProtocol>, error: String) {
+        METRICS.incr("deadletter_batches", 1);
+        let batch = RejectedBatch {
+            at: influx::timestamp_now(),
+            error,
+            lines,
+        };
+        dump(&batch);
+        self.batches.push_back(batch);
+        while self.batches.len() > CAPACITY {
+            self.batches.pop_front();
+        }
+        METRICS.set_gauge("deadletter_buffered_batches", self.batches.len() as f64);
+    }
+
+    /// Remove and return everything buffered, for a retry.
+    pub fn take_all(&mut self) -> Vec<RejectedBatch> {
+        METRICS.set_gauge("deadletter_buffered_batches", 0.0);
+        self.batches.drain(..).collect()
+    }
+
+    /// The buffered batches as a JSON document for the status server.
+    pub fn to_json(&self) -> String {
+        let batches: Vec<String> = self
+            .batches
+            .iter()
+            .map(|batch| {
+                let lines: Vec<String> = batch
+                    .lines
+                    .iter()
+                    .map(|line| format!("\"{}\"", json_escape(&line.0)))
+                    .collect();
+                format!(
+                    "{{\"at\":{},\"error\":\"{}\",\"lines\":[{}]}}",
+                    batch.at,
+                    json_escape(&batch.error),
+                    lines.join(",")
+                )
+            })
+            .collect();
+        format!("[{}]", batches.join(","))
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Dump a rejected batch to the configured directory, if any.
+fn dump(batch: &RejectedBatch) {
+    let Ok(dir) = std::env::var(DUMP_DIR_ENV) else {
+        return;
+    };
+    let path = std::path::Path::new(&dir).join(format!("rejected-{}.lp", batch.at));
+    let mut body = format!("# {}\n", batch.error);
+    for line in &batch.lines {
+        body.push_str(&line.0);
+        body.push('\n');
+    }
+    if let Err(e) = std::fs::write(&path, body) {
+        tracing::warn!("failed to dump rejected batch to {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_evicts_past_capacity() {
+        let mut dl = DeadLetter::default();
+        for i in 0..(CAPACITY + 3) {
+            dl.record(vec![LineProtocol(format!("m value={i} 1"))], "boom".into());
+        }
+        assert_eq!(dl.batches.len(), CAPACITY);
+        // The oldest three were evicted.
+        assert_eq!(dl.batches[0].lines[0].0, "m value=3 1");
+        assert_eq!(dl.take_all().len(), CAPACITY);
+        assert!(dl.batches.is_empty());
+    }
+
+    #[test]
+    fn json_escapes_quotes() {
+        let mut dl = DeadLetter::default();
+        dl.record(
+            vec![LineProtocol(r#"crash,task=t payload="x" 1"#.into())],
+            "unbalanced \"quotes\"".into(),
+        );
+        let json = dl.to_json();
+        assert!(json.contains(r#"payload=\"x\""#));
+        assert!(json.contains(r#"unbalanced \"quotes\""#));
+    }
+}
@@ -0,0 +1,93 @@
+//! Hardware discovery: expected devices versus what the bus answers.
+//!
+//! A transducer on the wrong segment or a dead pull-up otherwise shows up as
+//! confusing read errors minutes into a test; comparing a bus scan against
+//! the configured device list at cold boot (and on demand) turns that into a
+//! one-line report naming exactly what is wrong.
+
+use crate::config::DeviceConfig;
+use std::fmt;
+
+/// Result of matching a bus scan against the configured devices.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Configured devices that acknowledged, `(id, address)`.
+    pub found: Vec<(String, u8)>,
+    /// Configured devices that did not acknowledge.
+    pub missing: Vec<(String, u8)>,
+    /// Responding addresses no configured device claims.
+    pub unexpected: Vec<u8>,
+}
+
+impl Report {
+    /// Every configured device answered and nothing unexpected did.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "discovery: {} found", self.found.len())?;
+        for (id, address) in &self.missing {
+            write!(f, ", missing {id}@{address:#04x}")?;
+        }
+        for address in &self.unexpected {
+            write!(f, ", unexpected {address:#04x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Match the addresses a scan reported against the expected device list.
+pub fn compare(expected: &[DeviceConfig], responding: &[u8]) -> Report {
+    let mut report = Report::default();
+    for device in expected {
+        let entry = (device.id.clone(), device.address);
+        if responding.contains(&device.address) {
+            report.found.push(entry);
+        } else {
+            report.missing.push(entry);
+        }
+    }
+    for &address in responding {
+        if !expected.iter().any(|device| device.address == address) {
+            report.unexpected.push(address);
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, address: u8) -> DeviceConfig {
+        DeviceConfig {
+            id: id.to_string(),
+            address,
+            mux_channel: None,
+            auto_range: false,
+        }
+    }
+
+    #[test]
+    fn report_classifies_found_missing_and_unexpected() {
+        let expected = [device("adc0", 0x48), device("adc1", 0x49)];
+        let report = compare(&expected, &[0x48, 0x70]);
+        assert_eq!(report.found, [("adc0".to_string(), 0x48)]);
+        assert_eq!(report.missing, [("adc1".to_string(), 0x49)]);
+        assert_eq!(report.unexpected, [0x70]);
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "discovery: 1 found, missing adc1@0x49, unexpected 0x70"
+        );
+    }
+
+    #[test]
+    fn clean_report_when_scan_matches_config() {
+        let expected = [device("adc0", 0x48)];
+        assert!(compare(&expected, &[0x48]).is_clean());
+    }
+}
@@ -0,0 +1,130 @@
+//! Connection-level protections for the WebSocket listener.
+//!
+//! The stand network is shared with other teams; a misbehaving scanner, a
+//! reconnect loop gone wild or a deliberately hostile peer must not be able
+//! to exhaust connection tasks or drown the command router. The listener
+//! caps concurrent connections per IP, bounds the handshake, rejects
+//! oversized messages before they are deserialized, and budgets each
+//! session's command rate — violations are logged and the offending
+//! connection is dropped.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Caps concurrent connections per peer IP.
+#[derive(Clone)]
+pub struct IpLimiter {
+    max_per_ip: usize,
+    active: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl IpLimiter {
+    pub fn new(max_per_ip: usize) -> Self {
+        Self {
+            max_per_ip,
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Claim a connection slot for `ip`; `None` when the IP is at its cap.
+    /// The slot is released when the returned permit drops, so it stays
+    /// correct even when a connection task unwinds.
+    pub fn try_acquire(&self, ip: IpAddr) -> Option<IpPermit> {
+        let mut active = self.active.lock().expect("ip limiter mutex poisoned");
+        let count = active.entry(ip).or_insert(0);
+        if *count >= self.max_per_ip {
+            return None;
+        }
+        *count += 1;
+        Some(IpPermit {
+            ip,
+            active: self.active.clone(),
+        })
+    }
+}
+
+/// One held connection slot; see [`IpLimiter::try_acquire`].
+pub struct IpPermit {
+    ip: IpAddr,
+    active: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl Drop for IpPermit {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().expect("ip limiter mutex poisoned");
+        if let Some(count) = active.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                active.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Per-session command budget: a token bucket holding at most `burst`
+/// tokens, refilled at `rate` per second. Legitimate operators click in
+/// bursts well under the cap; anything that drains it is flooding.
+pub struct CmdBudget {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl CmdBudget {
+    pub fn new(rate: u32, burst: u32) -> Self {
+        Self {
+            rate: f64::from(rate),
+            burst: f64::from(burst),
+            tokens: f64::from(burst),
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Spend one token; `false` means the budget is exhausted.
+    pub fn allow(&mut self) -> bool {
+        let now = Instant::now();
+        self.tokens = (self.tokens + (now - self.refilled).as_secs_f64() * self.rate)
+            .min(self.burst);
+        self.refilled = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ip_limiter_caps_and_releases_slots() {
+        let limiter = IpLimiter::new(2);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let first = limiter.try_acquire(ip).unwrap();
+        let _second = limiter.try_acquire(ip).unwrap();
+        assert!(limiter.try_acquire(ip).is_none());
+        // The cap is per IP, not global.
+        assert!(limiter.try_acquire(other).is_some());
+
+        drop(first);
+        assert!(limiter.try_acquire(ip).is_some());
+    }
+
+    #[test]
+    fn cmd_budget_exhausts_at_the_burst_size() {
+        // A refill rate of zero isolates the burst accounting from timing.
+        let mut budget = CmdBudget::new(0, 3);
+        assert!(budget.allow());
+        assert!(budget.allow());
+        assert!(budget.allow());
+        assert!(!budget.allow());
+    }
+}
@@ -0,0 +1,243 @@
+//! Bounded on-disk history cache with query-by-time access.
+//!
+//! The snapshot backfills a reconnecting client with the latest frame; this
+//! cache covers the rest of a session. Frames are appended to one segment
+//! file per wall-clock hour under a cache directory, segments older than the
+//! configured retention are pruned as new ones open, and
+//! [`CmdEnum::QueryHistory`] reads the segments back — filtered to the
+//! current session, decimated to a point budget — so the GUI can scroll
+//! through a test even when no InfluxDB is reachable.
+//!
+//! Records are length-prefixed bincode of `(session, Data)`; the session
+//! stamp keeps a query from mixing frames of an earlier run whose mission
+//! times overlap.
+
+use rctrl_api::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Appends between explicit flushes of the segment writer (1 s at the loop
+/// rate), bounding how much history a crash can lose.
+const FLUSH_EVERY: u64 = 100;
+
+/// Appends frames to the rolling segment files.
+pub struct HistoryWriter {
+    dir: PathBuf,
+    retention: Duration,
+    /// Stamp of this run, written into every record.
+    session: u64,
+    /// The open segment: its wall-clock hour and the buffered file.
+    segment: Option<(u64, BufWriter<File>)>,
+    appended: u64,
+}
+
+impl HistoryWriter {
+    pub fn new(
+        dir: impl AsRef<Path>,
+        retention: Duration,
+        session: u64,
+    ) -> std::io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            retention,
+            session,
+            segment: None,
+            appended: 0,
+        })
+    }
+
+    /// Append one frame; rolls to a new segment (and prunes old ones) when
+    /// the wall-clock hour changes. Failures drop the frame with a log line
+    /// rather than touching the pipeline.
+    pub fn append(&mut self, data: &Data) {
+        let hour = (influx::timestamp_now() / 1_000_000_000 / 3_600) as u64;
+        if self.segment.as_ref().map(|(h, _)| *h) != Some(hour) {
+            self.roll(hour);
+        }
+        let Some((_, writer)) = self.segment.as_mut() else {
+            return;
+        };
+        let Ok(record) = bincode::serialize(&(self.session, data)) else {
+            return;
+        };
+        let result = writer
+            .write_all(&(record.len() as u32).to_le_bytes())
+            .and_then(|()| writer.write_all(&record));
+        if let Err(e) = result {
+            tracing::warn!("history append failed: {e}");
+            self.segment = None;
+            return;
+        }
+        self.appended += 1;
+        if self.appended.is_multiple_of(FLUSH_EVERY) {
+            if let Some((_, writer)) = self.segment.as_mut() {
+                let _ = writer.flush();
+            }
+        }
+    }
+
+    /// Open the segment for `hour` and drop segments past retention.
+    fn roll(&mut self, hour: u64) {
+        if let Some((_, mut writer)) = self.segment.take() {
+            let _ = writer.flush();
+        }
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&self.dir, hour))
+        {
+            Ok(file) => self.segment = Some((hour, BufWriter::new(file))),
+            Err(e) => tracing::error!("failed to open history segment: {e}"),
+        }
+
+        let keep_from = hour.saturating_sub(self.retention.as_secs() / 3_600);
+        for (segment_hour, path) in segments(&self.dir) {
+            if segment_hour < keep_from {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    tracing::warn!("failed to prune history segment {}: {e}", path.display());
+                }
+            }
+        }
+    }
+}
+
+/// Read the cached frames of `session` whose mission time falls in
+/// `start_s..=stop_s` and decimate each requested channel to at most
+/// `max_points` bin means.
+pub fn query(
+    dir: &Path,
+    session: u64,
+    start_s: f64,
+    stop_s: f64,
+    channels: &[ChannelId],
+    max_points: u32,
+) -> std::io::Result<Vec<HistorySeries>> {
+    let bins = max_points.max(1) as usize;
+    let span = (stop_s - start_s).max(f64::EPSILON);
+    // Per channel, per bin: sum and count of the samples that landed there.
+    let mut accum: Vec<Vec<(f64, u64)>> = vec![vec![(0.0, 0); bins]; channels.len()];
+
+    let mut ordered = segments(dir);
+    ordered.sort_unstable();
+    for (_, path) in ordered {
+        for (frame_session, data) in read_segment(&path)? {
+            if frame_session != session {
+                continue;
+            }
+            let t = data.time.as_secs_f64();
+            if !(start_s..=stop_s).contains(&t) {
+                continue;
+            }
+            let bin = (((t - start_s) / span * bins as f64) as usize).min(bins - 1);
+            for (channel, accum) in channels.iter().zip(accum.iter_mut()) {
+                if let Some(value) = data.channel_value(&channel.0) {
+                    accum[bin].0 += value;
+                    accum[bin].1 += 1;
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(channels.len());
+    for (channel, accum) in channels.iter().zip(accum) {
+        let points = accum
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(bin, (sum, count))| {
+                let t = start_s + (bin as f64 + 0.5) / bins as f64 * span;
+                (t, sum / *count as f64)
+            })
+            .collect();
+        result.push(HistorySeries {
+            channel: channel.clone(),
+            points,
+        });
+    }
+    Ok(result)
+}
+
+/// Decode every record of one segment; a truncated tail (crash mid-write)
+/// ends the segment instead of failing the query.
+fn read_segment(path: &Path) -> std::io::Result<Vec<(u64, Data)>> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+    let mut records = Vec::new();
+    let mut rest = contents.as_slice();
+    while rest.len() >= 4 {
+        let len = u32::from_le_bytes(rest[..4].try_into().expect("4 byte prefix")) as usize;
+        let Some(record) = rest.get(4..4 + len) else {
+            break;
+        };
+        match bincode::deserialize(record) {
+            Ok(record) => records.push(record),
+            Err(e) => {
+                tracing::warn!("undecodable history record in {}: {e}", path.display());
+                break;
+            }
+        }
+        rest = &rest[4 + len..];
+    }
+    Ok(records)
+}
+
+/// The retained segments as (hour, path) pairs.
+fn segments(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let hour = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((hour, path))
+        })
+        .collect()
+}
+
+fn segment_path(dir: &Path, hour: u64) -> PathBuf {
+    dir.join(format!("{hour}.bin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(seconds: f64, pressure: f64) -> Data {
+        Data {
+            time: Duration::from_secs_f64(seconds),
+            pressure: Some(pressure),
+            ..Data::default()
+        }
+    }
+
+    #[test]
+    fn query_filters_by_session_and_decimates() {
+        let dir = std::env::temp_dir().join("rctrl_history_test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut writer = HistoryWriter::new(&dir, Duration::from_secs(3_600 * 4), 7).unwrap();
+        for i in 0..100 {
+            writer.append(&frame(f64::from(i) * 0.1, f64::from(i)));
+        }
+        // A stale record from an earlier run must not leak into results.
+        let mut stale = HistoryWriter::new(&dir, Duration::from_secs(3_600 * 4), 6).unwrap();
+        stale.append(&frame(1.0, 9_999.0));
+        drop(writer);
+        drop(stale);
+
+        let series = query(&dir, 7, 0.0, 10.0, &["pressure".into()], 5).unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].points.len(), 5);
+        // First bin covers 0..2 s: samples 0..20, mean 9.5.
+        assert!((series[0].points[0].1 - 9.5).abs() < 1e-9);
+        assert!(series[0].points.iter().all(|(_, v)| *v < 9_999.0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,117 @@
+//! Electrical confirmation of igniter firing.
+//!
+//! The igniter current channel is watched for a firing pulse: current above
+//! a configured threshold sustained for a minimum duration. When the pulse
+//! ends, one discrete facts line (`igniter_fired=true` plus duration, peak
+//! current and an energy estimate) is written, so ignition can be confirmed
+//! electrically instead of by eye on the stand camera. Samples arrive at the
+//! sync loop rate; charge is integrated over the actual inter-sample
+//! intervals, so jitter does not bias the energy estimate.
+
+use std::time::Duration;
+
+/// One confirmed firing pulse.
+pub struct PulseReport {
+    /// Time above threshold, first to last qualifying sample.
+    pub duration_ms: f64,
+    pub peak_a: f64,
+    /// Energy delivered into the igniter, estimated as the configured supply
+    /// voltage times the integrated charge.
+    pub energy_j: f64,
+}
+
+/// The pulse currently above threshold.
+struct Pulse {
+    started: Duration,
+    last_at: Duration,
+    peak_a: f64,
+    /// Integrated charge in coulombs.
+    charge_c: f64,
+}
+
+/// Detects firing pulses on the igniter current channel.
+pub struct PulseDetector {
+    threshold_a: f64,
+    min_duration: Duration,
+    supply_v: f64,
+    active: Option<Pulse>,
+}
+
+impl PulseDetector {
+    pub fn new(threshold_a: f64, min_duration: Duration, supply_v: f64) -> Self {
+        Self {
+            threshold_a,
+            min_duration,
+            supply_v,
+            active: None,
+        }
+    }
+
+    /// Feed one sample at mission time `at`. Returns a report when a pulse
+    /// that lasted at least the minimum duration has ended; shorter blips
+    /// (contact bounce, switching transients) are discarded silently.
+    pub fn update(&mut self, current_a: Option<f64>, at: Duration) -> Option<PulseReport> {
+        // A dropped sample carries no information either way; the pulse in
+        // progress stays open until a below-threshold reading closes it.
+        let current = current_a?;
+        if current >= self.threshold_a {
+            match self.active.as_mut() {
+                Some(pulse) => {
+                    let dt = at.saturating_sub(pulse.last_at).as_secs_f64();
+                    pulse.charge_c += current * dt;
+                    pulse.peak_a = pulse.peak_a.max(current);
+                    pulse.last_at = at;
+                }
+                None => {
+                    self.active = Some(Pulse {
+                        started: at,
+                        last_at: at,
+                        peak_a: current,
+                        charge_c: 0.0,
+                    })
+                }
+            }
+            return None;
+        }
+        let pulse = self.active.take()?;
+        let duration = pulse.last_at.saturating_sub(pulse.started);
+        (duration >= self.min_duration).then_some(PulseReport {
+            duration_ms: duration.as_secs_f64() * 1_000.0,
+            peak_a: pulse.peak_a,
+            energy_j: self.supply_v * pulse.charge_c,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualifying_pulse_is_reported_with_energy_estimate() {
+        let mut detector = PulseDetector::new(2.0, Duration::from_millis(50), 12.0);
+        // 8 A for 100 ms at the 10 ms loop rate, then release.
+        for i in 0..=10u64 {
+            assert!(detector
+                .update(Some(8.0), Duration::from_millis(i * 10))
+                .is_none());
+        }
+        let report = detector
+            .update(Some(0.1), Duration::from_millis(110))
+            .expect("pulse should qualify");
+        assert!((report.duration_ms - 100.0).abs() < 1e-9);
+        assert_eq!(report.peak_a, 8.0);
+        // 12 V * 8 A * 0.1 s.
+        assert!((report.energy_j - 9.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn brief_spike_and_missing_samples_do_not_fire() {
+        let mut detector = PulseDetector::new(2.0, Duration::from_millis(50), 12.0);
+        // A single above-threshold sample is shorter than the minimum pulse.
+        assert!(detector.update(Some(9.0), Duration::from_millis(0)).is_none());
+        assert!(detector.update(Some(0.0), Duration::from_millis(10)).is_none());
+        // Dropped samples leave the detector idle.
+        assert!(detector.update(None, Duration::from_millis(20)).is_none());
+    }
+}
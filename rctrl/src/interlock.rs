@@ -0,0 +1,124 @@
+//! Actuator ordering interlocks.
+//!
+//! Rules like "the main valve may only open once the purge valve has been
+//! confirmed closed for 500 ms" must not live in operator training alone.
+//! Each rule names the actuator it guards and the feedback channel that has
+//! to read closed; the monitor follows the telemetry stream and answers
+//! whether an open command is currently permitted. A feedback channel that
+//! has never been seen blocks the actuator — no confirmation is treated the
+//! same as the wrong position.
+
+use crate::config::InterlockConfig;
+use rctrl_api::prelude::*;
+use std::time::{Duration, Instant};
+
+/// One configured rule plus how long its feedback channel has read closed.
+struct Rule {
+    config: InterlockConfig,
+    /// When the feedback channel was last seen transitioning to closed;
+    /// `None` until a closed reading arrives, and cleared by an open one.
+    closed_since: Option<Instant>,
+}
+
+/// Tracks feedback state for every configured interlock rule.
+pub struct InterlockMonitor {
+    rules: Vec<Rule>,
+}
+
+impl InterlockMonitor {
+    pub fn new(rules: Vec<InterlockConfig>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|config| Rule {
+                    config,
+                    closed_since: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Feed one telemetry frame. A frame that does not carry a rule's
+    /// feedback channel leaves that rule's state unchanged.
+    pub fn observe(&mut self, data: &Data) {
+        for rule in &mut self.rules {
+            match data.channel_state(&rule.config.requires_closed) {
+                Some(false) => {
+                    rule.closed_since.get_or_insert_with(Instant::now);
+                }
+                Some(true) => rule.closed_since = None,
+                None => {}
+            }
+        }
+    }
+
+    /// Whether `actuator` may open now; the error carries the violated rule.
+    pub fn check_open(&self, actuator: &str) -> Result<(), String> {
+        for rule in &self.rules {
+            if rule.config.actuator != actuator {
+                continue;
+            }
+            let settle = Duration::from_millis(rule.config.settle_ms);
+            match rule.closed_since {
+                Some(since) if since.elapsed() >= settle => {}
+                Some(_) => {
+                    return Err(format!(
+                        "interlock: '{}' must read closed for at least {} ms before '{}' may open",
+                        rule.config.requires_closed, rule.config.settle_ms, actuator
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "interlock: '{}' is not confirmed closed, '{}' may not open",
+                        rule.config.requires_closed, actuator
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(settle_ms: u64) -> InterlockConfig {
+        InterlockConfig {
+            actuator: "valve".to_string(),
+            requires_closed: "valve_feedback".to_string(),
+            settle_ms,
+        }
+    }
+
+    fn feedback(closed: bool) -> Data {
+        Data {
+            valve_feedback: Some(!closed),
+            ..Data::default()
+        }
+    }
+
+    #[test]
+    fn open_is_blocked_until_feedback_confirms_closed() {
+        let mut monitor = InterlockMonitor::new(vec![rule(0)]);
+        // Never observed: no confirmation is the same as the wrong position.
+        assert!(monitor.check_open("valve").is_err());
+        monitor.observe(&feedback(true));
+        assert!(monitor.check_open("valve").is_ok());
+        // A frame without the channel leaves the confirmation standing.
+        monitor.observe(&Data::default());
+        assert!(monitor.check_open("valve").is_ok());
+        monitor.observe(&feedback(false));
+        assert!(monitor.check_open("valve").is_err());
+        // Other actuators are not covered by this rule.
+        assert!(monitor.check_open("purge").is_ok());
+    }
+
+    #[test]
+    fn settle_time_must_elapse_after_the_closed_transition() {
+        let mut monitor = InterlockMonitor::new(vec![rule(10_000)]);
+        monitor.observe(&feedback(true));
+        let reason = monitor.check_open("valve").unwrap_err();
+        assert!(reason.contains("10000 ms"), "{reason}");
+    }
+}
@@ -0,0 +1,196 @@
+//! Inter-process transport between the sync loop and the network stack.
+//!
+//! In the default in-process mode the two halves share tokio channels. With
+//! `[process] mode = "socket"` the sync loop runs as its own process
+//! (`rctrl sync-loop`) and the halves exchange the same frames over a Unix
+//! domain socket, so a network-side crash or memory spike can never stall
+//! the control loop. The sync-loop process binds the socket and keeps
+//! accepting; the network process connects and reconnects, and while no peer
+//! is attached frames are dropped exactly as they are when the in-process
+//! channel is full.
+//!
+//! The wire format is the channel payloads as length-prefixed bincode:
+//! [`Data`] flowing sync → network, [`Cmd`] flowing network → sync.
+
+#![cfg(unix)]
+
+use rctrl_api::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Delay between connection attempts of the network side.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+fn write_frame<T: Serialize>(stream: &mut UnixStream, payload: &T) -> std::io::Result<()> {
+    let bytes = bincode::serialize(payload)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    stream.write_all(&bytes)
+}
+
+fn read_frame<T: DeserializeOwned>(stream: &mut UnixStream) -> std::io::Result<T> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    stream.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Sync-loop process end: bind `path`, serve one network peer at a time,
+/// forwarding its commands into `cmd_tx` and the loop's frames out to it.
+/// Runs on a plain thread until the data channel closes.
+pub fn serve_sync_side(
+    path: &Path,
+    mut data_rx: crate::ring::Receiver<Data>,
+    cmd_tx: mpsc::Sender<Cmd>,
+) {
+    // A stale socket from a previous run would make bind fail.
+    let _ = std::fs::remove_file(path);
+    let listener = match UnixListener::bind(path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("failed to bind ipc socket {}: {e}", path.display());
+            return;
+        }
+    };
+    tracing::info!("ipc socket listening on {}", path.display());
+
+    loop {
+        let Ok((mut stream, _)) = listener.accept() else {
+            continue;
+        };
+        tracing::info!("network process connected");
+        // Commands arrive on their own thread so a quiet peer does not
+        // block the frame writer below.
+        let reader = {
+            let mut stream = match stream.try_clone() {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::error!("failed to clone ipc stream: {e}");
+                    continue;
+                }
+            };
+            let cmd_tx = cmd_tx.clone();
+            std::thread::spawn(move || {
+                while let Ok(cmd) = read_frame::<Cmd>(&mut stream) {
+                    if cmd_tx.blocking_send(cmd).is_err() {
+                        return;
+                    }
+                }
+            })
+        };
+        loop {
+            let Some(data) = data_rx.blocking_recv() else {
+                return;
+            };
+            if let Err(e) = write_frame(&mut stream, &data) {
+                tracing::warn!("network process lost: {e}");
+                break;
+            }
+        }
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+        let _ = reader.join();
+    }
+}
+
+/// Network process end: connect to `path` (retrying while the sync-loop
+/// process is absent), forward its frames into `data_tx` and drain `cmd_rx`
+/// out to it. Runs on a plain thread until the command channel closes.
+pub fn connect_async_side(
+    path: &Path,
+    mut data_tx: crate::ring::Sender<Data>,
+    mut cmd_rx: mpsc::Receiver<Cmd>,
+) {
+    loop {
+        let mut stream = match UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(_) => {
+                std::thread::sleep(RECONNECT_DELAY);
+                continue;
+            }
+        };
+        tracing::info!("connected to sync loop at {}", path.display());
+        let mut read_stream = match stream.try_clone() {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::error!("failed to clone ipc stream: {e}");
+                continue;
+            }
+        };
+        // The ring producer is exclusive, so the per-connection reader
+        // borrows it from a scoped thread instead of cloning a handle.
+        let cmd_channel_closed = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while let Ok(data) = read_frame::<Data>(&mut read_stream) {
+                    // Mirror the in-process ring: a full pipeline drops the
+                    // frame rather than stalling the transport.
+                    let _ = data_tx.try_send(data);
+                }
+            });
+            let closed = loop {
+                let Some(cmd) = cmd_rx.blocking_recv() else {
+                    break true;
+                };
+                if let Err(e) = write_frame(&mut stream, &cmd) {
+                    // The command is lost with the connection; the operator
+                    // sees no state change and reissues it after the
+                    // reconnect.
+                    tracing::warn!("sync loop lost, dropping command: {e}");
+                    break false;
+                }
+            };
+            // Unblock the reader so the scope can join it.
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+            closed
+        });
+        if cmd_channel_closed {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_and_commands_cross_the_socket() {
+        let path = std::env::temp_dir().join("rctrl_ipc_test.sock");
+        let (mut sync_data_tx, sync_data_rx) = crate::ring::channel::<Data>(16);
+        let (sync_cmd_tx, mut sync_cmd_rx) = mpsc::channel::<Cmd>(16);
+        let (net_data_tx, mut net_data_rx) = crate::ring::channel::<Data>(16);
+        let (net_cmd_tx, net_cmd_rx) = mpsc::channel::<Cmd>(16);
+
+        let server_path = path.clone();
+        std::thread::spawn(move || serve_sync_side(&server_path, sync_data_rx, sync_cmd_tx));
+        let client_path = path.clone();
+        std::thread::spawn(move || connect_async_side(&client_path, net_data_tx, net_cmd_rx));
+
+        sync_data_tx
+            .try_send(Data {
+                seq: 7,
+                pressure: Some(20.5),
+                ..Data::default()
+            })
+            .unwrap();
+        net_cmd_tx
+            .blocking_send(Cmd {
+                cmd: CmdEnum::ValveOpen,
+            })
+            .unwrap();
+
+        let data = net_data_rx.blocking_recv().unwrap();
+        assert_eq!(data.seq, 7);
+        assert_eq!(data.pressure, Some(20.5));
+        let cmd = sync_cmd_rx.blocking_recv().unwrap();
+        assert_eq!(cmd.cmd, CmdEnum::ValveOpen);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,218 @@
+//! Forwarding of WARN/ERROR tracing events into influx and to clients.
+//!
+//! Events land in an `app_logs` measurement so operator-visible problems are
+//! queryable next to the telemetry they coincided with, and are mirrored as
+//! [`LogRecord`]s for connected clients' log views. A per (target, message)
+//! rate limit keeps an error storm — a sensor failing at loop rate — from
+//! flooding the writer: once a key exhausts its window budget further
+//! occurrences are only counted, and the count is attached to the next line
+//! that key writes. Records share the admit decision, so clients see exactly
+//! what influx stores.
+
+use influx::{LineProtocol, ToFieldValue};
+use rctrl_api::prelude::{LogLevel, LogRecord};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Length of one rate limit window.
+const WINDOW: Duration = Duration::from_secs(10);
+/// Events a single (target, message) key may write per window.
+const MAX_PER_WINDOW: u32 = 5;
+
+/// Per (target, message) token budget with suppression counting.
+struct RateLimiter {
+    window: Duration,
+    max_per_window: u32,
+    keys: HashMap<(String, String), KeyState>,
+}
+
+struct KeyState {
+    window_start: Instant,
+    sent: u32,
+    suppressed: u64,
+}
+
+impl RateLimiter {
+    fn new(window: Duration, max_per_window: u32) -> Self {
+        Self {
+            window,
+            max_per_window,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Whether this occurrence should be written; `Some(n)` carries the
+    /// number of occurrences suppressed since the key last wrote.
+    fn admit(&mut self, target: &str, message: &str) -> Option<u64> {
+        let state = self
+            .keys
+            .entry((target.to_string(), message.to_string()))
+            .or_insert(KeyState {
+                window_start: Instant::now(),
+                sent: 0,
+                suppressed: 0,
+            });
+        if state.window_start.elapsed() >= self.window {
+            state.window_start = Instant::now();
+            state.sent = 0;
+        }
+        if state.sent < self.max_per_window {
+            state.sent += 1;
+            Some(std::mem::take(&mut state.suppressed))
+        } else {
+            state.suppressed += 1;
+            None
+        }
+    }
+}
+
+/// Tracing layer mapping WARN/ERROR events into `app_logs` lines and client
+/// [`LogRecord`]s.
+pub struct ForwardLayer {
+    line_tx: mpsc::Sender<LineProtocol>,
+    record_tx: mpsc::Sender<LogRecord>,
+    limiter: std::sync::Mutex<RateLimiter>,
+}
+
+impl ForwardLayer {
+    pub fn new(line_tx: mpsc::Sender<LineProtocol>, record_tx: mpsc::Sender<LogRecord>) -> Self {
+        Self {
+            line_tx,
+            record_tx,
+            limiter: std::sync::Mutex::new(RateLimiter::new(WINDOW, MAX_PER_WINDOW)),
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ForwardLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let message = visitor.message;
+
+        let suppressed = {
+            let mut limiter = self.limiter.lock().expect("logfwd mutex poisoned");
+            match limiter.admit(metadata.target(), &message) {
+                Some(suppressed) => suppressed,
+                None => {
+                    // Counted twice on purpose: per key above for the next
+                    // written line, globally here for the metrics snapshot.
+                    crate::metrics::METRICS.incr("log_events_suppressed", 1);
+                    return;
+                }
+            }
+        };
+
+        let level = if *metadata.level() == Level::ERROR {
+            LogLevel::Error
+        } else {
+            LogLevel::Warn
+        };
+        let timestamp = influx::timestamp_now();
+        let line = app_log_line(level, metadata.target(), &message, suppressed, timestamp);
+        // Never block a logging call site on the pipeline; if either channel
+        // is full the event still reached stderr through the fmt layer.
+        let _ = self.line_tx.try_send(line);
+        let _ = self.record_tx.try_send(LogRecord {
+            level,
+            unix_ms: (timestamp / 1_000_000) as u64,
+            source: metadata.target().to_string(),
+            message,
+        });
+    }
+}
+
+/// Extracts the `message` field of an event.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        }
+    }
+}
+
+/// Render one `app_logs` line. Newlines in the message flatten to spaces
+/// first (line protocol is line oriented); `ToFieldValue` quotes and escapes.
+fn app_log_line(
+    level: LogLevel,
+    target: &str,
+    message: &str,
+    suppressed: u64,
+    timestamp: u128,
+) -> LineProtocol {
+    let suppressed = if suppressed > 0 {
+        format!(",suppressed={suppressed}i")
+    } else {
+        String::new()
+    };
+    LineProtocol(format!(
+        "app_logs,level={},target={} message={}{} {}",
+        match level {
+            LogLevel::Error => "error",
+            _ => "warn",
+        },
+        target,
+        message.replace('\n', " ").to_field_value(),
+        suppressed,
+        timestamp
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limiter_admits_up_to_the_window_budget() {
+        let mut limiter = RateLimiter::new(Duration::from_secs(60), 2);
+        assert_eq!(limiter.admit("t", "m"), Some(0));
+        assert_eq!(limiter.admit("t", "m"), Some(0));
+        assert_eq!(limiter.admit("t", "m"), None);
+        assert_eq!(limiter.admit("t", "m"), None);
+        // Distinct keys have their own budget.
+        assert_eq!(limiter.admit("t", "other"), Some(0));
+    }
+
+    #[test]
+    fn suppressed_count_is_reported_once_the_window_rolls() {
+        let mut limiter = RateLimiter::new(Duration::ZERO, 1);
+        assert_eq!(limiter.admit("t", "m"), Some(0));
+        // Zero length window: every call starts a fresh window, so nothing
+        // is ever suppressed.
+        assert_eq!(limiter.admit("t", "m"), Some(0));
+
+        let mut limiter = RateLimiter::new(Duration::from_secs(60), 1);
+        assert_eq!(limiter.admit("t", "m"), Some(0));
+        assert_eq!(limiter.admit("t", "m"), None);
+        assert_eq!(limiter.admit("t", "m"), None);
+        // Force the window to roll without sleeping.
+        limiter.keys.get_mut(&("t".to_string(), "m".to_string())).unwrap().window_start =
+            Instant::now() - Duration::from_secs(61);
+        assert_eq!(limiter.admit("t", "m"), Some(2));
+    }
+
+    #[test]
+    fn field_values_are_escaped() {
+        assert_eq!(
+            app_log_line(LogLevel::Warn, "t", "a \"b\" c\\d\nsecond", 0, 1).0,
+            r#"app_logs,level=warn,target=t message="a \"b\" c\\d second" 1"#
+        );
+        assert_eq!(
+            app_log_line(LogLevel::Error, "t", "m", 3, 1).0,
+            r#"app_logs,level=error,target=t message="m",suppressed=3i 1"#
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! rCTRL ground control daemon.
+//!
+//! Two halves share the process: a plain thread running the hard real time
+//! sync loop ([`rctrl_sync`]) and a tokio runtime running the network stack
+//! and logging pipeline ([`rctrl_async`]). They communicate over bounded
+//! channels: telemetry frames flow sync → async, commands flow async → sync.
+//! With `[process] mode = "socket"` the halves run as separate processes
+//! instead, bridged over a Unix domain socket by [`ipc`].
+
+mod abortbox;
+mod admin;
+mod aliases;
+mod archive;
+mod audit;
+mod buckets;
+mod burst;
+mod camera;
+mod config;
+mod consent;
+mod crash;
+mod deadletter;
+mod discovery;
+mod guard;
+mod history;
+mod igniter;
+mod interlock;
+mod ipc;
+mod logfwd;
+mod metrics;
+mod notes;
+mod params;
+mod pipeline;
+mod pool;
+mod provenance;
+mod psu;
+mod quality;
+mod rctrl_async;
+mod rctrl_sync;
+mod recorder;
+mod redundancy;
+mod ring;
+mod rules;
+mod sdnotify;
+mod serial;
+mod shutdown;
+mod sim;
+mod skew;
+mod status;
+mod valve;
+mod weather;
+mod writefilter;
+
+/// Default config file path, next to the binary's working directory.
+const CONFIG_PATH: &str = "rctrl.toml";
+
+fn main() {
+    // `rctrl check-config [path]` validates the config and exits, for CI on
+    // the config repo.
+    let mut args = std::env::args().skip(1);
+    if let Some(arg) = args.next() {
+        match arg.as_str() {
+            "check-config" => {
+                let path = args.next().unwrap_or_else(|| CONFIG_PATH.to_string());
+                match config::Config::load(&path) {
+                    Ok(_) => {
+                        println!("{path}: ok");
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        eprintln!("{path}: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            // `rctrl archive <start> <stop> [dir]` exports a test session
+            // from influx into Parquet for long-term storage.
+            "archive" => {
+                let (Some(start), Some(stop)) = (args.next(), args.next()) else {
+                    eprintln!("usage: rctrl archive <start> <stop> [out-dir]");
+                    std::process::exit(2);
+                };
+                let out_dir = args.next().unwrap_or_else(|| "archive".to_string());
+                tracing_subscriber::fmt::init();
+                // The archive only needs the bucket name; a missing config
+                // means the default bucket.
+                let config = config::Config::load(CONFIG_PATH).unwrap_or_default();
+                let client = influx::client::Client::new(
+                    &config.influx.url,
+                    &config.influx.org,
+                    &config.buckets.default,
+                    &std::env::var("INFLUX_TOKEN").unwrap_or_default(),
+                );
+                let runtime =
+                    tokio::runtime::Runtime::new().expect("failed to build tokio runtime");
+                match runtime.block_on(archive::run(
+                    &client,
+                    &config.buckets.default,
+                    &start,
+                    &stop,
+                    std::path::Path::new(&out_dir),
+                )) {
+                    Ok(archives) => {
+                        for archive in &archives {
+                            println!(
+                                "{}: {} rows -> {}",
+                                archive.measurement,
+                                archive.rows,
+                                archive.path.display()
+                            );
+                        }
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        eprintln!("archive failed: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            // `rctrl sync-loop` runs only the sync loop, serving frames and
+            // accepting commands over the `[process]` socket; the network
+            // half runs as a separate `rctrl` with `mode = "socket"`.
+            #[cfg(unix)]
+            "sync-loop" => {
+                tracing_subscriber::fmt::init();
+                let config = match config::Config::load(CONFIG_PATH) {
+                    Ok(config) => config,
+                    Err(config::ConfigError::Io(e))
+                        if e.kind() == std::io::ErrorKind::NotFound =>
+                    {
+                        tracing::info!("no {CONFIG_PATH} found, using defaults");
+                        config::Config::default()
+                    }
+                    Err(e) => {
+                        eprintln!("error: {e}");
+                        std::process::exit(shutdown::ShutdownReason::ConfigError.exit_code());
+                    }
+                };
+                let (data_tx, data_rx) = ring::channel(1024);
+                let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel(64);
+                let (shutdown, _shutdown_rx) = shutdown::Shutdown::new();
+                let sync_config = rctrl_sync::SyncConfig {
+                    devices: config.devices.clone(),
+                    interlocks: config.interlocks.clone(),
+                    rules: config.rules.clone(),
+                    sim: config.sim.clone(),
+                    valve: config.valve.clone(),
+                };
+                std::thread::Builder::new()
+                    .name("rctrl_sync".to_string())
+                    .spawn(move || {
+                        rctrl_sync::run(rctrl_sync::Context::new(
+                            data_tx,
+                            cmd_rx,
+                            // Frames leave over the socket and never return.
+                            pool::detached(),
+                            shutdown,
+                            sync_config,
+                        ))
+                    })
+                    .expect("failed to spawn sync loop thread");
+                ipc::serve_sync_side(std::path::Path::new(&config.process.socket), data_rx, cmd_tx);
+                std::process::exit(0);
+            }
+            other => {
+                eprintln!(
+                    "unknown argument '{other}' (try 'check-config', 'archive' or 'sync-loop')"
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+
+    use tracing_subscriber::layer::SubscriberExt as _;
+    use tracing_subscriber::util::SubscriberInitExt as _;
+    // WARN/ERROR events additionally flow into influx and to connected
+    // clients; both channels are drained once the async side is up.
+    let (log_tx, log_rx) = tokio::sync::mpsc::channel(256);
+    let (record_tx, record_rx) = tokio::sync::mpsc::channel(256);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(logfwd::ForwardLayer::new(log_tx, record_tx))
+        .init();
+    crash::install_panic_hook();
+
+    // A config staged over the WebSocket during the previous session takes
+    // effect now, before the active file is loaded.
+    admin::promote_staged(CONFIG_PATH);
+
+    let config = match config::Config::load(CONFIG_PATH) {
+        Ok(config) => config,
+        Err(config::ConfigError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::info!("no {CONFIG_PATH} found, using defaults");
+            config::Config::default()
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(shutdown::ShutdownReason::ConfigError.exit_code());
+        }
+    };
+
+    // Frames cross the sync → async boundary through a lock-free ring so
+    // the 100 Hz loop never takes a lock or allocates on the hot path; spent
+    // frames come back for reuse through the pool's return ring.
+    let (data_tx, data_rx) = ring::channel(1024);
+    let (frame_pool, frame_return) = pool::pool(256);
+    let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel(64);
+    let (shutdown, shutdown_rx) = shutdown::Shutdown::new();
+
+    // In `socket` mode the sync loop lives in its own `rctrl sync-loop`
+    // process; this process only bridges the channels to its socket.
+    let sync_handle = match config.process.mode {
+        config::ProcessMode::InProcess => {
+            let sync_shutdown = shutdown.clone();
+            let sync_config = rctrl_sync::SyncConfig {
+                devices: config.devices.clone(),
+                interlocks: config.interlocks.clone(),
+                rules: config.rules.clone(),
+                sim: config.sim.clone(),
+                valve: config.valve.clone(),
+            };
+            std::thread::Builder::new()
+                .name("rctrl_sync".to_string())
+                .spawn(move || {
+                    rctrl_sync::run(rctrl_sync::Context::new(
+                        data_tx,
+                        cmd_rx,
+                        frame_pool,
+                        sync_shutdown,
+                        sync_config,
+                    ))
+                })
+                .expect("failed to spawn sync loop thread")
+        }
+        #[cfg(unix)]
+        config::ProcessMode::Socket => {
+            let socket = std::path::PathBuf::from(&config.process.socket);
+            std::thread::Builder::new()
+                .name("rctrl_ipc".to_string())
+                .spawn(move || ipc::connect_async_side(&socket, data_tx, cmd_rx))
+                .expect("failed to spawn ipc bridge thread")
+        }
+        #[cfg(not(unix))]
+        config::ProcessMode::Socket => unreachable!("rejected by config validation"),
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to build tokio runtime");
+    let reason = runtime.block_on(rctrl_async::run(
+        data_rx,
+        frame_return,
+        cmd_tx,
+        log_rx,
+        record_rx,
+        config,
+        shutdown,
+        shutdown_rx,
+    ));
+    // Dropping the runtime drops the command channel, which stops the sync
+    // loop; the exit code then reports why we stopped.
+    drop(runtime);
+    sync_handle.join().expect("sync loop thread panicked");
+    std::process::exit(reason.exit_code());
+}
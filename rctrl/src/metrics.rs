@@ -0,0 +1,200 @@
+//! Internal metrics registry.
+//!
+//! Subsystems increment counters, set gauges and record latency observations
+//! through the global [`METRICS`] registry; the async side periodically
+//! snapshots it into a `metrics` measurement so ground control health is
+//! visible next to the telemetry.
+
+use influx::LineProtocol;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The process wide metrics registry.
+pub static METRICS: LazyLock<MetricsRegistry> = LazyLock::new(MetricsRegistry::default);
+
+#[derive(Default)]
+pub struct MetricsRegistry {
+    counters: Mutex<BTreeMap<String, u64>>,
+    gauges: Mutex<BTreeMap<String, f64>>,
+    histograms: Mutex<BTreeMap<String, Histogram>>,
+}
+
+/// Latency histogram with power-of-two microsecond buckets.
+///
+/// The geometric buckets give roughly 2x resolution at every scale — enough
+/// for percentiles from sub-millisecond to minutes — without storing samples
+/// or allocating per observation.
+#[derive(Default)]
+pub struct Histogram {
+    /// `buckets[i]` counts values in `[2^i, 2^(i+1))` microseconds.
+    buckets: [u64; 32],
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value_ms: f64) {
+        let us = (value_ms * 1000.0).max(0.0) as u64;
+        self.buckets[(us.max(1).ilog2() as usize).min(31)] += 1;
+        self.count += 1;
+    }
+
+    /// Upper bound of the bucket holding the `q`-quantile sample, in
+    /// milliseconds; 0 while nothing has been observed.
+    fn percentile(&self, q: f64) -> f64 {
+        let target = ((self.count as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return (1u64 << (i + 1)) as f64 / 1000.0;
+            }
+        }
+        0.0
+    }
+}
+
+impl MetricsRegistry {
+    /// Increment a monotonically increasing counter.
+    pub fn incr(&self, name: &str, by: u64) {
+        let mut counters = self.counters.lock().expect("metrics mutex poisoned");
+        *counters.entry(name.to_string()).or_insert(0) += by;
+    }
+
+    /// Set a gauge to its latest observed value.
+    pub fn set_gauge(&self, name: &str, value: f64) {
+        let mut gauges = self.gauges.lock().expect("metrics mutex poisoned");
+        gauges.insert(name.to_string(), value);
+    }
+
+    /// Record one observation into a latency histogram, in milliseconds.
+    pub fn observe(&self, name: &str, value_ms: f64) {
+        let mut histograms = self.histograms.lock().expect("metrics mutex poisoned");
+        histograms.entry(name.to_string()).or_default().observe(value_ms);
+    }
+
+    /// Snapshot all counters, gauges and histogram percentiles as
+    /// (name, value) pairs.
+    pub fn snapshot(&self) -> Vec<(String, f64)> {
+        let counters = self.counters.lock().expect("metrics mutex poisoned");
+        let gauges = self.gauges.lock().expect("metrics mutex poisoned");
+        let histograms = self.histograms.lock().expect("metrics mutex poisoned");
+        counters
+            .iter()
+            .map(|(k, v)| (k.clone(), *v as f64))
+            .chain(gauges.iter().map(|(k, v)| (k.clone(), *v)))
+            .chain(histograms.iter().flat_map(|(k, h)| {
+                [
+                    (format!("{k}_p50"), h.percentile(0.50)),
+                    (format!("{k}_p90"), h.percentile(0.90)),
+                    (format!("{k}_p99"), h.percentile(0.99)),
+                ]
+            }))
+            .collect()
+    }
+
+    /// Render the registry in Prometheus text exposition format.
+    ///
+    /// Metric names are prefixed with `rctrl_` so they are namespaced in a
+    /// facility wide Prometheus.
+    pub fn to_prometheus(&self) -> String {
+        let counters = self.counters.lock().expect("metrics mutex poisoned");
+        let gauges = self.gauges.lock().expect("metrics mutex poisoned");
+
+        let mut out = String::new();
+        for (name, value) in counters.iter() {
+            out.push_str(&format!(
+                "# TYPE rctrl_{name} counter\nrctrl_{name} {value}\n"
+            ));
+        }
+        for (name, value) in gauges.iter() {
+            out.push_str(&format!(
+                "# TYPE rctrl_{name} gauge\nrctrl_{name} {value}\n"
+            ));
+        }
+        let histograms = self.histograms.lock().expect("metrics mutex poisoned");
+        for (name, histogram) in histograms.iter() {
+            out.push_str(&format!(
+                "# TYPE rctrl_{name} summary\nrctrl_{name}{{quantile=\"0.5\"}} {}\nrctrl_{name}{{quantile=\"0.9\"}} {}\nrctrl_{name}{{quantile=\"0.99\"}} {}\nrctrl_{name}_count {}\n",
+                histogram.percentile(0.50),
+                histogram.percentile(0.90),
+                histogram.percentile(0.99),
+                histogram.count,
+            ));
+        }
+        out
+    }
+
+    /// Render the registry as a single `metrics` measurement line.
+    pub fn to_line_protocol(&self) -> Option<LineProtocol> {
+        let snapshot = self.snapshot();
+        if snapshot.is_empty() {
+            return None;
+        }
+
+        let fields = snapshot
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(",");
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_nanos();
+        Some(LineProtocol(format!("metrics {} {}", fields, timestamp)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_accumulate() {
+        let registry = MetricsRegistry::default();
+        registry.incr("frames_dropped", 1);
+        registry.incr("frames_dropped", 2);
+        registry.set_gauge("loop_period_ms", 10.0);
+        let snapshot = registry.snapshot();
+        assert!(snapshot.contains(&("frames_dropped".to_string(), 3.0)));
+        assert!(snapshot.contains(&("loop_period_ms".to_string(), 10.0)));
+    }
+
+    #[test]
+    fn histogram_percentiles_bound_their_samples() {
+        let registry = MetricsRegistry::default();
+        // 98 fast observations and two slow outliers: the median stays in
+        // the fast bucket, the p99 lands in the outliers'.
+        for _ in 0..98 {
+            registry.observe("writer_latency_ms", 2.5);
+        }
+        registry.observe("writer_latency_ms", 400.0);
+        registry.observe("writer_latency_ms", 400.0);
+        let snapshot = registry.snapshot();
+        let get = |name: &str| {
+            snapshot
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| *v)
+                .unwrap()
+        };
+        // 2.5 ms falls in [2048, 4096) us, so the bucket bound is 4.096 ms.
+        assert!((get("writer_latency_ms_p50") - 4.096).abs() < 1e-9);
+        // 400 ms falls in [262144, 524288) us.
+        assert!((get("writer_latency_ms_p99") - 524.288).abs() < 1e-9);
+
+        let text = registry.to_prometheus();
+        assert!(text.contains("# TYPE rctrl_writer_latency_ms summary\n"));
+        assert!(text.contains("rctrl_writer_latency_ms_count 100\n"));
+    }
+
+    #[test]
+    fn prometheus_exposition_format() {
+        let registry = MetricsRegistry::default();
+        registry.incr("cmd_accepted", 2);
+        registry.set_gauge("burst_active", 0.0);
+        let text = registry.to_prometheus();
+        assert!(text.contains("# TYPE rctrl_cmd_accepted counter\nrctrl_cmd_accepted 2\n"));
+        assert!(text.contains("# TYPE rctrl_burst_active gauge\nrctrl_burst_active 0\n"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Operator shift log persisted with the session state.
+//!
+//! Free-text handover notes replace the paper notebook in the blockhouse:
+//! entries arrive as [`CmdEnum::AddNote`], are appended to a TOML file next
+//! to the config so they survive restarts, written to the `operator_notes`
+//! measurement, and handed to later-connecting clients through the state
+//! snapshot.
+
+use influx::{LineProtocol, ToFieldValue};
+use rctrl_api::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where the shift log lives, next to the config file.
+pub const NOTES_PATH: &str = "notes.toml";
+
+/// Longest accepted note text, in characters.
+pub const MAX_NOTE_LEN: usize = 2_000;
+
+/// On-disk shape of the shift log.
+#[derive(Default, Serialize, Deserialize)]
+struct NotesFile {
+    #[serde(default, rename = "note")]
+    notes: Vec<Note>,
+}
+
+/// The shift log: in-memory entries backed by a file.
+pub struct NotesLog {
+    path: PathBuf,
+    notes: Vec<Note>,
+}
+
+impl NotesLog {
+    /// Load the shift log; a missing file is an empty log, an unreadable one
+    /// is reported and starts over rather than blocking startup.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let notes = match std::fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str::<NotesFile>(&contents) {
+                Ok(file) => file.notes,
+                Err(e) => {
+                    tracing::error!("unreadable shift log {}: {e}", path.display());
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+        Self { path, notes }
+    }
+
+    /// Append a note and persist the log; returns the stored entry.
+    pub fn add(&mut self, author: &str, text: String) -> Note {
+        let note = Note {
+            id: self.notes.last().map_or(0, |n| n.id + 1),
+            unix_ms: (influx::timestamp_now() / 1_000_000) as u64,
+            author: author.to_string(),
+            text,
+        };
+        self.notes.push(note.clone());
+        self.save();
+        note
+    }
+
+    /// All entries, oldest first.
+    pub fn all(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Best effort write-out; a failed save loses persistence across a
+    /// restart, not the note itself.
+    fn save(&self) {
+        let file = NotesFile {
+            notes: self.notes.clone(),
+        };
+        match toml::to_string(&file) {
+            Ok(contents) => {
+                if let Err(e) = std::fs::write(&self.path, contents) {
+                    tracing::error!("failed to write shift log {}: {e}", self.path.display());
+                }
+            }
+            Err(e) => tracing::error!("failed to serialize shift log: {e}"),
+        }
+    }
+}
+
+/// The influx line for one note. Newlines flatten to spaces first (line
+/// protocol is line oriented); `ToFieldValue` quotes and escapes the text.
+pub fn to_line_protocol(note: &Note) -> LineProtocol {
+    LineProtocol(format!(
+        "operator_notes id={}i,author={},text={} {}",
+        note.id,
+        note.author.replace('\n', " ").to_field_value(),
+        note.text.replace('\n', " ").to_field_value(),
+        u128::from(note.unix_ms) * 1_000_000
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_survive_a_reload() {
+        let dir = std::env::temp_dir().join("rctrl_notes_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.toml");
+        let _ = std::fs::remove_file(&path);
+
+        let mut log = NotesLog::load(&path);
+        log.add("127.0.0.1:9", "LOX topped off, handing over to night shift".to_string());
+        log.add("127.0.0.1:9", "second entry".to_string());
+
+        let reloaded = NotesLog::load(&path);
+        assert_eq!(reloaded.all().len(), 2);
+        assert_eq!(reloaded.all()[1].id, 1);
+        assert_eq!(
+            reloaded.all()[0].text,
+            "LOX topped off, handing over to night shift"
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn note_lines_escape_text_fields() {
+        let note = Note {
+            id: 3,
+            unix_ms: 1_000,
+            author: "peer".to_string(),
+            text: "valve \"A\" stuck\nretry".to_string(),
+        };
+        assert_eq!(
+            to_line_protocol(&note).0,
+            r#"operator_notes id=3i,author="peer",text="valve \"A\" stuck retry" 1000000000"#
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Runtime-adjustable parameters.
+//!
+//! Holds the current value of every [`Param`] a client can change with
+//! `Cmd::SetParam`. Values are validated and clamped here — the hot paths
+//! that consume them (per-connection stream decimation, the aggregator) read
+//! them through relaxed atomics every iteration, so changes apply without a
+//! restart.
+
+use rctrl_api::prelude::*;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+/// Allowed range per parameter; values outside are rejected, not clamped, so
+/// an operator typo does not silently become something else.
+const STREAM_DIVISOR_RANGE: std::ops::RangeInclusive<f64> = 1.0..=100.0;
+const PLOT_RETENTION_RANGE: std::ops::RangeInclusive<f64> = 10.0..=3600.0;
+const AGGREGATION_WINDOW_RANGE: std::ops::RangeInclusive<f64> = 1.0..=1000.0;
+
+pub struct RuntimeParams {
+    stream_divisor: AtomicU32,
+    plot_retention_s: AtomicU32,
+    aggregation_window: AtomicUsize,
+}
+
+impl Default for RuntimeParams {
+    fn default() -> Self {
+        Self {
+            stream_divisor: AtomicU32::new(1),
+            plot_retention_s: AtomicU32::new(60),
+            aggregation_window: AtomicUsize::new(crate::rctrl_async::AGGREGATION_WINDOW),
+        }
+    }
+}
+
+impl RuntimeParams {
+    /// Validate and apply a parameter change, returning the applied value.
+    pub fn set(&self, param: Param, value: f64) -> Result<f64, String> {
+        let range = match param {
+            Param::StreamDivisor => STREAM_DIVISOR_RANGE,
+            Param::PlotRetentionS => PLOT_RETENTION_RANGE,
+            Param::AggregationWindow => AGGREGATION_WINDOW_RANGE,
+            _ => return Err(format!("unknown parameter {param:?}")),
+        };
+        if !value.is_finite() || value.fract() != 0.0 {
+            return Err(format!("{param:?} must be an integer"));
+        }
+        if !range.contains(&value) {
+            return Err(format!(
+                "{param:?} must be in {:.0}..={:.0}",
+                range.start(),
+                range.end()
+            ));
+        }
+
+        match param {
+            Param::StreamDivisor => self.stream_divisor.store(value as u32, Ordering::Relaxed),
+            Param::PlotRetentionS => self.plot_retention_s.store(value as u32, Ordering::Relaxed),
+            Param::AggregationWindow => self
+                .aggregation_window
+                .store(value as usize, Ordering::Relaxed),
+            _ => unreachable!(),
+        }
+        Ok(value)
+    }
+
+    pub fn stream_divisor(&self) -> u32 {
+        self.stream_divisor.load(Ordering::Relaxed)
+    }
+
+    pub fn aggregation_window(&self) -> usize {
+        self.aggregation_window.load(Ordering::Relaxed)
+    }
+
+    /// Current value of every parameter, for the connection snapshot.
+    pub fn snapshot(&self) -> Vec<(Param, f64)> {
+        vec![
+            (Param::StreamDivisor, self.stream_divisor() as f64),
+            (
+                Param::PlotRetentionS,
+                self.plot_retention_s.load(Ordering::Relaxed) as f64,
+            ),
+            (Param::AggregationWindow, self.aggregation_window() as f64),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_validates_range_and_integrality() {
+        let params = RuntimeParams::default();
+        assert_eq!(params.set(Param::StreamDivisor, 5.0), Ok(5.0));
+        assert_eq!(params.stream_divisor(), 5);
+        assert!(params.set(Param::StreamDivisor, 0.0).is_err());
+        assert!(params.set(Param::StreamDivisor, 2.5).is_err());
+        assert!(params.set(Param::AggregationWindow, f64::NAN).is_err());
+        // Rejected values leave the previous setting in place.
+        assert_eq!(params.stream_divisor(), 5);
+    }
+}
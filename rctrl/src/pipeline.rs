@@ -0,0 +1,531 @@
+//! Telemetry pipeline stages between the sync loop and the influx writer.
+
+use crate::config::RoutingConfig;
+use crate::metrics::METRICS;
+use influx::LineProtocol;
+use rctrl_api::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Detects dropped frames from the sequence numbers the sync loop assigns.
+///
+/// The first frame after a hole gets its `gap` flag set before it reaches
+/// the GUI or influx, so nothing downstream silently interpolates across the
+/// missing samples.
+#[derive(Default)]
+pub struct GapDetector {
+    last_seq: Option<u64>,
+}
+
+impl GapDetector {
+    /// Inspect one frame, flagging it when frames were lost before it.
+    pub fn check(&mut self, data: &mut Data) {
+        if let Some(last_seq) = self.last_seq {
+            let expected = last_seq.wrapping_add(1);
+            if data.seq != expected {
+                let missing = data.seq.saturating_sub(expected);
+                tracing::warn!("data gap: {missing} frame(s) lost before seq {}", data.seq);
+                METRICS.incr("data_gaps", 1);
+                METRICS.incr("frames_lost", missing);
+                data.gap = true;
+            }
+        }
+        self.last_seq = Some(data.seq);
+    }
+}
+
+/// Runs the frame validators and tags invalid samples before anything
+/// downstream consumes them.
+///
+/// Violations are carried on the frame as channel tags rather than by
+/// dropping the value, so redlines and aggregation can skip the sample while
+/// operators still see what the instrument reported. Alarms fire on the
+/// transition into and out of the invalid state, not at frame rate.
+#[derive(Default)]
+pub struct FrameValidator {
+    state: <Data as Validate>::State,
+    /// Channels currently invalid, for transition detection.
+    active: HashSet<String>,
+}
+
+impl FrameValidator {
+    /// Validate one frame, tagging every channel that failed a check.
+    pub fn check(&mut self, data: &mut Data) {
+        let violations = data.validate(&mut self.state);
+        METRICS.incr("invalid_samples", violations.len() as u64);
+        let mut now_invalid = HashSet::new();
+        for violation in violations {
+            if self.active.insert(violation.channel.to_string()) {
+                tracing::error!(
+                    target: "alarm",
+                    "invalid sample on '{}': {}",
+                    violation.channel,
+                    violation.reason
+                );
+            }
+            now_invalid.insert(violation.channel.to_string());
+            data.invalid.push(violation.channel.to_string());
+        }
+        self.active.retain(|channel| {
+            let keep = now_invalid.contains(channel);
+            if !keep {
+                tracing::info!("channel '{channel}' is valid again");
+            }
+            keep
+        });
+    }
+}
+
+/// Averages raw frames over a fixed window before they are logged.
+///
+/// The GUI receives every raw frame; influx receives one aggregated frame per
+/// window so a 100 Hz loop does not produce 100 points per second per channel.
+pub struct Aggregator {
+    window: usize,
+    count: usize,
+    pressure_sum: f64,
+    pressure_count: usize,
+    temperature_sum: f64,
+    temperature_count: usize,
+    gap_seen: bool,
+    /// Most recent travel time measurement in the window, if any.
+    travel_ms: Option<f64>,
+    last: Data,
+}
+
+impl Aggregator {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "aggregation window must be non-zero");
+        Self {
+            window,
+            count: 0,
+            pressure_sum: 0.0,
+            pressure_count: 0,
+            temperature_sum: 0.0,
+            temperature_count: 0,
+            gap_seen: false,
+            travel_ms: None,
+            last: Data::default(),
+        }
+    }
+
+    /// Change the window size; a shrunken window takes effect on the next
+    /// frame, a grown one extends the current window.
+    pub fn set_window(&mut self, window: usize) {
+        if window > 0 {
+            self.window = window;
+        }
+    }
+
+    /// Feed one raw frame; returns an aggregated frame when a window closes.
+    pub fn push(&mut self, data: &Data) -> Option<Data> {
+        // Invalid samples are excluded: averaging garbage into the logged
+        // point would defeat the tagging.
+        if let Some(pressure) = data.pressure {
+            if !data.channel_invalid("pressure") {
+                self.pressure_sum += pressure;
+                self.pressure_count += 1;
+            }
+        }
+        if let Some(temperature) = data.temperature {
+            if !data.channel_invalid("temperature") {
+                self.temperature_sum += temperature;
+                self.temperature_count += 1;
+            }
+        }
+        self.gap_seen |= data.gap;
+        if data.valve_travel_ms.is_some() {
+            self.travel_ms = data.valve_travel_ms;
+        }
+        self.last = data.clone();
+        self.count += 1;
+
+        if self.count < self.window {
+            return None;
+        }
+
+        let aggregated = Data {
+            time: self.last.time,
+            seq: self.last.seq,
+            gap: self.gap_seen,
+            pressure: (self.pressure_count > 0)
+                .then(|| self.pressure_sum / self.pressure_count as f64),
+            temperature: (self.temperature_count > 0)
+                .then(|| self.temperature_sum / self.temperature_count as f64),
+            valve: self.last.valve,
+            valve_feedback: self.last.valve_feedback,
+            valve_travel_ms: self.travel_ms,
+            ..Data::default()
+        };
+
+        self.count = 0;
+        self.pressure_sum = 0.0;
+        self.pressure_count = 0;
+        self.temperature_sum = 0.0;
+        self.temperature_count = 0;
+        self.gap_seen = false;
+        self.travel_ms = None;
+
+        Some(aggregated)
+    }
+}
+
+/// Per-channel raw logging rate overrides, set from clients for
+/// troubleshooting.
+///
+/// Influx normally receives one aggregated point per window; an override
+/// logs every `rate`-th raw frame of one channel in addition, so a
+/// misbehaving transducer can be inspected at full rate without a config
+/// edit. Requested rates are clamped to the configured floor and every
+/// override reverts on its own after the configured timeout.
+pub struct LogRateOverrides {
+    min_rate: u32,
+    timeout: Duration,
+    overrides: HashMap<String, Override>,
+}
+
+struct Override {
+    rate: u32,
+    /// Raw frames seen since the override was set.
+    count: u64,
+    set_at: Instant,
+}
+
+impl LogRateOverrides {
+    pub fn new(min_rate: u32, timeout: Duration) -> Self {
+        Self {
+            min_rate: min_rate.max(1),
+            timeout,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Set or clear (`rate == 0`) the override for one channel; returns the
+    /// applied rate after clamping.
+    pub fn set(&mut self, channel: &str, rate: u32) -> u32 {
+        if rate == 0 {
+            self.overrides.remove(channel);
+            return 0;
+        }
+        let applied = rate.max(self.min_rate);
+        self.overrides.insert(
+            channel.to_string(),
+            Override {
+                rate: applied,
+                count: 0,
+                set_at: Instant::now(),
+            },
+        );
+        applied
+    }
+
+    /// Drop overrides past their lifetime; returns whether any reverted.
+    pub fn expire(&mut self) -> bool {
+        let before = self.overrides.len();
+        let timeout = self.timeout;
+        self.overrides.retain(|channel, ov| {
+            let keep = ov.set_at.elapsed() < timeout;
+            if !keep {
+                tracing::info!("log rate override for '{channel}' expired");
+            }
+            keep
+        });
+        before != self.overrides.len()
+    }
+
+    /// Feed one raw frame; returns the raw lines due under active overrides.
+    pub fn lines_for(&mut self, data: &Data, timestamp: u128) -> Vec<LineProtocol> {
+        let mut lines = Vec::new();
+        for (channel, ov) in &mut self.overrides {
+            ov.count += 1;
+            if !ov.count.is_multiple_of(ov.rate as u64) {
+                continue;
+            }
+            if let Some(value) = data.channel_value(channel) {
+                lines.push(LineProtocol(format!("{channel} value={value} {timestamp}")));
+            }
+        }
+        lines
+    }
+
+    /// Active overrides for the state snapshot.
+    pub fn active(&self) -> Vec<(ChannelId, u32)> {
+        let mut active: Vec<_> = self
+            .overrides
+            .iter()
+            .map(|(channel, ov)| (ChannelId(channel.clone()), ov.rate))
+            .collect();
+        active.sort();
+        active
+    }
+}
+
+/// Safety state of the stand, as the pipeline sees it.
+///
+/// `Abort` is entered when a rule with the close-valve action fires — a rule
+/// closing the main valve is the stand's abort response — and routing holds
+/// it for each route's configured window rather than ever transitioning back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SafetyState {
+    Nominal,
+    Abort,
+}
+
+/// Duplicates configured channels into a dedicated measurement while the
+/// stand is in a matching safety state.
+///
+/// Aggregation makes anomalies cheap to store but coarse to inspect; a route
+/// writes its channels raw at full rate under its own measurement (e.g.
+/// `abort_event`) for `hold_s` after its state is entered, so post-test
+/// queries find the anomaly window by name instead of by timestamp hunting.
+/// The normal aggregated series are unaffected.
+pub struct StateRouter {
+    routes: Vec<Route>,
+}
+
+struct Route {
+    config: RoutingConfig,
+    /// End of the active window, set when the matching state is entered.
+    active_until: Option<Instant>,
+}
+
+impl StateRouter {
+    pub fn new(routes: impl IntoIterator<Item = RoutingConfig>) -> Self {
+        Self {
+            routes: routes
+                .into_iter()
+                .map(|config| Route {
+                    config,
+                    active_until: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Record a safety state transition, opening the window of every route
+    /// configured for the new state. Re-entering a state restarts its
+    /// routes' windows.
+    pub fn on_state(&mut self, state: SafetyState) {
+        for route in &mut self.routes {
+            if route.config.state != state {
+                continue;
+            }
+            tracing::info!(
+                "routing channels {:?} into '{}' for {} s",
+                route.config.channels,
+                route.config.measurement,
+                route.config.hold_s
+            );
+            route.active_until = Some(Instant::now() + Duration::from_secs(route.config.hold_s));
+        }
+    }
+
+    /// Feed one raw frame; returns the duplicated lines of active routes.
+    pub fn lines_for(&mut self, data: &Data, timestamp: u128) -> Vec<LineProtocol> {
+        let mut lines = Vec::new();
+        for route in &mut self.routes {
+            let active = route.active_until.is_some_and(|until| Instant::now() < until);
+            if !active {
+                route.active_until = None;
+                continue;
+            }
+            for channel in &route.config.channels {
+                if let Some(value) = data.channel_value(channel) {
+                    lines.push(LineProtocol(format!(
+                        "{},channel={} value={value} {timestamp}",
+                        route.config.measurement,
+                        influx::escape::tag_value(channel)
+                    )));
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// Suppresses unchanged lines of discrete channels.
+///
+/// Valve states and switch inputs rarely change but would otherwise be
+/// written every cycle. Measurements configured as sparse are only written
+/// when their rendered value changes, plus a periodic keepalive so a query
+/// window is never empty and a wedged producer still shows up.
+pub struct ChangeDetector {
+    sparse: HashSet<String>,
+    keepalive: Duration,
+    /// Last written line body (without timestamp) and when it was written.
+    last: HashMap<String, (String, Instant)>,
+}
+
+impl ChangeDetector {
+    pub fn new(sparse: impl IntoIterator<Item = String>, keepalive: Duration) -> Self {
+        Self {
+            sparse: sparse.into_iter().collect(),
+            keepalive,
+            last: HashMap::new(),
+        }
+    }
+
+    /// Whether this line should be written. Lines of non-sparse measurements
+    /// always pass.
+    pub fn admit(&mut self, line: &LineProtocol) -> bool {
+        let measurement = line.0.split([',', ' ']).next().unwrap_or_default();
+        if !self.sparse.contains(measurement) {
+            return true;
+        }
+        // Everything but the trailing timestamp participates in the
+        // comparison, so tag or field changes both count as changes.
+        let body = line.0.rsplit_once(' ').map_or(line.0.as_str(), |(b, _)| b);
+
+        match self.last.get(measurement) {
+            Some((last_body, written_at))
+                if last_body == body && written_at.elapsed() < self.keepalive =>
+            {
+                METRICS.incr("sparse_suppressed", 1);
+                false
+            }
+            _ => {
+                self.last
+                    .insert(measurement.to_string(), (body.to_string(), Instant::now()));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregator_averages_over_window() {
+        let mut agg = Aggregator::new(2);
+        let frame = |t: u64, p: f64| Data {
+            time: Duration::from_millis(t),
+            pressure: Some(p),
+            valve: Some(false),
+            ..Data::default()
+        };
+
+        assert!(agg.push(&frame(0, 10.0)).is_none());
+        let out = agg.push(&frame(10, 20.0)).expect("window closed");
+        assert_eq!(out.pressure, Some(15.0));
+        assert_eq!(out.time, Duration::from_millis(10));
+        assert!(agg.push(&frame(20, 1.0)).is_none());
+    }
+
+    #[test]
+    fn gap_detector_flags_first_frame_after_hole() {
+        let mut detector = GapDetector::default();
+        let mut frames: Vec<Data> = [0u64, 1, 4]
+            .iter()
+            .map(|&seq| Data {
+                seq,
+                ..Data::default()
+            })
+            .collect();
+
+        for frame in &mut frames {
+            detector.check(frame);
+        }
+        assert!(!frames[0].gap);
+        assert!(!frames[1].gap);
+        assert!(frames[2].gap);
+    }
+
+    #[test]
+    fn log_rate_override_admits_every_nth_raw_frame_within_bounds() {
+        let mut overrides = LogRateOverrides::new(2, Duration::from_secs(60));
+        // Requests below the configured floor are clamped to it.
+        assert_eq!(overrides.set("pressure", 1), 2);
+        assert_eq!(overrides.active(), vec![(ChannelId::from("pressure"), 2)]);
+
+        let frame = Data {
+            pressure: Some(12.5),
+            ..Data::default()
+        };
+        assert!(overrides.lines_for(&frame, 1).is_empty());
+        assert_eq!(
+            overrides.lines_for(&frame, 2),
+            vec![LineProtocol("pressure value=12.5 2".to_string())]
+        );
+        // Frames without the channel consume the cadence but emit nothing.
+        assert!(overrides.lines_for(&Data::default(), 3).is_empty());
+        assert!(overrides.lines_for(&Data::default(), 4).is_empty());
+
+        // Rate zero clears the override.
+        assert_eq!(overrides.set("pressure", 0), 0);
+        assert!(overrides.active().is_empty());
+        assert!(overrides.lines_for(&frame, 5).is_empty());
+    }
+
+    #[test]
+    fn log_rate_override_reverts_after_its_timeout() {
+        let mut overrides = LogRateOverrides::new(1, Duration::ZERO);
+        overrides.set("pressure", 1);
+        assert!(overrides.expire());
+        assert!(overrides.active().is_empty());
+        assert!(!overrides.expire());
+    }
+
+    #[test]
+    fn state_router_duplicates_channels_while_a_route_is_active() {
+        let route = |hold_s| RoutingConfig {
+            state: SafetyState::Abort,
+            channels: vec!["pressure".to_string()],
+            measurement: "abort_event".to_string(),
+            hold_s,
+        };
+        let mut router = StateRouter::new([route(60)]);
+        let frame = Data {
+            pressure: Some(31.5),
+            ..Data::default()
+        };
+
+        // Nothing routes until the matching state is entered.
+        assert!(router.lines_for(&frame, 1).is_empty());
+        router.on_state(SafetyState::Nominal);
+        assert!(router.lines_for(&frame, 2).is_empty());
+
+        router.on_state(SafetyState::Abort);
+        assert_eq!(
+            router.lines_for(&frame, 3),
+            vec![LineProtocol(
+                "abort_event,channel=pressure value=31.5 3".to_string()
+            )]
+        );
+        // Frames without the channel emit nothing while the route is active.
+        assert!(router.lines_for(&Data::default(), 4).is_empty());
+
+        // An elapsed hold window closes the route again.
+        let mut router = StateRouter::new([route(0)]);
+        router.on_state(SafetyState::Abort);
+        assert!(router.lines_for(&frame, 5).is_empty());
+    }
+
+    #[test]
+    fn change_detector_suppresses_unchanged_sparse_lines() {
+        let mut detector =
+            ChangeDetector::new(["valve".to_string()], Duration::from_secs(60));
+        let line = |s: &str| LineProtocol(s.to_string());
+
+        assert!(detector.admit(&line("valve state=false 1")));
+        // Same state, new timestamp: suppressed.
+        assert!(!detector.admit(&line("valve state=false 2")));
+        // State change: written.
+        assert!(detector.admit(&line("valve state=true 3")));
+        // Non-sparse measurements always pass.
+        assert!(detector.admit(&line("pressure value=1 4")));
+        assert!(detector.admit(&line("pressure value=1 5")));
+    }
+
+    #[test]
+    fn change_detector_keepalive_readmits_unchanged_lines() {
+        let mut detector = ChangeDetector::new(["valve".to_string()], Duration::ZERO);
+        let line = LineProtocol("valve state=false 1".to_string());
+        assert!(detector.admit(&line));
+        // With an elapsed keepalive the unchanged line passes again.
+        assert!(detector.admit(&line));
+    }
+}
@@ -0,0 +1,104 @@
+//! Pre-allocated frame reuse across the sync/async boundary.
+//!
+//! Building a `Data` per iteration is cheap until the heap-backed fields —
+//! `invalid`, `rules_fired` — have grown; dropping the frame then throws
+//! that capacity away and the next occurrence re-allocates it, on the hot
+//! path of the control loop. The pool closes the cycle: the async side hands
+//! spent frames back over a second lock-free ring, and the sync loop resets
+//! and reuses them, so steady state runs without frame allocation.
+//!
+//! The return path is best-effort by design. A full return ring drops the
+//! frame and the sync loop falls back to `Data::default()`; in socket mode,
+//! where the sync loop is its own process and frames cross a Unix socket,
+//! nothing comes back at all and every acquire is a default.
+
+use rctrl_api::prelude::*;
+use ringbuf::traits::{Consumer as _, Producer as _, Split as _};
+use ringbuf::HeapRb;
+
+/// The reusing end; owned by the sync loop.
+pub struct FramePool {
+    cons: ringbuf::HeapCons<Data>,
+}
+
+/// The recycling end; owned by the pipeline.
+pub struct FrameReturn {
+    prod: ringbuf::HeapProd<Data>,
+}
+
+/// A frame pool recycling up to `capacity` frames at a time.
+pub fn pool(capacity: usize) -> (FramePool, FrameReturn) {
+    assert!(capacity > 0, "pool capacity must be non-zero");
+    let (prod, cons) = HeapRb::new(capacity).split();
+    (FramePool { cons }, FrameReturn { prod })
+}
+
+/// A pool that never recycles, for the standalone sync-loop process: its
+/// frames leave over the IPC socket and have no way back.
+pub fn detached() -> FramePool {
+    pool(1).0
+}
+
+impl FramePool {
+    /// A frame in its default state, reusing a recycled frame's heap
+    /// capacity when one is waiting.
+    pub fn acquire(&mut self) -> Data {
+        match self.cons.try_pop() {
+            Some(mut frame) => {
+                reset(&mut frame);
+                frame
+            }
+            None => Data::default(),
+        }
+    }
+}
+
+impl FrameReturn {
+    /// Hand a spent frame back for reuse. Best-effort: with the pool full or
+    /// detached the frame is simply dropped.
+    pub fn recycle(&mut self, frame: Data) {
+        let _ = self.prod.try_push(frame);
+    }
+}
+
+/// Clear a frame back to its default state, keeping allocated capacity.
+fn reset(frame: &mut Data) {
+    let mut invalid = std::mem::take(&mut frame.invalid);
+    invalid.clear();
+    let mut rules_fired = std::mem::take(&mut frame.rules_fired);
+    rules_fired.clear();
+    *frame = Data {
+        invalid,
+        rules_fired,
+        ..Data::default()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recycled_frames_come_back_reset_with_their_capacity() {
+        let (mut pool, mut returns) = pool(4);
+        let mut frame = pool.acquire();
+        frame.seq = 42;
+        frame.pressure = Some(1.5);
+        frame.rules_fired.push("overpressure".to_string());
+        returns.recycle(frame);
+
+        let frame = pool.acquire();
+        assert_eq!(frame.seq, 0);
+        assert_eq!(frame.pressure, None);
+        assert!(frame.rules_fired.is_empty());
+        // The point of the exercise: the cleared vec kept its allocation.
+        assert!(frame.rules_fired.capacity() >= 1);
+    }
+
+    #[test]
+    fn an_empty_or_detached_pool_falls_back_to_defaults() {
+        let (mut pool, _returns) = pool(1);
+        assert_eq!(pool.acquire(), Data::default());
+        assert_eq!(detached().acquire(), Data::default());
+    }
+}
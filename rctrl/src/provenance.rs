@@ -0,0 +1,107 @@
+//! Build and configuration provenance for the startup banner.
+//!
+//! "Which build produced this dataset" comes up in every post-test anomaly
+//! discussion; answering it from memory has gone wrong often enough. One
+//! `rctrl_run` line per startup pins the git hash, build profile, compiler
+//! version, config file hash and hardware summary to the session's data, and
+//! the same [`BuildInfo`] rides in the client handshake snapshot and the
+//! `/status` endpoint.
+
+use crate::config::Config;
+use influx::{LineProtocol, ToFieldValue};
+use rctrl_api::prelude::*;
+
+/// The provenance of this build, combined with what was loaded at startup.
+pub fn build_info(config: &Config, config_hash: String) -> BuildInfo {
+    BuildInfo {
+        git_hash: env!("RCTRL_GIT_HASH").to_string(),
+        profile: env!("RCTRL_PROFILE").to_string(),
+        rustc: env!("RCTRL_RUSTC").to_string(),
+        config_hash,
+        hardware: hardware_summary(config),
+    }
+}
+
+/// Hash of the config file as loaded, so two datasets can be compared for
+/// "same binary, different config". FNV-1a over the raw bytes is plenty:
+/// this distinguishes revisions, it does not authenticate them.
+pub fn config_hash(path: &str) -> String {
+    let Ok(bytes) = std::fs::read(path) else {
+        return "absent".to_string();
+    };
+    hash_bytes(&bytes)
+}
+
+/// The same FNV-1a hash over bytes already in memory, so a staged upload can
+/// be hashed before it is written and compared against the file afterwards.
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// One line naming the configured telemetry source and devices.
+fn hardware_summary(config: &Config) -> String {
+    if config.devices.is_empty() {
+        return "no devices configured (simulated source)".to_string();
+    }
+    let devices: Vec<String> = config
+        .devices
+        .iter()
+        .map(|device| format!("{}@{:#04x}", device.id, device.address))
+        .collect();
+    format!("{} device(s): {}", devices.len(), devices.join(", "))
+}
+
+/// Render the startup banner as a single `rctrl_run` line.
+pub fn banner_line(info: &BuildInfo, timestamp_ns: u128) -> LineProtocol {
+    LineProtocol(format!(
+        "rctrl_run git_hash={},profile={},rustc={},config_hash={},hardware={} {}",
+        info.git_hash.to_field_value(),
+        info.profile.to_field_value(),
+        info.rustc.to_field_value(),
+        info.config_hash.to_field_value(),
+        info.hardware.to_field_value(),
+        timestamp_ns
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn banner_line_quotes_its_fields() {
+        let info = BuildInfo {
+            git_hash: "abc123".to_string(),
+            profile: "debug".to_string(),
+            rustc: "rustc 1.80.0 (abc 2024-01-01)".to_string(),
+            config_hash: "absent".to_string(),
+            hardware: "no devices configured (simulated source)".to_string(),
+        };
+        assert_eq!(
+            banner_line(&info, 7).0,
+            "rctrl_run git_hash=\"abc123\",profile=\"debug\",\
+             rustc=\"rustc 1.80.0 (abc 2024-01-01)\",config_hash=\"absent\",\
+             hardware=\"no devices configured (simulated source)\" 7"
+        );
+    }
+
+    #[test]
+    fn config_hash_distinguishes_content_and_absence() {
+        assert_eq!(config_hash("/nonexistent/rctrl.toml"), "absent");
+
+        let dir = std::env::temp_dir();
+        let a = dir.join("provenance_hash_a.toml");
+        let b = dir.join("provenance_hash_b.toml");
+        std::fs::write(&a, "x = 1").unwrap();
+        std::fs::write(&b, "x = 2").unwrap();
+        let hash_a = config_hash(a.to_str().unwrap());
+        let hash_b = config_hash(b.to_str().unwrap());
+        assert_ne!(hash_a, hash_b);
+        assert_eq!(hash_a, config_hash(a.to_str().unwrap()));
+    }
+}
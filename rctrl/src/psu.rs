@@ -0,0 +1,112 @@
+//! Instrumentation power supply control and telemetry.
+//!
+//! Owns the SCPI connection to the bench supply powering the stand sensors:
+//! guarded commands from the router switch the output and set limits, and the
+//! measured output voltage/current is polled into the pipeline as the
+//! `psu_volts`/`psu_amps` channels so a browning-out supply is visible next
+//! to the sensors it is starving.
+
+use crate::config::PsuConfig;
+use crate::metrics::METRICS;
+use rctrl_api::prelude::*;
+use rctrl_hw::psu::Psu;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Delay before reconnecting after a connection failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Run the power supply connection until the command channel closes.
+///
+/// The SCPI driver does blocking socket I/O, so the whole loop runs on the
+/// blocking pool; commands and telemetry frames cross over non-blocking
+/// channel operations.
+pub async fn task(config: PsuConfig, cmd_rx: mpsc::Receiver<CmdEnum>, data_tx: mpsc::Sender<Data>) {
+    let _ = tokio::task::spawn_blocking(move || run(config, cmd_rx, data_tx)).await;
+}
+
+fn run(config: PsuConfig, mut cmd_rx: mpsc::Receiver<CmdEnum>, data_tx: mpsc::Sender<Data>) {
+    let poll_period = Duration::from_secs(config.poll_s);
+    let start = Instant::now();
+    loop {
+        let mut psu = match Psu::connect(&config.addr) {
+            Ok(psu) => psu,
+            Err(e) => {
+                tracing::warn!("psu {} unreachable ({e}), retrying", config.addr);
+                std::thread::sleep(RECONNECT_DELAY);
+                continue;
+            }
+        };
+        match psu.identify() {
+            Ok(idn) => tracing::info!("psu connected: {idn}"),
+            Err(e) => {
+                tracing::warn!("psu {} did not identify: {e}", config.addr);
+                std::thread::sleep(RECONNECT_DELAY);
+                continue;
+            }
+        }
+
+        let mut next_poll = Instant::now();
+        'connected: loop {
+            // Apply pending commands first, so a power-cycle is not delayed
+            // behind a telemetry poll.
+            loop {
+                match cmd_rx.try_recv() {
+                    Ok(cmd) => {
+                        if let Err(e) = apply(&mut psu, &cmd) {
+                            tracing::warn!("psu command failed: {e}");
+                            break 'connected;
+                        }
+                    }
+                    Err(mpsc::error::TryRecvError::Empty) => break,
+                    Err(mpsc::error::TryRecvError::Disconnected) => return,
+                }
+            }
+
+            if Instant::now() >= next_poll {
+                next_poll += poll_period;
+                match psu.telemetry() {
+                    Ok(telemetry) => {
+                        let data = Data {
+                            time: start.elapsed(),
+                            psu_volts: Some(telemetry.volts),
+                            psu_amps: Some(telemetry.amps),
+                            ..Data::default()
+                        };
+                        if data_tx.try_send(data).is_err() {
+                            tracing::warn!("pipeline full, dropping psu telemetry");
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("psu telemetry poll failed: {e}");
+                        break 'connected;
+                    }
+                }
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        METRICS.incr("psu_reconnects", 1);
+        tracing::warn!("psu connection lost, reconnecting");
+    }
+}
+
+/// Execute one routed command against the supply. Limits are applied with the
+/// current limit first, so raising the voltage into a misconfigured limit
+/// cannot transiently overdrive a sensor bus.
+fn apply<T: std::io::Read + std::io::Write>(
+    psu: &mut Psu<T>,
+    cmd: &CmdEnum,
+) -> Result<(), rctrl_hw::psu::PsuError> {
+    match *cmd {
+        CmdEnum::PsuOutput { enable } => {
+            tracing::info!("psu output {}", if enable { "on" } else { "off" });
+            psu.set_output(enable)
+        }
+        CmdEnum::PsuLimits { volts, amps } => {
+            tracing::info!("psu limits: {volts} V, {amps} A");
+            psu.set_current_limit(amps)?;
+            psu.set_voltage(volts)
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,203 @@
+//! Pre-test data quality check.
+//!
+//! On `Cmd::DataQualityCheck` a collector subscribes to the raw frame
+//! broadcast for the requested window, accumulates per-channel statistics and
+//! grades them against the expectations in config — catching a disconnected
+//! transducer or a noisy harness before propellant is loaded.
+
+use crate::config::QualityExpectation;
+use rctrl_api::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Built-in expectations for channels not covered by config, so a bare
+/// install still gets a meaningful check.
+fn default_expectation(channel: &str) -> QualityExpectation {
+    match channel {
+        "pressure" => QualityExpectation {
+            ambient: 1.0,
+            tolerance: 0.5,
+            max_noise: 0.3,
+            max_dropout: 0.05,
+        },
+        // Anything else: only dropout is graded.
+        _ => QualityExpectation {
+            ambient: 0.0,
+            tolerance: f64::INFINITY,
+            max_noise: f64::INFINITY,
+            max_dropout: 0.05,
+        },
+    }
+}
+
+/// Running statistics for one channel.
+#[derive(Default)]
+struct ChannelStats {
+    samples: u64,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl ChannelStats {
+    fn push(&mut self, value: f64) {
+        self.samples += 1;
+        self.sum += value;
+        self.sum_sq += value * value;
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.samples as f64
+    }
+
+    /// Population standard deviation of the samples seen so far.
+    fn stddev(&self) -> f64 {
+        let mean = self.mean();
+        (self.sum_sq / self.samples as f64 - mean * mean).max(0.0).sqrt()
+    }
+}
+
+/// Grade one channel's statistics against its expectation.
+fn evaluate(
+    channel: &str,
+    stats: &ChannelStats,
+    frames: u64,
+    expectation: &QualityExpectation,
+) -> ChannelQuality {
+    let dropout_rate = if frames == 0 {
+        1.0
+    } else {
+        1.0 - stats.samples as f64 / frames as f64
+    };
+    let (noise_floor, offset) = if stats.samples > 0 {
+        (stats.stddev(), stats.mean() - expectation.ambient)
+    } else {
+        (0.0, 0.0)
+    };
+
+    let verdict = if stats.samples == 0
+        || dropout_rate > expectation.max_dropout
+        || offset.abs() > expectation.tolerance
+    {
+        QualityVerdict::Fail
+    } else if noise_floor > expectation.max_noise {
+        QualityVerdict::Warn
+    } else {
+        QualityVerdict::Pass
+    };
+
+    ChannelQuality {
+        channel: channel.into(),
+        noise_floor,
+        offset,
+        dropout_rate,
+        verdict,
+    }
+}
+
+/// Sample the raw frame broadcast for `duration_s` seconds and grade every
+/// analog channel.
+pub async fn run_check(
+    duration_s: u16,
+    mut bcast_rx: broadcast::Receiver<Data>,
+    expectations: HashMap<String, QualityExpectation>,
+) -> QualityReport {
+    let mut frames: u64 = 0;
+    let mut stats: HashMap<&str, ChannelStats> = HashMap::new();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(duration_s.into());
+    loop {
+        let data = tokio::select! {
+            data = bcast_rx.recv() => data,
+            _ = tokio::time::sleep_until(deadline) => break,
+        };
+        let Ok(data) = data else { break };
+        frames += 1;
+        if let Some(pressure) = data.pressure {
+            stats.entry("pressure").or_default().push(pressure);
+        }
+        if let Some(temperature) = data.temperature {
+            stats.entry("temperature").or_default().push(temperature);
+        }
+    }
+
+    let mut channels: Vec<ChannelQuality> = ["pressure", "temperature"]
+        .iter()
+        .map(|&channel| {
+            let expectation = expectations
+                .get(channel)
+                .copied()
+                .unwrap_or_else(|| default_expectation(channel));
+            let empty = ChannelStats::default();
+            let stats = stats.get(channel).unwrap_or(&empty);
+            evaluate(channel, stats, frames, &expectation)
+        })
+        .collect();
+    channels.sort_by(|a, b| a.channel.cmp(&b.channel));
+
+    QualityReport { channels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expectation() -> QualityExpectation {
+        QualityExpectation {
+            ambient: 1.0,
+            tolerance: 0.5,
+            max_noise: 0.2,
+            max_dropout: 0.1,
+        }
+    }
+
+    fn stats_of(values: &[f64]) -> ChannelStats {
+        let mut stats = ChannelStats::default();
+        for &v in values {
+            stats.push(v);
+        }
+        stats
+    }
+
+    #[test]
+    fn quiet_channel_at_ambient_passes() {
+        let stats = stats_of(&[1.0, 1.1, 0.9, 1.0]);
+        let quality = evaluate("pressure", &stats, 4, &expectation());
+        assert_eq!(quality.verdict, QualityVerdict::Pass);
+    }
+
+    #[test]
+    fn noisy_channel_warns() {
+        let stats = stats_of(&[0.5, 1.5, 0.5, 1.5]);
+        let quality = evaluate("pressure", &stats, 4, &expectation());
+        assert_eq!(quality.verdict, QualityVerdict::Warn);
+        assert!(quality.noise_floor > 0.2);
+    }
+
+    #[test]
+    fn offset_or_dropouts_fail() {
+        // Reads 3 bar at rest: transducer or plumbing problem.
+        let offset = evaluate("pressure", &stats_of(&[3.0, 3.0]), 2, &expectation());
+        assert_eq!(offset.verdict, QualityVerdict::Fail);
+
+        // Half the frames carried no sample: wiring problem.
+        let dropouts = evaluate("pressure", &stats_of(&[1.0, 1.0]), 4, &expectation());
+        assert_eq!(dropouts.verdict, QualityVerdict::Fail);
+        assert_eq!(dropouts.dropout_rate, 0.5);
+
+        // Dead channel.
+        let dead = evaluate("pressure", &ChannelStats::default(), 4, &expectation());
+        assert_eq!(dead.verdict, QualityVerdict::Fail);
+    }
+
+    #[test]
+    fn report_verdict_is_the_worst_channel() {
+        let report = QualityReport {
+            channels: vec![
+                evaluate("pressure", &stats_of(&[1.0]), 1, &expectation()),
+                evaluate("temperature", &ChannelStats::default(), 1, &expectation()),
+            ],
+        };
+        assert_eq!(report.verdict(), QualityVerdict::Fail);
+    }
+}
@@ -0,0 +1,1573 @@
+//! The async side: WebSocket listener, command routing and the influx
+//! logging pipeline.
+
+use crate::aliases::AliasMap;
+use crate::audit::{AuditLog, Outcome};
+use crate::buckets::BucketRouter;
+use crate::burst::BurstCapture;
+use crate::config::{Config, PermissionMatrix, ProtectionConfig, QualityExpectation, RoutingConfig, RuleAction, RuleConfig, TimestampSourceConfig};
+use crate::crash::Supervisor;
+use crate::deadletter::DeadLetter;
+use crate::igniter::PulseDetector;
+use crate::metrics::METRICS;
+use crate::params::RuntimeParams;
+use crate::pipeline::{Aggregator, ChangeDetector, FrameValidator, GapDetector, LogRateOverrides, SafetyState, StateRouter};
+use crate::config::RedundantConfig;
+use crate::quality;
+use crate::redundancy::Voter;
+use crate::sdnotify::SdNotify;
+use crate::shutdown::{Shutdown, ShutdownReason};
+use crate::status::{self, StatusState};
+use futures_util::{SinkExt, StreamExt};
+use influx::FieldSchema;
+use influx::LineProtocol;
+use influx::ToLineProtocolEntries;
+use rctrl_api::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Raw frames averaged into one logged frame (default; adjustable at runtime
+/// via `Param::AggregationWindow`).
+pub const AGGREGATION_WINDOW: usize = 10;
+/// Period between metrics snapshots.
+const METRICS_PERIOD: Duration = Duration::from_secs(5);
+/// Raw frames retained before a burst trigger (1 s at 100 Hz).
+const BURST_PRE_FRAMES: usize = 100;
+/// Raw frames logged after a burst trigger (2 s at 100 Hz).
+const BURST_POST_FRAMES: usize = 200;
+/// Period between transport-level WebSocket pings on each client socket.
+const WS_PING_PERIOD: Duration = Duration::from_secs(5);
+
+/// Run the async side until a shutdown is requested or the data channel from
+/// the sync loop closes. Returns the shutdown reason for the exit code.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    data_rx: crate::ring::Receiver<Data>,
+    frame_return: crate::pool::FrameReturn,
+    cmd_tx: mpsc::Sender<Cmd>,
+    mut log_rx: mpsc::Receiver<LineProtocol>,
+    mut record_rx: mpsc::Receiver<LogRecord>,
+    config: Config,
+    shutdown: Shutdown,
+    shutdown_rx: watch::Receiver<Option<ShutdownReason>>,
+) -> ShutdownReason {
+    let (bcast_tx, _) = broadcast::channel::<Data>(256);
+    // Non-telemetry messages fanned out to every client, e.g. confirmation
+    // gate state changes.
+    let (msg_tx, _) = broadcast::channel::<WsMessage>(16);
+    // Side channel for lines that do not originate from telemetry frames
+    // (audit events, metrics snapshots).
+    let (line_tx, line_rx) = mpsc::channel::<LineProtocol>(256);
+    // Companion field schema, re-emitted every startup so dashboard builders
+    // can read units and descriptions next to the data they describe.
+    {
+        let fields = [Pressure::FIELDS, Temperature::FIELDS, Current::FIELDS].concat();
+        for line in influx::schema_lines_at(&fields, influx::timestamp_now()) {
+            let _ = line_tx.try_send(line);
+        }
+    }
+    // Startup banner: one rctrl_run line pinning build and config provenance
+    // to the session's data.
+    let build = crate::provenance::build_info(
+        &config,
+        crate::provenance::config_hash(crate::CONFIG_PATH),
+    );
+    tracing::info!(
+        "rctrl {} ({}, {}), config {}, {}",
+        build.git_hash,
+        build.profile,
+        build.rustc,
+        build.config_hash,
+        build.hardware
+    );
+    let _ = line_tx.try_send(crate::provenance::banner_line(&build, influx::timestamp_now()));
+    // Burst trigger reasons flow from the command router to the pipeline.
+    let (burst_tx, burst_rx) = mpsc::channel::<String>(8);
+    // Avionics frames arrive on their own channel so their sequence numbers
+    // are gap-checked independently of the ground-side stream.
+    let (serial_tx, serial_rx) = mpsc::channel::<Data>(256);
+    // Every long-lived task goes through the supervisor so a panic is
+    // reported instead of silently taking the task down.
+    let supervisor = Supervisor::new(line_tx.clone(), shutdown.clone());
+    // Ctrl-c is the operator asking the process to stop.
+    {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                shutdown.request(ShutdownReason::OperatorRequest);
+            }
+        });
+    }
+    if let Some(serial_config) = config.serial.clone() {
+        supervisor.spawn(
+            "serial",
+            crate::serial::task(
+                serial_config,
+                Duration::from_secs(config.io.serial_read_timeout_s),
+                serial_tx,
+                line_tx.clone(),
+            ),
+        );
+    }
+    // Ambient conditions poll in on their own channel, at weather-station
+    // cadence rather than loop rate.
+    let (weather_tx, weather_rx) = mpsc::channel::<Data>(8);
+    if let Some(weather_config) = config.weather.clone() {
+        supervisor.spawn("weather", crate::weather::task(weather_config, weather_tx));
+    }
+    // Power supply commands flow router → psu task; its telemetry re-enters
+    // the pipeline as frames like the avionics stream does.
+    let (psu_tx, psu_rx) = mpsc::channel::<Data>(64);
+    let psu_cmd_tx = config.psu.clone().map(|psu_config| {
+        let (psu_cmd_tx, psu_cmd_rx) = mpsc::channel::<CmdEnum>(8);
+        supervisor.spawn("psu", crate::psu::task(psu_config, psu_cmd_rx, psu_tx));
+        psu_cmd_tx
+    });
+    // The abort box link is deliberately isolated: its own socket, its own
+    // clock, fed the latest state through a watch channel so it keeps
+    // reporting — flagged stale — even with the rest of the pipeline wedged.
+    let (abortbox_tx, abortbox_rx) = watch::channel(crate::abortbox::Status::default());
+    if let Some(abortbox_config) = config.abort_box.clone() {
+        supervisor.spawn("abortbox", crate::abortbox::task(abortbox_config, abortbox_rx));
+    }
+
+    // A config staged during an earlier session that was not applied yet
+    // (the process was killed before restarting, say) is still reported.
+    let staged_config_hash = crate::admin::staged_hash(crate::CONFIG_PATH);
+    let state = StatusState::new(build.clone(), staged_config_hash.clone());
+    let params = Arc::new(RuntimeParams::default());
+    // Latest known state, sent to clients as a snapshot on (re)connection.
+    let snapshot = Arc::new(Mutex::new(StateSnapshot::default()));
+    // The shift log outlives connections and restarts; its entries are
+    // folded into the snapshot so late clients get the whole handover.
+    let notes = Arc::new(Mutex::new(crate::notes::NotesLog::load(
+        crate::notes::NOTES_PATH,
+    )));
+    snapshot.lock().expect("snapshot mutex poisoned").notes =
+        notes.lock().expect("notes mutex poisoned").all().to_vec();
+    snapshot.lock().expect("snapshot mutex poisoned").build = build;
+    snapshot
+        .lock()
+        .expect("snapshot mutex poisoned")
+        .staged_config_hash = staged_config_hash;
+
+    let client = influx::client::Client::new(
+        &config.influx.url,
+        &config.influx.org,
+        &config.buckets.default,
+        &std::env::var("INFLUX_TOKEN").unwrap_or_default(),
+    )
+    .with_timeout(Duration::from_secs(config.io.influx_timeout_s));
+
+    // Interlock state follows the broadcast stream — the same frames the
+    // quality checker samples — so the router judges open commands against
+    // what the stand last reported, not against what was last commanded.
+    let interlocks = Arc::new(Mutex::new(crate::interlock::InterlockMonitor::new(
+        config.interlocks.clone(),
+    )));
+    {
+        let interlocks = interlocks.clone();
+        let mut bcast_rx = bcast_tx.subscribe();
+        supervisor.spawn("interlocks", async move {
+            loop {
+                match bcast_rx.recv().await {
+                    Ok(data) => interlocks
+                        .lock()
+                        .expect("interlock mutex poisoned")
+                        .observe(&data),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // The history cache stamps every record with this run, so a query never
+    // mixes in frames of an earlier session whose mission times overlap.
+    let session = (influx::timestamp_now() / 1_000_000) as u64;
+    let history_dir = std::path::PathBuf::from(&config.history.dir);
+    let history = match crate::history::HistoryWriter::new(
+        &history_dir,
+        Duration::from_secs(config.history.retention_h * 3_600),
+        session,
+    ) {
+        Ok(writer) => Some(writer),
+        Err(e) => {
+            tracing::error!("history cache disabled: {e}");
+            None
+        }
+    };
+
+    // The black box records every frame to local disk, independent of the
+    // influx write path; a failure here disables it rather than the stand.
+    let recorder = config.recorder.as_ref().and_then(|recorder_config| {
+        match crate::recorder::Recorder::new(recorder_config) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                tracing::error!("flight recorder disabled: {e}");
+                None
+            }
+        }
+    });
+
+    // Per-channel log rate overrides, set by the router and honored (and
+    // expired) by the pipeline.
+    let log_rate = Arc::new(Mutex::new(LogRateOverrides::new(
+        config.log_rate.min_rate,
+        Duration::from_secs(config.log_rate.timeout_s),
+    )));
+
+    let router = Router {
+        cmd_tx,
+        influx: client.clone(),
+        burst_tx,
+        psu_cmd_tx,
+        bcast_tx: bcast_tx.clone(),
+        permissions: Arc::new(config.permissions),
+        params: params.clone(),
+        quality: Arc::new(config.quality),
+        audit: AuditLog::new(line_tx.clone()),
+        notes,
+        snapshot: snapshot.clone(),
+        line_tx: line_tx.clone(),
+        supervisor: supervisor.clone(),
+        history_dir,
+        session,
+        interlocks,
+        rules: Arc::new(config.rules.clone()),
+        log_rate: log_rate.clone(),
+        consent: config.confirmation.require_second_operator.then(|| {
+            Arc::new(Mutex::new(crate::consent::ConsentGate::new(
+                Duration::from_secs(config.confirmation.timeout_s),
+            )))
+        }),
+        msg_tx: msg_tx.clone(),
+        ws_send_timeout: Duration::from_secs(config.io.ws_send_timeout_s),
+        admin_token: std::env::var(crate::admin::TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty()),
+        status: state.clone(),
+    };
+
+    // Rejected influx batches, kept for inspection/retry via the status
+    // server.
+    let deadletter = Arc::new(Mutex::new(DeadLetter::default()));
+    // Writer-side measurement filter, hot-swappable through the status
+    // server for debugging sessions.
+    let filter = Arc::new(Mutex::new(crate::writefilter::WriteFilter::new(
+        config.filter.clone(),
+    )));
+
+    supervisor.spawn(
+        "status",
+        status::serve(
+            config.network.status.clone(),
+            state.clone(),
+            deadletter.clone(),
+            filter.clone(),
+            line_tx.clone(),
+        ),
+    );
+    supervisor.spawn("metrics", metrics_task(line_tx.clone()));
+    // `app_logs` lines produced by the tracing forwarder enter the pipeline
+    // through the same side channel as audit events and metrics.
+    {
+        let line_tx = line_tx.clone();
+        supervisor.spawn("log_forward", async move {
+            while let Some(line) = log_rx.recv().await {
+                let _ = line_tx.send(line).await;
+            }
+        });
+    }
+    // The same events, as structured records, fan out to every client's log
+    // view.
+    {
+        let msg_tx = msg_tx.clone();
+        supervisor.spawn("log_broadcast", async move {
+            while let Some(record) = record_rx.recv().await {
+                let _ = msg_tx.send(WsMessage::Log(record));
+            }
+        });
+    }
+    // Liveness beacon: one heartbeat per second to every client, so a GUI
+    // can tell a legitimately quiet backend from a dead link.
+    {
+        let msg_tx = msg_tx.clone();
+        supervisor.spawn("heartbeat", async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            let mut seq: u64 = 0;
+            loop {
+                interval.tick().await;
+                seq += 1;
+                let _ = msg_tx.send(WsMessage::Heartbeat(Heartbeat {
+                    seq,
+                    unix_ms: (influx::timestamp_now() / 1_000_000) as u64,
+                }));
+            }
+        });
+    }
+    // Under systemd: readiness is signalled by the listener once it is
+    // accepting, the watchdog is fed here while no shutdown is in progress.
+    let sd = SdNotify::from_env();
+    supervisor.spawn("sd_watchdog", sd_watchdog(sd.clone(), shutdown_rx.clone()));
+    supervisor.spawn(
+        "listener",
+        listen(
+            config.network.listen.clone(),
+            router,
+            state,
+            snapshot.clone(),
+            supervisor.clone(),
+            sd.clone(),
+            config.protection,
+        ),
+    );
+
+    let buckets = BucketRouter::new(config.buckets);
+    let aliases = AliasMap::new(config.aliases);
+    // The monotonic source is anchored here, before the pipeline starts, so
+    // a clock step at any later point cannot reorder stored timestamps.
+    let clock = match config.timestamp.source {
+        TimestampSourceConfig::System => influx::TimestampSource::System,
+        TimestampSourceConfig::Monotonic => influx::TimestampSource::monotonic_anchored(),
+        TimestampSourceConfig::External => influx::TimestampSource::External,
+    };
+    let sparse = ChangeDetector::new(
+        config.sparse.measurements,
+        Duration::from_secs(config.sparse.keepalive_s),
+    );
+    let igniter = config.igniter.map(|igniter| {
+        PulseDetector::new(
+            igniter.threshold_a,
+            Duration::from_millis(igniter.min_pulse_ms),
+            igniter.supply_v,
+        )
+    });
+    let writer = influx::writer::BatchWriter::new(
+        config.writer.batch_lines,
+        Duration::from_millis(config.writer.flush_interval_ms),
+        config.writer.max_buffered_lines,
+    )
+    .with_monotonic(config.writer.monotonic);
+    process_data(
+        client,
+        clock,
+        writer,
+        aliases,
+        sparse,
+        igniter,
+        config
+            .camera
+            .clone()
+            .map(|camera| crate::camera::Snapshotter::new(camera, line_tx.clone(), msg_tx.clone())),
+        history,
+        recorder,
+        data_rx,
+        frame_return,
+        serial_rx,
+        psu_rx,
+        weather_rx,
+        line_rx,
+        burst_rx,
+        bcast_tx,
+        abortbox_tx,
+        snapshot,
+        params,
+        deadletter,
+        filter,
+        buckets,
+        config.redundant,
+        config.rules,
+        config.routing,
+        AuditLog::new(line_tx.clone()),
+        log_rate,
+        shutdown_rx.clone(),
+    )
+    .await;
+
+    // The pipeline only exits without a recorded reason when the sync loop
+    // dropped its data channel, which should not happen on its own.
+    let reason = shutdown_rx.borrow().unwrap_or(ShutdownReason::Watchdog);
+    tracing::info!("shutting down: {reason}");
+    sd.notify(&format!("STOPPING=1\nSTATUS=shutting down: {reason}"));
+    reason
+}
+
+/// Feed the systemd watchdog while the process is healthy. Stops feeding once
+/// a shutdown is requested, so a shutdown that hangs gets killed by systemd.
+async fn sd_watchdog(sd: SdNotify, shutdown_rx: watch::Receiver<Option<ShutdownReason>>) {
+    let Some(period) = SdNotify::watchdog_period() else {
+        return;
+    };
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        if shutdown_rx.borrow().is_some() {
+            return;
+        }
+        sd.notify("WATCHDOG=1");
+    }
+}
+
+/// Periodically snapshot the metrics registry into the line channel.
+async fn metrics_task(line_tx: mpsc::Sender<LineProtocol>) {
+    let mut interval = tokio::time::interval(METRICS_PERIOD);
+    loop {
+        interval.tick().await;
+        if let Some(line) = METRICS.to_line_protocol() {
+            let _ = line_tx.send(line).await;
+        }
+    }
+}
+
+/// Shared handles the command router needs, cloned into every connection.
+#[derive(Clone)]
+struct Router {
+    cmd_tx: mpsc::Sender<Cmd>,
+    influx: influx::client::Client,
+    burst_tx: mpsc::Sender<String>,
+    /// Command channel to the power supply task; `None` when no supply is
+    /// configured.
+    psu_cmd_tx: Option<mpsc::Sender<CmdEnum>>,
+    bcast_tx: broadcast::Sender<Data>,
+    permissions: Arc<PermissionMatrix>,
+    params: Arc<RuntimeParams>,
+    quality: Arc<HashMap<String, QualityExpectation>>,
+    audit: AuditLog,
+    /// The operator shift log, shared with nothing else; the snapshot copy
+    /// below is what late-connecting clients receive.
+    notes: Arc<Mutex<crate::notes::NotesLog>>,
+    snapshot: Arc<Mutex<StateSnapshot>>,
+    /// Side channel into the pipeline, for `operator_notes` lines.
+    line_tx: mpsc::Sender<LineProtocol>,
+    supervisor: Supervisor,
+    /// Where the pipeline's history cache keeps its segment files.
+    history_dir: std::path::PathBuf,
+    /// This run's history session stamp; queries only return its frames.
+    session: u64,
+    /// Actuator ordering rules, fed by the broadcast stream; the sync loop
+    /// re-checks them as the last line of defence.
+    interlocks: Arc<Mutex<crate::interlock::InterlockMonitor>>,
+    /// Automated response rules, for validating runtime arm/disarm commands
+    /// against the configured names.
+    rules: Arc<Vec<RuleConfig>>,
+    /// Per-channel log rate overrides, applied here and honored by the
+    /// pipeline; active overrides are echoed in the snapshot.
+    log_rate: Arc<Mutex<LogRateOverrides>>,
+    /// Two-person gate for hazardous commands; `None` when `[confirmation]`
+    /// is not enabled.
+    consent: Option<Arc<Mutex<crate::consent::ConsentGate>>>,
+    /// Protocol messages fanned out to every connected client, e.g. the
+    /// confirmation gate state.
+    msg_tx: broadcast::Sender<WsMessage>,
+    /// Deadline on each send to a client's socket.
+    ws_send_timeout: Duration,
+    /// Token authorizing staged config uploads, from the environment at
+    /// startup; `None` disables the upload path entirely.
+    admin_token: Option<String>,
+    /// Status server state, for reporting the staged config hash.
+    status: Arc<StatusState>,
+}
+
+impl Router {
+    /// Whether the stand is safe for administrative changes: the valve is
+    /// neither commanded nor reported open. No telemetry yet counts as
+    /// closed — the stand cannot be live before its first frame.
+    fn valve_closed(&self) -> bool {
+        let snapshot = self.snapshot.lock().expect("snapshot mutex poisoned");
+        snapshot.last_data.as_ref().is_none_or(|data| {
+            data.valve != Some(true) && data.valve_feedback != Some(true)
+        })
+    }
+
+    /// Check a command against the permission matrix and execute or forward
+    /// it. Returns a categorized denial when the sender's role is not allowed
+    /// to issue commands in this category or the command's arguments are
+    /// invalid; commands that produce replies send them through `reply_tx`.
+    async fn route(
+        &self,
+        peer: &str,
+        role: Role,
+        cmd: &Cmd,
+        reply_tx: &mpsc::Sender<WsMessage>,
+    ) -> Result<(), UserError> {
+        let action = match &cmd.cmd {
+            // The debug form would carry the token into the audit trail and
+            // every log line below; the bare name is all the record needs.
+            CmdEnum::StageConfig { .. } => "StageConfig".to_string(),
+            cmd => format!("{cmd:?}"),
+        };
+        let category = cmd.cmd.category();
+        if !self.permissions.allows(role, category) {
+            METRICS.incr("cmd_rejected", 1);
+            self.audit.record(peer, &action, Outcome::Rejected);
+            tracing::warn!("rejecting {action} from {peer}: role {role:?} lacks {category:?}");
+            return Err(UserError::safety(
+                "SAF-001",
+                format!("role {role:?} may not issue {category:?} commands"),
+            ));
+        }
+
+        // Interlocked opens are refused here so the operator gets the reason;
+        // the sync loop independently refuses anything that slips past.
+        if let CmdEnum::ValveOpen = cmd.cmd {
+            if let Err(reason) = self
+                .interlocks
+                .lock()
+                .expect("interlock mutex poisoned")
+                .check_open("valve")
+            {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                tracing::warn!("rejecting {action} from {peer}: {reason}");
+                return Err(UserError::safety("SAF-002", reason));
+            }
+        }
+
+        // Schedules are validated here; the sync loop holds and fires them.
+        if let CmdEnum::At {
+            mission_time_s,
+            ref inner,
+        } = cmd.cmd
+        {
+            let error = if !mission_time_s.is_finite() || mission_time_s < 0.0 {
+                Some(UserError::protocol(
+                    "PRO-001",
+                    format!("invalid schedule time {mission_time_s}"),
+                ))
+            } else if matches!(
+                **inner,
+                CmdEnum::At { .. } | CmdEnum::CancelScheduled { .. }
+            ) {
+                Some(UserError::protocol(
+                    "PRO-002",
+                    "scheduled commands cannot nest",
+                ))
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                return Err(error);
+            }
+        }
+
+        // Power supply commands are validated here; the psu task applies them.
+        if let CmdEnum::PsuOutput { .. } | CmdEnum::PsuLimits { .. } = cmd.cmd {
+            let error = match (&self.psu_cmd_tx, &cmd.cmd) {
+                (None, _) => Some(UserError::config(
+                    "CFG-001",
+                    "no power supply is configured",
+                )),
+                (Some(_), &CmdEnum::PsuLimits { volts, amps })
+                    if !(volts.is_finite() && volts >= 0.0 && amps.is_finite() && amps >= 0.0) =>
+                {
+                    Some(UserError::protocol(
+                        "PRO-003",
+                        format!("invalid psu limits: {volts} V, {amps} A"),
+                    ))
+                }
+                _ => None,
+            };
+            if let Some(error) = error {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                return Err(error);
+            }
+        }
+
+        // Shift log entries are validated here; empty or oversized notes
+        // are refused before they reach the log.
+        if let CmdEnum::AddNote { ref text } = cmd.cmd {
+            let error = if text.trim().is_empty() {
+                Some(UserError::protocol("PRO-004", "empty note"))
+            } else if text.chars().count() > crate::notes::MAX_NOTE_LEN {
+                Some(UserError::protocol(
+                    "PRO-005",
+                    format!("note longer than {} characters", crate::notes::MAX_NOTE_LEN),
+                ))
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                return Err(error);
+            }
+        }
+
+        // History queries are validated here; the read itself runs in its
+        // own task so a slow disk never stalls the connection.
+        if let CmdEnum::QueryHistory {
+            start_s, stop_s, ..
+        } = cmd.cmd
+        {
+            if !(start_s.is_finite() && stop_s.is_finite() && start_s < stop_s) {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                return Err(UserError::protocol(
+                    "PRO-006",
+                    format!("invalid history range: {start_s}..{stop_s}"),
+                ));
+            }
+        }
+
+        // Arm/disarm commands are checked against the configured rule names
+        // so a typo is refused with a reason instead of dying as a warning
+        // in the sync loop's log.
+        if let CmdEnum::SetRuleEnabled { ref rule, .. } = cmd.cmd {
+            if !self.rules.iter().any(|r| r.name == *rule) {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                return Err(UserError::config(
+                    "CFG-002",
+                    format!("unknown rule '{rule}'"),
+                ));
+            }
+        }
+
+        // Parameter changes are applied here, not in the sync loop; the
+        // applied value is echoed back so the settings panel reflects
+        // reality.
+        if let CmdEnum::SetParam { param, value } = cmd.cmd {
+            return match self.params.set(param, value) {
+                Ok(applied) => {
+                    METRICS.incr("cmd_accepted", 1);
+                    self.audit.record(peer, &action, Outcome::Accepted);
+                    let _ = reply_tx
+                        .send(WsMessage::ParamApplied {
+                            param,
+                            value: applied,
+                        })
+                        .await;
+                    Ok(())
+                }
+                Err(reason) => {
+                    METRICS.incr("cmd_rejected", 1);
+                    self.audit.record(peer, &action, Outcome::Rejected);
+                    Err(UserError::protocol("PRO-007", reason))
+                }
+            };
+        }
+
+        // Config uploads are authenticated, validated and staged here; the
+        // staged file is applied by the next restart, never mid-session.
+        if let CmdEnum::StageConfig {
+            ref admin_token,
+            ref contents,
+        } = cmd.cmd
+        {
+            let error = match &self.admin_token {
+                None => Some(UserError::config(
+                    "CFG-003",
+                    format!(
+                        "config uploads are disabled ({} is not set on the server)",
+                        crate::admin::TOKEN_ENV
+                    ),
+                )),
+                Some(expected) if !crate::admin::token_matches(expected, admin_token) => {
+                    Some(UserError::safety("SAF-004", "admin token mismatch"))
+                }
+                _ if !self.valve_closed() => Some(UserError::safety(
+                    "SAF-005",
+                    "config uploads are only accepted while the valve is closed",
+                )),
+                _ => None,
+            };
+            if let Some(error) = error {
+                METRICS.incr("cmd_rejected", 1);
+                self.audit.record(peer, &action, Outcome::Rejected);
+                tracing::warn!("rejecting {action} from {peer}: {error}");
+                return Err(error);
+            }
+            // Validation and the write are blocking file I/O, kept off the
+            // runtime threads like the history reads.
+            let contents = contents.clone();
+            let staged = tokio::task::spawn_blocking(move || {
+                crate::admin::stage(crate::CONFIG_PATH, &contents)
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result);
+            return match staged {
+                Ok(hash) => {
+                    METRICS.incr("cmd_accepted", 1);
+                    self.audit.record(peer, &action, Outcome::Accepted);
+                    tracing::info!("config staged by {peer}: {hash}");
+                    self.snapshot
+                        .lock()
+                        .expect("snapshot mutex poisoned")
+                        .staged_config_hash = Some(hash.clone());
+                    *self
+                        .status
+                        .staged_config_hash
+                        .lock()
+                        .expect("staged hash mutex poisoned") = Some(hash.clone());
+                    let _ = reply_tx.send(WsMessage::ConfigStaged { hash }).await;
+                    Ok(())
+                }
+                Err(reason) => {
+                    METRICS.incr("cmd_rejected", 1);
+                    self.audit.record(peer, &action, Outcome::Rejected);
+                    Err(UserError::config("CFG-004", reason))
+                }
+            };
+        }
+
+        // Hazardous commands pass the two-person gate last, once everything
+        // else about them is valid: the first client's command becomes a
+        // proposal, and only the same command from a different client within
+        // the timeout executes it. Every step is audited and the gate state
+        // is broadcast so all consoles see what is pending.
+        let mut confirmed = false;
+        if let Some(consent) = &self.consent {
+            if crate::consent::hazardous(&cmd.cmd) {
+                let (decision, state) = {
+                    let mut consent = consent.lock().expect("consent mutex poisoned");
+                    let decision = consent.offer(&action, peer);
+                    (decision, consent.state())
+                };
+                match decision {
+                    crate::consent::Decision::Proposed { expires_in_s } => {
+                        METRICS.incr("cmd_proposed", 1);
+                        self.audit.record(peer, &action, Outcome::Proposed);
+                        tracing::warn!(
+                            "{action} from {peer} held for confirmation ({expires_in_s:.0} s)"
+                        );
+                        let _ = self.msg_tx.send(WsMessage::Confirmation(state));
+                        return Ok(());
+                    }
+                    crate::consent::Decision::Refused(reason) => {
+                        METRICS.incr("cmd_rejected", 1);
+                        self.audit.record(peer, &action, Outcome::Rejected);
+                        return Err(UserError::safety("SAF-003", reason));
+                    }
+                    crate::consent::Decision::Execute => {
+                        METRICS.incr("cmd_confirmed", 1);
+                        self.audit.record(peer, &action, Outcome::Confirmed);
+                        let _ = self.msg_tx.send(WsMessage::Confirmation(state));
+                        confirmed = true;
+                    }
+                }
+            }
+        }
+
+        if !confirmed {
+            METRICS.incr("cmd_accepted", 1);
+            self.audit.record(peer, &action, Outcome::Accepted);
+        }
+
+        match cmd.cmd {
+            // Burst capture is a pipeline concern, not a sync loop one.
+            CmdEnum::TriggerBurst => {
+                let _ = self.burst_tx.send(format!("command from {peer}")).await;
+            }
+            // Quality checks run in their own task; the report arrives at the
+            // issuing client when the sampling window closes.
+            CmdEnum::PsuOutput { .. } | CmdEnum::PsuLimits { .. } => {
+                // Presence was checked above, before the command was accepted.
+                if let Some(psu_cmd_tx) = &self.psu_cmd_tx {
+                    let _ = psu_cmd_tx.send(cmd.cmd.clone()).await;
+                }
+            }
+            // Stored, persisted, logged to influx and echoed back; the
+            // snapshot copy is what later-connecting clients receive.
+            CmdEnum::AddNote { ref text } => {
+                let note = self
+                    .notes
+                    .lock()
+                    .expect("notes mutex poisoned")
+                    .add(peer, text.clone());
+                self.snapshot
+                    .lock()
+                    .expect("snapshot mutex poisoned")
+                    .notes
+                    .push(note.clone());
+                let _ = self
+                    .line_tx
+                    .send(crate::notes::to_line_protocol(&note))
+                    .await;
+                let _ = reply_tx.send(WsMessage::NoteAdded(note)).await;
+            }
+            // Cache reads are blocking file I/O, kept off the runtime threads.
+            CmdEnum::QueryHistory {
+                start_s,
+                stop_s,
+                ref channels,
+                max_points,
+            } => {
+                let dir = self.history_dir.clone();
+                let session = self.session;
+                let channels = channels.clone();
+                let max_points = max_points.clamp(1, 100_000);
+                let reply_tx = reply_tx.clone();
+                self.supervisor.spawn("history_query", async move {
+                    let result = tokio::task::spawn_blocking(move || {
+                        crate::history::query(
+                            &dir, session, start_s, stop_s, &channels, max_points,
+                        )
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|read| read.map_err(|e| e.to_string()));
+                    let _ = reply_tx.send(WsMessage::HistoryResult(result)).await;
+                });
+            }
+            // Log rate overrides live in the pipeline's stage; the applied
+            // (clamped) rate is reflected in the snapshot for every client.
+            CmdEnum::SetChannelLogRate { ref channel, rate } => {
+                let active = {
+                    let mut log_rate = self.log_rate.lock().expect("log rate mutex poisoned");
+                    let applied = log_rate.set(&channel.0, rate);
+                    tracing::info!(
+                        "log rate override for '{channel}' set to {applied} by {peer}"
+                    );
+                    log_rate.active()
+                };
+                self.snapshot
+                    .lock()
+                    .expect("snapshot mutex poisoned")
+                    .log_rate_overrides = active;
+            }
+            CmdEnum::DataQualityCheck { duration_s } => {
+                let duration_s = duration_s.clamp(1, 60);
+                let bcast_rx = self.bcast_tx.subscribe();
+                let expectations = (*self.quality).clone();
+                let reply_tx = reply_tx.clone();
+                self.supervisor.spawn("quality_check", async move {
+                    let report = quality::run_check(duration_s, bcast_rx, expectations).await;
+                    let _ = reply_tx.send(WsMessage::QualityReport(report)).await;
+                });
+            }
+            _ => {
+                if self.cmd_tx.send(cmd.clone()).await.is_err() {
+                    tracing::error!("sync loop command channel closed");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run an ad-hoc Flux query in its own task; the result (or the influx
+    /// rejection text) arrives at the issuing client when it completes.
+    /// Queries are read-only, so any role may issue them.
+    fn flux_query(&self, peer: &str, query: String, reply_tx: &mpsc::Sender<WsMessage>) {
+        METRICS.incr("flux_queries", 1);
+        tracing::info!("flux query from {peer}");
+        let client = self.influx.clone();
+        let reply_tx = reply_tx.clone();
+        self.supervisor.spawn("flux_query", async move {
+            let result = client
+                .query(&query)
+                .await
+                .map(|table| FluxTable {
+                    columns: table.columns,
+                    rows: table.rows,
+                })
+                .map_err(|e| e.to_string());
+            let _ = reply_tx.send(WsMessage::FluxResult(result)).await;
+        });
+    }
+}
+
+/// Accept WebSocket connections from GUI clients.
+async fn listen(
+    addr: String,
+    router: Router,
+    state: Arc<StatusState>,
+    snapshot: Arc<Mutex<StateSnapshot>>,
+    supervisor: Supervisor,
+    sd: SdNotify,
+    protection: ProtectionConfig,
+) {
+    let listener = TcpListener::bind(&addr)
+        .await
+        .expect("failed to bind websocket listener");
+    tracing::info!("listening on {addr}");
+    sd.notify("READY=1\nSTATUS=operating");
+
+    let ip_limiter = crate::guard::IpLimiter::new(protection.max_conns_per_ip);
+    loop {
+        let Ok((stream, peer)) = listener.accept().await else {
+            continue;
+        };
+        // The per-IP cap is enforced before a task is spawned, so a
+        // reconnect loop gone wild cannot exhaust connection tasks.
+        let Some(permit) = ip_limiter.try_acquire(peer.ip()) else {
+            METRICS.incr("conn_rejected_per_ip", 1);
+            tracing::warn!(
+                "rejecting connection from {peer}: {} connections from this ip already open",
+                protection.max_conns_per_ip
+            );
+            continue;
+        };
+        let router = router.clone();
+        let state = state.clone();
+        let snapshot = snapshot.clone();
+        supervisor.spawn("connection", async move {
+            // Held across the whole connection; drop (and decrement) even
+            // if the task unwinds.
+            let _guard = state.client_guard();
+            let _permit = permit;
+            if let Err(e) =
+                handle_connection(stream, peer.to_string(), router, snapshot, protection).await
+            {
+                tracing::warn!("connection {peer} closed with error: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    peer: String,
+    router: Router,
+    snapshot: Arc<Mutex<StateSnapshot>>,
+    protection: ProtectionConfig,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Oversized messages are refused by the transport, before any byte of
+    // them reaches the decoder.
+    let ws_config = tokio_tungstenite::tungstenite::protocol::WebSocketConfig {
+        max_message_size: Some(protection.max_message_kb * 1024),
+        max_frame_size: Some(protection.max_message_kb * 1024),
+        ..Default::default()
+    };
+    // A peer that opens a socket and stalls mid-handshake would otherwise
+    // hold its connection slot forever.
+    let handshake_timeout = Duration::from_secs(protection.handshake_timeout_s);
+    let ws = match tokio::time::timeout(
+        handshake_timeout,
+        tokio_tungstenite::accept_async_with_config(stream, Some(ws_config)),
+    )
+    .await
+    {
+        Ok(ws) => ws?,
+        Err(_) => {
+            METRICS.incr("conn_handshake_timeouts", 1);
+            return Err(format!("handshake did not complete within {handshake_timeout:?}").into());
+        }
+    };
+    let (mut ws_tx, mut ws_rx) = ws.split();
+    let mut bcast_rx = router.bcast_tx.subscribe();
+    let mut msg_rx = router.msg_tx.subscribe();
+    // Replies produced asynchronously by routed commands.
+    let (reply_tx, mut reply_rx) = mpsc::channel::<WsMessage>(8);
+
+    // First message from the client declares its role, under the same
+    // deadline as the handshake it completes.
+    let role = match tokio::time::timeout(handshake_timeout, ws_rx.next()).await {
+        Ok(Some(Ok(Message::Binary(bytes)))) => bincode::deserialize::<Role>(&bytes)?,
+        Ok(_) => return Err("client did not declare a role".into()),
+        Err(_) => {
+            METRICS.incr("conn_handshake_timeouts", 1);
+            return Err(
+                format!("client did not declare a role within {handshake_timeout:?}").into(),
+            );
+        }
+    };
+    tracing::info!("client {peer} connected as {role:?}");
+
+    // Resynchronize the client before incremental updates resume.
+    let mut current = snapshot.lock().expect("snapshot mutex poisoned").clone();
+    current.params = router.params.snapshot();
+    current.loop_period = crate::rctrl_sync::LOOP_PERIOD;
+    send_timed(
+        &mut ws_tx,
+        Message::Binary(encode(&WsMessage::Snapshot(Box::new(current)))?),
+        router.ws_send_timeout,
+    )
+    .await?;
+    // A late joiner must also see a confirmation already in flight.
+    if let Some(consent) = &router.consent {
+        let state = consent.lock().expect("consent mutex poisoned").state();
+        send_timed(
+            &mut ws_tx,
+            Message::Binary(encode(&WsMessage::Confirmation(state))?),
+            router.ws_send_timeout,
+        )
+        .await?;
+    }
+
+    let mut stream_seq: u64 = 0;
+    // Per-session command budget; a session that drains it is flooding and
+    // gets dropped rather than throttled, so the router never queues behind
+    // a hostile peer.
+    let mut budget = crate::guard::CmdBudget::new(protection.cmd_rate, protection.cmd_burst);
+    // Transport-level liveness, under the protocol heartbeat: tungstenite
+    // answers the peer's pings on its own, and a peer that is gone without a
+    // FIN turns the ping send into a timeout instead of a socket parked open
+    // forever.
+    let mut ping_interval = tokio::time::interval(WS_PING_PERIOD);
+    loop {
+        tokio::select! {
+            _ = ping_interval.tick() => {
+                send_timed(&mut ws_tx, Message::Ping(Vec::new()), router.ws_send_timeout).await?;
+            }
+            data = bcast_rx.recv() => {
+                let Ok(data) = data else { break };
+                // Per-client stream decimation.
+                stream_seq += 1;
+                if !stream_seq.is_multiple_of(router.params.stream_divisor() as u64) {
+                    continue;
+                }
+                let bytes = encode(&WsMessage::Data(data))?;
+                send_timed(&mut ws_tx, Message::Binary(bytes), router.ws_send_timeout).await?;
+            }
+            reply = reply_rx.recv() => {
+                // The sender side lives in this task; the channel cannot close.
+                let Some(reply) = reply else { break };
+                send_timed(
+                    &mut ws_tx,
+                    Message::Binary(encode(&reply)?),
+                    router.ws_send_timeout,
+                )
+                .await?;
+            }
+            msg = msg_rx.recv() => {
+                let Ok(msg) = msg else { continue };
+                send_timed(
+                    &mut ws_tx,
+                    Message::Binary(encode(&msg)?),
+                    router.ws_send_timeout,
+                )
+                .await?;
+            }
+            msg = ws_rx.next() => {
+                let Some(msg) = msg else { break };
+                if let Message::Binary(bytes) = msg? {
+                    match decode(&bytes) {
+                        Ok(WsMessage::Cmd(cmd)) => {
+                            if !budget.allow() {
+                                METRICS.incr("conn_rate_limited", 1);
+                                return Err(format!(
+                                    "command rate exceeded ({}/s sustained, burst {})",
+                                    protection.cmd_rate, protection.cmd_burst
+                                )
+                                .into());
+                            }
+                            if let Err(error) = router.route(&peer, role, &cmd, &reply_tx).await {
+                                // Report the denial back to the sender.
+                                let rejection = WsMessage::CmdRejection(CmdRejection {
+                                    cmd: cmd.cmd,
+                                    error,
+                                });
+                                send_timed(
+                                    &mut ws_tx,
+                                    Message::Binary(encode(&rejection)?),
+                                    router.ws_send_timeout,
+                                )
+                                .await?;
+                            }
+                        }
+                        Ok(WsMessage::FluxQuery(query)) => {
+                            // Queries spend the same budget as commands;
+                            // each one costs the influx backend a read.
+                            if !budget.allow() {
+                                METRICS.incr("conn_rate_limited", 1);
+                                return Err(format!(
+                                    "command rate exceeded ({}/s sustained, burst {})",
+                                    protection.cmd_rate, protection.cmd_burst
+                                )
+                                .into());
+                            }
+                            router.flux_query(&peer, query, &reply_tx);
+                        }
+                        Ok(WsMessage::Ping(nonce)) => {
+                            send_timed(
+                                &mut ws_tx,
+                                Message::Binary(encode(&WsMessage::Pong(nonce))?),
+                                router.ws_send_timeout,
+                            )
+                            .await?;
+                        }
+                        Ok(other) => {
+                            tracing::warn!("client {peer} sent unexpected message: {other:?}");
+                        }
+                        Err(e) => tracing::warn!("client {peer} sent undecodable message: {e}"),
+                    }
+                }
+            }
+        }
+    }
+    tracing::info!("client {peer} disconnected");
+    Ok(())
+}
+
+/// One send on a client's socket, bounded by the configured deadline.
+///
+/// A socket that will not drain within the deadline belongs to a wedged or
+/// vanished client; the timeout turns that into a disconnect instead of a
+/// connection task parked forever mid-send.
+async fn send_timed(
+    ws_tx: &mut (impl futures_util::Sink<Message, Error = tokio_tungstenite::tungstenite::Error>
+              + Unpin),
+    msg: Message,
+    timeout: Duration,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match tokio::time::timeout(timeout, ws_tx.send(msg)).await {
+        Ok(result) => Ok(result?),
+        Err(_) => {
+            METRICS.incr("ws_send_timeouts", 1);
+            Err(format!("client send timed out after {timeout:?}").into())
+        }
+    }
+}
+
+/// Drain telemetry from the sync loop: broadcast raw frames to clients,
+/// aggregate and batch entries for influx.
+#[allow(clippy::too_many_arguments)]
+async fn process_data(
+    client: influx::client::Client,
+    clock: influx::TimestampSource,
+    mut writer: influx::writer::BatchWriter,
+    mut aliases: AliasMap,
+    mut sparse: ChangeDetector,
+    mut igniter: Option<PulseDetector>,
+    mut camera: Option<crate::camera::Snapshotter>,
+    mut history: Option<crate::history::HistoryWriter>,
+    mut recorder: Option<crate::recorder::Recorder>,
+    mut data_rx: crate::ring::Receiver<Data>,
+    mut frame_return: crate::pool::FrameReturn,
+    mut serial_rx: mpsc::Receiver<Data>,
+    mut psu_rx: mpsc::Receiver<Data>,
+    mut weather_rx: mpsc::Receiver<Data>,
+    mut line_rx: mpsc::Receiver<LineProtocol>,
+    mut burst_rx: mpsc::Receiver<String>,
+    bcast_tx: broadcast::Sender<Data>,
+    abortbox_tx: watch::Sender<crate::abortbox::Status>,
+    snapshot: Arc<Mutex<StateSnapshot>>,
+    params: Arc<RuntimeParams>,
+    deadletter: Arc<Mutex<DeadLetter>>,
+    filter: Arc<Mutex<crate::writefilter::WriteFilter>>,
+    buckets: BucketRouter,
+    redundant: Vec<RedundantConfig>,
+    rules: Vec<RuleConfig>,
+    routing: Vec<RoutingConfig>,
+    audit: AuditLog,
+    log_rate: Arc<Mutex<LogRateOverrides>>,
+    mut shutdown_rx: watch::Receiver<Option<ShutdownReason>>,
+) {
+    // Per-pair voting state plus whether the pair was degraded last frame,
+    // so the alarm fires on the transition instead of at frame rate.
+    let mut voters: Vec<(RedundantConfig, Voter, bool)> = redundant
+        .into_iter()
+        .map(|pair| {
+            let voter = Voter::new(pair.max_disagreement);
+            (pair, voter, false)
+        })
+        .collect();
+    let mut aggregator = Aggregator::new(AGGREGATION_WINDOW);
+    let mut gap_detector = GapDetector::default();
+    let mut validator = FrameValidator::default();
+    let mut serial_gap_detector = GapDetector::default();
+    // Avionics frames have their own counters; a shared validator would see
+    // the interleaved sequence numbers as endless steps backwards.
+    let mut serial_validator = FrameValidator::default();
+    let mut serial_open = true;
+    let mut psu_open = true;
+    let mut weather_open = true;
+    let mut burst = BurstCapture::new(BURST_PRE_FRAMES, BURST_POST_FRAMES);
+    let mut state_router = StateRouter::new(routing);
+    // Every frame producer measures mission time from process start, so one
+    // anchor — pinned by the first frame seen — maps it onto the epoch for
+    // the external timestamp source.
+    let mut mission_anchor: Option<u128> = None;
+
+    loop {
+        tokio::select! {
+            data = data_rx.recv() => {
+                let Some(mut data) = data else { break };
+                METRICS.incr("frames_received", 1);
+                // Acquisition-to-writer latency, the daemon-side half of the
+                // "it feels laggy" question.
+                if let Some(wall_ns) = data.wall_ns {
+                    let elapsed = influx::timestamp_now().saturating_sub(wall_ns);
+                    METRICS.observe("writer_latency_ms", elapsed as f64 / 1e6);
+                }
+                // Flag gaps and invalid samples before anything downstream
+                // sees the frame.
+                gap_detector.check(&mut data);
+                validator.check(&mut data);
+                {
+                    let mut snapshot = snapshot.lock().expect("snapshot mutex poisoned");
+                    if data.gap {
+                        snapshot.gaps += 1;
+                    }
+                    if let Some(schedule) = &data.schedule {
+                        snapshot.scheduled = schedule.clone();
+                    }
+                    snapshot.last_data = Some(data.clone());
+                }
+                // Raw frames go to every connected client; send errors just
+                // mean nobody is listening.
+                let _ = bcast_tx.send(data.clone());
+                if let Some(history) = history.as_mut() {
+                    history.append(&data);
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.append(&data);
+                }
+                let stamp = frame_stamp(&clock, &mut mission_anchor, data.time);
+
+                // Redundant pairs vote on every frame; the logical value and
+                // the disagreement magnitude are logged alongside the raw
+                // channels.
+                for (pair, voter, was_degraded) in &mut voters {
+                    // An invalid sample never reaches the vote; the voter
+                    // falls back to the healthy partner as for a dropout.
+                    let side = |channel: &str| {
+                        (!data.channel_invalid(channel))
+                            .then(|| data.channel_value(channel))
+                            .flatten()
+                    };
+                    let vote = voter.vote(side(&pair.primary), side(&pair.secondary));
+                    let Some(vote) = vote else { continue };
+                    if vote.degraded && !*was_degraded {
+                        METRICS.incr("redundancy_degraded", 1);
+                        tracing::error!(
+                            target: "alarm",
+                            "redundant pair '{}' degraded: disagreement {:?}, using {:?}",
+                            pair.id, vote.disagreement, vote.selected
+                        );
+                    }
+                    *was_degraded = vote.degraded;
+                    let disagreement = match vote.disagreement {
+                        Some(d) => format!(",disagreement={d}"),
+                        None => String::new(),
+                    };
+                    writer.push(LineProtocol(format!(
+                        "redundant,channel={} value={},degraded={}{} {}",
+                        pair.id,
+                        vote.value,
+                        vote.degraded,
+                        disagreement,
+                        stamp
+                    )));
+                }
+
+                // Firing confirmation: one discrete facts line per detected
+                // pulse, written next to the current samples it summarizes.
+                if let Some(detector) = igniter.as_mut() {
+                    if let Some(pulse) = detector.update(data.igniter_current, data.time) {
+                        METRICS.incr("igniter_pulses", 1);
+                        tracing::info!(
+                            "igniter fired: {:.0} ms, peak {:.1} A, ~{:.1} J",
+                            pulse.duration_ms,
+                            pulse.peak_a,
+                            pulse.energy_j
+                        );
+                        writer.push(LineProtocol(format!(
+                            "igniter igniter_fired=true,pulse_ms={},peak_a={},energy_j={} {}",
+                            pulse.duration_ms, pulse.peak_a, pulse.energy_j, stamp
+                        )));
+                        if let Some(camera) = camera.as_mut() {
+                            camera.trigger(
+                                "ignition",
+                                &format!("igniter pulse {:.0} ms", pulse.duration_ms),
+                                stamp,
+                            );
+                        }
+                    }
+                }
+
+                // Executed scheduled commands are audited with both their
+                // target and actual mission times, so post-test review can
+                // see the timing error as well as the action.
+                for (entry, executed_s) in &data.schedule_fired {
+                    audit.record("scheduler", &entry.cmd, Outcome::Accepted);
+                    writer.push(LineProtocol(format!(
+                        "schedule,id={},cmd={} scheduled_s={},executed_s={} {}",
+                        entry.id,
+                        influx::escape::tag_value(&entry.cmd),
+                        entry.at_s,
+                        executed_s,
+                        stamp
+                    )));
+                }
+
+                // Rule firings ride on the frame that tripped them: the sync
+                // loop has already applied local actions, the pipeline audits
+                // each firing and runs the pipeline-side ones.
+                for name in &data.rules_fired {
+                    audit.record("rules", name, Outcome::Accepted);
+                    let action = rules.iter().find(|r| r.name == *name).map(|r| r.action);
+                    if action == Some(RuleAction::CloseValve) {
+                        // A rule closing the main valve is the stand's abort
+                        // response; worth a camera frame.
+                        if let Some(camera) = camera.as_mut() {
+                            camera.trigger("abort", &format!("rule '{name}'"), stamp);
+                        }
+                        state_router.on_state(SafetyState::Abort);
+                    }
+                    if action == Some(RuleAction::TriggerBurst) {
+                        METRICS.incr("burst_triggers", 1);
+                        for frame in burst.trigger(&format!("rule '{name}'")) {
+                            let pre_stamp = frame_stamp(&clock, &mut mission_anchor, frame.time);
+                            writer.extend(frame.to_line_protocol_entries_at(pre_stamp));
+                        }
+                    }
+                }
+
+                if let Some(raw) = burst.push(&data) {
+                    // Inside a burst window the raw frame is logged as-is, in
+                    // addition to its contribution to the aggregate.
+                    writer.extend(raw.to_line_protocol_entries_at(stamp));
+                }
+                aggregator.set_window(params.aggregation_window());
+                if let Some(aggregated) = aggregator.push(&data) {
+                    // A window that aggregates to nothing still writes a
+                    // heartbeat line, so a dead data path shows up in influx
+                    // instead of as silence.
+                    if let Ok(entries) = aggregated.to_line_protocol_entries_at_with(
+                        stamp,
+                        influx::NullPolicy::Heartbeat,
+                    ) {
+                        writer.extend(entries);
+                    }
+                }
+                // Channels under a log rate override are logged raw on top of
+                // their aggregate; expired overrides leave the snapshot here.
+                {
+                    let mut log_rate = log_rate.lock().expect("log rate mutex poisoned");
+                    if log_rate.expire() {
+                        snapshot
+                            .lock()
+                            .expect("snapshot mutex poisoned")
+                            .log_rate_overrides = log_rate.active();
+                    }
+                    writer.extend(log_rate.lines_for(&data, stamp));
+                }
+                // Active safety-state routes duplicate their channels raw
+                // into their dedicated measurement, on top of the aggregate.
+                writer.extend(state_router.lines_for(&data, stamp));
+                // The abort box link reads the latest state from its watch
+                // channel; nothing on its path waits on this loop.
+                abortbox_tx.send_modify(|status| status.update(&data));
+                // The frame is spent; hand it back for the sync loop to
+                // reuse instead of dropping its heap capacity.
+                frame_return.recycle(data);
+            }
+            data = serial_rx.recv(), if serial_open => {
+                let Some(mut data) = data else {
+                    serial_open = false;
+                    continue;
+                };
+                METRICS.incr("avionics_frames_received", 1);
+                serial_gap_detector.check(&mut data);
+                serial_validator.check(&mut data);
+                let _ = bcast_tx.send(data.clone());
+                if let Some(history) = history.as_mut() {
+                    history.append(&data);
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.append(&data);
+                }
+                // Chamber pressure in the abort box packets comes from this
+                // stream.
+                abortbox_tx.send_modify(|status| status.update(&data));
+                // Avionics frames are logged as-is; aggregation is a
+                // ground-side concern.
+                let stamp = frame_stamp(&clock, &mut mission_anchor, data.time);
+                writer.extend(data.to_line_protocol_entries_at(stamp));
+            }
+            data = psu_rx.recv(), if psu_open => {
+                let Some(data) = data else {
+                    psu_open = false;
+                    continue;
+                };
+                // Supply telemetry has no sequence numbers to gap-check; it
+                // is broadcast and logged as-is at its own poll rate.
+                let _ = bcast_tx.send(data.clone());
+                if let Some(history) = history.as_mut() {
+                    history.append(&data);
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.append(&data);
+                }
+                let stamp = frame_stamp(&clock, &mut mission_anchor, data.time);
+                writer.extend(data.to_line_protocol_entries_at(stamp));
+            }
+            data = weather_rx.recv(), if weather_open => {
+                let Some(data) = data else {
+                    weather_open = false;
+                    continue;
+                };
+                // Ambient readings arrive at station cadence; they are
+                // broadcast and logged as-is like supply telemetry.
+                let _ = bcast_tx.send(data.clone());
+                if let Some(history) = history.as_mut() {
+                    history.append(&data);
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.append(&data);
+                }
+                let stamp = frame_stamp(&clock, &mut mission_anchor, data.time);
+                writer.extend(data.to_line_protocol_entries_at(stamp));
+            }
+            reason = burst_rx.recv() => {
+                let Some(reason) = reason else { break };
+                METRICS.incr("burst_triggers", 1);
+                for frame in burst.trigger(&reason) {
+                    let stamp = frame_stamp(&clock, &mut mission_anchor, frame.time);
+                    writer.extend(frame.to_line_protocol_entries_at(stamp));
+                }
+            }
+            line = line_rx.recv() => {
+                let Some(line) = line else { break };
+                // Alarms reach the pipeline as forwarded `app_logs` lines;
+                // keying the camera off the alarm target here covers every
+                // alarm site without instrumenting each one.
+                if line.0.starts_with("app_logs,") && line.0.contains(",target=alarm ") {
+                    if let Some(camera) = camera.as_mut() {
+                        camera.trigger("alarm", "alarm raised, see app_logs", influx::timestamp_now());
+                    }
+                }
+                writer.push(line);
+            }
+            // Wakes the loop when the flush interval expires, so a quiet
+            // pipeline still writes its stragglers; the flush itself happens
+            // in the shared check below.
+            _ = tokio::time::sleep(writer.until_due()) => {}
+            _ = shutdown_rx.changed() => break,
+        }
+
+        METRICS.set_gauge("pipeline_buffered_lines", writer.len() as f64);
+        METRICS.set_gauge("writer_dropped_lines", writer.dropped() as f64);
+        METRICS.set_gauge("writer_timestamp_corrections", writer.corrected() as f64);
+        METRICS.set_gauge("ring_dropped_frames", data_rx.dropped() as f64);
+        METRICS.set_gauge("burst_active", u8::from(burst.active()) as f64);
+        if writer.due() {
+            let mut batch = writer.take();
+            flush(
+                &client,
+                &mut aliases,
+                &mut sparse,
+                &filter,
+                &buckets,
+                &mut batch,
+                &deadletter,
+            )
+            .await;
+            writer.recycle(batch);
+        }
+    }
+
+    // Final event so the shutdown cause is queryable next to the data it
+    // interrupted, then drain whatever the batch threshold left behind.
+    if let Some(reason) = *shutdown_rx.borrow() {
+        writer.push(LineProtocol(format!(
+            "shutdown,reason={reason} exit_code={}i {}",
+            reason.exit_code(),
+            clock.timestamp(None)
+        )));
+    }
+    let mut batch = writer.take();
+    flush(
+        &client,
+        &mut aliases,
+        &mut sparse,
+        &filter,
+        &buckets,
+        &mut batch,
+        &deadletter,
+    )
+    .await;
+    tracing::info!("pipeline stopped");
+}
+
+/// The timestamp for one telemetry frame. On first use the frame's mission
+/// time is pinned to the system clock; the resulting per-sample timestamps
+/// feed [`influx::TimestampSource::External`] and are ignored by the other
+/// sources.
+fn frame_stamp(
+    clock: &influx::TimestampSource,
+    anchor: &mut Option<u128>,
+    mission: Duration,
+) -> u128 {
+    let anchor =
+        *anchor.get_or_insert_with(|| influx::timestamp_now().saturating_sub(mission.as_nanos()));
+    clock.timestamp(Some(anchor + mission.as_nanos()))
+}
+
+/// Write the buffered lines, one request per destination bucket; most batches
+/// route entirely to the default bucket and still flush in a single request.
+async fn flush(
+    client: &influx::client::Client,
+    aliases: &mut AliasMap,
+    sparse: &mut ChangeDetector,
+    filter: &Arc<Mutex<crate::writefilter::WriteFilter>>,
+    buckets: &BucketRouter,
+    buffer: &mut Vec<LineProtocol>,
+    deadletter: &Arc<Mutex<DeadLetter>>,
+) {
+    let mut batches: Vec<(&str, Vec<LineProtocol>)> = Vec::new();
+    {
+        let mut filter = filter.lock().expect("filter mutex poisoned");
+        for line in buffer.drain(..) {
+            // Unchanged lines of sparse measurements are dropped before any
+            // renaming, so change detection keys on the producer's names.
+            if !sparse.admit(&line) {
+                continue;
+            }
+            // Deprecated measurements are renamed (and possibly duplicated)
+            // before bucket routing, so routes only ever see current names —
+            // and the allow/block filter judges those same names.
+            for line in aliases.apply(line) {
+                if !filter.admit(&line) {
+                    METRICS.incr("lines_filtered", 1);
+                    continue;
+                }
+                let bucket = buckets.bucket_for(&line);
+                match batches.iter_mut().find(|(b, _)| *b == bucket) {
+                    Some((_, lines)) => lines.push(line),
+                    None => batches.push((bucket, vec![line])),
+                }
+            }
+        }
+    }
+    for (bucket, lines) in batches {
+        match client.write_batch_to(bucket, &lines).await {
+            Ok(()) => {
+                METRICS.incr("influx_lines_written", lines.len() as u64);
+            }
+            // The server refused the batch: it will never succeed as-is, so
+            // park it in the dead-letter buffer instead of retrying.
+            Err(e @ influx::client::ClientError::Rejected { .. }) => {
+                METRICS.incr("influx_write_errors", 1);
+                tracing::warn!("influx write to '{bucket}' failed: {e}");
+                deadletter
+                    .lock()
+                    .expect("deadletter mutex poisoned")
+                    .record(lines, e.to_string());
+            }
+            // A hang is retryable: park the batch so `/deadletter/retry` can
+            // re-queue it once the endpoint recovers, and alarm — a stalled
+            // influx means data is about to be lost.
+            Err(e @ influx::client::ClientError::Timeout { .. }) => {
+                METRICS.incr("influx_write_errors", 1);
+                METRICS.incr("influx_write_timeouts", 1);
+                tracing::error!(
+                    target: "alarm",
+                    "{}",
+                    UserError::logging(
+                        "LOG-001",
+                        format!("influx write to '{bucket}' timed out: {e}"),
+                    )
+                );
+                deadletter
+                    .lock()
+                    .expect("deadletter mutex poisoned")
+                    .record(lines, e.to_string());
+            }
+            Err(e) => {
+                METRICS.incr("influx_write_errors", 1);
+                tracing::warn!("influx write to '{bucket}' failed: {e}");
+            }
+        }
+    }
+}
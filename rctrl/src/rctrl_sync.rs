@@ -0,0 +1,541 @@
+//! The hard real time sync loop.
+//!
+//! Runs on its own thread at a fixed rate, samples hardware, applies pending
+//! commands and hands frames to the async side over a bounded channel with a
+//! non-blocking send, so the loop can never stall on the network stack.
+
+use crate::config::{
+    DeviceConfig, InterlockConfig, RuleAction, RuleConfig, SimConfig, ValveConfig,
+};
+#[cfg(target_os = "linux")]
+use crate::discovery;
+use crate::interlock::InterlockMonitor;
+use crate::metrics::METRICS;
+use crate::rules::RuleEngine;
+use crate::shutdown::Shutdown;
+#[cfg(target_os = "linux")]
+use crate::shutdown::ShutdownReason;
+use crate::sim::SimSource;
+use crate::valve::TravelMonitor;
+#[cfg(target_os = "linux")]
+use linux_embedded_hal::{CdevPin, I2cdev};
+use rctrl_api::prelude::*;
+#[cfg(target_os = "linux")]
+use rctrl_hw::actuator::{Actuator as _, GpioActuator};
+#[cfg(target_os = "linux")]
+use rctrl_hw::adc::{Ads101x, AutoRange, FullScaleRange, Mux};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Sync loop period: 100 Hz.
+pub const LOOP_PERIOD: Duration = Duration::from_millis(10);
+
+/// Allowed fractional deviation of valve travel time from its baseline.
+const VALVE_TRAVEL_DEVIATION_LIMIT: f64 = 0.5;
+/// Modelled valve travel time until a position feedback input exists.
+const VALVE_TRAVEL_SIM: Duration = Duration::from_millis(150);
+
+/// Consecutive ADC read failures before the hardware is declared dead and a
+/// fatal shutdown is requested (1 s at the loop rate).
+#[cfg(target_os = "linux")]
+const ADC_FAILURE_LIMIT: u32 = 100;
+
+/// One pending scheduled command.
+struct ScheduleEntry {
+    listing: ScheduledCmd,
+    cmd: CmdEnum,
+}
+
+/// The slice of the daemon configuration the sync loop owns.
+pub struct SyncConfig {
+    pub devices: Vec<DeviceConfig>,
+    pub interlocks: Vec<InterlockConfig>,
+    pub rules: Vec<RuleConfig>,
+    pub sim: SimConfig,
+    pub valve: ValveConfig,
+}
+
+/// Where telemetry frames come from.
+enum DataSource {
+    /// ADS101x on the stand I2C bus.
+    #[cfg(target_os = "linux")]
+    Hardware(Box<Ads101x<I2cdev>>),
+    /// Simulated waveforms for development without hardware.
+    Simulation(SimSource),
+}
+
+/// State owned by the sync loop.
+pub struct Context {
+    data_tx: crate::ring::Sender<Data>,
+    cmd_rx: mpsc::Receiver<Cmd>,
+    /// Recycled frames from the async side, reused instead of allocating.
+    pool: crate::pool::FramePool,
+    source: DataSource,
+    valve: bool,
+    /// Hardware drive behind the valve; `None` leaves the valve model-only,
+    /// which is the development-machine configuration.
+    #[cfg(target_os = "linux")]
+    actuator: Option<GpioActuator<CdevPin>>,
+    /// Confirmed valve position. Until a discrete feedback input exists this
+    /// is modelled: it follows the command after [`VALVE_TRAVEL_SIM`].
+    valve_feedback: bool,
+    valve_commanded_at: Option<Instant>,
+    travel: TravelMonitor,
+    /// Last line of defence for actuator ordering rules; the command router
+    /// enforces the same rules ahead of the command channel.
+    interlocks: InterlockMonitor,
+    /// Automated response rules, evaluated against every sampled frame.
+    rules: RuleEngine,
+    seq: u64,
+    start: Instant,
+    /// Requests a fatal shutdown on persistent ADC failure; only the
+    /// hardware path reads it.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    shutdown: Shutdown,
+    /// Consecutive ADC read failures; see [`ADC_FAILURE_LIMIT`].
+    #[cfg(target_os = "linux")]
+    adc_failures: u32,
+    /// PGA gain auto-ranging for the pressure channel; `None` keeps the
+    /// fixed default range.
+    #[cfg(target_os = "linux")]
+    auto_range: Option<AutoRange>,
+    /// Expected hardware, for discovery reports.
+    #[cfg(target_os = "linux")]
+    devices: Vec<DeviceConfig>,
+    /// Message attached to the next frame, e.g. an on-demand discovery
+    /// report.
+    pending_log: Option<String>,
+    /// Commands waiting for their mission time; see [`CmdEnum::At`].
+    schedule: Vec<ScheduleEntry>,
+    next_schedule_id: u64,
+    /// The schedule changed since the last emitted frame; the next frame
+    /// carries the full listing so the snapshot stays current.
+    schedule_dirty: bool,
+}
+
+impl Context {
+    pub fn new(
+        data_tx: crate::ring::Sender<Data>,
+        cmd_rx: mpsc::Receiver<Cmd>,
+        pool: crate::pool::FramePool,
+        shutdown: Shutdown,
+        config: SyncConfig,
+    ) -> Self {
+        let SyncConfig {
+            devices,
+            interlocks,
+            rules,
+            sim,
+            #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+            valve,
+        } = config;
+        #[cfg(target_os = "linux")]
+        let source = match I2cdev::new("/dev/i2c-1") {
+            Ok(mut bus) => {
+                // Cold-boot discovery: wiring mistakes surface as a report
+                // here instead of as read errors minutes into a test.
+                let report = discovery::compare(&devices, &rctrl_hw::scan::scan(&mut bus));
+                if report.is_clean() {
+                    tracing::info!("{report}");
+                } else {
+                    tracing::error!(target: "alarm", "{report}");
+                }
+                DataSource::Hardware(Box::new(Ads101x::new(bus, 0x48)))
+            }
+            Err(e) => {
+                tracing::warn!("i2c bus unavailable ({e}), falling back to simulation");
+                DataSource::Simulation(SimSource::from_config(sim.profile.as_deref(), LOOP_PERIOD))
+            }
+        };
+        #[cfg(target_os = "linux")]
+        let actuator = valve.gpio_line.and_then(|line| match open_valve_gpio(&valve, line) {
+            Ok(actuator) => {
+                tracing::info!("valve on {} line {line}", valve.gpio_chip);
+                Some(actuator)
+            }
+            Err(e) => {
+                // A valve that silently cannot actuate is worse than one
+                // known to be model-only; the commanded state still tracks.
+                tracing::error!(
+                    target: "alarm",
+                    "{}",
+                    UserError::hardware(
+                        "HW-001",
+                        format!("valve gpio unavailable ({e}), valve is model-only"),
+                    )
+                );
+                None
+            }
+        });
+        #[cfg(not(target_os = "linux"))]
+        let source = {
+            tracing::warn!(
+                "no i2c support on this platform ({} devices configured), using simulation",
+                devices.len()
+            );
+            DataSource::Simulation(SimSource::from_config(sim.profile.as_deref(), LOOP_PERIOD))
+        };
+
+        Self {
+            data_tx,
+            cmd_rx,
+            pool,
+            source,
+            valve: false,
+            #[cfg(target_os = "linux")]
+            actuator,
+            valve_feedback: false,
+            valve_commanded_at: None,
+            travel: TravelMonitor::new(VALVE_TRAVEL_DEVIATION_LIMIT),
+            interlocks: InterlockMonitor::new(interlocks),
+            rules: RuleEngine::new(rules),
+            seq: 0,
+            start: Instant::now(),
+            shutdown,
+            #[cfg(target_os = "linux")]
+            adc_failures: 0,
+            #[cfg(target_os = "linux")]
+            auto_range: devices
+                .iter()
+                .any(|device| device.auto_range)
+                .then(AutoRange::default),
+            #[cfg(target_os = "linux")]
+            devices,
+            pending_log: None,
+            schedule: Vec::new(),
+            next_schedule_id: 0,
+            schedule_dirty: false,
+        }
+    }
+
+    fn handle_cmd(&mut self, cmd: Cmd) {
+        match cmd.cmd {
+            CmdEnum::ValveOpen => self.command_valve(true),
+            CmdEnum::ValveClose => self.command_valve(false),
+            CmdEnum::DiscoverHardware => self.discover(),
+            CmdEnum::At {
+                mission_time_s,
+                inner,
+            } => self.schedule_cmd(mission_time_s, *inner),
+            CmdEnum::CancelScheduled { id } => self.cancel_scheduled(id),
+            CmdEnum::SetRuleEnabled { ref rule, enabled } => {
+                // The router validated the name against the same config; a
+                // failure here means the halves run different configs.
+                match self.rules.set_enabled(rule, enabled) {
+                    Ok(()) => tracing::info!(
+                        "rule '{rule}' {}",
+                        if enabled { "armed" } else { "disarmed" }
+                    ),
+                    Err(reason) => tracing::warn!("{reason}"),
+                }
+            }
+            _ => tracing::warn!(?cmd, "unhandled command"),
+        }
+    }
+
+    /// Hold a command until the mission clock reaches its target. The router
+    /// already validated the time and refused nesting; a target in the past
+    /// executes on the next iteration, which is what "as soon as the clock
+    /// allows" means here.
+    fn schedule_cmd(&mut self, mission_time_s: f64, cmd: CmdEnum) {
+        let id = self.next_schedule_id;
+        self.next_schedule_id += 1;
+        tracing::info!("command {cmd:?} scheduled as #{id} for t={mission_time_s:.3} s");
+        self.schedule.push(ScheduleEntry {
+            listing: ScheduledCmd {
+                id,
+                at_s: mission_time_s,
+                cmd: format!("{cmd:?}"),
+            },
+            cmd,
+        });
+        self.schedule
+            .sort_by(|a, b| a.listing.at_s.total_cmp(&b.listing.at_s));
+        self.schedule_dirty = true;
+    }
+
+    fn cancel_scheduled(&mut self, id: u64) {
+        let before = self.schedule.len();
+        self.schedule.retain(|entry| entry.listing.id != id);
+        if self.schedule.len() < before {
+            tracing::info!("scheduled command #{id} cancelled");
+            self.schedule_dirty = true;
+        } else {
+            // Raced its own execution or a typo; either way nothing to undo.
+            tracing::warn!("cancel for unknown scheduled command #{id}");
+        }
+    }
+
+    /// Execute every scheduled command whose time has come; returns the
+    /// executed entries with their actual execution times for the frame.
+    fn run_due_scheduled(&mut self) -> Vec<(ScheduledCmd, f64)> {
+        let now_s = self.start.elapsed().as_secs_f64();
+        if self.schedule.first().is_none_or(|e| e.listing.at_s > now_s) {
+            return Vec::new();
+        }
+        let mut fired = Vec::new();
+        while let Some(entry) = self.schedule.first() {
+            if entry.listing.at_s > now_s {
+                break;
+            }
+            let entry = self.schedule.remove(0);
+            tracing::info!(
+                "scheduled command #{} ({}) executing at t={:.3} s (scheduled t={:.3} s)",
+                entry.listing.id,
+                entry.listing.cmd,
+                now_s,
+                entry.listing.at_s
+            );
+            self.handle_cmd(Cmd { cmd: entry.cmd });
+            fired.push((entry.listing, now_s));
+        }
+        self.schedule_dirty = true;
+        fired
+    }
+
+    /// On-demand bus scan; the report reaches clients as the next frame's
+    /// `log_msg`. Scanning takes on the order of one loop period and is only
+    /// issued while the stand is quiescent, so the overrun is acceptable.
+    fn discover(&mut self) {
+        let report = match &mut self.source {
+            #[cfg(target_os = "linux")]
+            DataSource::Hardware(adc) => {
+                let report =
+                    discovery::compare(&self.devices, &rctrl_hw::scan::scan(adc.bus_mut()));
+                if !report.is_clean() {
+                    tracing::error!(target: "alarm", "{report}");
+                }
+                report.to_string()
+            }
+            DataSource::Simulation(_) => "discovery unavailable: simulated data source".to_string(),
+        };
+        self.pending_log = Some(report);
+    }
+
+    fn command_valve(&mut self, target: bool) {
+        // The router already refused interlocked opens; a violation here
+        // means a command slipped past it and must still not actuate.
+        if target {
+            if let Err(reason) = self.interlocks.check_open("valve") {
+                METRICS.incr("interlock_violations", 1);
+                tracing::error!(target: "alarm", "refusing valve open: {reason}");
+                return;
+            }
+        }
+        if self.valve != target {
+            self.valve = target;
+            // A gain step mid-burn would step the quantization noise through
+            // the most interesting data; hold the range while the valve is
+            // commanded open.
+            #[cfg(target_os = "linux")]
+            if let Some(ranger) = self.auto_range.as_mut() {
+                if target {
+                    ranger.lock();
+                } else {
+                    ranger.unlock();
+                }
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(actuator) = &mut self.actuator {
+                if let Err(e) = actuator.set(target) {
+                    // The commanded state stands so the model keeps tracking
+                    // it, but the hardware may not have followed.
+                    METRICS.incr("actuator_faults", 1);
+                    tracing::error!(
+                        target: "alarm",
+                        "{}",
+                        UserError::hardware("HW-002", format!("valve gpio drive failed: {e}"))
+                    );
+                }
+            }
+            self.valve_commanded_at = Some(Instant::now());
+            self.travel.command(target);
+        }
+    }
+
+    fn sample(&mut self) -> Data {
+        // Due scheduled commands run first, so their effect — a valve
+        // command, an armed rule — is visible in this very frame.
+        let schedule_fired = self.run_due_scheduled();
+        let (pressure, pressure_fsr) = match &mut self.source {
+            #[cfg(target_os = "linux")]
+            DataSource::Hardware(adc) => {
+                // Under auto-ranging the active range is recorded with the
+                // sample, so stored data carries its quantization context.
+                let result = match self.auto_range.as_mut() {
+                    Some(ranger) => adc
+                        .read_auto::<Pressure>(ranger)
+                        .map(|(reading, fsr)| (reading, Some(fsr.volts()))),
+                    None => adc.read::<Pressure>().map(|reading| (reading, None)),
+                };
+                match result {
+                    Ok((reading, fsr)) => {
+                        self.adc_failures = 0;
+                        (Some(reading.value), fsr)
+                    }
+                    Err(e) => {
+                        tracing::error!("adc read failed: {e}");
+                        // A transient I2C hiccup drops a sample; a solid second of
+                        // failures means the hardware is gone.
+                        self.adc_failures += 1;
+                        if self.adc_failures == ADC_FAILURE_LIMIT {
+                            self.shutdown.request(ShutdownReason::FatalHwError);
+                        }
+                        (None, None)
+                    }
+                }
+            }
+            DataSource::Simulation(sim) => (Some(sim.update(self.valve)), None),
+        };
+        // Acquisition time, taken at conversion completion: sequential reads
+        // on a shared ADC each get their own timestamp instead of the frame's.
+        let pressure_at = pressure.is_some().then(|| self.start.elapsed());
+
+        // Igniter loop current on AIN1; the pressure transducer owns AIN0.
+        // The mux is restored so the next pressure read is unaffected. A
+        // failed read drops the sample without feeding the failure counter —
+        // pressure is the liveness proxy for the ADC.
+        let igniter_current = match &mut self.source {
+            #[cfg(target_os = "linux")]
+            DataSource::Hardware(adc) => {
+                adc.set_mux(Mux::Ain1Gnd);
+                // Auto-ranging belongs to the pressure channel; the shunt
+                // amplifier output needs the full range, so it is read at
+                // the default and the pressure range restored after.
+                let fsr = adc.fsr();
+                adc.set_fsr(FullScaleRange::Fsr6V144);
+                let reading = adc.read::<Current>();
+                adc.set_fsr(fsr);
+                adc.set_mux(Mux::Ain0Gnd);
+                match reading {
+                    Ok(reading) => Some(reading.value),
+                    Err(e) => {
+                        tracing::error!("igniter current read failed: {e}");
+                        None
+                    }
+                }
+            }
+            DataSource::Simulation(_) => None,
+        };
+        let igniter_current_at = igniter_current.is_some().then(|| self.start.elapsed());
+
+        // Modelled feedback until a discrete input is wired up.
+        if let Some(commanded_at) = self.valve_commanded_at {
+            if commanded_at.elapsed() >= VALVE_TRAVEL_SIM {
+                self.valve_feedback = self.valve;
+                self.valve_commanded_at = None;
+            }
+        }
+
+        let mut valve_travel_ms = None;
+        let mut log_msg = self.pending_log.take();
+        if let Some(measurement) = self.travel.update(self.valve_feedback) {
+            valve_travel_ms = Some(measurement.travel_ms);
+            if measurement.anomalous {
+                METRICS.incr("valve_travel_anomalies", 1);
+                let msg = format!(
+                    "valve travel time anomaly: {:.0} ms deviates from baseline",
+                    measurement.travel_ms
+                );
+                tracing::error!("{msg}");
+                log_msg = Some(msg);
+            }
+        }
+
+        let seq = self.seq;
+        self.seq += 1;
+        let time = self.start.elapsed();
+        // Achieved skew between first acquisition and the frame timestamp.
+        if let Some(at) = pressure_at {
+            METRICS.set_gauge(
+                "acquisition_skew_us",
+                time.saturating_sub(at).as_micros() as f64,
+            );
+        }
+        // Reuse a recycled frame where one is waiting, so the steady-state
+        // loop writes into existing capacity instead of allocating.
+        let mut data = self.pool.acquire();
+        data.time = time;
+        data.seq = seq;
+        // Wall stamp for end-to-end latency measurement downstream.
+        data.wall_ns = Some(influx::timestamp_now());
+        data.pressure = pressure;
+        data.pressure_at = pressure_at;
+        data.pressure_fsr = pressure_fsr;
+        data.valve = Some(self.valve);
+        data.valve_feedback = Some(self.valve_feedback);
+        data.valve_travel_ms = valve_travel_ms;
+        data.igniter_current = igniter_current;
+        data.igniter_current_at = igniter_current_at;
+        data.log_msg = log_msg;
+        data.schedule_fired = schedule_fired;
+        if self.schedule_dirty {
+            self.schedule_dirty = false;
+            data.schedule = Some(
+                self.schedule
+                    .iter()
+                    .map(|entry| entry.listing.clone())
+                    .collect(),
+            );
+        }
+        // Rules see the frame exactly as clients will; local actions apply
+        // immediately, the pipeline audits the firing and runs the rest.
+        for firing in self.rules.evaluate(&data) {
+            METRICS.incr("rule_firings", 1);
+            tracing::error!(
+                target: "alarm",
+                "rule '{}' fired: {} ({:?})",
+                firing.name, firing.reason, firing.action
+            );
+            if firing.action == RuleAction::CloseValve {
+                self.command_valve(false);
+                data.valve = Some(self.valve);
+            }
+            data.rules_fired.push(firing.name);
+        }
+        self.interlocks.observe(&data);
+        data
+    }
+}
+
+/// Request the valve's GPIO line as an output, initially closed.
+#[cfg(target_os = "linux")]
+fn open_valve_gpio(
+    valve: &ValveConfig,
+    line: u32,
+) -> Result<GpioActuator<CdevPin>, linux_embedded_hal::gpio_cdev::errors::Error> {
+    use linux_embedded_hal::gpio_cdev::{Chip, LineRequestFlags};
+    let mut chip = Chip::new(&valve.gpio_chip)?;
+    let initial = if valve.active_high { 0 } else { 1 };
+    let handle = chip
+        .get_line(line)?
+        .request(LineRequestFlags::OUTPUT, initial, "rctrl valve")?;
+    Ok(GpioActuator::new(CdevPin::new(handle)?, valve.active_high))
+}
+
+/// Run the sync loop until the command channel closes.
+pub fn run(mut ctx: Context) {
+    loop {
+        let iteration_start = Instant::now();
+
+        loop {
+            match ctx.cmd_rx.try_recv() {
+                Ok(cmd) => ctx.handle_cmd(cmd),
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    tracing::info!("command channel closed, stopping sync loop");
+                    return;
+                }
+            }
+        }
+
+        let data = ctx.sample();
+        if ctx.data_tx.try_send(data).is_err() {
+            tracing::warn!("data ring full, dropping frame");
+        }
+
+        if let Some(remaining) = LOOP_PERIOD.checked_sub(iteration_start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+}
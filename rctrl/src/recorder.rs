@@ -0,0 +1,206 @@
+//! Black-box CSV flight recorder.
+//!
+//! Influx logging rides on the network; when it fails mid-test the stored
+//! record simply stops at the worst possible moment. This recorder appends
+//! every frame to rotating CSV files on the stand computer's own disk,
+//! independent of the influx pipeline — CSV deliberately, so a post-incident
+//! reader needs nothing installed, and an append-only text file stays
+//! readable up to its last fsync after a crash or power cut. Files rotate by
+//! size and age, and the fsync cadence bounds how much a power cut can lose.
+
+use crate::config::RecorderConfig;
+use rctrl_api::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Column order of every file; the header row is written at open. Optional
+/// channels render empty cells when absent.
+const HEADER: &str = "time_s,seq,gap,pressure,temperature,valve,valve_feedback,\
+                      valve_travel_ms,igniter_current,fc_pressure,fc_altitude,\
+                      ambient_pressure,ambient_temperature,psu_volts,psu_amps";
+
+/// Appends frames to the rotating CSV files.
+pub struct Recorder {
+    dir: PathBuf,
+    max_bytes: u64,
+    rotate_after: Duration,
+    fsync_every: Duration,
+    /// Rolls during this run, part of each file name so rotating twice
+    /// within one wall-clock millisecond cannot collide.
+    rolls: u64,
+    file: Option<OpenFile>,
+}
+
+/// The open file and the counters its rotation and fsync cadence run on.
+struct OpenFile {
+    writer: BufWriter<File>,
+    written: u64,
+    opened: Instant,
+    synced: Instant,
+}
+
+impl Recorder {
+    pub fn new(config: &RecorderConfig) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        Ok(Self {
+            dir: PathBuf::from(&config.dir),
+            max_bytes: config.max_file_mb * 1024 * 1024,
+            rotate_after: Duration::from_secs(config.rotate_s),
+            fsync_every: Duration::from_secs(config.fsync_s),
+            rolls: 0,
+            file: None,
+        })
+    }
+
+    /// Append one frame; rotates when the open file exceeds the size or age
+    /// limit and fsyncs on the configured cadence. Failures drop the frame
+    /// with a log line rather than touching the pipeline.
+    pub fn append(&mut self, data: &Data) {
+        if self.file.as_ref().is_none_or(|open| {
+            open.written >= self.max_bytes || open.opened.elapsed() >= self.rotate_after
+        }) {
+            self.roll();
+        }
+        let Some(open) = self.file.as_mut() else {
+            return;
+        };
+        let row = row(data);
+        if let Err(e) = open.writer.write_all(row.as_bytes()) {
+            tracing::warn!("recorder append failed: {e}");
+            self.file = None;
+            return;
+        }
+        open.written += row.len() as u64;
+        if open.synced.elapsed() >= self.fsync_every {
+            let result = open
+                .writer
+                .flush()
+                .and_then(|()| open.writer.get_ref().sync_data());
+            if let Err(e) = result {
+                tracing::warn!("recorder fsync failed: {e}");
+                self.file = None;
+                return;
+            }
+            open.synced = Instant::now();
+        }
+    }
+
+    /// Open the next file, named by wall-clock milliseconds at open, after
+    /// flushing and syncing the one it replaces.
+    fn roll(&mut self) {
+        if let Some(open) = self.file.take() {
+            let mut writer = open.writer;
+            let _ = writer.flush().and_then(|()| writer.get_ref().sync_data());
+        }
+        self.rolls += 1;
+        let path = self.dir.join(format!(
+            "{}-{}.csv",
+            influx::timestamp_now() / 1_000_000,
+            self.rolls
+        ));
+        match File::create(&path) {
+            Ok(file) => {
+                let mut writer = BufWriter::new(file);
+                if let Err(e) = writeln!(writer, "{HEADER}") {
+                    tracing::error!("failed to start recorder file {}: {e}", path.display());
+                    return;
+                }
+                let now = Instant::now();
+                self.file = Some(OpenFile {
+                    writer,
+                    written: HEADER.len() as u64 + 1,
+                    opened: now,
+                    synced: now,
+                });
+            }
+            Err(e) => tracing::error!("failed to open recorder file {}: {e}", path.display()),
+        }
+    }
+}
+
+/// One CSV row in [`HEADER`] order; absent samples become empty cells.
+fn row(data: &Data) -> String {
+    fn cell<T: std::fmt::Display>(value: &Option<T>) -> String {
+        value.as_ref().map(ToString::to_string).unwrap_or_default()
+    }
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+        data.time.as_secs_f64(),
+        data.seq,
+        data.gap,
+        cell(&data.pressure),
+        cell(&data.temperature),
+        cell(&data.valve),
+        cell(&data.valve_feedback),
+        cell(&data.valve_travel_ms),
+        cell(&data.igniter_current),
+        cell(&data.fc_pressure),
+        cell(&data.fc_altitude),
+        cell(&data.ambient_pressure),
+        cell(&data.ambient_temperature),
+        cell(&data.psu_volts),
+        cell(&data.psu_amps),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &std::path::Path) -> RecorderConfig {
+        RecorderConfig {
+            dir: dir.to_str().unwrap().to_string(),
+            ..RecorderConfig::default()
+        }
+    }
+
+    fn frame(seconds: f64, pressure: Option<f64>) -> Data {
+        Data {
+            time: Duration::from_secs_f64(seconds),
+            seq: (seconds * 10.0) as u64,
+            pressure,
+            ..Data::default()
+        }
+    }
+
+    #[test]
+    fn rows_follow_the_header_with_empty_cells_for_missing_channels() {
+        let dir = std::env::temp_dir().join("rctrl_recorder_rows");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut recorder = Recorder::new(&config(&dir)).unwrap();
+        recorder.append(&frame(1.0, Some(20.5)));
+        recorder.append(&frame(1.1, None));
+        drop(recorder);
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().collect();
+        assert_eq!(files.len(), 1);
+        let contents = std::fs::read_to_string(files[0].path()).unwrap();
+        let mut lines = contents.lines();
+        assert_eq!(lines.next().unwrap(), HEADER);
+        assert_eq!(lines.next().unwrap(), "1,10,false,20.5,,,,,,,,,,,");
+        assert_eq!(lines.next().unwrap(), "1.1,11,false,,,,,,,,,,,,");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotates_when_the_size_limit_is_reached() {
+        let dir = std::env::temp_dir().join("rctrl_recorder_rotate");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut recorder = Recorder::new(&config(&dir)).unwrap();
+        // Shrink the limit so the header alone exceeds it: every append
+        // lands in a fresh file.
+        recorder.max_bytes = 1;
+        recorder.append(&frame(1.0, Some(20.5)));
+        recorder.append(&frame(1.1, Some(20.5)));
+        drop(recorder);
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().flatten().count(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
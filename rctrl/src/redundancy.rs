@@ -0,0 +1,136 @@
+//! Voting for dual-redundant sensor channels.
+//!
+//! Critical measurements carry two transducers. A [`Voter`] folds the pair
+//! into one logical value: the average while the readings agree, the healthier
+//! reading — the one closer to the last agreed value — when they drift apart.
+//! Disagreement is flagged and its magnitude logged so a failing transducer
+//! is visible long before it matters.
+
+/// Which physical reading produced the logical value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selected {
+    /// Both readings agreed; the value is their average.
+    Both,
+    Primary,
+    Secondary,
+}
+
+/// Outcome of one vote.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vote {
+    /// The logical value redlines and logging should use.
+    pub value: f64,
+    /// Absolute difference between the readings when both were present.
+    pub disagreement: Option<f64>,
+    /// The pair is not healthy: readings disagree or one is missing.
+    pub degraded: bool,
+    pub selected: Selected,
+}
+
+/// Voting state for one redundant pair.
+pub struct Voter {
+    max_disagreement: f64,
+    /// Average of the last agreeing vote, used to pick the healthy side of a
+    /// disagreement.
+    last_agreed: Option<f64>,
+}
+
+impl Voter {
+    pub fn new(max_disagreement: f64) -> Self {
+        Self {
+            max_disagreement,
+            last_agreed: None,
+        }
+    }
+
+    /// Fold one pair of readings; `None` when neither side produced one.
+    pub fn vote(&mut self, primary: Option<f64>, secondary: Option<f64>) -> Option<Vote> {
+        match (primary, secondary) {
+            (Some(a), Some(b)) => {
+                let disagreement = (a - b).abs();
+                if disagreement <= self.max_disagreement {
+                    let value = (a + b) / 2.0;
+                    self.last_agreed = Some(value);
+                    Some(Vote {
+                        value,
+                        disagreement: Some(disagreement),
+                        degraded: false,
+                        selected: Selected::Both,
+                    })
+                } else {
+                    // Without a reference value there is nothing to arbitrate
+                    // with; trust the primary.
+                    let reference = self.last_agreed.unwrap_or(a);
+                    let selected = if (a - reference).abs() <= (b - reference).abs() {
+                        Selected::Primary
+                    } else {
+                        Selected::Secondary
+                    };
+                    Some(Vote {
+                        value: match selected {
+                            Selected::Secondary => b,
+                            _ => a,
+                        },
+                        disagreement: Some(disagreement),
+                        degraded: true,
+                        selected,
+                    })
+                }
+            }
+            (Some(a), None) => Some(Vote {
+                value: a,
+                disagreement: None,
+                degraded: true,
+                selected: Selected::Primary,
+            }),
+            (None, Some(b)) => Some(Vote {
+                value: b,
+                disagreement: None,
+                degraded: true,
+                selected: Selected::Secondary,
+            }),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agreeing_readings_average() {
+        let mut voter = Voter::new(0.5);
+        let vote = voter.vote(Some(10.0), Some(10.2)).unwrap();
+        assert_eq!(vote.value, 10.1);
+        assert_eq!(vote.selected, Selected::Both);
+        assert!(!vote.degraded);
+    }
+
+    #[test]
+    fn disagreement_selects_the_side_near_recent_history() {
+        let mut voter = Voter::new(0.5);
+        voter.vote(Some(10.0), Some(10.0));
+        // The secondary jumps; the primary stays near the agreed history.
+        let vote = voter.vote(Some(10.1), Some(14.0)).unwrap();
+        assert_eq!(vote.selected, Selected::Primary);
+        assert_eq!(vote.value, 10.1);
+        assert!(vote.degraded);
+        assert!((vote.disagreement.unwrap() - 3.9).abs() < 1e-9);
+
+        // And symmetrically when the primary is the one that fails.
+        let vote = voter.vote(Some(14.0), Some(10.1)).unwrap();
+        assert_eq!(vote.selected, Selected::Secondary);
+        assert_eq!(vote.value, 10.1);
+    }
+
+    #[test]
+    fn single_reading_is_used_but_degraded() {
+        let mut voter = Voter::new(0.5);
+        let vote = voter.vote(None, Some(9.0)).unwrap();
+        assert_eq!(vote.value, 9.0);
+        assert_eq!(vote.selected, Selected::Secondary);
+        assert!(vote.degraded);
+        assert!(voter.vote(None, None).is_none());
+    }
+}
@@ -0,0 +1,231 @@
+//! Lock-free SPSC handoff for telemetry frames.
+//!
+//! The sync loop used to hand frames to the pipeline through a tokio mpsc
+//! channel; `try_send` on that path takes a lock and allocates, and loop
+//! jitter measurements attributed their worst spikes to exactly this
+//! boundary. This wraps a fixed-capacity lock-free ring (`ringbuf`) so the
+//! producer writes frames in place and never blocks: a full ring drops the
+//! frame and counts it. The consumer reads non-blockingly and parks only
+//! when the ring is empty — the producer wakes it with a single notify per
+//! sleep, not per frame.
+
+use ringbuf::traits::{Consumer as _, Producer as _, Split as _};
+use ringbuf::HeapRb;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Wake-up bookkeeping shared by both ends. The data itself never passes
+/// through here; it lives in the ring.
+struct Shared {
+    /// Async-side wake-up; armed by [`Receiver::recv`] before it parks.
+    notify: Notify,
+    /// The async consumer has parked (or is about to); the producer only
+    /// notifies when this is set, so an actively draining consumer costs
+    /// the producer nothing.
+    waiting: AtomicBool,
+    /// Blocking-side wake-up, used by the IPC bridge thread in socket mode.
+    /// The mutex is only touched when a blocking waiter announced itself.
+    block: Mutex<()>,
+    blocked: Condvar,
+    blocking: AtomicBool,
+    /// The sender is gone; drain what remains and report end-of-stream.
+    closed: AtomicBool,
+    /// Frames dropped because the ring was full.
+    dropped: AtomicU64,
+}
+
+impl Shared {
+    /// Wake whichever consumer announced it is parked.
+    fn wake(&self) {
+        if self.waiting.swap(false, Ordering::SeqCst) {
+            self.notify.notify_one();
+        }
+        if self.blocking.swap(false, Ordering::SeqCst) {
+            // Taking the lock orders this wake after the waiter's re-check,
+            // so the notification cannot fall between check and wait.
+            drop(self.block.lock().expect("ring wake mutex poisoned"));
+            self.blocked.notify_all();
+        }
+    }
+}
+
+/// The producing end; owned by the sync loop (or the IPC bridge).
+pub struct Sender<T> {
+    prod: ringbuf::HeapProd<T>,
+    shared: Arc<Shared>,
+}
+
+/// The consuming end; owned by the pipeline (or the IPC frame writer).
+pub struct Receiver<T> {
+    cons: ringbuf::HeapCons<T>,
+    shared: Arc<Shared>,
+}
+
+/// A fixed-capacity SPSC ring holding up to `capacity` frames.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "ring capacity must be non-zero");
+    let (prod, cons) = HeapRb::new(capacity).split();
+    let shared = Arc::new(Shared {
+        notify: Notify::new(),
+        waiting: AtomicBool::new(false),
+        block: Mutex::new(()),
+        blocked: Condvar::new(),
+        blocking: AtomicBool::new(false),
+        closed: AtomicBool::new(false),
+        dropped: AtomicU64::new(0),
+    });
+    (
+        Sender {
+            prod,
+            shared: shared.clone(),
+        },
+        Receiver { cons, shared },
+    )
+}
+
+impl<T> Sender<T> {
+    /// Hand one frame to the consumer without blocking. A full ring means
+    /// the consumer is behind; the frame is dropped and counted rather than
+    /// stalling the producer.
+    pub fn try_send(&mut self, value: T) -> Result<(), T> {
+        match self.prod.try_push(value) {
+            Ok(()) => {
+                self.shared.wake();
+                Ok(())
+            }
+            Err(value) => {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(value)
+            }
+        }
+    }
+
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.shared.wake();
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Await the next frame; `None` once the sender is gone and the ring is
+    /// drained. Cancellation-safe: no frame is taken before the await.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(value) = self.cons.try_pop() {
+                return Some(value);
+            }
+            if self.shared.closed.load(Ordering::SeqCst) {
+                return self.cons.try_pop();
+            }
+            // Announce the park, then re-check: a frame pushed before the
+            // announcement would otherwise be missed until the next one.
+            let notified = self.shared.notify.notified();
+            self.shared.waiting.store(true, Ordering::SeqCst);
+            if let Some(value) = self.cons.try_pop() {
+                self.shared.waiting.store(false, Ordering::SeqCst);
+                return Some(value);
+            }
+            if self.shared.closed.load(Ordering::SeqCst) {
+                self.shared.waiting.store(false, Ordering::SeqCst);
+                return self.cons.try_pop();
+            }
+            notified.await;
+        }
+    }
+
+    /// Block this thread until the next frame; `None` once the sender is
+    /// gone and the ring is drained. Used by the IPC bridge, which runs on a
+    /// plain thread — the sync loop itself never blocks here.
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(value) = self.cons.try_pop() {
+                return Some(value);
+            }
+            if self.shared.closed.load(Ordering::SeqCst) {
+                return self.cons.try_pop();
+            }
+            let guard = self.shared.block.lock().expect("ring wake mutex poisoned");
+            self.shared.blocking.store(true, Ordering::SeqCst);
+            if let Some(value) = self.cons.try_pop() {
+                self.shared.blocking.store(false, Ordering::SeqCst);
+                return Some(value);
+            }
+            if self.shared.closed.load(Ordering::SeqCst) {
+                self.shared.blocking.store(false, Ordering::SeqCst);
+                return self.cons.try_pop();
+            }
+            // The timeout is belt and braces against a lost wake-up; it is
+            // never the steady-state wait path.
+            let _unused = self
+                .shared
+                .blocked
+                .wait_timeout(guard, Duration::from_millis(100))
+                .expect("ring wake mutex poisoned");
+        }
+    }
+
+    /// Frames dropped because the ring was full, since construction.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_pass_in_order_and_overflow_is_counted() {
+        let (mut tx, mut rx) = channel::<u32>(2);
+        assert!(tx.try_send(1).is_ok());
+        assert!(tx.try_send(2).is_ok());
+        // Full: the frame is dropped and counted, the producer not stalled.
+        assert_eq!(tx.try_send(3), Err(3));
+        drop(tx);
+
+        assert_eq!(rx.blocking_recv(), Some(1));
+        assert_eq!(rx.blocking_recv(), Some(2));
+        assert_eq!(rx.blocking_recv(), None);
+        assert_eq!(rx.dropped(), 1);
+    }
+
+    #[test]
+    fn blocking_recv_drains_then_reports_end_of_stream() {
+        let (mut tx, mut rx) = channel::<u32>(4);
+        tx.try_send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.blocking_recv(), Some(1));
+        assert_eq!(rx.blocking_recv(), None);
+    }
+
+    #[test]
+    fn blocking_recv_wakes_on_send_from_another_thread() {
+        let (mut tx, mut rx) = channel::<u32>(4);
+        let handle = std::thread::spawn(move || rx.blocking_recv());
+        std::thread::sleep(Duration::from_millis(20));
+        tx.try_send(7).unwrap();
+        assert_eq!(handle.join().unwrap(), Some(7));
+    }
+
+    #[test]
+    fn recv_drains_then_reports_end_of_stream() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let (mut tx, mut rx) = channel::<u32>(4);
+            tx.try_send(1).unwrap();
+            assert_eq!(rx.recv().await, Some(1));
+
+            // A frame sent while the consumer is parked wakes it.
+            let waiter = tokio::spawn(async move { rx.recv().await });
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            tx.try_send(2).unwrap();
+            drop(tx);
+            assert_eq!(waiter.await.unwrap(), Some(2));
+        });
+    }
+}
@@ -0,0 +1,188 @@
+//! Automated response rules: "if channel X then command Y".
+//!
+//! Short of a full sequencer, configured rules give the stand reflexes: a
+//! threshold condition on a telemetry channel, optionally sustained for a
+//! duration, mapped to an action (close the valve, raise the alarm, open a
+//! burst capture window). The engine runs in the sync loop and evaluates
+//! every armed rule against each sampled frame; a rule fires once per
+//! excursion and re-arms when the condition clears, so a value parked past
+//! its threshold does not fire at loop rate.
+
+use crate::config::{RuleAction, RuleConfig};
+use rctrl_api::prelude::*;
+use std::time::{Duration, Instant};
+
+/// One configured rule plus its runtime state.
+struct Rule {
+    config: RuleConfig,
+    enabled: bool,
+    /// When the condition was last seen becoming true; `None` while it does
+    /// not hold, and cleared by a reading inside the threshold.
+    met_since: Option<Instant>,
+    /// Whether this excursion has already fired.
+    fired: bool,
+}
+
+impl Rule {
+    /// Whether `value` violates the configured threshold.
+    fn condition_met(&self, value: f64) -> bool {
+        match (self.config.above, self.config.below) {
+            (Some(threshold), _) => value > threshold,
+            (_, Some(threshold)) => value < threshold,
+            // Rejected by config validation.
+            (None, None) => false,
+        }
+    }
+
+    /// A one-line description of the condition, for alarms and burst reasons.
+    fn describe(&self) -> String {
+        match (self.config.above, self.config.below) {
+            (Some(threshold), _) => format!("{} > {threshold}", self.config.channel),
+            _ => format!(
+                "{} < {}",
+                self.config.channel,
+                self.config.below.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// One rule firing, reported the cycle the condition has held long enough.
+pub struct Firing {
+    /// The configured rule name, used as the audit action.
+    pub name: String,
+    pub action: RuleAction,
+    /// The violated condition, e.g. `pressure > 30`.
+    pub reason: String,
+}
+
+/// Evaluates every configured rule against the telemetry stream.
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<RuleConfig>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|config| Rule {
+                    enabled: config.enabled,
+                    config,
+                    met_since: None,
+                    fired: false,
+                })
+                .collect(),
+        }
+    }
+
+    /// Feed one frame; returns the rules that fired this cycle. A frame that
+    /// does not carry a rule's channel leaves that rule's state unchanged.
+    pub fn evaluate(&mut self, data: &Data) -> Vec<Firing> {
+        let mut firings = Vec::new();
+        for rule in &mut self.rules {
+            if !rule.enabled {
+                continue;
+            }
+            let Some(value) = data.channel_value(&rule.config.channel) else {
+                continue;
+            };
+            if !rule.condition_met(value) {
+                rule.met_since = None;
+                rule.fired = false;
+                continue;
+            }
+            let since = *rule.met_since.get_or_insert_with(Instant::now);
+            if rule.fired || since.elapsed() < Duration::from_millis(rule.config.for_ms) {
+                continue;
+            }
+            rule.fired = true;
+            firings.push(Firing {
+                name: rule.config.name.clone(),
+                action: rule.config.action,
+                reason: rule.describe(),
+            });
+        }
+        firings
+    }
+
+    /// Arm or disarm a rule by name. Disarming clears the excursion state, so
+    /// re-arming starts from a clean slate.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let Some(rule) = self.rules.iter_mut().find(|r| r.config.name == name) else {
+            return Err(format!("unknown rule '{name}'"));
+        };
+        rule.enabled = enabled;
+        if !enabled {
+            rule.met_since = None;
+            rule.fired = false;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(for_ms: u64) -> RuleConfig {
+        RuleConfig {
+            name: "overpressure".to_string(),
+            channel: "pressure".to_string(),
+            above: Some(30.0),
+            below: None,
+            for_ms,
+            action: RuleAction::CloseValve,
+            enabled: true,
+        }
+    }
+
+    fn pressure(value: f64) -> Data {
+        Data {
+            pressure: Some(value),
+            ..Data::default()
+        }
+    }
+
+    #[test]
+    fn fires_once_per_excursion_and_rearms_when_the_condition_clears() {
+        let mut engine = RuleEngine::new(vec![rule(0)]);
+        assert!(engine.evaluate(&pressure(29.0)).is_empty());
+
+        let firings = engine.evaluate(&pressure(31.0));
+        assert_eq!(firings.len(), 1);
+        assert_eq!(firings[0].name, "overpressure");
+        assert_eq!(firings[0].action, RuleAction::CloseValve);
+        assert_eq!(firings[0].reason, "pressure > 30");
+        // Still past the threshold: the excursion already fired.
+        assert!(engine.evaluate(&pressure(35.0)).is_empty());
+        // A frame without the channel leaves the excursion standing.
+        assert!(engine.evaluate(&Data::default()).is_empty());
+
+        assert!(engine.evaluate(&pressure(29.0)).is_empty());
+        assert_eq!(engine.evaluate(&pressure(31.0)).len(), 1);
+    }
+
+    #[test]
+    fn duration_must_hold_before_the_rule_fires() {
+        let mut engine = RuleEngine::new(vec![rule(10_000)]);
+        assert!(engine.evaluate(&pressure(31.0)).is_empty());
+        assert!(engine.evaluate(&pressure(31.0)).is_empty());
+        // Dipping back inside the threshold restarts the clock.
+        assert!(engine.evaluate(&pressure(29.0)).is_empty());
+        assert!(engine.evaluate(&pressure(31.0)).is_empty());
+    }
+
+    #[test]
+    fn disarmed_rules_do_not_fire_and_rearm_cleanly() {
+        let mut engine = RuleEngine::new(vec![rule(0)]);
+        engine.set_enabled("overpressure", false).unwrap();
+        assert!(engine.evaluate(&pressure(31.0)).is_empty());
+
+        // Re-arming mid-excursion starts a fresh evaluation.
+        engine.set_enabled("overpressure", true).unwrap();
+        assert_eq!(engine.evaluate(&pressure(31.0)).len(), 1);
+
+        assert!(engine.set_enabled("unknown", true).is_err());
+    }
+}
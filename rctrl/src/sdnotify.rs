@@ -0,0 +1,57 @@
+//! Minimal sd_notify client for running under systemd.
+//!
+//! The protocol is a newline-separated list of `KEY=value` assignments sent
+//! as a single datagram to the unix socket named by `NOTIFY_SOCKET`; it is
+//! small enough that pulling in a systemd crate is not worth it. Everything
+//! here is best-effort and a no-op outside systemd, so development runs are
+//! unaffected.
+
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Handle to the systemd notification socket, if one was provided.
+#[derive(Clone)]
+pub struct SdNotify(Option<Arc<UnixDatagram>>);
+
+impl SdNotify {
+    /// Connect to `NOTIFY_SOCKET`; an inert handle when unset or unusable.
+    pub fn from_env() -> Self {
+        let Ok(path) = std::env::var("NOTIFY_SOCKET") else {
+            return Self(None);
+        };
+        let socket = UnixDatagram::unbound().and_then(|socket| {
+            // A leading '@' names a socket in the abstract namespace.
+            if let Some(name) = path.strip_prefix('@') {
+                socket.connect_addr(&SocketAddr::from_abstract_name(name)?)?;
+            } else {
+                socket.connect(&path)?;
+            }
+            Ok(socket)
+        });
+        match socket {
+            Ok(socket) => Self(Some(Arc::new(socket))),
+            Err(e) => {
+                tracing::warn!("notify socket {path} unusable: {e}");
+                Self(None)
+            }
+        }
+    }
+
+    /// Send one or more `KEY=value` assignments, newline separated.
+    pub fn notify(&self, state: &str) {
+        if let Some(socket) = &self.0 {
+            if let Err(e) = socket.send(state.as_bytes()) {
+                tracing::warn!("sd_notify failed: {e}");
+            }
+        }
+    }
+
+    /// Interval at which `WATCHDOG=1` must be sent: half the budget systemd
+    /// grants via `WATCHDOG_USEC`, or `None` when no watchdog is configured.
+    pub fn watchdog_period() -> Option<Duration> {
+        let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+        (usec > 0).then(|| Duration::from_micros(usec / 2))
+    }
+}
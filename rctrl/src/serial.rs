@@ -0,0 +1,222 @@
+//! Serial telemetry input from the flight computer.
+//!
+//! The flight computer streams frames over a USB serial port in one of two
+//! configurable formats: COBS framed postcard structs, or plain CSV lines.
+//! Parsed frames enter the same pipeline as ground-side sensors, so avionics
+//! data lands in influx and the GUI alongside everything else.
+
+use crate::config::{SerialConfig, SerialFormat};
+use crate::metrics::METRICS;
+use crate::skew::SkewEstimator;
+use influx::LineProtocol;
+use rctrl_api::prelude::*;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use tokio_serial::SerialPortBuilderExt;
+
+/// One telemetry frame as emitted by the flight computer firmware.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct AvionicsFrame {
+    pub seq: u32,
+    /// Flight computer mission time in milliseconds, on its own clock.
+    pub time_ms: u32,
+    /// Chamber pressure in bar.
+    pub pressure: f32,
+    /// Altitude above ground in metres.
+    pub altitude: f32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    #[error("cobs framing error")]
+    Cobs,
+    #[error("postcard: {0}")]
+    Postcard(#[from] postcard::Error),
+    #[error("malformed csv line: {0}")]
+    Csv(String),
+}
+
+/// Decode one COBS framed postcard frame (without the trailing delimiter).
+pub fn parse_cobs_postcard(frame: &[u8]) -> Result<AvionicsFrame, FrameError> {
+    let decoded = cobs::decode_vec(frame).map_err(|_| FrameError::Cobs)?;
+    Ok(postcard::from_bytes(&decoded)?)
+}
+
+/// Parse one CSV line of the form `seq,time_ms,pressure,altitude`.
+pub fn parse_csv_line(line: &str) -> Result<AvionicsFrame, FrameError> {
+    let mut parts = line.trim().split(',');
+    let mut next = |what: &str| {
+        parts
+            .next()
+            .ok_or_else(|| FrameError::Csv(format!("missing {what} in {line:?}")))
+    };
+    let seq = next("seq")?
+        .parse()
+        .map_err(|_| FrameError::Csv(line.to_string()))?;
+    let time_ms = next("time_ms")?
+        .parse()
+        .map_err(|_| FrameError::Csv(line.to_string()))?;
+    let pressure = next("pressure")?
+        .parse()
+        .map_err(|_| FrameError::Csv(line.to_string()))?;
+    let altitude = next("altitude")?
+        .parse()
+        .map_err(|_| FrameError::Csv(line.to_string()))?;
+    Ok(AvionicsFrame {
+        seq,
+        time_ms,
+        pressure,
+        altitude,
+    })
+}
+
+impl AvionicsFrame {
+    /// Map the frame into the pipeline's data frame format.
+    fn into_data(self, mission_time: Duration) -> Data {
+        Data {
+            time: mission_time,
+            seq: u64::from(self.seq),
+            fc_pressure: Some(f64::from(self.pressure)),
+            fc_altitude: Some(f64::from(self.altitude)),
+            ..Data::default()
+        }
+    }
+}
+
+/// Observations the skew fit slides over (~25 s at the avionics frame rate).
+const SKEW_WINDOW: usize = 256;
+/// Frames between `clock_skew` measurement lines.
+const SKEW_REPORT_EVERY: u64 = 100;
+
+/// Read the serial port forever, feeding parsed frames into the pipeline.
+/// `read_timeout` bounds each read: a port that goes silent for longer is
+/// treated as wedged and reopened.
+pub async fn task(
+    config: SerialConfig,
+    read_timeout: Duration,
+    data_tx: mpsc::Sender<Data>,
+    line_tx: mpsc::Sender<LineProtocol>,
+) {
+    let start = Instant::now();
+    // The estimator survives reconnects: a rebooted flight computer's new
+    // clock epoch displaces the old observations within one window.
+    let mut skew = SkewEstimator::new(SKEW_WINDOW);
+    let mut frames: u64 = 0;
+    loop {
+        let mut port = match tokio_serial::new(&config.port, config.baud).open_native_async() {
+            Ok(port) => port,
+            Err(e) => {
+                tracing::warn!("failed to open serial port {}: {e}, retrying", config.port);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        tracing::info!("serial telemetry input open on {}", config.port);
+
+        let mut pending = Vec::new();
+        let mut buf = [0u8; 512];
+        loop {
+            let n = match tokio::time::timeout(read_timeout, port.read(&mut buf)).await {
+                Ok(Ok(0)) | Ok(Err(_)) => break,
+                Ok(Ok(n)) => n,
+                Err(_) => {
+                    // A silent avionics link is a wedged driver or a dead
+                    // cable as often as a quiet flight computer; reopening
+                    // recovers the former and is harmless for the latter.
+                    METRICS.incr("serial_read_timeouts", 1);
+                    tracing::warn!(
+                        "serial port {} silent for {read_timeout:?}, reopening",
+                        config.port
+                    );
+                    break;
+                }
+            };
+            pending.extend_from_slice(&buf[..n]);
+
+            let delimiter = match config.format {
+                SerialFormat::CobsPostcard => 0x00,
+                SerialFormat::Csv => b'\n',
+            };
+            while let Some(end) = pending.iter().position(|&b| b == delimiter) {
+                let frame: Vec<u8> = pending.drain(..=end).take(end).collect();
+                let parsed = match config.format {
+                    SerialFormat::CobsPostcard => parse_cobs_postcard(&frame),
+                    SerialFormat::Csv => parse_csv_line(&String::from_utf8_lossy(&frame)),
+                };
+                match parsed {
+                    Ok(avionics) => {
+                        // Map the frame's own timestamp onto the ground
+                        // clock, so avionics channels line up with ground
+                        // channels in influx despite offset and drift.
+                        let local_s = start.elapsed().as_secs_f64();
+                        let remote_s = f64::from(avionics.time_ms) / 1000.0;
+                        skew.observe(remote_s, local_s);
+                        let corrected = skew.correct(remote_s).unwrap_or(local_s).max(0.0);
+                        let data = avionics.into_data(Duration::from_secs_f64(corrected));
+                        if data_tx.try_send(data).is_err() {
+                            tracing::warn!("pipeline full, dropping avionics frame");
+                        }
+
+                        frames += 1;
+                        if frames.is_multiple_of(SKEW_REPORT_EVERY) {
+                            if let Some(estimate) = skew.skew() {
+                                let _ = line_tx
+                                    .send(LineProtocol(format!(
+                                        "clock_skew offset_ms={},drift_ppm={} {}",
+                                        estimate.offset_s * 1000.0,
+                                        estimate.drift_ppm,
+                                        influx::timestamp_now()
+                                    )))
+                                    .await;
+                            }
+                        }
+                    }
+                    Err(e) => tracing::warn!("bad avionics frame: {e}"),
+                }
+            }
+        }
+        tracing::warn!("serial port {} closed, reopening", config.port);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_line_parses() {
+        let frame = parse_csv_line("17,250,12.5,340.0\n").unwrap();
+        assert_eq!(
+            frame,
+            AvionicsFrame {
+                seq: 17,
+                time_ms: 250,
+                pressure: 12.5,
+                altitude: 340.0,
+            }
+        );
+    }
+
+    #[test]
+    fn cobs_postcard_roundtrip() {
+        // postcard is self-describing enough to hand-encode: varint seq and
+        // time_ms, little endian f32s.
+        let mut payload = vec![17u8, 250, 0x01];
+        payload.extend_from_slice(&12.5f32.to_le_bytes());
+        payload.extend_from_slice(&340.0f32.to_le_bytes());
+        let encoded = cobs::encode_vec(&payload);
+
+        let frame = parse_cobs_postcard(&encoded).unwrap();
+        assert_eq!(frame.seq, 17);
+        assert_eq!(frame.time_ms, 250);
+        assert_eq!(frame.pressure, 12.5);
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(parse_csv_line("not,numbers,here").is_err());
+        assert!(parse_cobs_postcard(&[0xff, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! Structured shutdown: why the process is stopping and what it exits with.
+//!
+//! Any subsystem can request a shutdown with a [`ShutdownReason`]; the first
+//! request wins and later ones are ignored. The reason is logged, written to
+//! influx as a final event by the pipeline, and mapped to a distinct process
+//! exit code so the systemd unit can apply different restart policies per
+//! cause (e.g. restart on a watchdog trip but not on an operator stop).
+
+use tokio::sync::watch;
+
+/// Why the process is shutting down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// The operator asked the process to stop (SIGINT / ctrl-c).
+    OperatorRequest,
+    /// Hardware failed in a way the sync loop cannot recover from.
+    FatalHwError,
+    /// A supervised task crashed; the software watchdog pulled the plug.
+    Watchdog,
+    /// The configuration failed to load or validate.
+    ConfigError,
+}
+
+impl ShutdownReason {
+    /// Process exit code, one per cause.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ShutdownReason::OperatorRequest => 0,
+            ShutdownReason::ConfigError => 1,
+            ShutdownReason::FatalHwError => 10,
+            ShutdownReason::Watchdog => 11,
+        }
+    }
+
+    /// Tag value for the final influx event.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShutdownReason::OperatorRequest => "operator_request",
+            ShutdownReason::FatalHwError => "fatal_hw_error",
+            ShutdownReason::Watchdog => "watchdog",
+            ShutdownReason::ConfigError => "config_error",
+        }
+    }
+}
+
+impl std::fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cloneable handle subsystems use to initiate a shutdown.
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: watch::Sender<Option<ShutdownReason>>,
+}
+
+impl Shutdown {
+    pub fn new() -> (Self, watch::Receiver<Option<ShutdownReason>>) {
+        let (tx, rx) = watch::channel(None);
+        (Self { tx }, rx)
+    }
+
+    /// Request a shutdown. The first reason wins; subsequent requests are
+    /// logged and dropped.
+    pub fn request(&self, reason: ShutdownReason) {
+        let recorded = self.tx.send_if_modified(|current| {
+            if current.is_none() {
+                *current = Some(reason);
+                true
+            } else {
+                false
+            }
+        });
+        if recorded {
+            tracing::warn!(target: "alarm", "shutdown requested: {reason}");
+        } else {
+            tracing::info!("shutdown already in progress, ignoring request: {reason}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_reason_wins() {
+        let (shutdown, rx) = Shutdown::new();
+        shutdown.request(ShutdownReason::FatalHwError);
+        shutdown.request(ShutdownReason::OperatorRequest);
+        assert_eq!(*rx.borrow(), Some(ShutdownReason::FatalHwError));
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let codes = [
+            ShutdownReason::OperatorRequest,
+            ShutdownReason::FatalHwError,
+            ShutdownReason::Watchdog,
+            ShutdownReason::ConfigError,
+        ]
+        .map(ShutdownReason::exit_code);
+        for (i, a) in codes.iter().enumerate() {
+            for b in &codes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
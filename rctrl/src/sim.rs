@@ -0,0 +1,225 @@
+//! Simulated telemetry for development and sequence rehearsal without stand
+//! hardware.
+//!
+//! The simulation models a simple pressurized tank feeding a line through the
+//! main valve: opening the valve raises feed pressure toward tank pressure
+//! with a realistic time constant while slowly blowing the tank down, closing
+//! it lets the feed section vent toward ambient. Operators rehearsing
+//! sequences and abort responses therefore see plausible responses to their
+//! commands instead of canned waveforms.
+//!
+//! Alternatively a [`SimProfile`] replays a pressure trace recorded during a
+//! real test, so rehearsals see our actual transients and noise
+//! characteristics instead of the model.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Initial tank pressure in bar.
+const TANK_INITIAL_BAR: f64 = 60.0;
+/// Ambient pressure in bar.
+const AMBIENT_BAR: f64 = 1.0;
+/// Feed section fill time constant with the valve open, in seconds.
+const TAU_FILL_S: f64 = 0.8;
+/// Feed section vent time constant with the valve closed, in seconds.
+const TAU_VENT_S: f64 = 2.0;
+/// Feed section volume relative to the tank; sets the blowdown rate.
+const VOLUME_RATIO: f64 = 0.05;
+
+/// A recorded pressure trace replayed in place of the tank model.
+///
+/// Profiles are CSV `time_s,value` rows (comment lines start with `#`), as
+/// exported from a previous test's influx data. On load the trace is
+/// resampled onto the loop rate by linear interpolation, so the replay
+/// preserves the recorded transients and noise regardless of the rate the
+/// original was logged at. The replay follows the recorded timeline and
+/// ignores valve commands; it loops so a rehearsal can run longer than the
+/// recording.
+pub struct SimProfile {
+    /// The trace on the loop-rate grid, one value per iteration.
+    samples: Vec<f64>,
+    cursor: usize,
+}
+
+impl SimProfile {
+    pub fn load(path: &Path, period: Duration) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .split_once(',')
+                .and_then(|(t, v)| Some((t.trim().parse().ok()?, v.trim().parse().ok()?)));
+            match parsed {
+                Some(point) => points.push(point),
+                None => return Err(format!("line {}: expected 'time_s,value'", number + 1)),
+            }
+        }
+        if points.len() < 2 {
+            return Err("profile needs at least two samples".to_string());
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let period_s = period.as_secs_f64();
+        let t0 = points[0].0;
+        let steps = ((points[points.len() - 1].0 - t0) / period_s) as usize + 1;
+        let mut samples = Vec::with_capacity(steps);
+        let mut index = 0;
+        for step in 0..steps {
+            let t = t0 + step as f64 * period_s;
+            while index + 2 < points.len() && points[index + 1].0 <= t {
+                index += 1;
+            }
+            let (ta, va) = points[index];
+            let (tb, vb) = points[index + 1];
+            let frac = ((t - ta) / (tb - ta)).clamp(0.0, 1.0);
+            samples.push(va + frac * (vb - va));
+        }
+        Ok(Self { samples, cursor: 0 })
+    }
+
+    /// The value for the next loop iteration, wrapping at the end.
+    fn next(&mut self) -> f64 {
+        let value = self.samples[self.cursor];
+        self.cursor = (self.cursor + 1) % self.samples.len();
+        value
+    }
+}
+
+/// First order tank/feed-system model standing in for the stand sensors.
+pub struct SimSource {
+    last_update: Instant,
+    tank_bar: f64,
+    feed_bar: f64,
+    noise_state: u64,
+    /// Replayed in place of the model when a profile is configured.
+    profile: Option<SimProfile>,
+}
+
+impl SimSource {
+    pub fn new() -> Self {
+        Self {
+            last_update: Instant::now(),
+            tank_bar: TANK_INITIAL_BAR,
+            feed_bar: AMBIENT_BAR,
+            noise_state: 0x2545_f491_4f6c_dd1d,
+            profile: None,
+        }
+    }
+
+    /// Build the source from the `[sim]` config: replay `profile` when one
+    /// is set and loads, otherwise run the tank model.
+    pub fn from_config(profile: Option<&str>, period: Duration) -> Self {
+        let mut source = Self::new();
+        if let Some(path) = profile {
+            match SimProfile::load(Path::new(path), period) {
+                Ok(profile) => {
+                    tracing::info!("replaying recorded sim profile {path}");
+                    source.profile = Some(profile);
+                }
+                Err(e) => {
+                    tracing::error!("failed to load sim profile {path}: {e}; using the model")
+                }
+            }
+        }
+        source
+    }
+
+    /// Small pseudo random perturbation so plots look alive.
+    fn noise(&mut self) -> f64 {
+        // xorshift64*, scaled to ±0.5.
+        self.noise_state ^= self.noise_state >> 12;
+        self.noise_state ^= self.noise_state << 25;
+        self.noise_state ^= self.noise_state >> 27;
+        let bits = self.noise_state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (bits >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    }
+
+    /// Advance the model by `dt` seconds with the given valve command.
+    fn step(&mut self, dt: f64, valve_open: bool) {
+        if valve_open {
+            let flow = (self.tank_bar - self.feed_bar) * dt / TAU_FILL_S;
+            self.feed_bar += flow;
+            // What fills the feed section leaves the tank, scaled by the
+            // volume ratio.
+            self.tank_bar -= flow * VOLUME_RATIO;
+            self.tank_bar = self.tank_bar.max(AMBIENT_BAR);
+        } else {
+            self.feed_bar += (AMBIENT_BAR - self.feed_bar) * dt / TAU_VENT_S;
+        }
+    }
+
+    /// Advance the model to now and return the simulated feed pressure. With
+    /// a profile loaded the next recorded value is returned instead; the
+    /// recording already carries the real sensor noise.
+    pub fn update(&mut self, valve_open: bool) -> f64 {
+        if let Some(profile) = self.profile.as_mut() {
+            return profile.next();
+        }
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+        self.step(dt.min(0.1), valve_open);
+        self.feed_bar + 0.2 * self.noise()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(sim: &mut SimSource, seconds: f64, valve_open: bool) {
+        let dt = 0.01;
+        let steps = (seconds / dt) as usize;
+        for _ in 0..steps {
+            sim.step(dt, valve_open);
+        }
+    }
+
+    #[test]
+    fn opening_the_valve_pressurizes_the_feed_section() {
+        let mut sim = SimSource::new();
+        run(&mut sim, 5.0, true);
+        assert!(sim.feed_bar > 0.9 * sim.tank_bar);
+        // The tank blew down a little.
+        assert!(sim.tank_bar < TANK_INITIAL_BAR);
+    }
+
+    #[test]
+    fn closing_the_valve_vents_toward_ambient() {
+        let mut sim = SimSource::new();
+        run(&mut sim, 5.0, true);
+        run(&mut sim, 20.0, false);
+        assert!((sim.feed_bar - AMBIENT_BAR).abs() < 0.5);
+    }
+
+    #[test]
+    fn profile_resamples_onto_the_loop_rate_and_loops() {
+        let path = std::env::temp_dir().join("rctrl_sim_profile_test.csv");
+        // Recorded at 50 Hz; replayed at 100 Hz the gaps are interpolated.
+        std::fs::write(&path, "# coldflow excerpt\n0.00,10\n0.02,12\n0.04,16\n").unwrap();
+        let mut profile =
+            SimProfile::load(&path, Duration::from_millis(10)).expect("profile should load");
+        std::fs::remove_file(&path).unwrap();
+
+        let replayed: Vec<f64> = (0..6).map(|_| profile.next()).collect();
+        // 5 samples spanning the recording, then the replay wraps.
+        assert_eq!(replayed, vec![10.0, 11.0, 12.0, 14.0, 16.0, 10.0]);
+    }
+
+    #[test]
+    fn malformed_profile_is_rejected() {
+        let path = std::env::temp_dir().join("rctrl_sim_profile_bad_test.csv");
+        std::fs::write(&path, "0.00,10\nnot a row\n").unwrap();
+        let error = match SimProfile::load(&path, Duration::from_millis(10)) {
+            Ok(_) => panic!("malformed profile should not load"),
+            Err(e) => e,
+        };
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("line 2"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Clock skew estimation between the flight computer and the ground side.
+//!
+//! The flight computer stamps its frames with its own mission clock, which
+//! both starts at a different zero and drifts relative to the ground
+//! computer's. Fitting local arrival times against the remote timestamps over
+//! a sliding window yields the offset and drift, which are then applied to
+//! serial-sourced timestamps before logging, so avionics and ground channels
+//! line up in influx. Network jitter is absorbed by the fit: arrival delay
+//! noise averages out across the window.
+
+use std::collections::VecDeque;
+
+/// The estimated relation between the two clocks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Skew {
+    /// Local time minus remote time at the most recent observation, in
+    /// seconds.
+    pub offset_s: f64,
+    /// Remote clock rate error in parts per million; positive means the
+    /// remote clock runs slow relative to ground.
+    pub drift_ppm: f64,
+}
+
+/// Least squares fit of local time against remote time over a sliding window.
+pub struct SkewEstimator {
+    /// (remote, local) observation pairs, both in seconds.
+    samples: VecDeque<(f64, f64)>,
+    window: usize,
+}
+
+impl SkewEstimator {
+    pub fn new(window: usize) -> Self {
+        assert!(window >= 2, "skew fit needs at least two observations");
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Record one frame: its remote timestamp and the local time it arrived.
+    pub fn observe(&mut self, remote_s: f64, local_s: f64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((remote_s, local_s));
+    }
+
+    /// The fitted `local = intercept + slope * remote` line, if the window
+    /// holds enough spread to fit one.
+    fn fit(&self) -> Option<(f64, f64)> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let mean_remote = self.samples.iter().map(|(r, _)| r).sum::<f64>() / n;
+        let mean_local = self.samples.iter().map(|(_, l)| l).sum::<f64>() / n;
+        let covariance: f64 = self
+            .samples
+            .iter()
+            .map(|(r, l)| (r - mean_remote) * (l - mean_local))
+            .sum();
+        let variance: f64 = self
+            .samples
+            .iter()
+            .map(|(r, _)| (r - mean_remote).powi(2))
+            .sum();
+        // Degenerate window (single instant): offset only, unit rate.
+        let slope = if variance > 0.0 {
+            covariance / variance
+        } else {
+            1.0
+        };
+        Some((mean_local - slope * mean_remote, slope))
+    }
+
+    /// Current offset and drift estimate.
+    pub fn skew(&self) -> Option<Skew> {
+        let (intercept, slope) = self.fit()?;
+        let (remote, _) = *self.samples.back()?;
+        Some(Skew {
+            offset_s: intercept + (slope - 1.0) * remote,
+            drift_ppm: (slope - 1.0) * 1e6,
+        })
+    }
+
+    /// Map a remote timestamp onto the local clock.
+    pub fn correct(&self, remote_s: f64) -> Option<f64> {
+        let (intercept, slope) = self.fit()?;
+        Some(intercept + slope * remote_s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_offset_and_drift_from_noiseless_pairs() {
+        // Remote clock started 2 s after ground and runs 1000 ppm fast from
+        // the local frame: local = 2.0 + remote / 1.001.
+        let mut estimator = SkewEstimator::new(64);
+        for i in 0..50 {
+            let remote = i as f64 * 0.1;
+            estimator.observe(remote, 2.0 + remote / 1.001);
+        }
+        let skew = estimator.skew().unwrap();
+        assert!((skew.drift_ppm - (1.0 / 1.001 - 1.0) * 1e6).abs() < 1.0);
+
+        let corrected = estimator.correct(4.9).unwrap();
+        assert!((corrected - (2.0 + 4.9 / 1.001)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_observation_yields_a_pure_offset() {
+        let mut estimator = SkewEstimator::new(8);
+        estimator.observe(10.0, 13.5);
+        let skew = estimator.skew().unwrap();
+        assert_eq!(skew.offset_s, 3.5);
+        assert_eq!(skew.drift_ppm, 0.0);
+        assert_eq!(estimator.correct(11.0), Some(14.5));
+    }
+
+    #[test]
+    fn window_slides_so_old_epochs_age_out() {
+        let mut estimator = SkewEstimator::new(4);
+        // An old offset regime...
+        for i in 0..4 {
+            estimator.observe(i as f64, i as f64 + 1.0);
+        }
+        // ...fully displaced by a new one after a remote clock reset.
+        for i in 0..4 {
+            estimator.observe(i as f64, i as f64 + 7.0);
+        }
+        assert!((estimator.skew().unwrap().offset_s - 7.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,195 @@
+//! Minimal HTTP status server.
+//!
+//! Serves a JSON summary on `GET /status` for shell scripts, the internal
+//! metrics registry on `GET /metrics` in Prometheus text exposition format so
+//! the facility's existing Prometheus can scrape ground control health
+//! without touching InfluxDB, the influx dead-letter buffer on `/deadletter`
+//! (with `/deadletter/retry` re-queueing its lines), and the writer-side
+//! measurement filter on `/filter` (with
+//! `/filter/set?allow=...&block=...` swapping the lists live).
+
+use crate::deadletter::DeadLetter;
+use crate::metrics::METRICS;
+use crate::writefilter::WriteFilter;
+use influx::LineProtocol;
+use rctrl_api::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// State shared with the rest of the async side for reporting.
+pub struct StatusState {
+    pub start: Instant,
+    pub clients: AtomicUsize,
+    /// Provenance of this build, reported on `/status` so scripts can tell
+    /// which software produced the data they are about to pull.
+    pub build: BuildInfo,
+    /// Hash of a staged config awaiting the next restart, `None` when
+    /// nothing is staged; updated by the router when an upload is accepted.
+    pub staged_config_hash: Mutex<Option<String>>,
+}
+
+impl StatusState {
+    pub fn new(build: BuildInfo, staged_config_hash: Option<String>) -> Arc<Self> {
+        Arc::new(Self {
+            start: Instant::now(),
+            clients: AtomicUsize::new(0),
+            build,
+            staged_config_hash: Mutex::new(staged_config_hash),
+        })
+    }
+
+    /// Count a client for as long as the returned guard lives. The guard
+    /// decrements on drop, so the count stays correct even when a connection
+    /// task unwinds from a panic.
+    pub fn client_guard(self: &Arc<Self>) -> ClientGuard {
+        self.clients.fetch_add(1, Ordering::Relaxed);
+        ClientGuard(self.clone())
+    }
+}
+
+pub struct ClientGuard(Arc<StatusState>);
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        self.0.clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Serve status requests until the process exits.
+pub async fn serve(
+    addr: String,
+    state: Arc<StatusState>,
+    deadletter: Arc<Mutex<DeadLetter>>,
+    filter: Arc<Mutex<WriteFilter>>,
+    line_tx: mpsc::Sender<LineProtocol>,
+) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!("failed to bind status server on {addr}: {e}");
+            return;
+        }
+    };
+    tracing::info!("status server listening on {addr}");
+
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let state = state.clone();
+        let deadletter = deadletter.clone();
+        let filter = filter.clone();
+        let line_tx = line_tx.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let Ok(n) = stream.read(&mut buf).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+            let response = match path {
+                "/status" => {
+                    let staged = state
+                        .staged_config_hash
+                        .lock()
+                        .expect("staged hash mutex poisoned")
+                        .clone();
+                    let body = format!(
+                        "{{\"uptime_s\":{},\"clients\":{},\"git_hash\":{:?},\
+                         \"profile\":{:?},\"rustc\":{:?},\"config_hash\":{:?},\
+                         \"staged_config_hash\":{},\"hardware\":{:?}}}",
+                        state.start.elapsed().as_secs(),
+                        state.clients.load(Ordering::Relaxed),
+                        state.build.git_hash,
+                        state.build.profile,
+                        state.build.rustc,
+                        state.build.config_hash,
+                        staged.map_or("null".to_string(), |hash| format!("{hash:?}")),
+                        state.build.hardware,
+                    );
+                    http_response("200 OK", "application/json", &body)
+                }
+                "/metrics" => http_response(
+                    "200 OK",
+                    "text/plain; version=0.0.4",
+                    &METRICS.to_prometheus(),
+                ),
+                "/deadletter" => {
+                    let body = deadletter
+                        .lock()
+                        .expect("deadletter mutex poisoned")
+                        .to_json();
+                    http_response("200 OK", "application/json", &body)
+                }
+                "/deadletter/retry" => {
+                    let batches = deadletter
+                        .lock()
+                        .expect("deadletter mutex poisoned")
+                        .take_all();
+                    let mut requeued = 0usize;
+                    for batch in batches {
+                        for line in batch.lines {
+                            if line_tx.try_send(line).is_ok() {
+                                requeued += 1;
+                            }
+                        }
+                    }
+                    http_response(
+                        "200 OK",
+                        "application/json",
+                        &format!("{{\"requeued\":{requeued}}}"),
+                    )
+                }
+                "/filter" => {
+                    let body = filter.lock().expect("filter mutex poisoned").to_json();
+                    http_response("200 OK", "application/json", &body)
+                }
+                set if set.starts_with("/filter/set") => {
+                    // `allow`/`block` are comma separated pattern lists; an
+                    // omitted key leaves that list unchanged, an empty value
+                    // clears it.
+                    let query = set.split_once('?').map_or("", |(_, query)| query);
+                    let mut filter = filter.lock().expect("filter mutex poisoned");
+                    let (mut allow, mut block) = (None, None);
+                    for pair in query.split('&') {
+                        match pair.split_once('=') {
+                            Some(("allow", value)) => allow = Some(parse_patterns(value)),
+                            Some(("block", value)) => block = Some(parse_patterns(value)),
+                            _ => {}
+                        }
+                    }
+                    filter.update(allow, block);
+                    let body = filter.to_json();
+                    tracing::info!("writer filter updated: {body}");
+                    http_response("200 OK", "application/json", &body)
+                }
+                _ => http_response("404 Not Found", "text/plain", "not found"),
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// A comma separated pattern list; an empty value is an empty list.
+fn parse_patterns(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
@@ -0,0 +1,108 @@
+//! Valve travel time measurement and anomaly detection.
+//!
+//! Sticky valves are a recurring failure mode: the time between a command and
+//! the confirmed position creeping up is the early warning. Each actuation is
+//! timed from command to feedback confirmation, logged, and compared against
+//! a per-valve baseline.
+
+use std::time::{Duration, Instant};
+
+/// Result of one completed actuation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TravelMeasurement {
+    pub travel_ms: f64,
+    /// Travel time deviated from the baseline beyond the configured limit.
+    pub anomalous: bool,
+}
+
+/// Tracks travel time for a single valve.
+pub struct TravelMonitor {
+    /// Allowed fractional deviation from the baseline before an actuation is
+    /// flagged, e.g. 0.5 for ±50 %.
+    deviation_limit: f64,
+    /// Exponentially weighted baseline of healthy travel times.
+    baseline_ms: Option<f64>,
+    pending: Option<(bool, Instant)>,
+}
+
+/// Weight of the newest measurement in the baseline.
+const BASELINE_ALPHA: f64 = 0.2;
+
+impl TravelMonitor {
+    pub fn new(deviation_limit: f64) -> Self {
+        Self {
+            deviation_limit,
+            baseline_ms: None,
+            pending: None,
+        }
+    }
+
+    /// Record that the valve was just commanded to `target`.
+    pub fn command(&mut self, target: bool) {
+        self.pending = Some((target, Instant::now()));
+    }
+
+    /// Feed the current feedback state; returns a measurement when a pending
+    /// actuation is confirmed.
+    pub fn update(&mut self, feedback: bool) -> Option<TravelMeasurement> {
+        let (target, commanded_at) = self.pending?;
+        if feedback != target {
+            return None;
+        }
+        self.pending = None;
+        Some(self.complete(commanded_at.elapsed()))
+    }
+
+    fn complete(&mut self, travel: Duration) -> TravelMeasurement {
+        let travel_ms = travel.as_secs_f64() * 1e3;
+        let anomalous = self
+            .baseline_ms
+            .is_some_and(|base| (travel_ms - base).abs() > self.deviation_limit * base);
+
+        // Anomalous actuations do not drag the baseline along.
+        if !anomalous {
+            self.baseline_ms = Some(match self.baseline_ms {
+                Some(base) => base + BASELINE_ALPHA * (travel_ms - base),
+                None => travel_ms,
+            });
+        }
+
+        TravelMeasurement {
+            travel_ms,
+            anomalous,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_actuation_seeds_baseline() {
+        let mut monitor = TravelMonitor::new(0.5);
+        let m = monitor.complete(Duration::from_millis(100));
+        assert!(!m.anomalous);
+        assert_eq!(monitor.baseline_ms, Some(100.0));
+    }
+
+    #[test]
+    fn deviation_beyond_limit_is_flagged() {
+        let mut monitor = TravelMonitor::new(0.5);
+        monitor.complete(Duration::from_millis(100));
+        let m = monitor.complete(Duration::from_millis(200));
+        assert!(m.anomalous);
+        // Baseline is untouched by the anomalous actuation.
+        assert_eq!(monitor.baseline_ms, Some(100.0));
+    }
+
+    #[test]
+    fn update_waits_for_matching_feedback() {
+        let mut monitor = TravelMonitor::new(0.5);
+        monitor.command(true);
+        assert!(monitor.update(false).is_none());
+        assert!(monitor.update(true).is_some());
+        // Confirmed actuations are not reported twice.
+        assert!(monitor.update(true).is_none());
+    }
+}
@@ -0,0 +1,101 @@
+//! Ambient conditions from a local weather station.
+//!
+//! Ambient pressure and temperature feed the performance calculations, so
+//! they belong in the session data rather than in someone's notebook. The
+//! poller GETs the station's current-conditions endpoint on a slow cadence
+//! and maps the readings into the `ambient_pressure`/`ambient_temperature`
+//! channels; the frames enter the pipeline like supply telemetry does, so
+//! ambient data is logged with the same session stamps as the stand data it
+//! contextualizes.
+
+use crate::config::WeatherConfig;
+use crate::metrics::METRICS;
+use rctrl_api::prelude::*;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// The subset of the station's JSON answer we read. Stations differ in what
+/// they report; either reading may be absent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub struct Conditions {
+    /// Barometric pressure in millibar.
+    pub pressure_mbar: Option<f64>,
+    /// Air temperature in degrees Celsius.
+    pub temperature_c: Option<f64>,
+}
+
+/// Parse one current-conditions answer. Unknown keys are ignored, so a
+/// station reporting wind or humidity alongside does not need filtering.
+pub fn parse_conditions(body: &str) -> Result<Conditions, serde_json::Error> {
+    serde_json::from_str(body)
+}
+
+/// Poll the station until the pipeline channel closes.
+pub async fn task(config: WeatherConfig, data_tx: mpsc::Sender<Data>) {
+    // Bounded per-request so a wedged station cannot stall the poll loop;
+    // the station answers in milliseconds when it answers at all.
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("failed to build http client");
+    let start = Instant::now();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.poll_s));
+    loop {
+        interval.tick().await;
+        let conditions = match poll(&http, &config.url).await {
+            Ok(conditions) => conditions,
+            Err(e) => {
+                METRICS.incr("weather_poll_failures", 1);
+                tracing::warn!("weather poll failed: {e}");
+                continue;
+            }
+        };
+        if conditions == Conditions::default() {
+            // A station that answers but reports neither reading is most
+            // likely a schema mismatch, not weather worth logging.
+            METRICS.incr("weather_poll_failures", 1);
+            tracing::warn!("weather station answered without known readings");
+            continue;
+        }
+        let data = Data {
+            time: start.elapsed(),
+            ambient_pressure: conditions.pressure_mbar,
+            ambient_temperature: conditions.temperature_c,
+            ..Data::default()
+        };
+        if data_tx.send(data).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn poll(http: &reqwest::Client, url: &str) -> Result<Conditions, String> {
+    let response = http.get(url).send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("station answered {status}"));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    parse_conditions(&body).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditions_parse_with_extra_and_missing_keys() {
+        let conditions =
+            parse_conditions(r#"{"pressure_mbar": 1013.2, "humidity": 40, "wind_kmh": 3}"#)
+                .unwrap();
+        assert_eq!(conditions.pressure_mbar, Some(1013.2));
+        assert_eq!(conditions.temperature_c, None);
+
+        let conditions =
+            parse_conditions(r#"{"pressure_mbar": 1013.2, "temperature_c": -4.5}"#).unwrap();
+        assert_eq!(conditions.temperature_c, Some(-4.5));
+
+        assert!(parse_conditions("not json").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Writer-side measurement allowlist/blocklist.
+//!
+//! A noisy measurement sometimes has to be kept out of influx for a while —
+//! during sensor debugging, or when a misbehaving producer floods a bucket —
+//! without touching the producer. The filter drops lines by measurement name
+//! just before batching: a non-empty allowlist admits only matching
+//! measurements, and the blocklist removes matches from whatever the
+//! allowlist passed. Patterns are exact names or prefixes ending in `*`, the
+//! same syntax bucket routes use. The lists are seeded from `[filter]` config
+//! and hot-swappable through the status server, so a filter change never
+//! needs a restart mid-test.
+
+use crate::config::FilterConfig;
+use influx::LineProtocol;
+
+/// Measurement filter applied by the influx writer.
+pub struct WriteFilter {
+    allow: Vec<String>,
+    block: Vec<String>,
+    /// Lines dropped since startup, reported on the status endpoint.
+    filtered: u64,
+}
+
+impl WriteFilter {
+    pub fn new(config: FilterConfig) -> Self {
+        Self {
+            allow: config.allow,
+            block: config.block,
+            filtered: 0,
+        }
+    }
+
+    /// Replace the provided lists, leaving an omitted one unchanged; the
+    /// live counterpart of the `[filter]` config for debugging sessions.
+    pub fn update(&mut self, allow: Option<Vec<String>>, block: Option<Vec<String>>) {
+        if let Some(allow) = allow {
+            self.allow = allow;
+        }
+        if let Some(block) = block {
+            self.block = block;
+        }
+    }
+
+    /// Whether this line may be written. A dropped line is counted.
+    pub fn admit(&mut self, line: &LineProtocol) -> bool {
+        let measurement = line.0.split([',', ' ']).next().unwrap_or_default();
+        let admitted = (self.allow.is_empty() || matches_any(&self.allow, measurement))
+            && !matches_any(&self.block, measurement);
+        if !admitted {
+            self.filtered += 1;
+        }
+        admitted
+    }
+
+    /// Current lists and drop count for the status server.
+    pub fn to_json(&self) -> String {
+        let list = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| format!("\"{p}\""))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            "{{\"allow\":[{}],\"block\":[{}],\"filtered\":{}}}",
+            list(&self.allow),
+            list(&self.block),
+            self.filtered
+        )
+    }
+}
+
+/// Whether any pattern — exact name or `prefix*` — matches the measurement.
+fn matches_any(patterns: &[String], measurement: &str) -> bool {
+    patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => measurement.starts_with(prefix),
+        None => measurement == pattern,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(measurement: &str) -> LineProtocol {
+        LineProtocol(format!("{measurement} value=1 0"))
+    }
+
+    #[test]
+    fn blocklist_drops_and_allowlist_restricts() {
+        let mut filter = WriteFilter::new(FilterConfig {
+            allow: Vec::new(),
+            block: vec!["psu".to_string(), "fc_*".to_string()],
+        });
+        assert!(filter.admit(&line("pressure")));
+        assert!(!filter.admit(&line("psu")));
+        assert!(!filter.admit(&line("fc_pressure")));
+
+        // A non-empty allowlist admits only its matches; the blocklist still
+        // applies on top.
+        filter.update(
+            Some(vec!["pressure".to_string(), "fc_*".to_string()]),
+            Some(vec!["fc_altitude".to_string()]),
+        );
+        assert!(filter.admit(&line("pressure")));
+        assert!(!filter.admit(&line("temperature")));
+        assert!(filter.admit(&line("fc_pressure")));
+        assert!(!filter.admit(&line("fc_altitude")));
+
+        assert!(filter.to_json().contains("\"filtered\":4"));
+    }
+}
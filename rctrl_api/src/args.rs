@@ -0,0 +1,133 @@
+//! Validated command argument types.
+//!
+//! Commands carry their parameters in these newtypes so invariants are
+//! enforced once, at the wire boundary: a hand-written `Deserialize` rejects
+//! invalid values while decoding, and the only constructors are validating,
+//! so code deeper in the stack — the router, the sync loop — can take a
+//! [`Percent`] or [`SequenceName`] at face value.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Errors produced while constructing an argument type.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ArgError {
+    #[error("percentage {0} outside 0..=100")]
+    PercentOutOfRange(String),
+    #[error("invalid sequence name {0:?}: must be a plain file name")]
+    InvalidSequenceName(String),
+}
+
+/// A percentage in 0..=100, e.g. a commanded valve position.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize)]
+pub struct Percent(f64);
+
+impl Percent {
+    pub fn new(value: f64) -> Result<Self, ArgError> {
+        if value.is_finite() && (0.0..=100.0).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(ArgError::PercentOutOfRange(value.to_string()))
+        }
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// The percentage as a fraction in 0..=1.
+    pub fn fraction(self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} %", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Percent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        Self::new(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Name of a stored command sequence file.
+///
+/// Restricted to a plain file name — no path separators, no leading dot — so
+/// a remote client can never name a file outside the sequence directory.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct SequenceName(String);
+
+impl SequenceName {
+    pub fn new(name: impl Into<String>) -> Result<Self, ArgError> {
+        let name = name.into();
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if valid {
+            Ok(Self(name))
+        } else {
+            Err(ArgError::InvalidSequenceName(name))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SequenceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SequenceName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::new(name).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_accepts_only_the_closed_range() {
+        assert_eq!(Percent::new(0.0).unwrap().value(), 0.0);
+        assert_eq!(Percent::new(100.0).unwrap().fraction(), 1.0);
+        assert!(Percent::new(-0.1).is_err());
+        assert!(Percent::new(100.1).is_err());
+        assert!(Percent::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected_while_decoding() {
+        // Valid values round-trip through the wire encoding.
+        let bytes = bincode::serialize(&Percent::new(42.0).unwrap()).unwrap();
+        let percent: Percent = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(percent.value(), 42.0);
+
+        // An out-of-range raw value fails at the decode boundary.
+        let bytes = bincode::serialize(&250.0f64).unwrap();
+        assert!(bincode::deserialize::<Percent>(&bytes).is_err());
+
+        let bytes = bincode::serialize("../etc/shadow").unwrap();
+        assert!(bincode::deserialize::<SequenceName>(&bytes).is_err());
+    }
+
+    #[test]
+    fn sequence_names_are_plain_file_names() {
+        assert!(SequenceName::new("hotfire_3.seq").is_ok());
+        assert!(SequenceName::new("").is_err());
+        assert!(SequenceName::new(".hidden").is_err());
+        assert!(SequenceName::new("a/b").is_err());
+        assert!(SequenceName::new("a b").is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Telemetry channels and the per-iteration data frame.
+
+use influx::{LineProtocol, ToLineProtocolEntries};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Identifier of a logical telemetry channel.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChannelId(pub String);
+
+impl fmt::Display for ChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for ChannelId {
+    fn from(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+/// One frame of telemetry produced by the sync loop.
+///
+/// Every member besides `time` is optional: a frame carries only the channels
+/// that were sampled in the iteration that produced it.
+/// One command scheduled for a future mission time; see `CmdEnum::At`.
+///
+/// Carried by frames when the sync loop's schedule changes and listed in the
+/// state snapshot, so clients can show (and cancel) what is pending.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledCmd {
+    /// Server-assigned id, used to cancel the entry.
+    pub id: u64,
+    /// Target mission time in seconds.
+    pub at_s: f64,
+    /// Short name of the scheduled command, for display and audit.
+    pub cmd: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Mission time, measured from sync loop start.
+    pub time: Duration,
+    /// Sequence number assigned by the sync loop, incremented every
+    /// iteration. Consumers use it to detect dropped frames.
+    pub seq: u64,
+    /// Set by the pipeline on the first frame after a detected gap, so
+    /// downstream consumers do not silently interpolate across the hole.
+    pub gap: bool,
+    /// Channels whose value failed validation this frame, set by the
+    /// pipeline; see [`crate::validate`]. The values are carried anyway so
+    /// consumers can show what the instrument reported, but redlines and
+    /// aggregation must not act on them.
+    pub invalid: Vec<String>,
+    /// Wall-clock acquisition time in nanoseconds since the Unix epoch,
+    /// stamped by the sync loop when the frame is sampled. Consumers subtract
+    /// it from their own clock to measure data-path latency; with clients on
+    /// other machines the clock offset biases the result.
+    pub wall_ns: Option<u128>,
+    /// Feed system pressure in bar.
+    pub pressure: Option<f64>,
+    /// Acquisition time of the pressure sample (mission time). Channels read
+    /// sequentially on a shared ADC complete at different instants; the skew
+    /// from `time` is carried into the influx timestamp of this channel.
+    pub pressure_at: Option<Duration>,
+    /// Full scale range in volts of the ADC conversion behind the pressure
+    /// sample, recorded when gain auto-ranging is active so stored data
+    /// carries its quantization context.
+    pub pressure_fsr: Option<f64>,
+    /// Feed system temperature in degrees Celsius.
+    pub temperature: Option<f64>,
+    /// Acquisition time of the temperature sample (mission time).
+    pub temperature_at: Option<Duration>,
+    /// Commanded valve state.
+    pub valve: Option<bool>,
+    /// Confirmed valve position from feedback.
+    pub valve_feedback: Option<bool>,
+    /// Measured command-to-confirmation travel time of the actuation that
+    /// completed this iteration.
+    pub valve_travel_ms: Option<f64>,
+    /// Igniter loop current from the shunt sense channel, in amperes.
+    pub igniter_current: Option<f64>,
+    /// Acquisition time of the igniter current sample (mission time).
+    pub igniter_current_at: Option<Duration>,
+    /// Chamber pressure reported by the flight computer, in bar.
+    pub fc_pressure: Option<f64>,
+    /// Altitude reported by the flight computer, in metres.
+    pub fc_altitude: Option<f64>,
+    /// Ambient (atmospheric) pressure from the weather poller, in millibar.
+    pub ambient_pressure: Option<f64>,
+    /// Ambient air temperature from the weather poller, in degrees Celsius.
+    pub ambient_temperature: Option<f64>,
+    /// Measured instrumentation supply output voltage, in volts.
+    pub psu_volts: Option<f64>,
+    /// Measured instrumentation supply output current, in amperes.
+    pub psu_amps: Option<f64>,
+    /// Free-form log message attached to this frame.
+    pub log_msg: Option<String>,
+    /// Names of automated response rules that fired this iteration. Actions
+    /// local to the sync loop are already applied when the frame is emitted;
+    /// the pipeline audits each firing and executes the rest.
+    pub rules_fired: Vec<String>,
+    /// The sync loop's scheduled commands, attached on the iterations where
+    /// the schedule changed; `None` means unchanged since the last frame.
+    pub schedule: Option<Vec<ScheduledCmd>>,
+    /// Scheduled commands executed this iteration, with the mission time at
+    /// which each actually ran; the pipeline audits and logs them.
+    pub schedule_fired: Vec<(ScheduledCmd, f64)>,
+}
+
+impl Data {
+    /// Look up a sampled value by its telemetry channel name, as used in
+    /// configuration and quality reports.
+    pub fn channel_value(&self, channel: &str) -> Option<f64> {
+        match channel {
+            "pressure" => self.pressure,
+            "temperature" => self.temperature,
+            "igniter_current" => self.igniter_current,
+            "fc_pressure" => self.fc_pressure,
+            "fc_altitude" => self.fc_altitude,
+            "ambient_pressure" => self.ambient_pressure,
+            "ambient_temperature" => self.ambient_temperature,
+            "psu_volts" => self.psu_volts,
+            "psu_amps" => self.psu_amps,
+            _ => None,
+        }
+    }
+
+    /// Look up a discrete state by its telemetry channel name, as referenced
+    /// by actuator interlock rules.
+    pub fn channel_state(&self, channel: &str) -> Option<bool> {
+        match channel {
+            "valve" => self.valve,
+            "valve_feedback" => self.valve_feedback,
+            _ => None,
+        }
+    }
+
+    /// Whether a channel's value failed validation this frame.
+    pub fn channel_invalid(&self, channel: &str) -> bool {
+        self.invalid.iter().any(|c| c == channel)
+    }
+
+    /// The influx timestamp for a sample acquired at `sample_at` mission
+    /// time: `base` shifted by the sample's skew from the frame timestamp.
+    fn timestamp_for(&self, base: u128, sample_at: Option<Duration>) -> u128 {
+        let Some(sample_at) = sample_at else {
+            return base;
+        };
+        let skew = sample_at.as_nanos() as i128 - self.time.as_nanos() as i128;
+        (base as i128 + skew).max(0) as u128
+    }
+}
+
+impl ToLineProtocolEntries for Data {
+    fn to_line_protocol_entries_at(&self, timestamp: u128) -> Vec<LineProtocol> {
+        // The first sample after a gap is annotated so holes are visible in
+        // the stored data, and invalid samples carry their tag into storage
+        // so queries can exclude them.
+        let gap = if self.gap { ",gap=true" } else { "" };
+        let flags = |channels: &[&str]| {
+            let invalid = channels.iter().any(|c| self.channel_invalid(c));
+            format!("{}{}", gap, if invalid { ",invalid=true" } else { "" })
+        };
+
+        let mut entries = Vec::new();
+        if let Some(pressure) = self.pressure {
+            let fsr = match self.pressure_fsr {
+                Some(fsr) => format!(",fsr={}", fsr),
+                None => String::new(),
+            };
+            entries.push(LineProtocol(format!(
+                "pressure value={}{}{} {}",
+                pressure,
+                fsr,
+                flags(&["pressure"]),
+                self.timestamp_for(timestamp, self.pressure_at)
+            )));
+        }
+        if let Some(temperature) = self.temperature {
+            entries.push(LineProtocol(format!(
+                "temperature value={}{} {}",
+                temperature,
+                flags(&["temperature"]),
+                self.timestamp_for(timestamp, self.temperature_at)
+            )));
+        }
+        if let Some(igniter_current) = self.igniter_current {
+            entries.push(LineProtocol(format!(
+                "igniter_current value={}{} {}",
+                igniter_current,
+                flags(&["igniter_current"]),
+                self.timestamp_for(timestamp, self.igniter_current_at)
+            )));
+        }
+        if let Some(valve) = self.valve {
+            let travel = match self.valve_travel_ms {
+                Some(travel_ms) => format!(",travel_ms={}", travel_ms),
+                None => String::new(),
+            };
+            entries.push(LineProtocol(format!(
+                "valve state={}{}{} {}",
+                valve, travel, gap, timestamp
+            )));
+        }
+        if let Some(fc_pressure) = self.fc_pressure {
+            entries.push(LineProtocol(format!(
+                "fc_pressure value={}{} {}",
+                fc_pressure,
+                flags(&["fc_pressure"]),
+                timestamp
+            )));
+        }
+        if let Some(fc_altitude) = self.fc_altitude {
+            entries.push(LineProtocol(format!(
+                "fc_altitude value={}{} {}",
+                fc_altitude,
+                flags(&["fc_altitude"]),
+                timestamp
+            )));
+        }
+        if let Some(ambient_pressure) = self.ambient_pressure {
+            entries.push(LineProtocol(format!(
+                "ambient_pressure value={}{} {}",
+                ambient_pressure,
+                flags(&["ambient_pressure"]),
+                timestamp
+            )));
+        }
+        if let Some(ambient_temperature) = self.ambient_temperature {
+            entries.push(LineProtocol(format!(
+                "ambient_temperature value={}{} {}",
+                ambient_temperature,
+                flags(&["ambient_temperature"]),
+                timestamp
+            )));
+        }
+        if let (Some(volts), Some(amps)) = (self.psu_volts, self.psu_amps) {
+            entries.push(LineProtocol(format!(
+                "psu volts={},amps={}{} {}",
+                volts,
+                amps,
+                flags(&["psu_volts", "psu_amps"]),
+                timestamp
+            )));
+        }
+        // log_msg is not written to influx: it is a transient annotation for
+        // live consumers, recorded in the GUI session log instead.
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_frame_produces_no_entries() {
+        let data = Data::default();
+        assert!(data.to_line_protocol_entries().is_empty());
+    }
+
+    #[test]
+    fn populated_members_each_produce_an_entry() {
+        let data = Data {
+            pressure: Some(12.5),
+            valve: Some(true),
+            ..Data::default()
+        };
+        let entries = data.to_line_protocol_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].0.starts_with("pressure value=12.5 "));
+        assert!(entries[1].0.starts_with("valve state=true "));
+    }
+
+    #[test]
+    fn gap_flag_annotates_every_entry() {
+        let data = Data {
+            pressure: Some(1.0),
+            gap: true,
+            ..Data::default()
+        };
+        let entries = data.to_line_protocol_entries_at(0);
+        assert_eq!(entries[0].0, "pressure value=1,gap=true 0");
+    }
+
+    #[test]
+    fn invalid_channels_carry_their_tag_into_storage() {
+        let data = Data {
+            pressure: Some(9999.0),
+            temperature: Some(20.0),
+            invalid: vec!["pressure".to_string()],
+            ..Data::default()
+        };
+        let entries = data.to_line_protocol_entries_at(0);
+        assert_eq!(entries[0].0, "pressure value=9999,invalid=true 0");
+        assert_eq!(entries[1].0, "temperature value=20 0");
+    }
+
+    #[test]
+    fn acquisition_times_shift_channel_timestamps() {
+        let data = Data {
+            time: Duration::from_millis(10),
+            pressure: Some(1.0),
+            // Acquired 2 ms before the frame timestamp.
+            pressure_at: Some(Duration::from_millis(8)),
+            temperature: Some(20.0),
+            // No acquisition time recorded: uses the frame timestamp.
+            ..Data::default()
+        };
+        let entries = data.to_line_protocol_entries_at(1_000_000_000);
+        assert_eq!(entries[0].0, "pressure value=1 998000000");
+        assert_eq!(entries[1].0, "temperature value=20 1000000000");
+    }
+}
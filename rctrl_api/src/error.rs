@@ -0,0 +1,118 @@
+//! Categorized, user-facing errors.
+//!
+//! Internal error types (`thiserror` enums, I/O errors) are written for the
+//! developer reading a log; what crosses the wire to a client has so far
+//! been their `Debug`/`Display` text, which operators cannot act on or look
+//! up. A [`UserError`] is the operator-facing form: a category saying which
+//! part of the system failed, a stable short code that documentation and
+//! checklists can reference, and a human message. Servers build them at the
+//! point where an internal error becomes an answer to a person.
+//!
+//! Codes are never reused or renumbered — they appear in operator
+//! checklists and in stored audit data — so new errors take new codes.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Which part of the system an error belongs to.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+    /// Sensors, actuators, buses and the instruments behind them.
+    Hardware,
+    /// Malformed or invalid requests and wire-format trouble.
+    Protocol,
+    /// The influx write path and everything else that stores data.
+    Logging,
+    /// Refusals that exist to keep the stand safe: permissions, interlocks,
+    /// the two-person gate.
+    Safety,
+    /// Configuration that is missing, contradictory or does not match the
+    /// request.
+    Config,
+}
+
+impl ErrorCategory {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Hardware => "hardware",
+            Self::Protocol => "protocol",
+            Self::Logging => "logging",
+            Self::Safety => "safety",
+            Self::Config => "config",
+        }
+    }
+}
+
+/// A categorized error fit for showing to an operator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserError {
+    pub category: ErrorCategory,
+    /// Stable short code, e.g. `SAF-002`; see the module docs.
+    pub code: String,
+    /// Human explanation, written for the operator rather than the log.
+    pub message: String,
+}
+
+impl UserError {
+    pub fn new(
+        category: ErrorCategory,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            category,
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn hardware(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Hardware, code, message)
+    }
+
+    pub fn protocol(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Protocol, code, message)
+    }
+
+    pub fn logging(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Logging, code, message)
+    }
+
+    pub fn safety(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Safety, code, message)
+    }
+
+    pub fn config(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Config, code, message)
+    }
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}]: {}",
+            self.category.label(),
+            self.code,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for UserError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_reads_category_code_then_message() {
+        let error = UserError::safety("SAF-002", "valve open refused: interlock active");
+        assert_eq!(
+            error.to_string(),
+            "safety [SAF-002]: valve open refused: interlock active"
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Shared protocol and telemetry types for the rCTRL ground control stack.
+//!
+//! Both `rctrl` and `rctrl_gui` depend on this crate; it defines the wire
+//! messages exchanged over the remote connection ([`messages`]), the telemetry
+//! frame and channel identifiers ([`channels`]), validated command argument
+//! types ([`args`]), sensor reading types ([`sensor`]), value
+//! validation ([`validate`]) and the encoding of messages on the wire
+//! ([`protocol`]).
+//!
+//! Downstream code should import from [`prelude`] rather than from individual
+//! submodules; public enums are `#[non_exhaustive]` so new variants can be
+//! added without breaking the GUI.
+
+pub mod args;
+pub mod channels;
+pub mod error;
+pub mod messages;
+pub mod prelude;
+pub mod protocol;
+pub mod sensor;
+pub mod validate;
+
+/// Legacy import path, kept so existing `rctrl_api::remote` imports continue
+/// to work. New code should use [`messages`] or [`prelude`].
+pub mod remote {
+    pub use crate::messages::*;
+}
+
+/// Legacy import path, kept so existing `rctrl_api::dataframe` imports
+/// continue to work. New code should use [`channels`] or [`prelude`].
+pub mod dataframe {
+    pub use crate::channels::Data;
+}
@@ -0,0 +1,404 @@
+//! Wire messages exchanged between rctrl and its clients.
+
+use crate::channels::{ChannelId, Data, ScheduledCmd};
+use crate::error::UserError;
+use serde::{Deserialize, Serialize};
+
+/// Client role negotiated when a connection is established.
+///
+/// Observers receive telemetry but may not issue commands.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Observer,
+    Operator,
+}
+
+/// Command categories used by the permission matrix.
+///
+/// Every command belongs to exactly one category; roles are granted
+/// categories, not individual commands.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CmdCategory {
+    Valves,
+    /// Instrumentation power supply control.
+    Power,
+    Sequencer,
+    Tare,
+    Annotations,
+    ConfigReload,
+    Capture,
+    /// Administrative maintenance of the server itself, such as staging a
+    /// new config file; see [`CmdEnum::StageConfig`].
+    Admin,
+}
+
+/// Runtime-adjustable parameters, changed with [`CmdEnum::SetParam`].
+///
+/// These are the safe subset of settings that may change while the stand is
+/// live; everything else still requires a config edit and restart.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Param {
+    /// Send every Nth raw frame to a client's stream.
+    StreamDivisor,
+    /// Seconds of telemetry retained by GUI plots.
+    PlotRetentionS,
+    /// Raw frames averaged into one logged frame.
+    AggregationWindow,
+}
+
+/// Commands a client can issue to rctrl.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CmdEnum {
+    ValveOpen,
+    ValveClose,
+    /// Open a burst capture window: log affected channels at full rate for
+    /// the configured pre/post window around now.
+    TriggerBurst,
+    /// Change a runtime parameter. The server validates the value and echoes
+    /// the applied result back as [`WsMessage::ParamApplied`].
+    SetParam { param: Param, value: f64 },
+    /// Sample every channel for the given number of seconds and report noise
+    /// floor, ambient offset and dropout rate against the configured
+    /// per-channel expectations as [`WsMessage::QualityReport`].
+    DataQualityCheck { duration_s: u16 },
+    /// Scan the I2C bus and report found/missing/unexpected devices against
+    /// the configured device list. The report arrives as the `log_msg` of a
+    /// subsequent telemetry frame.
+    DiscoverHardware,
+    /// Switch the instrumentation power supply output on or off.
+    PsuOutput { enable: bool },
+    /// Set the instrumentation power supply voltage setpoint and current
+    /// limit.
+    PsuLimits { volts: f64, amps: f64 },
+    /// Append a free-text entry to the operator shift log. The stored note
+    /// is echoed back as [`WsMessage::NoteAdded`].
+    AddNote { text: String },
+    /// Override the logged rate of one channel for troubleshooting: every
+    /// `rate`-th raw frame of `channel` is logged raw, bypassing aggregation.
+    /// `rate = 0` clears the override; the server clamps `rate` to its
+    /// configured bounds and auto-reverts after a timeout. Active overrides
+    /// are echoed in [`StateSnapshot::log_rate_overrides`].
+    SetChannelLogRate { channel: ChannelId, rate: u32 },
+    /// Enable or disable one automated response rule by its configured name.
+    /// Rules themselves come from the config file; only their armed state is
+    /// runtime-adjustable.
+    SetRuleEnabled { rule: String, enabled: bool },
+    /// Read the server's on-disk history cache: frames of the current session
+    /// whose mission time falls in `start_s..=stop_s`, decimated per channel
+    /// to at most `max_points`. Answered with [`WsMessage::HistoryResult`].
+    QueryHistory {
+        start_s: f64,
+        stop_s: f64,
+        channels: Vec<ChannelId>,
+        max_points: u32,
+    },
+    /// Execute `inner` when the mission clock reaches `mission_time_s`. The
+    /// sync loop holds the schedule; pending entries are listed in
+    /// [`StateSnapshot::scheduled`] and cancellable with
+    /// [`Self::CancelScheduled`]. Scheduling requires the permission of the
+    /// inner command, and hazardous inner commands still pass the two-person
+    /// gate when the schedule is created.
+    At {
+        mission_time_s: f64,
+        inner: Box<CmdEnum>,
+    },
+    /// Remove a scheduled command by its server-assigned id.
+    CancelScheduled { id: u64 },
+    /// Upload a new config file to be staged on the server and applied on
+    /// its next restart — never mid-session. Beyond the permission matrix
+    /// the upload must present the server's admin token, is only accepted
+    /// while the stand is safe (valve closed), and the contents must pass
+    /// the same validation the server applies at startup. Success is
+    /// acknowledged with [`WsMessage::ConfigStaged`]; the staged hash also
+    /// appears in [`StateSnapshot::staged_config_hash`].
+    StageConfig {
+        /// Must match the token in the server's environment; compared
+        /// server-side and never logged.
+        admin_token: String,
+        /// The full TOML text of the new config file.
+        contents: String,
+    },
+}
+
+impl CmdEnum {
+    /// The permission category this command belongs to.
+    pub fn category(&self) -> CmdCategory {
+        match self {
+            CmdEnum::ValveOpen | CmdEnum::ValveClose => CmdCategory::Valves,
+            CmdEnum::TriggerBurst => CmdCategory::Capture,
+            CmdEnum::SetParam { .. } | CmdEnum::SetChannelLogRate { .. } => {
+                CmdCategory::ConfigReload
+            }
+            CmdEnum::DataQualityCheck { .. }
+            | CmdEnum::DiscoverHardware
+            | CmdEnum::SetRuleEnabled { .. } => CmdCategory::Sequencer,
+            CmdEnum::PsuOutput { .. } | CmdEnum::PsuLimits { .. } => CmdCategory::Power,
+            CmdEnum::AddNote { .. } => CmdCategory::Annotations,
+            CmdEnum::QueryHistory { .. } => CmdCategory::Capture,
+            // Scheduling is only as privileged as what it schedules.
+            CmdEnum::At { inner, .. } => inner.category(),
+            CmdEnum::CancelScheduled { .. } => CmdCategory::Sequencer,
+            CmdEnum::StageConfig { .. } => CmdCategory::Admin,
+        }
+    }
+}
+
+/// One operator shift log entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    /// Server-assigned, monotonically increasing within a log.
+    pub id: u64,
+    /// Wall clock time the note was recorded, milliseconds since the epoch.
+    pub unix_ms: u64,
+    /// The peer that wrote the note.
+    pub author: String,
+    pub text: String,
+}
+
+/// Severity of a [`LogRecord`], ordered least to most severe so clients can
+/// filter with a comparison.
+#[non_exhaustive]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Fixed-width label for log views.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+/// One structured log event from the server, carried as [`WsMessage::Log`].
+///
+/// These mirror the WARN/ERROR events the server writes to its `app_logs`
+/// measurement — same rate limit, same content — so what an operator sees in
+/// a client log view is exactly what the stored record will show.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// Wall clock time of the event, milliseconds since the epoch.
+    pub unix_ms: u64,
+    /// The module that emitted the event (the tracing target).
+    pub source: String,
+    pub message: String,
+}
+
+/// One channel of a [`CmdEnum::QueryHistory`] answer: `(mission seconds,
+/// value)` points, already decimated to the requested budget.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistorySeries {
+    pub channel: ChannelId,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Outcome of a data quality check, per channel and overall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum QualityVerdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Measured quality of one channel during a [`CmdEnum::DataQualityCheck`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChannelQuality {
+    pub channel: ChannelId,
+    /// Standard deviation of the samples.
+    pub noise_floor: f64,
+    /// Mean minus the expected ambient value.
+    pub offset: f64,
+    /// Fraction of frames in which the channel carried no sample.
+    pub dropout_rate: f64,
+    pub verdict: QualityVerdict,
+}
+
+/// Report produced by a data quality check.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityReport {
+    pub channels: Vec<ChannelQuality>,
+}
+
+impl QualityReport {
+    /// The worst verdict across all channels.
+    pub fn verdict(&self) -> QualityVerdict {
+        self.channels
+            .iter()
+            .map(|c| c.verdict)
+            .max()
+            .unwrap_or(QualityVerdict::Fail)
+    }
+}
+
+/// Tabular result of an ad-hoc Flux query, forwarded from influx by rctrl so
+/// clients never hold influx credentials.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FluxTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A command envelope as sent over the remote connection.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cmd {
+    pub cmd: CmdEnum,
+}
+
+/// Build and configuration provenance of the running server.
+///
+/// Written to influx as the `rctrl_run` startup banner and carried in the
+/// [`StateSnapshot`], so both stored datasets and connected clients can name
+/// the exact build that produced them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Abbreviated git commit hash of the build; `"unknown"` when the build
+    /// happened outside a checkout.
+    pub git_hash: String,
+    /// Cargo build profile (`debug`/`release`).
+    pub profile: String,
+    /// Version of the compiler that built the binary.
+    pub rustc: String,
+    /// Hash of the loaded config file; `"absent"` when defaults are in use.
+    pub config_hash: String,
+    /// One-line summary of the configured hardware.
+    pub hardware: String,
+}
+
+/// Full state snapshot sent to a client when its connection is established.
+///
+/// After a reconnect, commanded/actual states are unknown to the client until
+/// the next change; the snapshot is applied before incremental updates resume
+/// so panels are never stale or blank after a blip.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Most recent telemetry frame.
+    pub last_data: Option<Data>,
+    /// Data gaps seen since startup.
+    pub gaps: u64,
+    /// Current values of all runtime parameters.
+    pub params: Vec<(Param, f64)>,
+    /// The sync loop period, for client-side latency estimates.
+    pub loop_period: std::time::Duration,
+    /// Operator shift log, oldest first, so later-connecting clients see the
+    /// handover notes of the whole session.
+    pub notes: Vec<Note>,
+    /// Active per-channel log rate overrides, as `(channel, raw frames per
+    /// logged point)`; see [`CmdEnum::SetChannelLogRate`].
+    pub log_rate_overrides: Vec<(ChannelId, u32)>,
+    /// Provenance of the server build, for identifying which software
+    /// produced the session's data.
+    pub build: BuildInfo,
+    /// Commands scheduled for future mission times, soonest first.
+    pub scheduled: Vec<ScheduledCmd>,
+    /// Hash of a staged config file awaiting the next restart, `None` when
+    /// nothing is staged; see [`CmdEnum::StageConfig`]. The hash of the
+    /// active config is in [`BuildInfo::config_hash`].
+    pub staged_config_hash: Option<String>,
+}
+
+/// Live state of the two-person confirmation gate, broadcast to every
+/// client whenever it changes so all consoles see what is awaiting a second
+/// operator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmationState {
+    /// The command awaiting confirmation, as its audit action string; `None`
+    /// when nothing is pending.
+    pub pending: Option<String>,
+    /// Seconds until the pending proposal expires.
+    pub expires_in_s: f64,
+}
+
+/// Report sent back to a client whose command was not executed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CmdRejection {
+    pub cmd: CmdEnum,
+    /// Why, categorized and coded; clients show its [`Display`] form.
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub error: UserError,
+}
+
+/// Periodic liveness beacon broadcast to every client.
+///
+/// Telemetry can legitimately go quiet — stream decimation, a paused
+/// source — so a passive last-received time cannot distinguish a silent
+/// backend from a dead link. The heartbeat arrives once per second
+/// regardless of data flow; a client that stops seeing it flags the
+/// connection as stale.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// Beats since server startup.
+    pub seq: u64,
+    /// Server wall clock at the beat, milliseconds since the Unix epoch.
+    pub unix_ms: u64,
+}
+
+/// Top level message envelope exchanged over the WebSocket.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+// The data variant dominates the enum size, but it is also the per-frame hot
+// path; boxing it would trade an allocation per frame for a smaller envelope.
+#[allow(clippy::large_enum_variant)]
+pub enum WsMessage {
+    Data(Data),
+    Cmd(Cmd),
+    CmdRejection(CmdRejection),
+    /// Boxed: the snapshot dwarfs every other variant, and it is sent once
+    /// per connection rather than per frame.
+    Snapshot(Box<StateSnapshot>),
+    /// A runtime parameter change was accepted; carries the applied value.
+    ParamApplied { param: Param, value: f64 },
+    /// Result of a completed data quality check.
+    QualityReport(QualityReport),
+    /// Round-trip probe; the server echoes the nonce back as [`Self::Pong`].
+    /// Clients use the measured round-trip for their abort latency estimate.
+    Ping(u64),
+    Pong(u64),
+    /// An ad-hoc Flux query to run against the influx backend.
+    FluxQuery(String),
+    /// Answer to a [`Self::FluxQuery`]; the error side carries the influx
+    /// rejection text.
+    FluxResult(Result<FluxTable, String>),
+    /// A shift log note was stored, echoed to the issuing client.
+    NoteAdded(Note),
+    /// Answer to a [`CmdEnum::QueryHistory`]; the error side carries the
+    /// reason the cache could not be read.
+    HistoryResult(Result<Vec<HistorySeries>, String>),
+    /// The two-person confirmation gate changed state.
+    Confirmation(ConfirmationState),
+    /// A camera snapshot was stored for an event; `reference` is the image
+    /// URL or path the recorder returned, shown as a link in client log
+    /// views.
+    CameraEvent {
+        kind: String,
+        reference: String,
+        reason: String,
+    },
+    /// A WARN/ERROR event from the server's tracing output, for client log
+    /// views.
+    Log(LogRecord),
+    /// Once-per-second liveness beacon; see [`Heartbeat`].
+    Heartbeat(Heartbeat),
+    /// A [`CmdEnum::StageConfig`] upload was validated and staged; carries
+    /// the staged file's hash so the operator can read it back against the
+    /// copy they uploaded.
+    ConfigStaged { hash: String },
+}
@@ -0,0 +1,18 @@
+//! Convenience re-exports of the types most downstream code needs.
+//!
+//! ```
+//! use rctrl_api::prelude::*;
+//! ```
+
+pub use crate::args::{ArgError, Percent, SequenceName};
+pub use crate::channels::{ChannelId, Data, ScheduledCmd};
+pub use crate::error::{ErrorCategory, UserError};
+pub use crate::messages::{
+    BuildInfo, ChannelQuality, Cmd, CmdCategory, CmdEnum, CmdRejection, ConfirmationState,
+    FluxTable,
+    Heartbeat, HistorySeries, LogLevel, LogRecord, Note, Param, QualityReport, QualityVerdict, Role,
+    StateSnapshot, WsMessage,
+};
+pub use crate::protocol::{decode, encode, ProtocolError, PROTOCOL_VERSION};
+pub use crate::sensor::{Current, Pressure, Temperature};
+pub use crate::validate::{Validate, Violation};
@@ -0,0 +1,41 @@
+//! Encoding of [`WsMessage`] on the wire.
+//!
+//! Messages are serialized with bincode; both rctrl and the GUI must go
+//! through [`encode`]/[`decode`] so the wire format is defined in one place.
+
+use crate::messages::WsMessage;
+
+/// Protocol version, bumped whenever the wire format of messages changes.
+pub const PROTOCOL_VERSION: u32 = 15;
+
+/// Errors produced while encoding or decoding protocol messages.
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    #[error("bincode: {0}")]
+    Bincode(#[from] bincode::Error),
+}
+
+/// Encode a message for transmission.
+pub fn encode(msg: &WsMessage) -> Result<Vec<u8>, ProtocolError> {
+    Ok(bincode::serialize(msg)?)
+}
+
+/// Decode a received message.
+pub fn decode(bytes: &[u8]) -> Result<WsMessage, ProtocolError> {
+    Ok(bincode::deserialize(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{Cmd, CmdEnum};
+
+    #[test]
+    fn roundtrip_cmd() {
+        let msg = WsMessage::Cmd(Cmd {
+            cmd: CmdEnum::ValveOpen,
+        });
+        let decoded = decode(&encode(&msg).unwrap()).unwrap();
+        assert_eq!(decoded, msg);
+    }
+}
@@ -0,0 +1,40 @@
+//! Individual sensor reading types.
+
+use influx::ToLineProtocol;
+use serde::{Deserialize, Serialize};
+
+/// A pressure reading in bar.
+#[derive(ToLineProtocol, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[influx(measurement = "pressure")]
+pub struct Pressure {
+    #[influx(unit = "bar", description = "Feed system pressure")]
+    pub value: f64,
+}
+
+/// A temperature reading in degrees Celsius.
+#[derive(ToLineProtocol, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[influx(measurement = "temperature")]
+pub struct Temperature {
+    #[influx(unit = "°C", description = "Feed system temperature")]
+    pub value: f64,
+}
+
+/// A current reading in amperes.
+#[derive(ToLineProtocol, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[influx(measurement = "current")]
+pub struct Current {
+    #[influx(unit = "A", description = "Igniter loop current")]
+    pub value: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use influx::ToLineProtocol;
+
+    #[test]
+    fn pressure_line_protocol_shape() {
+        let line = Pressure { value: 20.5 }.to_line_protocol();
+        assert!(line.0.starts_with("pressure value=20.5 "));
+    }
+}
@@ -0,0 +1,178 @@
+//! Channel-level validation of telemetry values.
+//!
+//! A transducer that fails open, a counter that jumps backwards or a raw
+//! register word that decodes to no known state all produce values that are
+//! syntactically fine and physically meaningless. Validators catch these
+//! before redlines, clients or influx consume them; invalid samples are
+//! tagged ([`Data::invalid`](crate::channels::Data::invalid)), not dropped,
+//! so operators can still see what the instrument reported.
+//!
+//! The bounds used here are plausibility limits — the physical range the
+//! instrument could conceivably report — not redline limits, which are
+//! operational and live in configuration.
+
+use crate::channels::Data;
+
+/// One failed validation check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// The telemetry channel the value belongs to.
+    pub channel: &'static str,
+    /// Human-readable description of what the value violated.
+    pub reason: String,
+}
+
+/// Telemetry that can check its own values.
+///
+/// `State` carries whatever the checks need between consecutive frames, such
+/// as the last value of a monotonic counter; stateless implementations use
+/// `()`.
+pub trait Validate {
+    type State: Default;
+
+    /// Check every value against its rules; returns all violations found so
+    /// one bad frame reports every problem at once.
+    fn validate(&self, state: &mut Self::State) -> Vec<Violation>;
+}
+
+/// Check that a value is finite and within its plausibility bounds.
+pub fn check_range(
+    channel: &'static str,
+    value: f64,
+    min: f64,
+    max: f64,
+) -> Option<Violation> {
+    if !value.is_finite() {
+        return Some(Violation {
+            channel,
+            reason: format!("non-finite value {value}"),
+        });
+    }
+    if value < min || value > max {
+        return Some(Violation {
+            channel,
+            reason: format!("{value} outside plausible range {min}..={max}"),
+        });
+    }
+    None
+}
+
+/// Check that a counter never decreases. `last` is updated to the observed
+/// value either way, so a single step backwards is reported once rather than
+/// on every following frame.
+pub fn check_monotonic(
+    channel: &'static str,
+    last: &mut Option<u128>,
+    value: u128,
+) -> Option<Violation> {
+    let previous = last.replace(value);
+    match previous {
+        Some(previous) if value < previous => Some(Violation {
+            channel,
+            reason: format!("counter stepped backwards: {previous} -> {value}"),
+        }),
+        _ => None,
+    }
+}
+
+/// Check that a raw discrete word is one of the listed valid states.
+///
+/// Enum-typed channels are valid by construction once decoded; this is for
+/// driver code that reads raw register words and must reject patterns that
+/// map to no variant before they become a typed value.
+pub fn check_discrete(
+    channel: &'static str,
+    raw: u16,
+    allowed: &[u16],
+) -> Option<Violation> {
+    if allowed.contains(&raw) {
+        return None;
+    }
+    Some(Violation {
+        channel,
+        reason: format!("raw word {raw:#06x} decodes to no known state"),
+    })
+}
+
+/// Plausibility bounds of the numeric channels, as `(channel, min, max)`.
+/// Each range is the instrument's full scale with margin for noise and
+/// calibration offset.
+const PLAUSIBLE: &[(&str, f64, f64)] = &[
+    ("pressure", -2.0, 700.0),
+    ("temperature", -210.0, 1200.0),
+    ("igniter_current", -1.0, 50.0),
+    ("fc_pressure", -2.0, 700.0),
+    ("fc_altitude", -500.0, 200_000.0),
+    ("ambient_pressure", 300.0, 1100.0),
+    ("ambient_temperature", -90.0, 60.0),
+    ("psu_volts", -1.0, 60.0),
+    ("psu_amps", -1.0, 30.0),
+];
+
+/// Counter state carried between consecutive [`Data`] frames.
+#[derive(Default)]
+pub struct DataState {
+    last_seq: Option<u128>,
+    last_wall_ns: Option<u128>,
+}
+
+impl Validate for Data {
+    type State = DataState;
+
+    fn validate(&self, state: &mut Self::State) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for &(channel, min, max) in PLAUSIBLE {
+            if let Some(value) = self.channel_value(channel) {
+                violations.extend(check_range(channel, value, min, max));
+            }
+        }
+        // The sequence number and acquisition clock are the frame's two
+        // counters: the first steps backwards on a sync loop restart, the
+        // second when the system clock is stepped under us.
+        violations.extend(check_monotonic("seq", &mut state.last_seq, self.seq as u128));
+        if let Some(wall_ns) = self.wall_ns {
+            violations.extend(check_monotonic("wall_ns", &mut state.last_wall_ns, wall_ns));
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_range_and_non_finite_values_are_violations() {
+        let mut state = DataState::default();
+        let data = Data {
+            pressure: Some(f64::NAN),
+            temperature: Some(5000.0),
+            igniter_current: Some(1.2),
+            ..Data::default()
+        };
+        let violations = data.validate(&mut state);
+        let channels: Vec<_> = violations.iter().map(|v| v.channel).collect();
+        assert_eq!(channels, vec!["pressure", "temperature"]);
+    }
+
+    #[test]
+    fn counter_step_backwards_is_reported_once() {
+        let mut state = DataState::default();
+        let frame = |seq| Data {
+            seq,
+            ..Data::default()
+        };
+        assert!(frame(5).validate(&mut state).is_empty());
+        let violations = frame(3).validate(&mut state);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].channel, "seq");
+        // The counter resumed from the new value; no repeat report.
+        assert!(frame(4).validate(&mut state).is_empty());
+    }
+
+    #[test]
+    fn discrete_words_must_decode_to_a_known_state() {
+        assert!(check_discrete("valve_feedback", 0x01, &[0x01, 0x02]).is_none());
+        assert!(check_discrete("valve_feedback", 0x03, &[0x01, 0x02]).is_some());
+    }
+}
@@ -0,0 +1,189 @@
+//! Protocol conformance vectors for every wire message.
+//!
+//! `tests/vectors/` holds one canonical sample of each [`WsMessage`] variant:
+//! the encoded bytes as lowercase hex (`.hex`) next to a human-readable
+//! rendering of the decoded value (`.txt`). This test pins both directions —
+//! our encoder must produce exactly the checked-in bytes, and the checked-in
+//! bytes must decode back to the sample — so third-party clients have ground
+//! truth bytes to decode instead of reverse-engineering frames off the wire.
+//!
+//! After an intentional wire format change, regenerate with
+//! `UPDATE_VECTORS=1 cargo test -p rctrl_api conformance` and bump
+//! [`PROTOCOL_VERSION`]; a diff here without a version bump is a breakage.
+
+use rctrl_api::prelude::*;
+use std::time::Duration;
+
+/// One deterministic sample per [`WsMessage`] variant.
+fn vectors() -> Vec<(&'static str, WsMessage)> {
+    let data = Data {
+        time: Duration::from_millis(42_010),
+        seq: 4201,
+        gap: true,
+        pressure: Some(20.5),
+        pressure_at: Some(Duration::from_millis(42_008)),
+        temperature: Some(-12.25),
+        valve: Some(true),
+        valve_feedback: Some(false),
+        psu_volts: Some(12.01),
+        psu_amps: Some(0.25),
+        log_msg: Some("conformance".to_string()),
+        ..Data::default()
+    };
+    let note = Note {
+        id: 7,
+        unix_ms: 1_600_000_000_000,
+        author: "127.0.0.1:9000".to_string(),
+        text: "ground truth".to_string(),
+    };
+    vec![
+        ("data", WsMessage::Data(data.clone())),
+        (
+            "cmd",
+            WsMessage::Cmd(Cmd {
+                cmd: CmdEnum::SetParam {
+                    param: Param::StreamDivisor,
+                    value: 4.0,
+                },
+            }),
+        ),
+        (
+            "cmd_rejection",
+            WsMessage::CmdRejection(CmdRejection {
+                cmd: CmdEnum::ValveOpen,
+                error: UserError::safety(
+                    "SAF-001",
+                    "role observer may not issue Valves commands",
+                ),
+            }),
+        ),
+        ("snapshot", {
+            // StateSnapshot is non_exhaustive: built up from Default, the way
+            // external clients have to.
+            let mut snapshot = StateSnapshot::default();
+            snapshot.last_data = Some(data);
+            snapshot.gaps = 3;
+            snapshot.params = vec![(Param::AggregationWindow, 10.0)];
+            snapshot.loop_period = Duration::from_millis(10);
+            snapshot.notes = vec![note.clone()];
+            WsMessage::Snapshot(Box::new(snapshot))
+        }),
+        (
+            "param_applied",
+            WsMessage::ParamApplied {
+                param: Param::PlotRetentionS,
+                value: 300.0,
+            },
+        ),
+        (
+            "quality_report",
+            WsMessage::QualityReport(QualityReport {
+                channels: vec![ChannelQuality {
+                    channel: ChannelId::from("pressure"),
+                    noise_floor: 0.125,
+                    offset: -0.5,
+                    dropout_rate: 0.01,
+                    verdict: QualityVerdict::Warn,
+                }],
+            }),
+        ),
+        ("ping", WsMessage::Ping(99)),
+        ("pong", WsMessage::Pong(99)),
+        (
+            "flux_query",
+            WsMessage::FluxQuery("from(bucket: \"rctrl\") |> range(start: -1h)".to_string()),
+        ),
+        (
+            "flux_result",
+            WsMessage::FluxResult(Ok(FluxTable {
+                columns: vec!["_time".to_string(), "_value".to_string()],
+                rows: vec![vec!["2020-09-13T12:26:40Z".to_string(), "20.5".to_string()]],
+            })),
+        ),
+        ("note_added", WsMessage::NoteAdded(note)),
+        (
+            "history_result",
+            WsMessage::HistoryResult(Ok(vec![HistorySeries {
+                channel: ChannelId::from("pressure"),
+                points: vec![(1.0, 20.5), (2.0, 20.25)],
+            }])),
+        ),
+        (
+            "log",
+            WsMessage::Log(LogRecord {
+                level: LogLevel::Warn,
+                unix_ms: 1_600_000_000_000,
+                source: "rctrl::psu".to_string(),
+                message: "supply readback timed out".to_string(),
+            }),
+        ),
+        (
+            "heartbeat",
+            WsMessage::Heartbeat(Heartbeat {
+                seq: 3600,
+                unix_ms: 1_600_000_000_000,
+            }),
+        ),
+        (
+            "config_staged",
+            WsMessage::ConfigStaged {
+                hash: "cbf29ce484222325".to_string(),
+            },
+        ),
+        (
+            "confirmation",
+            WsMessage::Confirmation(ConfirmationState {
+                pending: Some("ValveOpen".to_string()),
+                expires_in_s: 10.0,
+            }),
+        ),
+    ]
+}
+
+fn vector_path(name: &str, ext: &str) -> String {
+    format!(
+        "{}/tests/vectors/{name}.{ext}",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    let hex = hex.trim();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("hex vector"))
+        .collect()
+}
+
+#[test]
+fn every_message_matches_its_canonical_vector() {
+    let update = std::env::var_os("UPDATE_VECTORS").is_some();
+    for (name, msg) in vectors() {
+        let encoded = encode(&msg).expect("encode sample");
+        if update {
+            std::fs::write(vector_path(name, "hex"), to_hex(&encoded)).unwrap();
+            std::fs::write(vector_path(name, "txt"), format!("{msg:#?}\n")).unwrap();
+        }
+
+        let path = vector_path(name, "hex");
+        let canonical = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read vector {path}: {e}"));
+        assert_eq!(
+            to_hex(&encoded),
+            canonical.trim(),
+            "'{name}' encodes differently from its vector; if the wire format \
+             change is intended, regenerate with UPDATE_VECTORS=1 and bump \
+             PROTOCOL_VERSION (currently {PROTOCOL_VERSION})"
+        );
+
+        let decoded = decode(&from_hex(&canonical)).expect("decode canonical bytes");
+        assert_eq!(decoded, msg, "'{name}' bytes decode to a different value");
+
+        let txt = std::fs::read_to_string(vector_path(name, "txt")).unwrap();
+        assert_eq!(format!("{msg:#?}\n"), txt, "'{name}' description is stale");
+    }
+}
@@ -0,0 +1,53 @@
+//! Golden file tests for the line protocol output of every API type.
+//!
+//! Each type is rendered with fixed inputs and a fixed timestamp through the
+//! explicit-timestamp API and compared against a checked-in expectation, so
+//! any derive or attribute change that alters the written schema shows up in
+//! review as a golden file diff.
+
+use influx::{ToLineProtocol, ToLineProtocolEntries};
+use rctrl_api::prelude::*;
+use std::time::Duration;
+
+const TIMESTAMP: u128 = 1_600_000_000_000_000_000;
+
+fn assert_golden(name: &str, rendered: &str) {
+    let path = format!("{}/tests/golden/{name}", env!("CARGO_MANIFEST_DIR"));
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read golden file {path}: {e}"));
+    assert_eq!(
+        rendered.trim_end(),
+        expected.trim_end(),
+        "rendered output differs from {path}; update the golden file if the schema change is intended"
+    );
+}
+
+#[test]
+fn pressure() {
+    let line = Pressure { value: 20.5 }.to_line_protocol_at(TIMESTAMP);
+    assert_golden("pressure.lp", &line.0);
+}
+
+#[test]
+fn temperature() {
+    let line = Temperature { value: -12.25 }.to_line_protocol_at(TIMESTAMP);
+    assert_golden("temperature.lp", &line.0);
+}
+
+#[test]
+fn data_frame() {
+    let data = Data {
+        time: Duration::from_secs(42),
+        pressure: Some(20.5),
+        temperature: Some(-12.25),
+        valve: Some(true),
+        ..Data::default()
+    };
+    let rendered = data
+        .to_line_protocol_entries_at(TIMESTAMP)
+        .iter()
+        .map(|line| line.0.clone())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_golden("data_frame.lp", &rendered);
+}
@@ -0,0 +1,93 @@
+//! Per-channel data age tracking.
+//!
+//! A frozen readout that still shows a plausible value is the most dangerous
+//! display failure a control room has. Every gauge therefore carries an age
+//! indicator: time since the channel's sample was *acquired* — the frame's
+//! acquisition timestamps carry the skew between conversion and frame — and
+//! it turns amber, then red, as the channel goes stale.
+
+use crate::palette::{self, Palette, Status};
+use rctrl_api::prelude::*;
+use std::collections::HashMap;
+use web_time::{Duration, Instant};
+
+/// Age beyond which a channel renders amber.
+const WARN_AGE: Duration = Duration::from_millis(250);
+/// Age beyond which a channel renders red.
+const STALE_AGE: Duration = Duration::from_millis(1000);
+
+/// When each channel's newest sample was acquired, in local time.
+#[derive(Default)]
+pub struct AgeTracker {
+    /// Local instant corresponding to the acquisition of the newest sample.
+    acquired: HashMap<ChannelId, Instant>,
+}
+
+impl AgeTracker {
+    /// Fold in an arriving frame: for every populated channel, reconstruct
+    /// when its sample was acquired by backdating the arrival instant with
+    /// the acquisition skew carried in the frame.
+    pub fn on_data(&mut self, data: &Data) {
+        let arrived = Instant::now();
+        let mut record = |channel: &str, value: Option<f64>, at: Option<Duration>| {
+            if value.is_none() {
+                return;
+            }
+            let skew = at.map_or(Duration::ZERO, |at| data.time.saturating_sub(at));
+            self.acquired
+                .insert(ChannelId::from(channel), arrived - skew);
+        };
+        record("pressure", data.pressure, data.pressure_at);
+        record("temperature", data.temperature, data.temperature_at);
+        record("fc_pressure", data.fc_pressure, None);
+        record("fc_altitude", data.fc_altitude, None);
+    }
+
+    /// Age of a channel's newest sample, if one has been seen.
+    pub fn age(&self, channel: &str) -> Option<Duration> {
+        self.acquired
+            .get(&ChannelId::from(channel))
+            .map(Instant::elapsed)
+    }
+
+    /// Compact age readout drawn next to a gauge.
+    pub fn ui(&self, ui: &mut egui::Ui, palette: Palette, channel: &str) {
+        match self.age(channel) {
+            Some(age) => {
+                let status = if age >= STALE_AGE {
+                    Status::Bad
+                } else if age >= WARN_AGE {
+                    Status::Warn
+                } else {
+                    Status::Neutral
+                };
+                palette::status_label(ui, palette, status, format!("{:.2} s", age.as_secs_f64()));
+            }
+            None => {
+                palette::status_label(ui, palette, Status::Bad, "never");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn age_accounts_for_acquisition_skew() {
+        let mut tracker = AgeTracker::default();
+        tracker.on_data(&Data {
+            time: Duration::from_millis(100),
+            pressure: Some(1.0),
+            // Acquired 40 ms before the frame timestamp.
+            pressure_at: Some(Duration::from_millis(60)),
+            ..Data::default()
+        });
+        let age = tracker.age("pressure").unwrap();
+        assert!(age >= Duration::from_millis(40));
+        assert!(age < Duration::from_millis(90));
+        // Unpopulated channels never get an age.
+        assert!(tracker.age("temperature").is_none());
+    }
+}
@@ -0,0 +1,178 @@
+//! Audio cues for events an operator watching the stand camera would miss.
+//!
+//! The GUI is a library crate, so it does not own an audio device; the shell
+//! injects an [`AudioBackend`] (rodio on native, `AudioContext` on wasm) and
+//! the library decides *when* to play and *what* it sounds like. Each cue has
+//! a distinct beep pattern rendered by [`Cue::samples`], so both shells sound
+//! identical. Per-category mutes and the master volume live in
+//! [`AudioSettings`], which the shell persists alongside its other state.
+
+use serde::{Deserialize, Serialize};
+
+/// Sample rate the cue PCM is rendered at.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// Events with an audible cue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    /// A critical alarm became active (failed quality check, degraded pair).
+    CriticalAlarm,
+    /// The backend refused a command.
+    CommandRejection,
+    /// The remote connection dropped.
+    ConnectionLoss,
+}
+
+impl Cue {
+    /// Beep pattern: (frequency in Hz, beep count). Patterns are chosen to be
+    /// distinguishable without looking: alarms are high and insistent,
+    /// rejections a single low buzz, connection loss two falling beeps.
+    fn pattern(self) -> (f32, u32) {
+        match self {
+            Cue::CriticalAlarm => (880.0, 3),
+            Cue::CommandRejection => (220.0, 1),
+            Cue::ConnectionLoss => (440.0, 2),
+        }
+    }
+
+    /// Render the cue as mono PCM at [`SAMPLE_RATE`], amplitude in ±1.
+    pub fn samples(self) -> Vec<f32> {
+        const BEEP: f32 = 0.12;
+        const GAP: f32 = 0.08;
+        let (freq, beeps) = self.pattern();
+        let beep_len = (BEEP * SAMPLE_RATE as f32) as usize;
+        let gap_len = (GAP * SAMPLE_RATE as f32) as usize;
+
+        let mut samples = Vec::new();
+        for beep in 0..beeps {
+            // Connection loss falls in pitch across its beeps.
+            let freq = match self {
+                Cue::ConnectionLoss => freq / (1.0 + beep as f32 * 0.5),
+                _ => freq,
+            };
+            for i in 0..beep_len {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                // Linear fade-out per beep avoids clicks at the edges.
+                let envelope = 1.0 - i as f32 / beep_len as f32;
+                samples.push((t * freq * std::f32::consts::TAU).sin() * envelope);
+            }
+            samples.extend(std::iter::repeat_n(0.0, gap_len));
+        }
+        samples
+    }
+}
+
+/// Shell provided audio output.
+pub trait AudioBackend {
+    /// Play a cue's PCM ([`Cue::samples`]) at the given volume in 0..=1.
+    fn play(&mut self, cue: Cue, volume: f32);
+}
+
+/// Persistable audio preferences.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    /// Master volume in 0..=1.
+    pub volume: f32,
+    pub mute_alarms: bool,
+    pub mute_rejections: bool,
+    pub mute_connection: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume: 0.7,
+            mute_alarms: false,
+            mute_rejections: false,
+            mute_connection: false,
+        }
+    }
+}
+
+/// Decides when cues fire and routes them to the shell's backend.
+#[derive(Default)]
+pub struct AudioCues {
+    backend: Option<Box<dyn AudioBackend>>,
+    pub settings: AudioSettings,
+}
+
+impl AudioCues {
+    /// Install the shell's audio output. Without a backend, triggers are
+    /// no-ops, so headless shells and tests need no audio stack.
+    pub fn set_backend(&mut self, backend: Box<dyn AudioBackend>) {
+        self.backend = Some(backend);
+    }
+
+    /// Play the cue for an event, honoring its category mute.
+    pub fn trigger(&mut self, cue: Cue) {
+        let muted = match cue {
+            Cue::CriticalAlarm => self.settings.mute_alarms,
+            Cue::CommandRejection => self.settings.mute_rejections,
+            Cue::ConnectionLoss => self.settings.mute_connection,
+        };
+        if muted || self.settings.volume <= 0.0 {
+            return;
+        }
+        if let Some(backend) = self.backend.as_mut() {
+            backend.play(cue, self.settings.volume);
+        }
+    }
+
+    /// Settings section: master volume and per-category mutes.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Audio");
+        ui.horizontal(|ui| {
+            ui.label("Volume");
+            ui.add(egui::Slider::new(&mut self.settings.volume, 0.0..=1.0).show_value(false));
+        });
+        ui.checkbox(&mut self.settings.mute_alarms, "Mute critical alarms");
+        ui.checkbox(&mut self.settings.mute_rejections, "Mute command rejections");
+        ui.checkbox(&mut self.settings.mute_connection, "Mute connection loss");
+        if self.backend.is_none() {
+            ui.weak("No audio output configured by this shell.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<Cue>>>);
+
+    impl AudioBackend for Recorder {
+        fn play(&mut self, cue: Cue, _volume: f32) {
+            self.0.borrow_mut().push(cue);
+        }
+    }
+
+    #[test]
+    fn cues_render_distinct_bounded_patterns() {
+        let alarm = Cue::CriticalAlarm.samples();
+        let rejection = Cue::CommandRejection.samples();
+        let loss = Cue::ConnectionLoss.samples();
+        // Beep counts differ, so so do the rendered lengths.
+        assert!(alarm.len() > loss.len() && loss.len() > rejection.len());
+        for sample in alarm.iter().chain(&rejection).chain(&loss) {
+            assert!(sample.abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn category_mutes_suppress_their_cue_only() {
+        let played = Rc::new(RefCell::new(Vec::new()));
+        let mut cues = AudioCues::default();
+        cues.set_backend(Box::new(Recorder(played.clone())));
+        cues.settings.mute_rejections = true;
+
+        cues.trigger(Cue::CommandRejection);
+        cues.trigger(Cue::CriticalAlarm);
+        assert_eq!(*played.borrow(), vec![Cue::CriticalAlarm]);
+
+        cues.settings.volume = 0.0;
+        cues.trigger(Cue::CriticalAlarm);
+        assert_eq!(played.borrow().len(), 1);
+    }
+}
@@ -0,0 +1,281 @@
+//! WebSocket connections to the rctrl backend.
+//!
+//! A dropped connection reconnects on its own with exponential backoff, so a
+//! restarted backend does not require a browser refresh; the GUI shows a
+//! reconnecting state until the link is back or the retry budget runs out.
+//! A link that stays open but goes silent past the server's heartbeat
+//! cadence is flagged stale rather than trusted.
+
+use crate::palette::{self, Palette, Status};
+use crate::session::{EventKind, SessionLog};
+use rctrl_api::prelude::*;
+use web_time::{Duration, Instant};
+
+/// Delay before the first reconnect attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Cap on the backoff delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Default reconnect attempts before the connection stays down.
+const DEFAULT_MAX_RETRIES: u32 = 20;
+/// Default silence tolerated before a connected link is flagged stale. The
+/// server heartbeats once per second, so three missed beats mean trouble.
+const DEFAULT_STALE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Backoff before attempt number `retries + 1`: doubles per failed attempt,
+/// capped at [`MAX_BACKOFF`].
+fn backoff(retries: u32) -> Duration {
+    INITIAL_BACKOFF
+        .checked_mul(1 << retries.min(16))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF)
+}
+
+/// State of a WebSocket connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected,
+    /// Up, but the server's heartbeat has stopped arriving.
+    Stale,
+    /// Down, with a retry scheduled.
+    Reconnecting,
+    /// Down for good: the retry budget is exhausted or the URL is unusable.
+    Disconnected,
+}
+
+/// One WebSocket connection to rctrl.
+pub struct WebSocketConnection {
+    pub url: String,
+    pub status: ConnectionStatus,
+    sender: ewebsock::WsSender,
+    receiver: ewebsock::WsReceiver,
+    /// Failed attempts since the link was last up.
+    retries: u32,
+    max_retries: u32,
+    /// When the next attempt is due, while reconnecting.
+    next_attempt: Option<Instant>,
+    /// When the last message arrived, for staleness detection.
+    last_rx: Option<Instant>,
+    stale_timeout: Duration,
+}
+
+impl WebSocketConnection {
+    /// Connect to the backend. An error here means the URL itself is
+    /// unusable; a connection that drops later retries on its own.
+    pub fn new(url: &str) -> Result<Self, String> {
+        let (sender, receiver) = ewebsock::connect(url, ewebsock::Options::default())?;
+        Ok(Self {
+            url: url.to_string(),
+            status: ConnectionStatus::Connecting,
+            sender,
+            receiver,
+            retries: 0,
+            max_retries: DEFAULT_MAX_RETRIES,
+            next_attempt: None,
+            last_rx: None,
+            stale_timeout: DEFAULT_STALE_TIMEOUT,
+        })
+    }
+
+    /// Override the reconnect attempt budget.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Override how long the link may be silent before it is flagged stale.
+    pub fn with_stale_timeout(mut self, stale_timeout: Duration) -> Self {
+        self.stale_timeout = stale_timeout;
+        self
+    }
+
+    /// Reconnect attempt counters, for status displays.
+    pub fn retry_counts(&self) -> (u32, u32) {
+        (self.retries, self.max_retries)
+    }
+
+    /// The link dropped: schedule a retry, or give up once the budget is
+    /// spent.
+    fn schedule_reconnect(&mut self) {
+        if self.retries >= self.max_retries {
+            self.status = ConnectionStatus::Disconnected;
+            self.next_attempt = None;
+            return;
+        }
+        self.status = ConnectionStatus::Reconnecting;
+        self.next_attempt = Some(Instant::now() + backoff(self.retries));
+    }
+
+    /// Replace the transport with a fresh connection attempt once the
+    /// scheduled backoff has elapsed.
+    fn try_reconnect(&mut self) {
+        let due = self
+            .next_attempt
+            .is_some_and(|at| Instant::now() >= at);
+        if !due {
+            return;
+        }
+        self.retries += 1;
+        self.next_attempt = None;
+        match ewebsock::connect(&self.url, ewebsock::Options::default()) {
+            Ok((sender, receiver)) => {
+                self.sender = sender;
+                self.receiver = receiver;
+                self.status = ConnectionStatus::Connecting;
+                self.last_rx = None;
+            }
+            Err(e) => {
+                tracing::error!("reconnect to {} failed: {e}", self.url);
+                self.schedule_reconnect();
+            }
+        }
+    }
+
+    /// Send a protocol message to rctrl.
+    pub fn send(&mut self, msg: &WsMessage) {
+        match encode(msg) {
+            Ok(bytes) => self.sender.send(ewebsock::WsMessage::Binary(bytes)),
+            Err(e) => tracing::error!("failed to encode message: {e}"),
+        }
+    }
+
+    /// Declare the client role; must be the first message after connecting.
+    pub fn send_role(&mut self, role: Role) {
+        match bincode::serialize(&role) {
+            Ok(bytes) => self.sender.send(ewebsock::WsMessage::Binary(bytes)),
+            Err(e) => tracing::error!("failed to encode role: {e}"),
+        }
+    }
+
+    /// Poll for the next decoded protocol message, updating connection status
+    /// from transport events along the way and driving scheduled reconnects.
+    pub fn try_recv(&mut self) -> Option<WsMessage> {
+        self.try_reconnect();
+        // The server heartbeats once per second even when telemetry is
+        // quiet, so a silent link past the timeout is stale, not idle. Any
+        // message — the next heartbeat included — clears the flag.
+        if self.status == ConnectionStatus::Connected
+            && self
+                .last_rx
+                .is_some_and(|at| at.elapsed() > self.stale_timeout)
+        {
+            self.status = ConnectionStatus::Stale;
+        }
+        while let Some(event) = self.receiver.try_recv() {
+            match event {
+                ewebsock::WsEvent::Opened => {
+                    self.status = ConnectionStatus::Connected;
+                    self.retries = 0;
+                    self.last_rx = Some(Instant::now());
+                    // Viewer builds declare the observer role, so the server
+                    // refuses commands independently of the stripped binary.
+                    #[cfg(feature = "viewer")]
+                    self.send_role(Role::Observer);
+                    #[cfg(not(feature = "viewer"))]
+                    self.send_role(Role::Operator);
+                }
+                ewebsock::WsEvent::Message(ewebsock::WsMessage::Binary(bytes)) => {
+                    self.last_rx = Some(Instant::now());
+                    if self.status == ConnectionStatus::Stale {
+                        self.status = ConnectionStatus::Connected;
+                    }
+                    match decode(&bytes) {
+                        Ok(msg) => return Some(msg),
+                        Err(e) => tracing::warn!("undecodable message from rctrl: {e}"),
+                    }
+                }
+                ewebsock::WsEvent::Message(_) => {}
+                ewebsock::WsEvent::Error(e) => {
+                    tracing::error!("websocket error: {e}");
+                    self.schedule_reconnect();
+                }
+                ewebsock::WsEvent::Closed => {
+                    self.schedule_reconnect();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Owns every connection the GUI holds to the backend.
+pub struct ConnectionManager {
+    /// The remote control/telemetry connection.
+    pub ws_remote: Option<WebSocketConnection>,
+    /// Record of operator interactions this session, for debrief replay.
+    pub session: SessionLog,
+}
+
+impl ConnectionManager {
+    pub fn new(server_url: &str) -> Self {
+        let ws_remote = match WebSocketConnection::new(server_url) {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                tracing::error!("failed to connect to {server_url}: {e}");
+                None
+            }
+        };
+        Self {
+            ws_remote,
+            session: SessionLog::default(),
+        }
+    }
+
+    /// Send on the remote connection, recording operator-initiated actions
+    /// in the session log. Panels send through here; transport-level traffic
+    /// like latency probes goes straight to the connection.
+    pub fn send_remote(&mut self, msg: &WsMessage) {
+        // Backstop for viewer builds: the control UI is compiled out, but a
+        // command that would still reach here is dropped, not sent.
+        #[cfg(feature = "viewer")]
+        if matches!(msg, WsMessage::Cmd(_) | WsMessage::FluxQuery(_)) {
+            tracing::warn!("viewer build dropped an outbound command");
+            return;
+        }
+        match msg {
+            WsMessage::Cmd(cmd) => self.session.record(EventKind::Cmd, format!("{:?}", cmd.cmd)),
+            WsMessage::FluxQuery(query) => self.session.record(
+                EventKind::Cmd,
+                format!("flux query: {}", query.lines().next().unwrap_or_default()),
+            ),
+            _ => {}
+        }
+        if let Some(ws) = self.ws_remote.as_mut() {
+            ws.send(msg);
+        }
+    }
+
+    /// Compact status widget for the top bar.
+    pub fn status_ui(&self, ui: &mut egui::Ui, palette: Palette) {
+        let (status, text) = match self.ws_remote.as_ref().map(|ws| ws.status) {
+            Some(ConnectionStatus::Connected) => (Status::Good, "CONNECTED".to_string()),
+            Some(ConnectionStatus::Connecting) => (Status::Warn, "CONNECTING".to_string()),
+            Some(ConnectionStatus::Stale) => (Status::Warn, "STALE".to_string()),
+            Some(ConnectionStatus::Reconnecting) => {
+                let (retries, max) = self
+                    .ws_remote
+                    .as_ref()
+                    .map(|ws| ws.retry_counts())
+                    .unwrap_or_default();
+                (Status::Warn, format!("RECONNECTING {}/{max}…", retries + 1))
+            }
+            _ => (Status::Bad, "DISCONNECTED".to_string()),
+        };
+        palette::status_label(ui, palette, status, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_per_attempt_and_caps() {
+        assert_eq!(backoff(0), INITIAL_BACKOFF);
+        assert_eq!(backoff(1), INITIAL_BACKOFF * 2);
+        assert_eq!(backoff(3), INITIAL_BACKOFF * 8);
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        // Large retry counts must not overflow the multiplication.
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+}
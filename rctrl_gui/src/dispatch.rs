@@ -0,0 +1,174 @@
+//! Central routing of protocol messages to the app panels.
+//!
+//! The connection decodes the envelope once; [`dispatch`] fans each typed
+//! message out to every panel that cares about it. A new message type gets
+//! one arm here — next to all the existing routing — instead of growing an
+//! inline match in `Gui::update`.
+
+use crate::age::AgeTracker;
+use crate::audio::{AudioCues, Cue};
+use crate::format::Formatter;
+use crate::latency::LatencyMonitor;
+use crate::logger::LoggerApp;
+use crate::notes::NotesApp;
+use crate::remote::RemoteApp;
+use crate::render::RenderGovernor;
+use crate::session::{EventKind, SessionLog};
+use crate::settings::SettingsApp;
+use crate::telemetry::TelemetryApp;
+use rctrl_api::prelude::*;
+
+/// Mutable views of every message consumer, borrowed from the [`crate::Gui`]
+/// for the duration of one drain of the connection.
+pub struct Sinks<'a> {
+    pub format: &'a mut Formatter,
+    pub age: &'a mut AgeTracker,
+    pub audio: &'a mut AudioCues,
+    pub latency: &'a mut LatencyMonitor,
+    pub render: &'a mut RenderGovernor,
+    pub confirmation: &'a mut Option<ConfirmationState>,
+    pub session: &'a mut SessionLog,
+    pub remote: &'a mut RemoteApp,
+    pub telemetry: &'a mut TelemetryApp,
+    pub logger: &'a mut LoggerApp,
+    pub notes: &'a mut NotesApp,
+    pub settings: &'a mut SettingsApp,
+}
+
+/// Route one decoded message to its consumers.
+pub fn dispatch(msg: WsMessage, sinks: &mut Sinks<'_>) {
+    match msg {
+        WsMessage::Data(data) => {
+            // Age and latency see every frame; the heavy panel updates are
+            // decimated by the render governor.
+            sinks.render.on_frame();
+            sinks.format.observe(data.time);
+            sinks.age.on_data(&data);
+            sinks.latency.on_data(&data);
+            if sinks.render.admit_remote() {
+                sinks.remote.on_data(&data);
+            }
+            if sinks.render.admit_telemetry() {
+                sinks.telemetry.on_data(&data);
+            }
+        }
+        WsMessage::Snapshot(snapshot) => {
+            sinks.remote.apply_snapshot(&snapshot);
+            sinks.settings.apply_snapshot(&snapshot);
+            sinks.latency.apply_snapshot(&snapshot);
+            sinks.notes.apply_snapshot(&snapshot);
+        }
+        WsMessage::Pong(nonce) => sinks.latency.on_pong(nonce),
+        WsMessage::ParamApplied { param, value } => {
+            sinks
+                .session
+                .record(EventKind::Ack, format!("{param:?} = {value}"));
+            sinks.settings.on_param_applied(param, value);
+        }
+        WsMessage::QualityReport(report) => {
+            sinks.session.record(
+                EventKind::Ack,
+                format!("quality report: {:?}", report.verdict()),
+            );
+            if report.verdict() == QualityVerdict::Fail {
+                sinks.audio.trigger(Cue::CriticalAlarm);
+            }
+            sinks.remote.on_quality_report(report);
+        }
+        WsMessage::FluxResult(result) => sinks.telemetry.query.on_result(result),
+        WsMessage::NoteAdded(note) => {
+            sinks
+                .session
+                .record(EventKind::Ack, format!("note #{} stored", note.id));
+            sinks.notes.on_note_added(note);
+        }
+        WsMessage::Confirmation(state) => {
+            match &state.pending {
+                Some(action) => sinks.session.record(
+                    EventKind::Ack,
+                    format!("{action} awaiting second operator"),
+                ),
+                None => sinks
+                    .session
+                    .record(EventKind::Ack, "confirmation resolved".to_string()),
+            }
+            *sinks.confirmation = Some(state);
+        }
+        WsMessage::CameraEvent {
+            kind,
+            reference,
+            reason,
+        } => {
+            sinks
+                .session
+                .record(EventKind::Ack, format!("camera snapshot: {reference}"));
+            sinks.logger.on_camera_event(kind, reference, reason);
+        }
+        WsMessage::Log(record) => sinks.logger.on_log(record),
+        WsMessage::CmdRejection(rejection) => {
+            sinks.session.record(
+                EventKind::Rejection,
+                format!("{:?}: {}", rejection.cmd, rejection.error),
+            );
+            sinks.audio.trigger(Cue::CommandRejection);
+            sinks.logger.on_error(rejection.error);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_sinks(msgs: Vec<WsMessage>) -> (Option<ConfirmationState>, SessionLog) {
+        let mut format = Formatter::default();
+        let mut age = AgeTracker::default();
+        let mut audio = AudioCues::default();
+        let mut latency = LatencyMonitor::default();
+        let mut render = RenderGovernor::default();
+        let mut confirmation = None;
+        let mut session = SessionLog::default();
+        let mut remote = RemoteApp::default();
+        let mut telemetry = TelemetryApp::default();
+        let mut logger = LoggerApp::default();
+        let mut notes = NotesApp::default();
+        let mut settings = SettingsApp::default();
+        let mut sinks = Sinks {
+            format: &mut format,
+            age: &mut age,
+            audio: &mut audio,
+            latency: &mut latency,
+            render: &mut render,
+            confirmation: &mut confirmation,
+            session: &mut session,
+            remote: &mut remote,
+            telemetry: &mut telemetry,
+            logger: &mut logger,
+            notes: &mut notes,
+            settings: &mut settings,
+        };
+        for msg in msgs {
+            dispatch(msg, &mut sinks);
+        }
+        (confirmation, session)
+    }
+
+    #[test]
+    fn confirmation_routes_to_its_slot_and_the_session_log() {
+        let state = ConfirmationState {
+            pending: Some("ValveOpen".to_string()),
+            expires_in_s: 10.0,
+        };
+        let (confirmation, session) =
+            with_sinks(vec![WsMessage::Confirmation(state.clone())]);
+        assert_eq!(confirmation, Some(state));
+        assert!(session.to_json().contains("awaiting second operator"));
+    }
+
+    #[test]
+    fn unknown_messages_are_ignored() {
+        let (confirmation, _) = with_sinks(vec![WsMessage::Ping(1)]);
+        assert_eq!(confirmation, None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Shared time and number formatting.
+//!
+//! Every panel formats through this module so timestamps and readouts look
+//! the same in the logger, the plots and any export, and so the UTC / local /
+//! mission-time preference applies everywhere at once.
+
+use chrono::{DateTime, Local, Utc};
+use std::time::Duration;
+
+/// How timestamps are rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeDisplay {
+    /// Mission elapsed time, `T+HH:MM:SS.s`.
+    #[default]
+    Mission,
+    Utc,
+    Local,
+}
+
+/// Session-wide formatter, owned by [`crate::Gui`] and shared with panels.
+#[derive(Default)]
+pub struct Formatter {
+    pub display: TimeDisplay,
+    /// Wall clock instant of mission time zero, anchored from the first frame
+    /// seen this session so mission timestamps can be mapped to UTC/local.
+    anchor: Option<DateTime<Utc>>,
+}
+
+impl Formatter {
+    /// Anchor mission time zero against the wall clock from an incoming
+    /// frame's mission timestamp. Only the first observation sticks.
+    pub fn observe(&mut self, mission: Duration) {
+        if self.anchor.is_none() {
+            let offset = chrono::Duration::from_std(mission).unwrap_or_default();
+            self.anchor = Some(Utc::now() - offset);
+        }
+    }
+
+    /// Format a mission timestamp in the selected display mode. Falls back to
+    /// mission time when no wall clock anchor exists yet.
+    pub fn time(&self, mission: Duration) -> String {
+        let wall = match (self.display, self.anchor) {
+            (TimeDisplay::Mission, _) | (_, None) => return mission_time(mission),
+            (_, Some(anchor)) => anchor + chrono::Duration::from_std(mission).unwrap_or_default(),
+        };
+        match self.display {
+            TimeDisplay::Utc => wall.format("%H:%M:%S%.1f UTC").to_string(),
+            TimeDisplay::Local => wall.with_timezone(&Local).format("%H:%M:%S%.1f").to_string(),
+            TimeDisplay::Mission => unreachable!(),
+        }
+    }
+
+    /// Display mode toggle, drawn in the app switcher bar.
+    pub fn toggle_ui(&mut self, ui: &mut egui::Ui) {
+        ui.selectable_value(&mut self.display, TimeDisplay::Mission, "T+");
+        ui.selectable_value(&mut self.display, TimeDisplay::Utc, "UTC");
+        ui.selectable_value(&mut self.display, TimeDisplay::Local, "Local");
+    }
+}
+
+/// Mission elapsed time as `T+HH:MM:SS.s`.
+pub fn mission_time(mission: Duration) -> String {
+    let total = mission.as_secs();
+    let tenths = mission.subsec_millis() / 100;
+    format!(
+        "T+{:02}:{:02}:{:02}.{}",
+        total / 3600,
+        (total / 60) % 60,
+        total % 60,
+        tenths
+    )
+}
+
+/// Wall clock `HH:MM:SSZ` of a unix millisecond timestamp.
+pub fn clock(unix_ms: u64) -> String {
+    let s = (unix_ms / 1_000) % 86_400;
+    format!("{:02}:{:02}:{:02}Z", s / 3_600, (s / 60) % 60, s % 60)
+}
+
+/// Fixed decimal places with thousands separators, e.g. `12,345.68`.
+pub fn number(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    let sign = if value < 0.0 { "-" } else { "" };
+    match fraction {
+        Some(fraction) => format!("{sign}{grouped}.{fraction}"),
+        None => format!("{sign}{grouped}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mission_time_renders_hours_minutes_seconds_tenths() {
+        assert_eq!(mission_time(Duration::from_millis(0)), "T+00:00:00.0");
+        assert_eq!(mission_time(Duration::from_millis(3_725_400)), "T+01:02:05.4");
+    }
+
+    #[test]
+    fn number_groups_thousands_and_fixes_decimals() {
+        assert_eq!(number(12345.678, 2), "12,345.68");
+        assert_eq!(number(-1234.5, 1), "-1,234.5");
+        assert_eq!(number(999.0, 0), "999");
+        assert_eq!(number(1_000_000.0, 0), "1,000,000");
+    }
+}
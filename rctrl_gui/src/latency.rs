@@ -0,0 +1,151 @@
+//! Worst-case abort latency estimate for the GUI header.
+//!
+//! The estimate sums the measured command round-trip (from periodic pings),
+//! one sync loop period (the longest a command can wait before being picked
+//! up) and the last measured valve travel time. The test director wants this
+//! number visible at all times, so it lives in the top bar and turns red when
+//! it exceeds the budget. The same widget shows the p99 of the data-path
+//! latency, from the wall stamps the sync loop puts on its frames.
+
+use crate::connection::ConnectionManager;
+use crate::format;
+use crate::palette::{self, Palette, Status};
+use rctrl_api::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use web_time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Abort latency budget in milliseconds.
+const ABORT_BUDGET_MS: f64 = 500.0;
+/// Period between round-trip probes.
+const PING_PERIOD: Duration = Duration::from_secs(1);
+/// Smoothing factor for the round-trip EWMA.
+const RTT_ALPHA: f64 = 0.2;
+/// Assumed valve travel time until one has been measured.
+const TRAVEL_DEFAULT_MS: f64 = 150.0;
+/// Recent data-path latency samples the p99 is computed over.
+const DATA_WINDOW: usize = 512;
+
+/// Measures command round-trip and maintains the abort latency estimate.
+pub struct LatencyMonitor {
+    next_nonce: u64,
+    in_flight: HashMap<u64, Instant>,
+    last_ping: Option<Instant>,
+    /// Smoothed command round-trip in milliseconds.
+    rtt_ms: Option<f64>,
+    loop_period_ms: f64,
+    travel_ms: f64,
+    /// Acquisition-to-GUI latencies of recent frames, in milliseconds, from
+    /// the wall stamp the sync loop puts on each frame. With the GUI on
+    /// another machine the clock offset biases these; the trend still shows
+    /// when the path degrades.
+    data_window: VecDeque<f64>,
+}
+
+impl Default for LatencyMonitor {
+    fn default() -> Self {
+        Self {
+            next_nonce: 0,
+            in_flight: HashMap::new(),
+            last_ping: None,
+            rtt_ms: None,
+            loop_period_ms: 10.0,
+            travel_ms: TRAVEL_DEFAULT_MS,
+            data_window: VecDeque::new(),
+        }
+    }
+}
+
+impl LatencyMonitor {
+    /// Send a probe if the last one is old enough. Called once per frame.
+    pub fn tick(&mut self, conn: &mut ConnectionManager) {
+        let due = self
+            .last_ping
+            .is_none_or(|last| last.elapsed() >= PING_PERIOD);
+        if !due {
+            return;
+        }
+        if let Some(ws) = conn.ws_remote.as_mut() {
+            let nonce = self.next_nonce;
+            self.next_nonce += 1;
+            self.in_flight.insert(nonce, Instant::now());
+            ws.send(&WsMessage::Ping(nonce));
+            self.last_ping = Some(Instant::now());
+        }
+    }
+
+    /// Fold a returned probe into the round-trip estimate.
+    pub fn on_pong(&mut self, nonce: u64) {
+        if let Some(sent_at) = self.in_flight.remove(&nonce) {
+            let rtt = sent_at.elapsed().as_secs_f64() * 1000.0;
+            self.rtt_ms = Some(match self.rtt_ms {
+                Some(smoothed) => smoothed + RTT_ALPHA * (rtt - smoothed),
+                None => rtt,
+            });
+        }
+    }
+
+    pub fn apply_snapshot(&mut self, snapshot: &StateSnapshot) {
+        if !snapshot.loop_period.is_zero() {
+            self.loop_period_ms = snapshot.loop_period.as_secs_f64() * 1000.0;
+        }
+    }
+
+    pub fn on_data(&mut self, data: &Data) {
+        if let Some(travel_ms) = data.valve_travel_ms {
+            self.travel_ms = travel_ms;
+        }
+        if let Some(wall_ns) = data.wall_ns {
+            let now_ns = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos());
+            if self.data_window.len() == DATA_WINDOW {
+                self.data_window.pop_front();
+            }
+            self.data_window
+                .push_back(now_ns.saturating_sub(wall_ns) as f64 / 1e6);
+        }
+    }
+
+    /// p99 of the recent data-path latencies, once the window has samples.
+    fn data_p99_ms(&self) -> Option<f64> {
+        if self.data_window.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.data_window.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let index = ((sorted.len() as f64 * 0.99).ceil() as usize).min(sorted.len()) - 1;
+        Some(sorted[index])
+    }
+
+    /// Worst-case abort latency estimate in milliseconds, once a round-trip
+    /// has been measured.
+    fn estimate_ms(&self) -> Option<f64> {
+        self.rtt_ms
+            .map(|rtt| rtt + self.loop_period_ms + self.travel_ms)
+    }
+
+    /// Compact readout for the top bar.
+    pub fn ui(&self, ui: &mut egui::Ui, palette: Palette) {
+        match self.estimate_ms() {
+            Some(estimate) => {
+                let status = if estimate > ABORT_BUDGET_MS {
+                    Status::Bad
+                } else {
+                    Status::Good
+                };
+                palette::status_label(
+                    ui,
+                    palette,
+                    status,
+                    format!("ABORT {} ms", format::number(estimate, 0)),
+                );
+            }
+            None => {
+                palette::status_label(ui, palette, Status::Neutral, "ABORT --- ms");
+            }
+        }
+        if let Some(p99) = self.data_p99_ms() {
+            ui.label(format!("DATA p99 {} ms", format::number(p99, 0)));
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Ground control GUI.
+//!
+//! The GUI is a library crate; the native and wasm shells construct a [`Gui`]
+//! and call [`Gui::update`] once per frame with the egui context.
+//!
+//! The `viewer` feature builds a read-only GUI for public viewing stations:
+//! every command-sending code path and control is compiled out, leaving
+//! telemetry, alarms and connection status — and the client connects with
+//! the observer role, so the server enforces the same restriction
+//! independently of what the binary can send.
+
+pub mod age;
+pub mod audio;
+pub mod connection;
+pub mod dispatch;
+pub mod format;
+pub mod latency;
+pub mod logger;
+pub mod notes;
+pub mod palette;
+pub mod persist;
+pub mod query;
+pub mod remote;
+pub mod render;
+pub mod session;
+pub mod settings;
+pub mod telemetry;
+
+use age::AgeTracker;
+use audio::{AudioCues, Cue};
+use connection::{ConnectionManager, ConnectionStatus};
+use format::Formatter;
+use latency::LatencyMonitor;
+use logger::LoggerApp;
+use notes::NotesApp;
+use palette::Palette;
+use persist::Persistence;
+use rctrl_api::prelude::*;
+use remote::RemoteApp;
+use render::RenderGovernor;
+use session::EventKind;
+use settings::SettingsApp;
+use telemetry::TelemetryApp;
+
+/// Which app panel is currently shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AppView {
+    Remote,
+    Telemetry,
+    Logger,
+    /// Operator shift coordination, not public telemetry; absent in viewer
+    /// builds.
+    #[cfg(not(feature = "viewer"))]
+    Notes,
+    Session,
+    Settings,
+}
+
+/// Top level GUI state.
+pub struct Gui {
+    conn: ConnectionManager,
+    view: AppView,
+    format: Formatter,
+    age: AgeTracker,
+    audio: AudioCues,
+    /// Connection state last frame, to sound the cue on the transition.
+    was_connected: bool,
+    latency: LatencyMonitor,
+    /// Two-person gate state from the server; `None` until one arrives.
+    confirmation: Option<ConfirmationState>,
+    render: RenderGovernor,
+    palette: Palette,
+    remote: RemoteApp,
+    telemetry: TelemetryApp,
+    logger: LoggerApp,
+    notes: NotesApp,
+    settings: SettingsApp,
+    /// Telemetry persistence across refreshes; only active in wasm builds.
+    persist: Persistence,
+}
+
+impl Gui {
+    pub fn new(server_url: &str) -> Self {
+        Self {
+            conn: ConnectionManager::new(server_url),
+            view: AppView::Remote,
+            format: Formatter::default(),
+            age: AgeTracker::default(),
+            audio: AudioCues::default(),
+            was_connected: false,
+            latency: LatencyMonitor::default(),
+            confirmation: None,
+            render: RenderGovernor::default(),
+            palette: Palette::default(),
+            remote: RemoteApp::default(),
+            telemetry: TelemetryApp::default(),
+            logger: LoggerApp::default(),
+            notes: NotesApp::default(),
+            settings: SettingsApp::default(),
+            persist: Persistence::default(),
+        }
+    }
+
+    /// Install the shell's audio output for alarm and event cues.
+    pub fn set_audio_backend(&mut self, backend: Box<dyn audio::AudioBackend>) {
+        self.audio.set_backend(backend);
+    }
+
+    /// Drain the connection and draw the active panel.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        // Age indicators must keep counting up when no data arrives to
+        // trigger a repaint; the cadence is the governed repaint cap.
+        ctx.request_repaint_after(self.render.repaint_after());
+        self.render.begin_pass();
+        self.latency.tick(&mut self.conn);
+        if let Some(ws) = self.conn.ws_remote.as_mut() {
+            // The envelope is decoded once by the connection; the dispatcher
+            // fans each typed message out to the panels.
+            let mut sinks = dispatch::Sinks {
+                format: &mut self.format,
+                age: &mut self.age,
+                audio: &mut self.audio,
+                latency: &mut self.latency,
+                render: &mut self.render,
+                confirmation: &mut self.confirmation,
+                session: &mut self.conn.session,
+                remote: &mut self.remote,
+                telemetry: &mut self.telemetry,
+                logger: &mut self.logger,
+                notes: &mut self.notes,
+                settings: &mut self.settings,
+            };
+            while let Some(msg) = ws.try_recv() {
+                dispatch::dispatch(msg, &mut sinks);
+            }
+        }
+
+        // Wasm builds restore the previous session's telemetry once the
+        // storage read settles, then snapshot it periodically.
+        let now_s = ctx.input(|i| i.time);
+        if let Some(snapshot) = self.persist.poll_restore(now_s) {
+            self.telemetry.restore(snapshot);
+        }
+        self.persist.maybe_save(now_s, || self.telemetry.snapshot());
+
+        let connected = self
+            .conn
+            .ws_remote
+            .as_ref()
+            .is_some_and(|ws| ws.status == ConnectionStatus::Connected);
+        if self.was_connected && !connected {
+            self.audio.trigger(Cue::ConnectionLoss);
+        }
+        self.was_connected = connected;
+
+        let previous_view = self.view;
+        egui::TopBottomPanel::top("app_switcher").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, AppView::Remote, "Remote");
+                ui.selectable_value(&mut self.view, AppView::Telemetry, "Telemetry");
+                ui.selectable_value(&mut self.view, AppView::Logger, "Logger");
+                #[cfg(not(feature = "viewer"))]
+                ui.selectable_value(&mut self.view, AppView::Notes, "Notes");
+                ui.selectable_value(&mut self.view, AppView::Session, "Session");
+                ui.selectable_value(&mut self.view, AppView::Settings, "Settings");
+                ui.separator();
+                self.format.toggle_ui(ui);
+                ui.separator();
+                self.latency.ui(ui, self.palette);
+                ui.separator();
+                self.conn.status_ui(ui, self.palette);
+                // A hazardous command awaiting a second operator concerns
+                // every console, so it lives in the top bar.
+                if let Some(state) = &self.confirmation {
+                    if let Some(action) = &state.pending {
+                        ui.separator();
+                        palette::status_label(
+                            ui,
+                            self.palette,
+                            palette::Status::Warn,
+                            format!("CONFIRM {action} ({:.0} s)", state.expires_in_s),
+                        );
+                    }
+                }
+            });
+        });
+
+        if self.view != previous_view {
+            self.conn
+                .session
+                .record(EventKind::View, format!("{:?}", self.view));
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| match self.view {
+            AppView::Remote => {
+                self.remote
+                    .ui(ui, &self.format, &self.age, self.palette, &mut self.conn)
+            }
+            AppView::Telemetry => self.telemetry.ui(ui, self.palette, &mut self.conn),
+            AppView::Logger => self.logger.ui(ui),
+            #[cfg(not(feature = "viewer"))]
+            AppView::Notes => self.notes.ui(ui, &mut self.conn),
+            AppView::Session => self.conn.session.ui(ui, &self.format),
+            AppView::Settings => {
+                // Backend parameters are controls; viewer builds keep only
+                // the local appearance settings below.
+                #[cfg(not(feature = "viewer"))]
+                {
+                    self.settings.ui(ui, &mut self.conn);
+                    ui.separator();
+                }
+                self.palette.ui(ui);
+                ui.separator();
+                self.render.ui(ui);
+                ui.separator();
+                self.audio.ui(ui);
+                self.persist.ui(ui);
+            }
+        });
+        self.render.end_pass();
+    }
+}
@@ -0,0 +1,178 @@
+//! Log viewer panel.
+
+use crate::format;
+use rctrl_api::prelude::*;
+use std::collections::VecDeque;
+
+/// Log records the panel keeps; a ring so a noisy session costs a bounded
+/// amount of memory and the view stays scrollable.
+const MAX_RECORDS: usize = 500;
+
+/// Camera snapshots the panel keeps; older entries scroll out of relevance
+/// anyway, and a bounded list keeps a stuck trigger from growing the panel.
+const MAX_SNAPSHOTS: usize = 50;
+
+/// Backend errors the panel keeps, bounded for the same reason.
+const MAX_ERRORS: usize = 50;
+
+/// One stored camera snapshot announced by the backend.
+struct SnapshotEntry {
+    kind: String,
+    reference: String,
+    reason: String,
+}
+
+/// Displays log messages from the backend.
+#[derive(Default)]
+pub struct LoggerApp {
+    /// Received records, newest first, bounded to [`MAX_RECORDS`].
+    records: VecDeque<LogRecord>,
+    /// Least severe level still shown.
+    min_level: LogLevel,
+    /// Case-insensitive substring filter over source and message.
+    search: String,
+    snapshots: Vec<SnapshotEntry>,
+    errors: Vec<UserError>,
+}
+
+impl LoggerApp {
+    /// A structured log record arrived from the server.
+    pub fn on_log(&mut self, record: LogRecord) {
+        self.records.push_front(record);
+        self.records.truncate(MAX_RECORDS);
+    }
+
+    /// The backend reported a categorized error; newest first.
+    pub fn on_error(&mut self, error: UserError) {
+        self.errors.insert(0, error);
+        self.errors.truncate(MAX_ERRORS);
+    }
+
+    /// A camera snapshot was stored; newest first.
+    pub fn on_camera_event(&mut self, kind: String, reference: String, reason: String) {
+        self.snapshots.insert(
+            0,
+            SnapshotEntry {
+                kind,
+                reference,
+                reason,
+            },
+        );
+        self.snapshots.truncate(MAX_SNAPSHOTS);
+    }
+
+    /// Whether the current filters admit a record.
+    fn admits(&self, record: &LogRecord) -> bool {
+        if record.level < self.min_level {
+            return false;
+        }
+        let needle = self.search.trim().to_lowercase();
+        needle.is_empty()
+            || record.source.to_lowercase().contains(&needle)
+            || record.message.to_lowercase().contains(&needle)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Logger");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("log_level")
+                .selected_text(self.min_level.label())
+                .show_ui(ui, |ui| {
+                    for level in [LogLevel::Info, LogLevel::Warn, LogLevel::Error] {
+                        ui.selectable_value(&mut self.min_level, level, level.label());
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("search"));
+            let shown = self.records.iter().filter(|r| self.admits(r)).count();
+            ui.weak(format!("{shown} of {}", self.records.len()));
+        });
+        if self.records.is_empty() {
+            ui.weak("No log messages this session.");
+        } else {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("log_rows").striped(true).show(ui, |ui| {
+                    // Stored newest first, which is also the display order:
+                    // the top of the list is the most recent problem.
+                    for record in self.records.iter().filter(|r| self.admits(r)) {
+                        ui.label(record.level.label());
+                        ui.label(format::clock(record.unix_ms));
+                        ui.label(&record.source);
+                        ui.label(&record.message);
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+        if !self.errors.is_empty() {
+            ui.separator();
+            ui.label("Errors");
+            egui::Grid::new("error_rows").striped(true).show(ui, |ui| {
+                for error in &self.errors {
+                    ui.label(format!("{:?}", error.category));
+                    ui.label(&error.code);
+                    ui.label(&error.message);
+                    ui.end_row();
+                }
+            });
+        }
+        if self.snapshots.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.label("Camera snapshots");
+        egui::Grid::new("snapshot_rows").striped(true).show(ui, |ui| {
+            for entry in &self.snapshots {
+                ui.label(&entry.kind);
+                ui.label(&entry.reason);
+                // The reference is a URL or recorder path; open it in the
+                // viewer the platform associates with it.
+                ui.hyperlink_to("📷 snapshot", &entry.reference);
+                ui.end_row();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: LogLevel, source: &str, message: &str) -> LogRecord {
+        LogRecord {
+            level,
+            unix_ms: 0,
+            source: source.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn ring_stays_bounded_with_newest_records_first() {
+        let mut app = LoggerApp::default();
+        for i in 0..MAX_RECORDS + 3 {
+            app.on_log(record(LogLevel::Warn, "rctrl::psu", &format!("event {i}")));
+        }
+        assert_eq!(app.records.len(), MAX_RECORDS);
+        assert_eq!(app.records[0].message, format!("event {}", MAX_RECORDS + 2));
+    }
+
+    #[test]
+    fn filters_admit_by_severity_and_search() {
+        let mut app = LoggerApp::default();
+        let warn = record(LogLevel::Warn, "rctrl::psu", "readback timed out");
+        let error = record(LogLevel::Error, "rctrl::valve", "gpio drive failed");
+        assert!(app.admits(&warn) && app.admits(&error));
+
+        app.min_level = LogLevel::Error;
+        assert!(!app.admits(&warn));
+        assert!(app.admits(&error));
+
+        app.min_level = LogLevel::Info;
+        app.search = "GPIO".to_string();
+        assert!(!app.admits(&warn));
+        // Search is case-insensitive and matches source as well as message.
+        assert!(app.admits(&error));
+        app.search = "rctrl::psu".to_string();
+        assert!(app.admits(&warn));
+    }
+}
@@ -0,0 +1,65 @@
+//! Operator shift log panel.
+//!
+//! The server owns the log: the composer sends [`CmdEnum::AddNote`], stored
+//! entries come back as [`WsMessage::NoteAdded`] and the full history arrives
+//! in the connection snapshot, so an operator taking over mid-session reads
+//! the same handover notes the previous shift wrote.
+
+use crate::connection::ConnectionManager;
+use crate::format;
+use rctrl_api::prelude::*;
+
+/// Shift log entries plus the composer for new ones.
+#[derive(Default)]
+pub struct NotesApp {
+    notes: Vec<Note>,
+    draft: String,
+}
+
+impl NotesApp {
+    /// Seed from the connection snapshot; the server's log is authoritative.
+    pub fn apply_snapshot(&mut self, snapshot: &StateSnapshot) {
+        self.notes = snapshot.notes.clone();
+    }
+
+    /// A stored note arrived, either our own echoed back or one written by
+    /// another client before we connected.
+    pub fn on_note_added(&mut self, note: Note) {
+        self.notes.push(note);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, conn: &mut ConnectionManager) {
+        ui.heading("Shift log");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.draft);
+            let ready = !self.draft.trim().is_empty();
+            if ui
+                .add_enabled(ready, egui::Button::new("Add note"))
+                .clicked()
+            {
+                conn.send_remote(&WsMessage::Cmd(Cmd {
+                    cmd: CmdEnum::AddNote {
+                        text: self.draft.trim().to_string(),
+                    },
+                }));
+                self.draft.clear();
+            }
+        });
+        ui.separator();
+        if self.notes.is_empty() {
+            ui.weak("No notes this session.");
+            return;
+        }
+        // Newest first: the top of the list is what the incoming shift
+        // needs to read.
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for note in self.notes.iter().rev() {
+                ui.horizontal(|ui| {
+                    ui.weak(format!("#{} {}", note.id, format::clock(note.unix_ms)));
+                    ui.label(&note.text);
+                });
+            }
+        });
+    }
+}
+
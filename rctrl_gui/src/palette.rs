@@ -0,0 +1,84 @@
+//! Status colors and redundant state encoding.
+//!
+//! Plain green/red readouts are indistinguishable to a red-green colorblind
+//! operator. Every status readout goes through [`status_label`]: the color
+//! comes from the selected palette — the colorblind-safe option uses the
+//! Okabe-Ito blue/orange/vermillion triple — and each status also carries a
+//! shape-coded glyph, so no state is conveyed by hue alone.
+
+/// Semantic severity of a status readout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Good,
+    Warn,
+    Bad,
+    /// Informational; rendered in the weak text color of the theme.
+    Neutral,
+}
+
+impl Status {
+    /// Glyph drawn before the text, distinguishable without color vision.
+    fn glyph(self) -> &'static str {
+        match self {
+            Status::Good => "✔",
+            Status::Warn => "▲",
+            Status::Bad => "✖",
+            Status::Neutral => "",
+        }
+    }
+}
+
+/// The selectable status color palettes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Default,
+    ColorblindSafe,
+}
+
+impl Palette {
+    pub fn color(self, ui: &egui::Ui, status: Status) -> egui::Color32 {
+        match (self, status) {
+            (_, Status::Neutral) => ui.visuals().weak_text_color(),
+            (Palette::Default, Status::Good) => egui::Color32::GREEN,
+            (Palette::Default, Status::Warn) => egui::Color32::YELLOW,
+            (Palette::Default, Status::Bad) => egui::Color32::RED,
+            (Palette::ColorblindSafe, Status::Good) => egui::Color32::from_rgb(0, 114, 178),
+            (Palette::ColorblindSafe, Status::Warn) => egui::Color32::from_rgb(230, 159, 0),
+            (Palette::ColorblindSafe, Status::Bad) => egui::Color32::from_rgb(213, 94, 0),
+        }
+    }
+
+    /// Palette selector for the settings panel.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Palette");
+        ui.horizontal(|ui| {
+            ui.radio_value(self, Palette::Default, "Default");
+            ui.radio_value(self, Palette::ColorblindSafe, "Colorblind safe");
+        });
+    }
+}
+
+/// Draw a status readout: palette color plus the status glyph.
+pub fn status_label(ui: &mut egui::Ui, palette: Palette, status: Status, text: impl Into<String>) {
+    let text = text.into();
+    let text = match status.glyph() {
+        "" => text,
+        glyph => format!("{glyph} {text}"),
+    };
+    ui.colored_label(palette.color(ui, status), text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_neutral_statuses_carry_distinct_glyphs() {
+        let glyphs = [Status::Good, Status::Warn, Status::Bad].map(Status::glyph);
+        for (i, glyph) in glyphs.iter().enumerate() {
+            assert!(!glyph.is_empty());
+            assert!(!glyphs[..i].contains(glyph));
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Persistence of recent telemetry across browser refreshes.
+//!
+//! An accidental refresh mid-test used to wipe everything the GUI had
+//! received. In wasm builds the live telemetry rings are periodically
+//! snapshotted into IndexedDB — chunked so each write stays small, bounded
+//! so storage cannot grow past [`MAX_CHUNKS`] — and read back once on the
+//! next startup, so the plots resume with the recent history instead of
+//! blank. Restore strictly precedes the first save, so a fresh session
+//! cannot clobber the stored one before it has been read.
+//!
+//! Native builds keep the same [`Persistence`] interface compiled down to a
+//! no-op: a desktop shell does not lose its process on a refresh, and its
+//! history belongs to influx anyway.
+//!
+//! The chunk codec is platform-neutral and tested natively; only the
+//! IndexedDB glue is wasm-specific.
+
+// The codec's only non-test caller is the wasm half.
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+use serde::{Deserialize, Serialize};
+
+/// Seconds between snapshots once restore has settled.
+#[cfg(target_arch = "wasm32")]
+const SAVE_INTERVAL_S: f64 = 5.0;
+
+/// Seconds to wait for the IndexedDB read before giving up and starting the
+/// session empty; a wedged browser storage layer must not block saves
+/// forever.
+#[cfg(target_arch = "wasm32")]
+const LOAD_TIMEOUT_S: f64 = 3.0;
+
+/// Bytes per stored chunk.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Chunks storage may hold (2 MiB); a snapshot that would exceed this is
+/// dropped rather than written, keeping the bound hard.
+const MAX_CHUNKS: usize = 32;
+
+/// Serializable image of the telemetry panel's live rings.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TelemetrySnapshot {
+    /// `(signal, [mission time s, value])` traces, oldest sample first.
+    pub traces: Vec<(String, Vec<[f64; 2]>)>,
+}
+
+/// Encode a snapshot into storage chunks; `None` when it would exceed the
+/// storage bound.
+fn encode_chunks(snapshot: &TelemetrySnapshot) -> Option<Vec<Vec<u8>>> {
+    let bytes = bincode::serialize(snapshot).ok()?;
+    if bytes.len() > MAX_CHUNKS * CHUNK_BYTES {
+        return None;
+    }
+    Some(bytes.chunks(CHUNK_BYTES).map(<[u8]>::to_vec).collect())
+}
+
+/// Decode stored chunks back into a snapshot; `None` on any corruption —
+/// a bad read starts the session empty rather than wedging startup.
+fn decode_chunks(chunks: &[Vec<u8>]) -> Option<TelemetrySnapshot> {
+    bincode::deserialize(&chunks.concat()).ok()
+}
+
+/// Drives the restore-then-save cycle; owned by [`crate::Gui`], which polls
+/// it every frame with egui's clock.
+// Braced rather than a unit struct so both platforms construct it through
+// `Default` the same way.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+pub struct Persistence {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Persistence {
+    pub fn poll_restore(&mut self, _now_s: f64) -> Option<TelemetrySnapshot> {
+        None
+    }
+
+    pub fn maybe_save(&mut self, _now_s: f64, _snapshot: impl FnOnce() -> TelemetrySnapshot) {}
+
+    pub fn ui(&mut self, _ui: &mut egui::Ui) {}
+}
+
+/// State of the one-shot IndexedDB read, shared with its callbacks.
+#[cfg(target_arch = "wasm32")]
+enum LoadState {
+    /// Read not started yet.
+    Idle,
+    /// Read in flight.
+    Pending,
+    /// Stored chunks, in key order.
+    Ready(Vec<Vec<u8>>),
+    /// Storage unavailable, empty or unreadable.
+    Failed,
+}
+
+/// Drives the restore-then-save cycle; owned by [`crate::Gui`], which polls
+/// it every frame with egui's clock.
+#[cfg(target_arch = "wasm32")]
+pub struct Persistence {
+    loaded: std::rc::Rc<std::cell::RefCell<LoadState>>,
+    /// When the read was kicked off, for the give-up timeout.
+    started_at_s: Option<f64>,
+    /// Restore settled (applied, failed or timed out); saves only run after.
+    restored: bool,
+    last_save_s: f64,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Default for Persistence {
+    fn default() -> Self {
+        Self {
+            loaded: std::rc::Rc::new(std::cell::RefCell::new(LoadState::Idle)),
+            started_at_s: None,
+            restored: false,
+            last_save_s: 0.0,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Persistence {
+    /// Advance the startup read; returns the stored snapshot exactly once,
+    /// when the read completes.
+    pub fn poll_restore(&mut self, now_s: f64) -> Option<TelemetrySnapshot> {
+        if self.restored {
+            return None;
+        }
+        let state = std::mem::replace(&mut *self.loaded.borrow_mut(), LoadState::Pending);
+        match state {
+            LoadState::Idle => {
+                self.started_at_s = Some(now_s);
+                idb::load(self.loaded.clone());
+                None
+            }
+            LoadState::Pending => {
+                if now_s - self.started_at_s.unwrap_or(now_s) > LOAD_TIMEOUT_S {
+                    self.restored = true;
+                }
+                None
+            }
+            LoadState::Ready(chunks) => {
+                self.restored = true;
+                decode_chunks(&chunks)
+            }
+            LoadState::Failed => {
+                self.restored = true;
+                None
+            }
+        }
+    }
+
+    /// Snapshot and store the telemetry rings when the interval has elapsed;
+    /// the closure only runs on a due save.
+    pub fn maybe_save(&mut self, now_s: f64, snapshot: impl FnOnce() -> TelemetrySnapshot) {
+        if !self.restored || now_s - self.last_save_s < SAVE_INTERVAL_S {
+            return;
+        }
+        self.last_save_s = now_s;
+        if let Some(chunks) = encode_chunks(&snapshot()) {
+            idb::save(chunks);
+        }
+    }
+
+    /// Storage section of the settings panel.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.separator();
+        ui.label("Stored telemetry");
+        if ui.button("Clear stored telemetry").clicked() {
+            idb::clear();
+        }
+        // Periodic saves resume afterwards; clearing discards what a refresh
+        // would otherwise restore right now.
+        ui.weak("Recent history kept in this browser for refresh recovery.");
+    }
+}
+
+/// IndexedDB glue: one database, one object store of byte chunks keyed by
+/// index. Every operation is fire-and-forget off the frame loop; failures
+/// surface as a missing restore, never as a panic.
+#[cfg(target_arch = "wasm32")]
+mod idb {
+    use super::LoadState;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::{JsCast, JsValue};
+
+    const DB_NAME: &str = "rctrl_gui";
+    const STORE: &str = "telemetry_chunks";
+
+    /// Open (creating on first use) the database and hand it to `on_db`.
+    fn open(on_db: Box<dyn FnOnce(web_sys::IdbDatabase)>, on_fail: Box<dyn FnOnce()>) {
+        let factory = web_sys::window().and_then(|w| w.indexed_db().ok().flatten());
+        let Some(factory) = factory else {
+            on_fail();
+            return;
+        };
+        let Ok(request) = factory.open_with_u32(DB_NAME, 1) else {
+            on_fail();
+            return;
+        };
+        {
+            let request = request.clone();
+            let upgrade = Closure::once_into_js(move || {
+                if let Ok(db) = request
+                    .result()
+                    .and_then(|r| r.dyn_into::<web_sys::IdbDatabase>())
+                {
+                    if !db.object_store_names().contains(STORE) {
+                        let _ = db.create_object_store(STORE);
+                    }
+                }
+            });
+            request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+        }
+        {
+            let request_in = request.clone();
+            let success = Closure::once_into_js(move || {
+                match request_in
+                    .result()
+                    .ok()
+                    .and_then(|r| r.dyn_into::<web_sys::IdbDatabase>().ok())
+                {
+                    Some(db) => on_db(db),
+                    None => on_fail(),
+                }
+            });
+            request.set_onsuccess(Some(success.unchecked_ref()));
+        }
+    }
+
+    /// Replace the stored chunks with `chunks`.
+    pub(super) fn save(chunks: Vec<Vec<u8>>) {
+        open(
+            Box::new(move |db| {
+                let Ok(tx) = db.transaction_with_str_and_mode(
+                    STORE,
+                    web_sys::IdbTransactionMode::Readwrite,
+                ) else {
+                    return;
+                };
+                let Ok(store) = tx.object_store(STORE) else {
+                    return;
+                };
+                let _ = store.clear();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let value = js_sys::Uint8Array::from(chunk.as_slice());
+                    let _ = store.put_with_key(&value, &JsValue::from(i as u32));
+                }
+            }),
+            Box::new(|| {}),
+        );
+    }
+
+    /// Read every stored chunk into `slot`, in key order.
+    pub(super) fn load(slot: Rc<RefCell<LoadState>>) {
+        let fail_slot = slot.clone();
+        open(
+            Box::new(move |db| {
+                let request = db
+                    .transaction_with_str(STORE)
+                    .and_then(|tx| tx.object_store(STORE))
+                    .and_then(|store| store.get_all());
+                let Ok(request) = request else {
+                    *slot.borrow_mut() = LoadState::Failed;
+                    return;
+                };
+                let error_slot = slot.clone();
+                {
+                    let request = request.clone();
+                    let done = Closure::once_into_js(move || {
+                        let chunks = request
+                            .result()
+                            .ok()
+                            .and_then(|r| r.dyn_into::<js_sys::Array>().ok())
+                            .map(|array| {
+                                array
+                                    .iter()
+                                    .map(|value| js_sys::Uint8Array::new(&value).to_vec())
+                                    .collect::<Vec<_>>()
+                            });
+                        *slot.borrow_mut() = match chunks {
+                            Some(chunks) if !chunks.is_empty() => LoadState::Ready(chunks),
+                            _ => LoadState::Failed,
+                        };
+                    });
+                    request.set_onsuccess(Some(done.unchecked_ref()));
+                }
+                let error = Closure::once_into_js(move || {
+                    *error_slot.borrow_mut() = LoadState::Failed;
+                });
+                request.set_onerror(Some(error.unchecked_ref()));
+            }),
+            Box::new(move || *fail_slot.borrow_mut() = LoadState::Failed),
+        );
+    }
+
+    /// Delete every stored chunk.
+    pub(super) fn clear() {
+        open(
+            Box::new(|db| {
+                if let Ok(store) = db
+                    .transaction_with_str_and_mode(STORE, web_sys::IdbTransactionMode::Readwrite)
+                    .and_then(|tx| tx.object_store(STORE))
+                {
+                    let _ = store.clear();
+                }
+            }),
+            Box::new(|| {}),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(samples: usize) -> TelemetrySnapshot {
+        TelemetrySnapshot {
+            traces: vec![(
+                "pressure".to_string(),
+                (0..samples).map(|i| [i as f64 * 0.01, i as f64]).collect(),
+            )],
+        }
+    }
+
+    #[test]
+    fn chunk_codec_roundtrips_across_chunk_boundaries() {
+        // Large enough to need several chunks.
+        let original = snapshot(3 * CHUNK_BYTES / 16);
+        let chunks = encode_chunks(&original).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= CHUNK_BYTES));
+        assert_eq!(decode_chunks(&chunks), Some(original));
+    }
+
+    #[test]
+    fn oversized_snapshots_and_corrupt_chunks_are_rejected() {
+        assert_eq!(encode_chunks(&snapshot(MAX_CHUNKS * CHUNK_BYTES / 16)), None);
+
+        let mut chunks = encode_chunks(&snapshot(4)).unwrap();
+        chunks.last_mut().unwrap().pop();
+        assert_eq!(decode_chunks(&chunks), None);
+    }
+}
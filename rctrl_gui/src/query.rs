@@ -0,0 +1,116 @@
+//! Ad-hoc Flux query editor.
+//!
+//! Lets an operator run quick historical queries without leaving the GUI or
+//! holding influx credentials: the query travels to rctrl as
+//! [`WsMessage::FluxQuery`] and the flattened table comes back as
+//! [`WsMessage::FluxResult`].
+
+use crate::connection::ConnectionManager;
+use crate::palette::{self, Palette, Status};
+use rctrl_api::prelude::*;
+
+/// Queries remembered for re-running, most recent first.
+const HISTORY_LIMIT: usize = 10;
+/// Result rows rendered before the table is truncated.
+const DISPLAY_ROW_LIMIT: usize = 200;
+
+/// Editor, history and last result of ad-hoc Flux queries.
+#[derive(Default)]
+pub struct QueryApp {
+    editor: String,
+    history: Vec<String>,
+    result: Option<Result<FluxTable, String>>,
+    /// A query is in flight and no result has arrived yet.
+    pending: bool,
+}
+
+impl QueryApp {
+    pub fn on_result(&mut self, result: Result<FluxTable, String>) {
+        self.pending = false;
+        self.result = Some(result);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, palette: Palette, conn: &mut ConnectionManager) {
+        ui.add(
+            egui::TextEdit::multiline(&mut self.editor)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .hint_text("from(bucket: \"rctrl\") |> range(start: -1h)"),
+        );
+
+        ui.horizontal(|ui| {
+            let runnable = !self.editor.trim().is_empty() && !self.pending;
+            if ui
+                .add_enabled(runnable, egui::Button::new("Run"))
+                .clicked()
+            {
+                self.run(conn);
+            }
+            if self.pending {
+                ui.spinner();
+            }
+        });
+
+        if !self.history.is_empty() {
+            ui.collapsing("History", |ui| {
+                for query in &self.history {
+                    // First line as the label; the full text loads on click.
+                    let label = query.lines().next().unwrap_or_default();
+                    if ui.button(label).clicked() {
+                        self.editor = query.clone();
+                    }
+                }
+            });
+        }
+
+        match &self.result {
+            Some(Ok(table)) => result_table(ui, table),
+            Some(Err(e)) => {
+                palette::status_label(ui, palette, Status::Bad, e);
+            }
+            None => {}
+        }
+    }
+
+    fn run(&mut self, conn: &mut ConnectionManager) {
+        let query = self.editor.trim().to_string();
+        if conn.ws_remote.is_some() {
+            conn.send_remote(&WsMessage::FluxQuery(query.clone()));
+            self.pending = true;
+            self.result = None;
+        }
+        self.history.retain(|q| *q != query);
+        self.history.insert(0, query);
+        self.history.truncate(HISTORY_LIMIT);
+    }
+}
+
+fn result_table(ui: &mut egui::Ui, table: &FluxTable) {
+    if table.columns.is_empty() {
+        ui.weak("empty result");
+        return;
+    }
+    egui::ScrollArea::both().show(ui, |ui| {
+        egui::Grid::new("flux_result")
+            .striped(true)
+            .show(ui, |ui| {
+                for column in &table.columns {
+                    ui.strong(column);
+                }
+                ui.end_row();
+                for row in table.rows.iter().take(DISPLAY_ROW_LIMIT) {
+                    for cell in row {
+                        ui.label(cell);
+                    }
+                    ui.end_row();
+                }
+            });
+        if table.rows.len() > DISPLAY_ROW_LIMIT {
+            ui.weak(format!(
+                "{} more rows not shown",
+                table.rows.len() - DISPLAY_ROW_LIMIT
+            ));
+        }
+    });
+}
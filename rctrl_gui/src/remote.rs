@@ -0,0 +1,213 @@
+//! Remote control panel.
+
+use crate::age::AgeTracker;
+use crate::connection::ConnectionManager;
+use crate::format::{self, Formatter};
+use crate::palette::{self, Palette, Status};
+use rctrl_api::prelude::*;
+
+/// Seconds the pre-test quality check samples for.
+#[cfg(not(feature = "viewer"))]
+const QUALITY_CHECK_DURATION_S: u16 = 10;
+
+/// Live view of the stand state.
+#[derive(Default)]
+pub struct RemoteApp {
+    last: Option<Data>,
+    /// Number of data gaps seen this session.
+    gaps_seen: u64,
+    /// Most recent data quality report.
+    quality: Option<QualityReport>,
+    /// A check is in flight; only control builds can start one.
+    #[cfg(not(feature = "viewer"))]
+    quality_pending: bool,
+    /// Most recent power supply readback, kept separately from `last` since
+    /// supply telemetry arrives in its own frames at its own rate.
+    psu: Option<(f64, f64)>,
+}
+
+impl RemoteApp {
+    /// Apply a full state snapshot received on (re)connection, replacing
+    /// whatever stale state the panel held.
+    pub fn apply_snapshot(&mut self, snapshot: &StateSnapshot) {
+        self.last = snapshot.last_data.clone();
+        self.gaps_seen = snapshot.gaps;
+    }
+
+    /// Apply an incoming telemetry frame.
+    pub fn on_data(&mut self, data: &Data) {
+        if data.gap {
+            self.gaps_seen += 1;
+        }
+        if let (Some(volts), Some(amps)) = (data.psu_volts, data.psu_amps) {
+            self.psu = Some((volts, amps));
+        }
+        self.last = Some(data.clone());
+    }
+
+    /// Record a completed data quality check.
+    pub fn on_quality_report(&mut self, report: QualityReport) {
+        self.quality = Some(report);
+        #[cfg(not(feature = "viewer"))]
+        {
+            self.quality_pending = false;
+        }
+    }
+
+    #[cfg_attr(feature = "viewer", allow(unused_variables))]
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        fmt: &Formatter,
+        ages: &AgeTracker,
+        palette: Palette,
+        conn: &mut ConnectionManager,
+    ) {
+        ui.heading("Remote");
+        match &self.last {
+            Some(data) => {
+                ui.label(format!("Last frame: {}", fmt.time(data.time)));
+                ui.horizontal(|ui| {
+                    if let Some(pressure) = data.pressure {
+                        ui.label(format!("Pressure: {} bar", format::number(pressure, 2)));
+                    } else {
+                        ui.label("Pressure: ---");
+                    }
+                    ages.ui(ui, palette, "pressure");
+                });
+            }
+            None => {
+                ui.label("No data received yet.");
+            }
+        }
+        if self.gaps_seen > 0 {
+            palette::status_label(
+                ui,
+                palette,
+                Status::Warn,
+                format!("Data gaps this session: {}", self.gaps_seen),
+            );
+        }
+
+        ui.separator();
+        ui.heading("Valves");
+        ui.horizontal(|ui| {
+            let (commanded, feedback) = match &self.last {
+                Some(data) => (data.valve, data.valve_feedback),
+                None => (None, None),
+            };
+            let state = |state: Option<bool>| match state {
+                Some(true) => "OPEN",
+                Some(false) => "closed",
+                None => "---",
+            };
+            ui.label(format!(
+                "valve: commanded {}, confirmed {}",
+                state(commanded),
+                state(feedback)
+            ));
+            // Commands are a control surface; viewer builds show state only.
+            // The buttons grey out while the connection is down so a click
+            // can never silently go nowhere.
+            #[cfg(not(feature = "viewer"))]
+            {
+                let connected = conn
+                    .ws_remote
+                    .as_ref()
+                    .is_some_and(|ws| ws.status == crate::connection::ConnectionStatus::Connected);
+                if ui
+                    .add_enabled(connected, egui::Button::new("Open"))
+                    .clicked()
+                {
+                    conn.send_remote(&WsMessage::Cmd(Cmd {
+                        cmd: CmdEnum::ValveOpen,
+                    }));
+                }
+                if ui
+                    .add_enabled(connected, egui::Button::new("Close"))
+                    .clicked()
+                {
+                    conn.send_remote(&WsMessage::Cmd(Cmd {
+                        cmd: CmdEnum::ValveClose,
+                    }));
+                }
+            }
+        });
+
+        ui.separator();
+        ui.heading("Instrumentation power");
+        ui.horizontal(|ui| {
+            match self.psu {
+                Some((volts, amps)) => {
+                    ui.label(format!(
+                        "{} V  {} A",
+                        format::number(volts, 2),
+                        format::number(amps, 3)
+                    ));
+                }
+                None => {
+                    ui.label("No supply telemetry.");
+                }
+            }
+            #[cfg(not(feature = "viewer"))]
+            {
+                if ui.button("Output on").clicked() {
+                    conn.send_remote(&WsMessage::Cmd(Cmd {
+                        cmd: CmdEnum::PsuOutput { enable: true },
+                    }));
+                }
+                if ui.button("Output off").clicked() {
+                    conn.send_remote(&WsMessage::Cmd(Cmd {
+                        cmd: CmdEnum::PsuOutput { enable: false },
+                    }));
+                }
+            }
+        });
+
+        ui.separator();
+        ui.heading("Data quality");
+        #[cfg(not(feature = "viewer"))]
+        ui.horizontal(|ui| {
+            let label = if self.quality_pending {
+                "Checking..."
+            } else {
+                "Run check"
+            };
+            let clicked = ui
+                .add_enabled(!self.quality_pending, egui::Button::new(label))
+                .clicked();
+            if clicked && conn.ws_remote.is_some() {
+                conn.send_remote(&WsMessage::Cmd(Cmd {
+                    cmd: CmdEnum::DataQualityCheck {
+                        duration_s: QUALITY_CHECK_DURATION_S,
+                    },
+                }));
+                self.quality_pending = true;
+            }
+        });
+        if let Some(report) = &self.quality {
+            egui::Grid::new("quality_rows").striped(true).show(ui, |ui| {
+                for channel in &report.channels {
+                    let (status, text) = verdict_label(channel.verdict);
+                    ui.label(channel.channel.to_string());
+                    palette::status_label(ui, palette, status, text);
+                    ui.label(format!("noise {}", format::number(channel.noise_floor, 3)));
+                    ui.label(format!("offset {}", format::number(channel.offset, 3)));
+                    ui.label(format!(
+                        "dropout {} %",
+                        format::number(channel.dropout_rate * 100.0, 1)
+                    ));
+                    ui.end_row();
+                }
+            });
+        }
+    }
+}
+
+fn verdict_label(verdict: QualityVerdict) -> (Status, &'static str) {
+    match verdict {
+        QualityVerdict::Pass => (Status::Good, "PASS"),
+        QualityVerdict::Warn => (Status::Warn, "WARN"),
+        QualityVerdict::Fail => (Status::Bad, "FAIL"),
+    }
+}
@@ -0,0 +1,143 @@
+//! Render-rate governor protecting UI responsiveness at high data rates.
+//!
+//! Every incoming frame could trigger a repaint and a per-panel update; at
+//! 100 Hz that leaves no headroom for interaction. The governor caps how
+//! often the GUI asks for a repaint and decimates panel updates — the
+//! broadcast already carries the latest value, so skipped frames cost
+//! nothing but intermediate points. It also measures the time one UI pass
+//! actually takes, so the operator can see whether a laggy panel is the
+//! renderer's fault or the link's.
+
+use serde::{Deserialize, Serialize};
+use web_time::{Duration, Instant};
+
+/// Smoothing factor of the frame time average.
+const FRAME_TIME_ALPHA: f32 = 0.1;
+
+/// Persistable render preferences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderSettings {
+    /// Upper bound on repaints requested per second.
+    pub max_fps: u32,
+    /// The remote panel sees every Nth frame.
+    pub remote_divisor: u32,
+    /// The telemetry plots see every Nth frame.
+    pub telemetry_divisor: u32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            // Matches the 100 ms repaint cadence the GUI always ran at.
+            max_fps: 10,
+            remote_divisor: 1,
+            telemetry_divisor: 1,
+        }
+    }
+}
+
+/// Caps repaint frequency, decimates per-panel updates and measures the UI
+/// frame time.
+#[derive(Default)]
+pub struct RenderGovernor {
+    pub settings: RenderSettings,
+    /// Start of the pass being measured.
+    pass_started: Option<Instant>,
+    /// Smoothed duration of one UI pass, in milliseconds.
+    frame_time_ms: f32,
+    /// Incoming frames seen, for the per-panel divisors.
+    frames: u64,
+}
+
+impl RenderGovernor {
+    /// How long to wait before the next data-driven repaint.
+    pub fn repaint_after(&self) -> Duration {
+        Duration::from_secs(1) / self.settings.max_fps.clamp(1, 60)
+    }
+
+    /// Called once per incoming frame, before the panel admission checks.
+    pub fn on_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    /// Whether the current frame reaches the remote panel.
+    pub fn admit_remote(&self) -> bool {
+        self.frames.is_multiple_of(u64::from(self.settings.remote_divisor.max(1)))
+    }
+
+    /// Whether the current frame reaches the telemetry plots.
+    pub fn admit_telemetry(&self) -> bool {
+        self.frames
+            .is_multiple_of(u64::from(self.settings.telemetry_divisor.max(1)))
+    }
+
+    /// Bracket one UI pass; the span between the calls is what the frame
+    /// time display shows.
+    pub fn begin_pass(&mut self) {
+        self.pass_started = Some(Instant::now());
+    }
+
+    pub fn end_pass(&mut self) {
+        let Some(started) = self.pass_started.take() else {
+            return;
+        };
+        let elapsed_ms = started.elapsed().as_secs_f32() * 1_000.0;
+        self.frame_time_ms += (elapsed_ms - self.frame_time_ms) * FRAME_TIME_ALPHA;
+    }
+
+    /// Settings section: repaint cap, per-panel decimation and the measured
+    /// frame time.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Rendering");
+        ui.horizontal(|ui| {
+            ui.label("Repaint cap (FPS)");
+            ui.add(egui::Slider::new(&mut self.settings.max_fps, 1..=60));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Remote panel: every Nth frame");
+            ui.add(egui::Slider::new(&mut self.settings.remote_divisor, 1..=50));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Telemetry plots: every Nth frame");
+            ui.add(egui::Slider::new(&mut self.settings.telemetry_divisor, 1..=50));
+        });
+        ui.label(format!("UI frame time: {:.1} ms", self.frame_time_ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divisors_decimate_per_panel_independently() {
+        let mut governor = RenderGovernor::default();
+        governor.settings.remote_divisor = 2;
+        governor.settings.telemetry_divisor = 5;
+
+        let mut remote = 0;
+        let mut telemetry = 0;
+        for _ in 0..100 {
+            governor.on_frame();
+            if governor.admit_remote() {
+                remote += 1;
+            }
+            if governor.admit_telemetry() {
+                telemetry += 1;
+            }
+        }
+        assert_eq!(remote, 50);
+        assert_eq!(telemetry, 20);
+    }
+
+    #[test]
+    fn repaint_interval_follows_the_fps_cap() {
+        let mut governor = RenderGovernor::default();
+        assert_eq!(governor.repaint_after(), Duration::from_millis(100));
+        governor.settings.max_fps = 50;
+        assert_eq!(governor.repaint_after(), Duration::from_millis(20));
+        // Out-of-range values clamp instead of dividing by zero.
+        governor.settings.max_fps = 0;
+        assert_eq!(governor.repaint_after(), Duration::from_secs(1));
+    }
+}
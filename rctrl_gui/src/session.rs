@@ -0,0 +1,157 @@
+//! Session recording of operator actions.
+//!
+//! Every interaction — panel switches, issued commands, and the backend's
+//! acknowledgements or rejections — is timestamped into a session log. The
+//! viewer replays the log event by event for post-test crew debriefs, and
+//! the whole session exports as JSON (copied to the clipboard) so it can be
+//! archived next to the telemetry recording.
+
+use crate::format::Formatter;
+use web_time::{Duration, Instant};
+
+/// What kind of interaction an event records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// The operator switched to another panel.
+    View,
+    /// A command or query was sent to rctrl.
+    Cmd,
+    /// The backend acknowledged an action (applied parameter, report).
+    Ack,
+    /// The backend rejected a command.
+    Rejection,
+}
+
+impl EventKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventKind::View => "view",
+            EventKind::Cmd => "cmd",
+            EventKind::Ack => "ack",
+            EventKind::Rejection => "rejection",
+        }
+    }
+}
+
+/// One recorded interaction.
+pub struct Event {
+    /// Time since the session log was created.
+    pub at: Duration,
+    pub kind: EventKind,
+    pub detail: String,
+}
+
+/// The session's interaction log and the viewer's replay state.
+pub struct SessionLog {
+    started: Instant,
+    events: Vec<Event>,
+    /// Events up to this index count as replayed in the viewer.
+    playhead: usize,
+}
+
+impl Default for SessionLog {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            events: Vec::new(),
+            playhead: 0,
+        }
+    }
+}
+
+impl SessionLog {
+    pub fn record(&mut self, kind: EventKind, detail: impl Into<String>) {
+        self.events.push(Event {
+            at: self.started.elapsed(),
+            kind,
+            detail: detail.into(),
+        });
+    }
+
+    /// The whole session as a JSON array of events.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("[");
+        for (i, event) in self.events.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!(
+                r#"{{"at_s":{:.3},"kind":"{}","detail":"{}"}}"#,
+                event.at.as_secs_f64(),
+                event.kind.as_str(),
+                json_escape(&event.detail)
+            ));
+        }
+        out.push(']');
+        out
+    }
+
+    /// Replay viewer: event table with a playhead, stepping through the
+    /// session in order.
+    pub fn ui(&mut self, ui: &mut egui::Ui, fmt: &Formatter) {
+        ui.heading("Session");
+
+        ui.horizontal(|ui| {
+            if ui.button("|<").clicked() {
+                self.playhead = 0;
+            }
+            if ui.button("Step").clicked() && self.playhead < self.events.len() {
+                self.playhead += 1;
+            }
+            if ui.button(">|").clicked() {
+                self.playhead = self.events.len();
+            }
+            ui.add(
+                egui::Slider::new(&mut self.playhead, 0..=self.events.len()).text("events"),
+            );
+            ui.separator();
+            if ui.button("Export JSON").clicked() {
+                ui.ctx().copy_text(self.to_json());
+            }
+        });
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("session_events")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("time");
+                    ui.strong("kind");
+                    ui.strong("detail");
+                    ui.end_row();
+                    for (i, event) in self.events.iter().enumerate() {
+                        // Events past the playhead have not "happened" yet in
+                        // the replay; dim them.
+                        let color = if i < self.playhead {
+                            ui.visuals().text_color()
+                        } else {
+                            ui.visuals().weak_text_color()
+                        };
+                        ui.colored_label(color, fmt.time(event.at));
+                        ui.colored_label(color, event.kind.as_str());
+                        ui.colored_label(color, &event.detail);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    s.replace('\\', r"\\").replace('"', r#"\""#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_events_as_json() {
+        let mut log = SessionLog::default();
+        log.record(EventKind::Cmd, r#"SetParam "x""#);
+        let json = log.to_json();
+        assert!(json.starts_with(r#"[{"at_s":"#));
+        assert!(json.contains(r#""kind":"cmd""#));
+        assert!(json.contains(r#"SetParam \"x\""#));
+        assert!(json.ends_with("}]"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Settings panel for runtime-adjustable backend parameters.
+//!
+//! Only parameters rctrl exposes through `Cmd::SetParam` appear here; the
+//! server validates every change and echoes the applied value back, so the
+//! "applied" column always shows what the backend is actually running with.
+
+use crate::connection::ConnectionManager;
+use rctrl_api::prelude::*;
+use std::collections::HashMap;
+
+/// Display metadata for each adjustable parameter.
+const PARAMS: &[(Param, &str, f64)] = &[
+    (Param::StreamDivisor, "Stream divisor", 1.0),
+    (Param::PlotRetentionS, "Plot retention (s)", 60.0),
+    (Param::AggregationWindow, "Aggregation window", 10.0),
+];
+
+/// Edits and applies runtime parameters.
+#[derive(Default)]
+pub struct SettingsApp {
+    /// Values the server has confirmed.
+    applied: HashMap<Param, f64>,
+    /// Local edits not yet sent or not yet confirmed.
+    edits: HashMap<Param, f64>,
+}
+
+impl SettingsApp {
+    /// Seed applied values from the connection snapshot.
+    pub fn apply_snapshot(&mut self, snapshot: &StateSnapshot) {
+        self.applied = snapshot.params.iter().copied().collect();
+    }
+
+    /// Record a server-confirmed parameter change.
+    pub fn on_param_applied(&mut self, param: Param, value: f64) {
+        self.applied.insert(param, value);
+        self.edits.remove(&param);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, conn: &mut ConnectionManager) {
+        ui.heading("Settings");
+        egui::Grid::new("settings_rows").striped(true).show(ui, |ui| {
+            ui.label("Parameter");
+            ui.label("Value");
+            ui.label("Applied");
+            ui.label("");
+            ui.end_row();
+
+            for &(param, label, default) in PARAMS {
+                let applied = self.applied.get(&param).copied();
+                let edit = self
+                    .edits
+                    .entry(param)
+                    .or_insert_with(|| applied.unwrap_or(default));
+
+                ui.label(label);
+                ui.add(egui::DragValue::new(edit).speed(1));
+                match applied {
+                    Some(value) => ui.label(format!("{value}")),
+                    None => ui.label("---"),
+                };
+                let value = *edit;
+                if ui.button("Apply").clicked() {
+                    conn.send_remote(&WsMessage::Cmd(Cmd {
+                        cmd: CmdEnum::SetParam { param, value },
+                    }));
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
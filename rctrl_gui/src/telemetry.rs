@@ -0,0 +1,515 @@
+//! Telemetry plotting panel.
+//!
+//! Long cold-flow tests run for hours; no plot can show the whole session at
+//! full rate. The panel keeps a fixed-size decimated store of the entire
+//! session — each bin holds the min/max envelope of the samples it covers,
+//! and the bin width doubles whenever the store fills — plus an overview
+//! strip that renders that envelope and lets the operator drag the zoom
+//! window across the session.
+//!
+//! The main plot shows the live tail: a bounded ring of recent samples per
+//! signal, with the plotted signals selectable and the stream pausable so a
+//! transient can be inspected while data keeps arriving underneath.
+
+use crate::connection::ConnectionManager;
+use crate::palette::Palette;
+use crate::query::QueryApp;
+use rctrl_api::prelude::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of min/max bins the session store holds.
+const STORE_BINS: usize = 512;
+/// Samples per bin before the first doubling.
+const INITIAL_BIN_SAMPLES: u32 = 4;
+/// Height of the overview strip in points.
+const STRIP_HEIGHT: f32 = 48.0;
+/// Samples each live ring holds; about three minutes at the decimated
+/// update rate the render governor admits.
+const LIVE_SAMPLES: usize = 2048;
+
+/// The signals the live plot offers, in display order.
+const SIGNALS: &[&str] = &[
+    "pressure",
+    "temperature",
+    "igniter_current",
+    "fc_pressure",
+    "fc_altitude",
+    "ambient_pressure",
+    "ambient_temperature",
+    "psu_volts",
+    "psu_amps",
+];
+
+/// Display-side smoothing applied to the live traces.
+///
+/// Smoothing is a rendering aid only — the rings, the session store and
+/// everything leaving this panel stay raw — and the legend names filtered
+/// traces as such so nobody mistakes them for data.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Smoothing {
+    #[default]
+    Off,
+    /// Trailing moving average over the window.
+    MovingAverage,
+    /// Exponential with `alpha = 2 / (window + 1)`, the usual equivalence.
+    Exponential,
+}
+
+impl Smoothing {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::MovingAverage => "moving average",
+            Self::Exponential => "exponential",
+        }
+    }
+}
+
+/// Apply a smoothing filter to a trace; times pass through untouched.
+fn smooth(points: &VecDeque<[f64; 2]>, smoothing: Smoothing, window: usize) -> Vec<[f64; 2]> {
+    match smoothing {
+        Smoothing::Off => points.iter().copied().collect(),
+        Smoothing::MovingAverage => {
+            let mut sum = 0.0;
+            let mut out = Vec::with_capacity(points.len());
+            for (i, point) in points.iter().enumerate() {
+                sum += point[1];
+                if i >= window {
+                    sum -= points[i - window][1];
+                }
+                out.push([point[0], sum / window.min(i + 1) as f64]);
+            }
+            out
+        }
+        Smoothing::Exponential => {
+            let alpha = 2.0 / (window as f64 + 1.0);
+            let mut state: Option<f64> = None;
+            points
+                .iter()
+                .map(|point| {
+                    let smoothed = match state {
+                        Some(prev) => prev + alpha * (point[1] - prev),
+                        None => point[1],
+                    };
+                    state = Some(smoothed);
+                    [point[0], smoothed]
+                })
+                .collect()
+        }
+    }
+}
+
+/// Min/max envelope of the samples a bin covers.
+#[derive(Clone, Copy)]
+struct Bin {
+    min: f64,
+    max: f64,
+}
+
+/// Fixed-memory decimated store of one channel over the whole session.
+///
+/// Pushing beyond [`STORE_BINS`] full bins merges adjacent pairs and doubles
+/// the per-bin sample count, so memory stays constant while resolution
+/// degrades gracefully as the session grows.
+pub struct SessionStore {
+    bins: Vec<Bin>,
+    /// Samples folded into one bin.
+    samples_per_bin: u32,
+    /// Samples folded into the bin currently being filled.
+    pending: u32,
+    /// Time of the first and last sample, for the strip's span.
+    first_at: Option<Duration>,
+    last_at: Option<Duration>,
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self {
+            bins: Vec::with_capacity(STORE_BINS),
+            samples_per_bin: INITIAL_BIN_SAMPLES,
+            pending: 0,
+            first_at: None,
+            last_at: None,
+        }
+    }
+}
+
+impl SessionStore {
+    pub fn push(&mut self, at: Duration, value: f64) {
+        self.first_at.get_or_insert(at);
+        self.last_at = Some(at);
+
+        if self.pending == 0 {
+            self.bins.push(Bin {
+                min: value,
+                max: value,
+            });
+        } else if let Some(bin) = self.bins.last_mut() {
+            bin.min = bin.min.min(value);
+            bin.max = bin.max.max(value);
+        }
+        self.pending += 1;
+        if self.pending == self.samples_per_bin {
+            self.pending = 0;
+        }
+
+        if self.bins.len() > STORE_BINS {
+            self.halve();
+        }
+    }
+
+    /// Merge adjacent bin pairs, doubling the per-bin sample count.
+    fn halve(&mut self) {
+        let mut merged = Vec::with_capacity(STORE_BINS);
+        for pair in self.bins.chunks(2) {
+            let mut bin = pair[0];
+            if let Some(second) = pair.get(1) {
+                bin.min = bin.min.min(second.min);
+                bin.max = bin.max.max(second.max);
+            }
+            merged.push(bin);
+        }
+        self.bins = merged;
+        self.samples_per_bin *= 2;
+        self.pending = 0;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// Value range across the whole session.
+    fn value_range(&self) -> (f64, f64) {
+        let min = self.bins.iter().map(|b| b.min).fold(f64::INFINITY, f64::min);
+        let max = self
+            .bins
+            .iter()
+            .map(|b| b.max)
+            .fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    }
+}
+
+/// Time series plots of incoming telemetry.
+pub struct TelemetryApp {
+    store: SessionStore,
+    /// Visible window as fractions of the session span.
+    window_start: f32,
+    window_end: f32,
+    /// Recent samples per signal as `[time s, value]`, parallel to
+    /// [`SIGNALS`].
+    live: Vec<VecDeque<[f64; 2]>>,
+    /// Which signals the live plot draws, parallel to [`SIGNALS`].
+    selected: Vec<bool>,
+    /// Frozen for inspection; the session store keeps filling underneath.
+    paused: bool,
+    /// Display-side filter on the live traces; never touches the data.
+    smoothing: Smoothing,
+    /// Filter window in samples.
+    smoothing_window: usize,
+    /// Draw the raw trace faintly behind the smoothed one.
+    show_raw: bool,
+    pub query: QueryApp,
+}
+
+impl Default for TelemetryApp {
+    fn default() -> Self {
+        Self {
+            store: SessionStore::default(),
+            window_start: 0.0,
+            window_end: 1.0,
+            live: SIGNALS.iter().map(|_| VecDeque::new()).collect(),
+            selected: SIGNALS.iter().map(|signal| *signal == "pressure").collect(),
+            paused: false,
+            smoothing: Smoothing::default(),
+            smoothing_window: 16,
+            show_raw: true,
+            query: QueryApp::default(),
+        }
+    }
+}
+
+impl TelemetryApp {
+    /// Image of the live rings for [`crate::persist`].
+    pub fn snapshot(&self) -> crate::persist::TelemetrySnapshot {
+        crate::persist::TelemetrySnapshot {
+            traces: SIGNALS
+                .iter()
+                .zip(&self.live)
+                .filter(|(_, ring)| !ring.is_empty())
+                .map(|(signal, ring)| (signal.to_string(), ring.iter().copied().collect()))
+                .collect(),
+        }
+    }
+
+    /// Refill the live rings from a stored snapshot. Signals the build does
+    /// not know are dropped; oversized traces keep only the newest samples;
+    /// the restored pressure trace re-seeds the session envelope so the
+    /// overview strip is not blank either.
+    pub fn restore(&mut self, snapshot: crate::persist::TelemetrySnapshot) {
+        for (signal, points) in snapshot.traces {
+            let Some(i) = SIGNALS.iter().position(|s| *s == signal) else {
+                continue;
+            };
+            if signal == "pressure" {
+                for point in &points {
+                    self.store.push(Duration::from_secs_f64(point[0].max(0.0)), point[1]);
+                }
+            }
+            let skip = points.len().saturating_sub(LIVE_SAMPLES);
+            self.live[i] = points.into_iter().skip(skip).collect();
+        }
+    }
+
+    pub fn on_data(&mut self, data: &Data) {
+        if let Some(pressure) = data.pressure {
+            self.store.push(data.time, pressure);
+        }
+        if self.paused {
+            return;
+        }
+        for (signal, buffer) in SIGNALS.iter().zip(&mut self.live) {
+            if let Some(value) = data.channel_value(signal) {
+                if buffer.len() == LIVE_SAMPLES {
+                    buffer.pop_front();
+                }
+                buffer.push_back([data.time.as_secs_f64(), value]);
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "viewer", allow(unused_variables))]
+    pub fn ui(&mut self, ui: &mut egui::Ui, palette: Palette, conn: &mut ConnectionManager) {
+        ui.heading("Telemetry");
+
+        // Ad-hoc queries reach the backend and are a control surface; viewer
+        // builds show plots only.
+        #[cfg(not(feature = "viewer"))]
+        ui.collapsing("Flux query", |ui| self.query.ui(ui, palette, conn));
+
+        ui.horizontal_wrapped(|ui| {
+            let label = if self.paused { "▶ Resume" } else { "⏸ Pause" };
+            if ui.button(label).clicked() {
+                self.paused = !self.paused;
+            }
+            ui.separator();
+            for (signal, selected) in SIGNALS.iter().zip(&mut self.selected) {
+                ui.toggle_value(selected, *signal);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Smoothing");
+            egui::ComboBox::from_id_salt("smoothing")
+                .selected_text(self.smoothing.label())
+                .show_ui(ui, |ui| {
+                    for option in [
+                        Smoothing::Off,
+                        Smoothing::MovingAverage,
+                        Smoothing::Exponential,
+                    ] {
+                        ui.selectable_value(&mut self.smoothing, option, option.label());
+                    }
+                });
+            if self.smoothing != Smoothing::Off {
+                ui.add(
+                    egui::Slider::new(&mut self.smoothing_window, 2..=128)
+                        .text("window [samples]"),
+                );
+                ui.checkbox(&mut self.show_raw, "raw behind");
+            }
+        });
+
+        let strip_reserve = STRIP_HEIGHT + ui.spacing().item_spacing.y;
+        let plot_height = (ui.available_height() - strip_reserve).max(0.0);
+        // Bounds follow the data by default, so the plot autoscales to
+        // whatever combination of signals is selected.
+        let raw_color = ui.visuals().weak_text_color();
+        egui_plot::Plot::new("live_plot")
+            .height(plot_height)
+            .legend(egui_plot::Legend::default())
+            .x_axis_label("mission time [s]")
+            .show(ui, |plot_ui| {
+                for (i, signal) in SIGNALS.iter().enumerate() {
+                    if !self.selected[i] || self.live[i].is_empty() {
+                        continue;
+                    }
+                    if self.smoothing == Smoothing::Off {
+                        let points: egui_plot::PlotPoints =
+                            self.live[i].iter().copied().collect();
+                        plot_ui.line(egui_plot::Line::new(points).name(*signal));
+                        continue;
+                    }
+                    // Filtered display is labeled as such; the raw trace sits
+                    // faintly behind it when enabled.
+                    if self.show_raw {
+                        let raw: egui_plot::PlotPoints = self.live[i].iter().copied().collect();
+                        plot_ui.line(
+                            egui_plot::Line::new(raw)
+                                .name(format!("{signal} (raw)"))
+                                .color(raw_color)
+                                .width(0.5),
+                        );
+                    }
+                    let smoothed: egui_plot::PlotPoints =
+                        smooth(&self.live[i], self.smoothing, self.smoothing_window).into();
+                    plot_ui.line(
+                        egui_plot::Line::new(smoothed).name(format!("{signal} (smoothed)")),
+                    );
+                }
+            });
+
+        self.overview_strip(ui);
+    }
+
+    /// Whole-session envelope with the current zoom window, draggable to
+    /// navigate.
+    fn overview_strip(&mut self, ui: &mut egui::Ui) {
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(ui.available_width(), STRIP_HEIGHT),
+            egui::Sense::click_and_drag(),
+        );
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+        if self.store.is_empty() {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "no session data",
+                egui::FontId::proportional(12.0),
+                ui.visuals().weak_text_color(),
+            );
+            return;
+        }
+
+        // Envelope: one vertical min/max segment per bin.
+        let (min, max) = self.store.value_range();
+        let span = (max - min).max(f64::EPSILON);
+        let y_of = |value: f64| {
+            rect.bottom() - ((value - min) / span) as f32 * (rect.height() - 4.0) - 2.0
+        };
+        let bin_width = rect.width() / self.store.bins.len() as f32;
+        let stroke = egui::Stroke::new(1.0, ui.visuals().weak_text_color());
+        for (i, bin) in self.store.bins.iter().enumerate() {
+            let x = rect.left() + (i as f32 + 0.5) * bin_width;
+            painter.line_segment(
+                [egui::pos2(x, y_of(bin.max)), egui::pos2(x, y_of(bin.min))],
+                stroke,
+            );
+        }
+
+        // Zoom window overlay; dragging recentres it, clamped to the session.
+        let width = self.window_end - self.window_start;
+        if response.dragged() || response.clicked() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let center = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                self.window_start = (center - width / 2.0).clamp(0.0, 1.0 - width);
+                self.window_end = self.window_start + width;
+            }
+        }
+        let window = egui::Rect::from_min_max(
+            egui::pos2(rect.left() + self.window_start * rect.width(), rect.top()),
+            egui::pos2(rect.left() + self.window_end * rect.width(), rect.bottom()),
+        );
+        painter.rect(
+            window,
+            2.0,
+            ui.visuals().selection.bg_fill.linear_multiply(0.3),
+            egui::Stroke::new(1.0, ui.visuals().selection.stroke.color),
+            egui::StrokeKind::Inside,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_rings_are_bounded_and_freeze_while_paused() {
+        let mut app = TelemetryApp::default();
+        for i in 0..LIVE_SAMPLES + 10 {
+            app.on_data(&Data {
+                time: Duration::from_millis(i as u64 * 10),
+                pressure: Some(i as f64),
+                ..Data::default()
+            });
+        }
+        let pressure = SIGNALS.iter().position(|s| *s == "pressure").unwrap();
+        assert_eq!(app.live[pressure].len(), LIVE_SAMPLES);
+        // Oldest samples fell out; the newest is the last pushed.
+        assert_eq!(app.live[pressure].back().unwrap()[1], (LIVE_SAMPLES + 9) as f64);
+
+        app.paused = true;
+        app.on_data(&Data {
+            pressure: Some(-1.0),
+            ..Data::default()
+        });
+        assert_ne!(app.live[pressure].back().unwrap()[1], -1.0);
+    }
+
+    #[test]
+    fn smoothing_attenuates_a_spike_and_keeps_timestamps() {
+        let trace: VecDeque<[f64; 2]> = (0..64)
+            .map(|i| [i as f64 * 0.01, if i == 32 { 100.0 } else { 10.0 }])
+            .collect();
+        for smoothing in [Smoothing::MovingAverage, Smoothing::Exponential] {
+            let smoothed = smooth(&trace, smoothing, 16);
+            assert_eq!(smoothed.len(), trace.len());
+            // Times pass through untouched; only values are filtered.
+            assert_eq!(smoothed[32][0], trace[32][0]);
+            let peak = smoothed.iter().map(|p| p[1]).fold(f64::MIN, f64::max);
+            assert!(peak < 50.0, "spike survived {smoothing:?} smoothing: {peak}");
+        }
+        // A constant trace is a fixed point of every filter.
+        let flat: VecDeque<[f64; 2]> = (0..16).map(|i| [i as f64, 7.0]).collect();
+        for smoothing in [Smoothing::MovingAverage, Smoothing::Exponential] {
+            assert!(smooth(&flat, smoothing, 8).iter().all(|p| p[1] == 7.0));
+        }
+    }
+
+    #[test]
+    fn snapshot_and_restore_roundtrip_the_live_rings() {
+        let mut app = TelemetryApp::default();
+        for i in 0..100u64 {
+            app.on_data(&Data {
+                time: Duration::from_millis(i * 10),
+                pressure: Some(i as f64),
+                psu_volts: Some(12.0),
+                psu_amps: Some(0.25),
+                ..Data::default()
+            });
+        }
+        let snapshot = app.snapshot();
+
+        let mut restored = TelemetryApp::default();
+        restored.restore(snapshot);
+        assert_eq!(restored.live, app.live);
+        // The pressure trace re-seeds the overview envelope.
+        assert!(!restored.store.is_empty());
+
+        // A snapshot from a build with other signals is dropped, not a panic.
+        restored.restore(crate::persist::TelemetrySnapshot {
+            traces: vec![("no_such_signal".to_string(), vec![[0.0, 1.0]])],
+        });
+        assert_eq!(restored.live, app.live);
+    }
+
+    #[test]
+    fn store_decimates_without_losing_the_envelope() {
+        let mut store = SessionStore::default();
+        for i in 0..20_000u32 {
+            // A spike early in the session must survive every halving.
+            let value = if i == 17 { 900.0 } else { f64::from(i % 10) };
+            store.push(Duration::from_millis(u64::from(i) * 10), value);
+        }
+        assert!(store.bins.len() <= STORE_BINS);
+        assert!(store.samples_per_bin > INITIAL_BIN_SAMPLES);
+        let (min, max) = store.value_range();
+        assert_eq!(min, 0.0);
+        assert_eq!(max, 900.0);
+        assert_eq!(store.first_at, Some(Duration::ZERO));
+        assert_eq!(store.last_at, Some(Duration::from_millis(199_990)));
+    }
+}
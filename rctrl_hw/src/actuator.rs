@@ -0,0 +1,133 @@
+//! Valve and actuator drive abstraction.
+//!
+//! The sync loop commands actuators through the [`Actuator`] trait so the
+//! control logic is independent of how a valve is actually driven. The first
+//! implementation is a single GPIO line ([`GpioActuator`]) for
+//! solenoid-style valves behind a driver transistor; pneumatic or motorised
+//! actuators with richer interfaces can implement the same trait later.
+//!
+//! The driver is generic over the `embedded-hal` digital pin traits for the
+//! same reason the I2C drivers are generic over the bus: tests run against a
+//! mock pin, the stand computer uses a character-device GPIO line.
+
+use embedded_hal::digital::{InputPin, OutputPin};
+
+/// Errors returned by actuator drivers.
+#[derive(Debug, thiserror::Error)]
+pub enum ActuatorError {
+    /// The underlying pin refused the operation. Digital pin errors only
+    /// guarantee `Debug`, so the detail is captured as text.
+    #[error("pin error: {0}")]
+    Pin(String),
+}
+
+/// Something that can be driven open or closed and report its driven state.
+pub trait Actuator {
+    /// Drive the actuator; `true` is open.
+    fn set(&mut self, open: bool) -> Result<(), ActuatorError>;
+
+    /// Read back the currently driven state.
+    ///
+    /// This confirms what the driver is outputting, not where the mechanism
+    /// physically is — position feedback is a separate input.
+    fn state(&mut self) -> Result<bool, ActuatorError>;
+
+    fn open(&mut self) -> Result<(), ActuatorError> {
+        self.set(true)
+    }
+
+    fn close(&mut self) -> Result<(), ActuatorError> {
+        self.set(false)
+    }
+}
+
+/// An actuator driven by one GPIO line.
+///
+/// `active_high` maps the logical open state to the electrical level:
+/// `false` suits driver boards that energise on a low-side pull. Readback
+/// goes through the pin's input path, so [`state`](Actuator::state) reports
+/// the level actually on the line rather than a shadow variable.
+pub struct GpioActuator<P> {
+    pin: P,
+    active_high: bool,
+}
+
+impl<P: OutputPin + InputPin> GpioActuator<P> {
+    pub fn new(pin: P, active_high: bool) -> Self {
+        Self { pin, active_high }
+    }
+}
+
+impl<P: OutputPin + InputPin> Actuator for GpioActuator<P> {
+    fn set(&mut self, open: bool) -> Result<(), ActuatorError> {
+        let result = if open == self.active_high {
+            self.pin.set_high()
+        } else {
+            self.pin.set_low()
+        };
+        result.map_err(|e| ActuatorError::Pin(format!("{e:?}")))
+    }
+
+    fn state(&mut self) -> Result<bool, ActuatorError> {
+        let high = self
+            .pin
+            .is_high()
+            .map_err(|e| ActuatorError::Pin(format!("{e:?}")))?;
+        Ok(high == self.active_high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    /// A pin that remembers the level driven onto it.
+    struct MockPin {
+        high: bool,
+    }
+
+    impl embedded_hal::digital::ErrorType for MockPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for MockPin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.high = false;
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl InputPin for MockPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.high)
+        }
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn active_high_open_drives_the_line_high() {
+        let mut actuator = GpioActuator::new(MockPin { high: false }, true);
+        actuator.open().unwrap();
+        assert!(actuator.pin.high);
+        assert!(actuator.state().unwrap());
+        actuator.close().unwrap();
+        assert!(!actuator.pin.high);
+        assert!(!actuator.state().unwrap());
+    }
+
+    #[test]
+    fn active_low_inverts_the_electrical_level() {
+        let mut actuator = GpioActuator::new(MockPin { high: true }, false);
+        actuator.open().unwrap();
+        // Open means energised, which on a low-side driver is line low.
+        assert!(!actuator.pin.high);
+        assert!(actuator.state().unwrap());
+    }
+}
@@ -0,0 +1,353 @@
+//! Driver for the TI ADS101x family of I2C ADCs (12 bit, ADS1013/1014/1015).
+
+use crate::sensor::Sensor;
+use embedded_hal::i2c::I2c;
+
+const REG_CONVERSION: u8 = 0x00;
+const REG_CONFIG: u8 = 0x01;
+
+/// Config register OS bit: start a single conversion / conversion ready.
+const CONFIG_OS: u16 = 0x8000;
+/// Single shot mode.
+const CONFIG_MODE_SINGLE: u16 = 0x0100;
+
+/// Input multiplexer configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum Mux {
+    /// AINp = AIN0, AINn = GND.
+    Ain0Gnd = 0x4000,
+    /// AINp = AIN1, AINn = GND.
+    Ain1Gnd = 0x5000,
+    /// AINp = AIN2, AINn = GND.
+    Ain2Gnd = 0x6000,
+    /// AINp = AIN3, AINn = GND.
+    Ain3Gnd = 0x7000,
+}
+
+/// Programmable gain amplifier full scale range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum FullScaleRange {
+    /// ±6.144 V.
+    Fsr6V144 = 0x0000,
+    /// ±4.096 V.
+    Fsr4V096 = 0x0200,
+    /// ±2.048 V.
+    Fsr2V048 = 0x0400,
+    /// ±1.024 V.
+    Fsr1V024 = 0x0600,
+}
+
+impl FullScaleRange {
+    /// The range's full scale in volts.
+    pub fn volts(self) -> f64 {
+        match self {
+            Self::Fsr6V144 => 6.144,
+            Self::Fsr4V096 => 4.096,
+            Self::Fsr2V048 => 2.048,
+            Self::Fsr1V024 => 1.024,
+        }
+    }
+
+    /// The next narrower range (higher gain), if any.
+    fn narrower(self) -> Option<Self> {
+        match self {
+            Self::Fsr6V144 => Some(Self::Fsr4V096),
+            Self::Fsr4V096 => Some(Self::Fsr2V048),
+            Self::Fsr2V048 => Some(Self::Fsr1V024),
+            Self::Fsr1V024 => None,
+        }
+    }
+
+    /// The next wider range (lower gain), if any.
+    fn wider(self) -> Option<Self> {
+        match self {
+            Self::Fsr6V144 => None,
+            Self::Fsr4V096 => Some(Self::Fsr6V144),
+            Self::Fsr2V048 => Some(Self::Fsr4V096),
+            Self::Fsr1V024 => Some(Self::Fsr2V048),
+        }
+    }
+}
+
+/// Errors returned by the ADS101x driver.
+#[derive(Debug, thiserror::Error)]
+pub enum AdcError<E: core::fmt::Debug> {
+    #[error("i2c transfer failed: {0:?}")]
+    I2c(E),
+    #[error("conversion did not complete in time")]
+    ConversionTimeout,
+}
+
+/// An ADS101x on an I2C bus.
+pub struct Ads101x<I2C> {
+    i2c: I2C,
+    address: u8,
+    mux: Mux,
+    fsr: FullScaleRange,
+}
+
+impl<I2C> Ads101x<I2C> {
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            mux: Mux::Ain0Gnd,
+            fsr: FullScaleRange::Fsr6V144,
+        }
+    }
+
+    /// Select the input multiplexer configuration for subsequent reads.
+    pub fn with_mux(mut self, mux: Mux) -> Self {
+        self.mux = mux;
+        self
+    }
+
+    /// Select the programmable gain amplifier range for subsequent reads.
+    pub fn with_fsr(mut self, fsr: FullScaleRange) -> Self {
+        self.fsr = fsr;
+        self
+    }
+
+    /// Change the input multiplexer for subsequent reads, so several inputs
+    /// of one device can be sampled sequentially within a loop iteration.
+    pub fn set_mux(&mut self, mux: Mux) {
+        self.mux = mux;
+    }
+
+    /// The config register value for a single shot conversion with the
+    /// currently selected mux and range.
+    fn config_word(&self) -> u16 {
+        CONFIG_OS | self.mux as u16 | self.fsr as u16 | CONFIG_MODE_SINGLE
+    }
+
+    /// The currently selected programmable gain amplifier range.
+    pub fn fsr(&self) -> FullScaleRange {
+        self.fsr
+    }
+
+    /// Change the programmable gain amplifier range for subsequent reads.
+    pub fn set_fsr(&mut self, fsr: FullScaleRange) {
+        self.fsr = fsr;
+    }
+
+    /// Access the underlying bus, e.g. to scan it for other devices.
+    pub fn bus_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
+    }
+}
+
+impl<I2C: I2c> Ads101x<I2C> {
+    /// Perform a single shot conversion and convert it into a sensor reading.
+    pub fn read<T: Sensor>(&mut self) -> Result<T, AdcError<I2C::Error>> {
+        let raw = self.read_raw()?;
+        Ok(T::from_volts(f64::from(raw) * self.fsr.volts() / 2048.0))
+    }
+
+    /// Perform a single shot conversion under gain auto-ranging: the reading
+    /// is taken at the current range, the ranger observes the raw result,
+    /// and any range change it decides applies from the next conversion.
+    /// Returns the reading together with the range it was taken at.
+    pub fn read_auto<T: Sensor>(
+        &mut self,
+        ranger: &mut AutoRange,
+    ) -> Result<(T, FullScaleRange), AdcError<I2C::Error>> {
+        let taken_at = self.fsr;
+        let raw = self.read_raw()?;
+        if let Some(fsr) = ranger.next_fsr(raw, taken_at) {
+            self.fsr = fsr;
+        }
+        Ok((
+            T::from_volts(f64::from(raw) * taken_at.volts() / 2048.0),
+            taken_at,
+        ))
+    }
+
+    /// Perform a single shot conversion and return the raw 12 bit result.
+    fn read_raw(&mut self) -> Result<i16, AdcError<I2C::Error>> {
+        let config = self.config_word();
+        self.i2c
+            .write(
+                self.address,
+                &[REG_CONFIG, (config >> 8) as u8, config as u8],
+            )
+            .map_err(AdcError::I2c)?;
+
+        // Poll the OS bit until the conversion completes; at the slowest data
+        // rate a conversion takes well under 10 ms.
+        let mut buf = [0u8; 2];
+        for _ in 0..100 {
+            self.i2c
+                .write_read(self.address, &[REG_CONFIG], &mut buf)
+                .map_err(AdcError::I2c)?;
+            if u16::from_be_bytes(buf) & CONFIG_OS != 0 {
+                self.i2c
+                    .write_read(self.address, &[REG_CONVERSION], &mut buf)
+                    .map_err(AdcError::I2c)?;
+                return Ok(i16::from_be_bytes(buf) >> 4);
+            }
+        }
+        Err(AdcError::ConversionTimeout)
+    }
+}
+
+/// Raw counts above this fraction of full scale widen the range immediately:
+/// a clipped sample is lost information.
+const WIDEN_FRACTION: f64 = 0.9;
+/// Headroom required in the narrower range before stepping into it.
+const NARROW_MARGIN: f64 = 0.8;
+
+/// Gain auto-ranging policy for one channel.
+///
+/// A transducer that spans most of the ADC range only during the burn wastes
+/// resolution at idle. The ranger watches raw conversion results and steps
+/// the PGA range: wider immediately when a sample approaches full scale
+/// (clipping loses information), narrower only after the signal has stayed
+/// comfortably inside the narrower range for a settle period and a minimum
+/// hold between changes has elapsed — the hysteresis keeps a signal sitting
+/// near a threshold from toggling the gain.
+///
+/// [`lock`](Self::lock) holds the current range through critical windows, so
+/// a gain step never lands mid-burn.
+#[derive(Debug)]
+pub struct AutoRange {
+    /// Consecutive samples that must fit the narrower range before stepping.
+    settle: u32,
+    /// Minimum samples between range changes, in either direction counted
+    /// from the last change.
+    min_hold: u32,
+    below: u32,
+    since_change: u32,
+    locked: bool,
+}
+
+impl Default for AutoRange {
+    /// Half a second of settling and a second of hold at the 100 Hz loop
+    /// rate.
+    fn default() -> Self {
+        Self::new(50, 100)
+    }
+}
+
+impl AutoRange {
+    pub fn new(settle: u32, min_hold: u32) -> Self {
+        Self {
+            settle,
+            min_hold,
+            below: 0,
+            since_change: 0,
+            locked: false,
+        }
+    }
+
+    /// Hold the current range, e.g. through a burn; observations continue
+    /// but no change is decided until [`unlock`](Self::unlock).
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Observe a raw conversion result taken at `fsr` and decide the range
+    /// for subsequent conversions; `None` means stay.
+    pub fn next_fsr(&mut self, raw: i16, fsr: FullScaleRange) -> Option<FullScaleRange> {
+        self.since_change = self.since_change.saturating_add(1);
+        let magnitude = f64::from(raw.unsigned_abs());
+        // A sample that fits the narrower range with margin counts toward
+        // settling; anything else resets it.
+        let fits_narrower = fsr.narrower().is_some_and(|narrower| {
+            magnitude * fsr.volts() < NARROW_MARGIN * narrower.volts() * 2048.0
+        });
+        self.below = if fits_narrower { self.below + 1 } else { 0 };
+        if self.locked {
+            return None;
+        }
+        if magnitude >= WIDEN_FRACTION * 2048.0 {
+            if let Some(wider) = fsr.wider() {
+                self.below = 0;
+                self.since_change = 0;
+                return Some(wider);
+            }
+            return None;
+        }
+        if self.below >= self.settle && self.since_change >= self.min_hold {
+            if let Some(narrower) = fsr.narrower() {
+                self.below = 0;
+                self.since_change = 0;
+                return Some(narrower);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_word_encodes_mux_and_range() {
+        let adc = Ads101x::new((), 0x48)
+            .with_mux(Mux::Ain1Gnd)
+            .with_fsr(FullScaleRange::Fsr4V096);
+        assert_eq!(adc.config_word(), 0x8000 | 0x5000 | 0x0200 | 0x0100);
+    }
+
+    #[test]
+    fn auto_range_widens_immediately_on_near_clipping() {
+        let mut ranger = AutoRange::new(4, 8);
+        assert_eq!(
+            ranger.next_fsr(2000, FullScaleRange::Fsr2V048),
+            Some(FullScaleRange::Fsr4V096)
+        );
+        // The widest range has nowhere wider to go.
+        assert_eq!(ranger.next_fsr(2047, FullScaleRange::Fsr6V144), None);
+    }
+
+    #[test]
+    fn auto_range_narrows_only_after_settling_and_hold() {
+        let mut ranger = AutoRange::new(4, 8);
+        // 100 counts at ±6.144 V fits ±4.096 V with plenty of margin, but
+        // neither the settle count nor the hold elapse for the first samples.
+        for _ in 0..7 {
+            assert_eq!(ranger.next_fsr(100, FullScaleRange::Fsr6V144), None);
+        }
+        assert_eq!(
+            ranger.next_fsr(100, FullScaleRange::Fsr6V144),
+            Some(FullScaleRange::Fsr4V096)
+        );
+        // A single out-of-margin sample resets the settle count.
+        let mut ranger = AutoRange::new(4, 0);
+        for _ in 0..3 {
+            assert_eq!(ranger.next_fsr(100, FullScaleRange::Fsr6V144), None);
+        }
+        assert_eq!(ranger.next_fsr(1500, FullScaleRange::Fsr6V144), None);
+        for _ in 0..3 {
+            assert_eq!(ranger.next_fsr(100, FullScaleRange::Fsr6V144), None);
+        }
+        assert_eq!(
+            ranger.next_fsr(100, FullScaleRange::Fsr6V144),
+            Some(FullScaleRange::Fsr4V096)
+        );
+    }
+
+    #[test]
+    fn a_locked_ranger_holds_the_range() {
+        let mut ranger = AutoRange::new(1, 0);
+        ranger.lock();
+        assert_eq!(ranger.next_fsr(2047, FullScaleRange::Fsr2V048), None);
+        assert_eq!(ranger.next_fsr(10, FullScaleRange::Fsr6V144), None);
+        ranger.unlock();
+        assert_eq!(
+            ranger.next_fsr(2047, FullScaleRange::Fsr2V048),
+            Some(FullScaleRange::Fsr4V096)
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//! Analog to digital converter drivers.
+
+pub mod ads101x;
+pub mod spi;
+
+pub use ads101x::{Ads101x, AutoRange, FullScaleRange, Mux};
+pub use spi::{Channel, Mcp3008};
@@ -0,0 +1,161 @@
+//! Driver for the Microchip MCP3008 8 channel SPI ADC (10 bit).
+//!
+//! The SPI counterpart of the I2C [`Ads101x`](crate::adc::Ads101x) driver:
+//! generic over [`SpiDevice`], so the stand computer hands it a `spidev`
+//! backed device from `linux-embedded-hal` and tests hand it a mock, and
+//! exposing the same `read::<T: Sensor>()` interface so a sensor can move
+//! between ADC backends without touching sync loop code. The MCP3008 has no
+//! programmable gain amplifier; readings scale against the reference
+//! voltage wired to its VREF pin, so there is no auto-ranging here.
+
+use crate::sensor::Sensor;
+use embedded_hal::spi::SpiDevice;
+
+/// Start bit of the request byte.
+const START: u8 = 0x01;
+/// Single-ended conversion (as opposed to differential between a pair).
+const SINGLE_ENDED: u8 = 0x80;
+
+/// Input channel selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Channel {
+    Ch0 = 0,
+    Ch1 = 1,
+    Ch2 = 2,
+    Ch3 = 3,
+    Ch4 = 4,
+    Ch5 = 5,
+    Ch6 = 6,
+    Ch7 = 7,
+}
+
+/// Errors returned by the MCP3008 driver.
+#[derive(Debug, thiserror::Error)]
+pub enum AdcError<E: core::fmt::Debug> {
+    #[error("spi transfer failed: {0:?}")]
+    Spi(E),
+}
+
+/// An MCP3008 behind an SPI chip select.
+pub struct Mcp3008<SPI> {
+    spi: SPI,
+    channel: Channel,
+    /// Voltage on the VREF pin; the 10 bit result scales against it.
+    vref: f64,
+}
+
+impl<SPI> Mcp3008<SPI> {
+    pub fn new(spi: SPI, vref: f64) -> Self {
+        Self {
+            spi,
+            channel: Channel::Ch0,
+            vref,
+        }
+    }
+
+    /// Select the input channel for subsequent reads.
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Change the input channel for subsequent reads, so several inputs of
+    /// one device can be sampled sequentially within a loop iteration.
+    pub fn set_channel(&mut self, channel: Channel) {
+        self.channel = channel;
+    }
+
+    /// The request byte selecting a single-ended conversion of the current
+    /// channel.
+    fn request_byte(&self) -> u8 {
+        SINGLE_ENDED | (self.channel as u8) << 4
+    }
+
+    /// Access the underlying device, e.g. to reconfigure its bus.
+    pub fn bus_mut(&mut self) -> &mut SPI {
+        &mut self.spi
+    }
+}
+
+impl<SPI: SpiDevice> Mcp3008<SPI> {
+    /// Perform one conversion and convert it into a sensor reading.
+    pub fn read<T: Sensor>(&mut self) -> Result<T, AdcError<SPI::Error>> {
+        let raw = self.read_raw()?;
+        Ok(T::from_volts(f64::from(raw) * self.vref / 1024.0))
+    }
+
+    /// Perform one conversion and return the raw 10 bit result.
+    fn read_raw(&mut self) -> Result<u16, AdcError<SPI::Error>> {
+        // One three-byte transfer: start bit, request byte, then clocking
+        // out the result — a null bit followed by the 10 data bits.
+        let mut buf = [START, self.request_byte(), 0x00];
+        self.spi
+            .transfer_in_place(&mut buf)
+            .map_err(AdcError::Spi)?;
+        Ok(u16::from(buf[1] & 0x03) << 8 | u16::from(buf[2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::spi::Operation;
+    use rctrl_api::sensor::Pressure;
+
+    /// Mock SPI device answering conversions from a canned result list.
+    struct MockSpi {
+        /// 10 bit results returned by successive conversions.
+        results: Vec<u16>,
+        /// Request bytes seen, for asserting the wire encoding.
+        requests: Vec<Vec<u8>>,
+    }
+
+    impl embedded_hal::spi::ErrorType for MockSpi {
+        type Error = core::convert::Infallible;
+    }
+
+    impl SpiDevice for MockSpi {
+        fn transaction(
+            &mut self,
+            operations: &mut [Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                if let Operation::TransferInPlace(buf) = operation {
+                    self.requests.push(buf.to_vec());
+                    let raw = self.results.remove(0);
+                    buf[1] = (raw >> 8) as u8 & 0x03;
+                    buf[2] = raw as u8;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn request_encodes_start_and_channel() {
+        let spi = MockSpi {
+            results: vec![0],
+            requests: Vec::new(),
+        };
+        let mut adc = Mcp3008::new(spi, 5.0).with_channel(Channel::Ch6);
+        let _: Pressure = adc.read().unwrap();
+        assert_eq!(adc.bus_mut().requests, vec![vec![0x01, 0xE0, 0x00]]);
+    }
+
+    #[test]
+    fn reads_scale_against_the_reference_voltage() {
+        // 0.5 V at VREF = 5.0 V is the transducer's 0 bar point.
+        let raw = (0.5 * 1024.0 / 5.0) as u16;
+        let spi = MockSpi {
+            results: vec![raw, 1023],
+            requests: Vec::new(),
+        };
+        let mut adc = Mcp3008::new(spi, 5.0);
+        let zero: Pressure = adc.read().unwrap();
+        assert!(zero.value.abs() < 0.5);
+        // Full scale counts read as the full reference voltage.
+        let full: Pressure = adc.read().unwrap();
+        assert!((full.value - Pressure::from_volts(5.0).value).abs() < 0.5);
+    }
+}
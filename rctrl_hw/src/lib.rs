@@ -0,0 +1,14 @@
+//! Hardware drivers for the rCTRL stand computer.
+//!
+//! Drivers are generic over the `embedded-hal` bus traits so they can be used
+//! with `linux-embedded-hal` on the stand computer and with mock buses in
+//! tests. Nothing in this crate is platform specific — the Linux-only bus
+//! implementations live in the `rctrl` daemon — so it builds unchanged on
+//! development machines of any OS.
+
+pub mod actuator;
+pub mod adc;
+pub mod mux;
+pub mod psu;
+pub mod scan;
+pub mod sensor;
@@ -0,0 +1,147 @@
+//! Driver for the TI TCA9548A 8-channel I2C multiplexer.
+//!
+//! The harness hangs several ADS101x with identical addresses behind one
+//! TCA9548A. [`Tca9548a::segment`] hands out one [`Segment`] per downstream
+//! bus; a segment implements [`I2c`] itself and selects its mux channel
+//! before forwarding each transaction, so existing drivers work behind the
+//! mux without knowing it is there. Device configs reference segments by
+//! their `mux_channel`.
+
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+use std::sync::{Arc, Mutex};
+
+/// Shared state behind all segments: the upstream bus and the channel the
+/// mux currently has selected, so back-to-back transactions on one segment
+/// skip the redundant select write.
+struct Inner<I2C> {
+    bus: I2C,
+    active: Option<u8>,
+}
+
+/// A TCA9548A on an upstream I2C bus.
+pub struct Tca9548a<I2C> {
+    inner: Arc<Mutex<Inner<I2C>>>,
+    address: u8,
+}
+
+impl<I2C> Tca9548a<I2C> {
+    /// Default address with A0..A2 strapped low.
+    pub const DEFAULT_ADDRESS: u8 = 0x70;
+
+    pub fn new(bus: I2C, address: u8) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner { bus, active: None })),
+            address,
+        }
+    }
+
+    /// A handle to the downstream bus on `channel` (0..=7).
+    ///
+    /// # Panics
+    ///
+    /// Panics when `channel` is out of range; channel numbers come from
+    /// config and are validated at load time.
+    pub fn segment(&self, channel: u8) -> Segment<I2C> {
+        assert!(channel < 8, "TCA9548A has channels 0..=7, got {channel}");
+        Segment {
+            inner: self.inner.clone(),
+            mux_address: self.address,
+            channel,
+        }
+    }
+}
+
+/// One downstream bus segment of a [`Tca9548a`].
+pub struct Segment<I2C> {
+    inner: Arc<Mutex<Inner<I2C>>>,
+    mux_address: u8,
+    channel: u8,
+}
+
+impl<I2C: I2c> ErrorType for Segment<I2C> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: I2c> I2c for Segment<I2C> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut inner = self.inner.lock().expect("mux bus mutex poisoned");
+        if inner.active != Some(self.channel) {
+            let control = 1u8 << self.channel;
+            let mux_address = self.mux_address;
+            inner.bus.write(mux_address, &[control])?;
+            inner.active = Some(self.channel);
+        }
+        inner.bus.transaction(address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::ErrorKind;
+
+    /// Records every write on the upstream bus.
+    #[derive(Default)]
+    struct RecordingBus {
+        writes: Vec<(u8, Vec<u8>)>,
+    }
+
+    impl ErrorType for RecordingBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for RecordingBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                if let Operation::Write(bytes) = op {
+                    self.writes.push((address, bytes.to_vec()));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn segment_selects_its_channel_before_forwarding() {
+        let mux = Tca9548a::new(RecordingBus::default(), Tca9548a::<RecordingBus>::DEFAULT_ADDRESS);
+        let mut segment = mux.segment(3);
+        segment.write(0x48, &[0xaa]).unwrap();
+
+        let inner = mux.inner.lock().unwrap();
+        assert_eq!(
+            inner.bus.writes,
+            vec![(0x70, vec![1 << 3]), (0x48, vec![0xaa])]
+        );
+    }
+
+    #[test]
+    fn selection_is_cached_until_the_channel_changes() {
+        let mux = Tca9548a::new(RecordingBus::default(), 0x70);
+        let mut a = mux.segment(0);
+        let mut b = mux.segment(5);
+
+        a.write(0x48, &[1]).unwrap();
+        a.write(0x48, &[2]).unwrap();
+        b.write(0x48, &[3]).unwrap();
+
+        let inner = mux.inner.lock().unwrap();
+        let selects: Vec<&(u8, Vec<u8>)> =
+            inner.bus.writes.iter().filter(|(addr, _)| *addr == 0x70).collect();
+        assert_eq!(selects, vec![&(0x70, vec![1u8]), &(0x70, vec![1 << 5])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "channels 0..=7")]
+    fn out_of_range_channel_panics() {
+        let mux = Tca9548a::new(RecordingBus::default(), 0x70);
+        let _ = mux.segment(8);
+    }
+}
@@ -0,0 +1,57 @@
+//! I2C bus scanning.
+
+use embedded_hal::i2c::I2c;
+
+/// The 7-bit address range scanned; addresses outside it are reserved.
+pub const ADDRESS_RANGE: std::ops::RangeInclusive<u8> = 0x08..=0x77;
+
+/// Probe every address in [`ADDRESS_RANGE`] with a zero-length write and
+/// return those that acknowledged.
+///
+/// A zero-length write is the probe `i2cdetect` uses for most addresses: it
+/// addresses the device without transferring data, so it is safe against
+/// read-sensitive devices.
+pub fn scan<I2C: I2c>(bus: &mut I2C) -> Vec<u8> {
+    ADDRESS_RANGE
+        .filter(|address| bus.write(*address, &[]).is_ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+
+    /// Acknowledges only the configured addresses.
+    struct FakeBus {
+        present: Vec<u8>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if self.present.contains(&address) {
+                Ok(())
+            } else {
+                Err(ErrorKind::NoAcknowledge(
+                    embedded_hal::i2c::NoAcknowledgeSource::Address,
+                ))
+            }
+        }
+    }
+
+    #[test]
+    fn scan_reports_acknowledging_addresses() {
+        let mut bus = FakeBus {
+            present: vec![0x48, 0x70],
+        };
+        assert_eq!(scan(&mut bus), vec![0x48, 0x70]);
+    }
+}
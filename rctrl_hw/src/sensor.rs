@@ -0,0 +1,69 @@
+//! Conversion of raw ADC counts into engineering values.
+
+use rctrl_api::sensor::{Current, Pressure, Temperature};
+
+/// Conversion from a raw ADC conversion result into an engineering value.
+///
+/// Implementations encode the transducer transfer function, so sync loop code
+/// can read any sensor with `adc.read::<Pressure>()` without knowing the
+/// scaling.
+pub trait Sensor: Sized {
+    /// Construct a reading from the measured input voltage. The ADC driver
+    /// applies the counts-to-volts scaling of its active gain range before
+    /// calling this, so the transfer function holds under auto-ranging.
+    fn from_volts(volts: f64) -> Self;
+
+    /// Construct a reading from a raw ADC conversion result taken in the
+    /// ±6.144 V range.
+    fn from_raw(raw: i16) -> Self {
+        Self::from_volts(f64::from(raw) * 6.144 / 2048.0)
+    }
+}
+
+/// 0-100 bar transducer, 0.5-4.5 V ratiometric.
+impl Sensor for Pressure {
+    fn from_volts(volts: f64) -> Self {
+        Self {
+            value: (volts - 0.5) * 100.0 / 4.0,
+        }
+    }
+}
+
+/// LM35 style linear output, 10 mV per degree Celsius.
+impl Sensor for Temperature {
+    fn from_volts(volts: f64) -> Self {
+        Self {
+            value: volts * 100.0,
+        }
+    }
+}
+
+/// Igniter current sense: 50 A / 75 mV shunt through a 20 V/V amplifier.
+impl Sensor for Current {
+    fn from_volts(volts: f64) -> Self {
+        Self {
+            value: volts * 50.0 / 1.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_full_scale() {
+        // 1.5 V at the amplifier output corresponds to 50 A in the shunt.
+        let raw = (1.5 * 2048.0 / 6.144) as i16;
+        let reading = Current::from_raw(raw);
+        assert!((reading.value - 50.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn pressure_zero_point() {
+        // 0.5 V corresponds to 0 bar.
+        let raw = (0.5 * 2048.0 / 6.144) as i16;
+        let reading = Pressure::from_raw(raw);
+        assert!(reading.value.abs() < 0.5);
+    }
+}